@@ -16,6 +16,7 @@ export function WorkflowProvider({ children }) {
   const [loading, setLoading] = useState(false);
   const [error, setError] = useState(null);
   const [isRecording, setIsRecording] = useState(false);
+  const [isPaused, setIsPaused] = useState(false);
 
   // Carica i workflow all'avvio
   useEffect(() => {
@@ -61,6 +62,7 @@ export function WorkflowProvider({ children }) {
     try {
       await tauriApi.stopRecording();
       setIsRecording(false);
+      setIsPaused(false);
       return true;
     } catch (err) {
       setError(err.message || 'Errore arresto registrazione');
@@ -68,6 +70,56 @@ export function WorkflowProvider({ children }) {
     }
   };
 
+  const pauseRecording = async () => {
+    try {
+      await tauriApi.pauseRecording();
+      setIsPaused(true);
+      return true;
+    } catch (err) {
+      setError(err.message || 'Errore pausa registrazione');
+      throw err;
+    }
+  };
+
+  const resumeRecording = async () => {
+    try {
+      await tauriApi.resumeRecording();
+      setIsPaused(false);
+      return true;
+    } catch (err) {
+      setError(err.message || 'Errore ripresa registrazione');
+      throw err;
+    }
+  };
+
+  // La pausa può essere attivata anche dalla hotkey globale
+  const checkPausedStatus = async () => {
+    try {
+      const paused = await tauriApi.isPaused();
+      setIsPaused(paused);
+    } catch (err) {
+      console.error('Errore verifica pausa:', err);
+    }
+  };
+
+  const addMarker = async (label) => {
+    try {
+      return await tauriApi.addMarker(label);
+    } catch (err) {
+      setError(err.message || 'Errore aggiunta marker');
+      throw err;
+    }
+  };
+
+  const undoLastActions = async (count = 1) => {
+    try {
+      return await tauriApi.undoLastActions(count);
+    } catch (err) {
+      setError(err.message || 'Errore annullamento azioni');
+      throw err;
+    }
+  };
+
   const saveWorkflow = async (fileName) => {
     try {
       const result = await tauriApi.saveWorkflow(fileName);
@@ -115,9 +167,15 @@ export function WorkflowProvider({ children }) {
     loading,
     error,
     isRecording,
+    isPaused,
     loadWorkflows,
     startRecording,
     stopRecording,
+    pauseRecording,
+    resumeRecording,
+    checkPausedStatus,
+    addMarker,
+    undoLastActions,
     saveWorkflow,
     deleteWorkflow,
     executeWorkflow,
@@ -1,6 +1,6 @@
 import React, { useState, useEffect } from 'react';
 import { useNavigate } from 'react-router-dom';
-import { Circle, Square, Loader2, CheckCircle } from 'lucide-react';
+import { Circle, Square, Loader2, CheckCircle, Pause, Play, Flag, Undo2 } from 'lucide-react';
 import { useWorkflows } from '../contexts/WorkflowContext';
 import { Card } from '../components/Card';
 import { Button } from '../components/Button';
@@ -8,7 +8,18 @@ import { Input } from '../components/Input';
 import { Modal } from '../components/Modal';
 
 export function RecordWorkflow() {
-  const { startRecording, stopRecording, saveWorkflow, isRecording } = useWorkflows();
+  const {
+    startRecording,
+    stopRecording,
+    saveWorkflow,
+    isRecording,
+    isPaused,
+    pauseRecording,
+    resumeRecording,
+    checkPausedStatus,
+    addMarker,
+    undoLastActions,
+  } = useWorkflows();
   const navigate = useNavigate();
   const [status, setStatus] = useState('idle'); // idle, recording, stopped, saving, saved
   const [workflowName, setWorkflowName] = useState('');
@@ -16,17 +27,22 @@ export function RecordWorkflow() {
   const [elapsedTime, setElapsedTime] = useState(0);
   const [showSaveModal, setShowSaveModal] = useState(false);
   const [error, setError] = useState('');
+  const [markerLabel, setMarkerLabel] = useState('');
+  const [notice, setNotice] = useState('');
 
-  // Timer per tempo di registrazione
+  // Timer per tempo di registrazione (fermo durante la pausa)
   useEffect(() => {
     let interval;
     if (status === 'recording') {
       interval = setInterval(() => {
-        setElapsedTime(prev => prev + 1);
+        checkPausedStatus();
+        if (!isPaused) {
+          setElapsedTime(prev => prev + 1);
+        }
       }, 1000);
     }
     return () => clearInterval(interval);
-  }, [status]);
+  }, [status, isPaused]);
 
   const handleStartRecording = async () => {
     if (!tempWorkflowName.trim()) {
@@ -58,6 +74,40 @@ export function RecordWorkflow() {
     }
   };
 
+  const handleTogglePause = async () => {
+    try {
+      if (isPaused) {
+        await resumeRecording();
+      } else {
+        await pauseRecording();
+      }
+    } catch (err) {
+      setError(err.message || 'Errore pausa registrazione');
+    }
+  };
+
+  const handleAddMarker = async () => {
+    if (!markerLabel.trim()) {
+      return;
+    }
+    try {
+      await addMarker(markerLabel.trim());
+      setNotice(`Marker "${markerLabel.trim()}" aggiunto`);
+      setMarkerLabel('');
+    } catch (err) {
+      setError(err.message || 'Errore aggiunta marker');
+    }
+  };
+
+  const handleUndo = async () => {
+    try {
+      const undone = await undoLastActions(1);
+      setNotice(undone > 0 ? 'Ultima azione annullata' : 'Nessuna azione da annullare');
+    } catch (err) {
+      setError(err.message || 'Errore annullamento azioni');
+    }
+  };
+
   const handleSave = async () => {
     setStatus('saving');
     try {
@@ -130,7 +180,7 @@ export function RecordWorkflow() {
               <Circle className="w-12 h-12 text-red-600 fill-red-600" />
             </div>
             <h2 className="text-2xl font-bold text-gray-900 mb-2">
-              🎬 Registrazione in Corso...
+              {isPaused ? '⏸️ Registrazione in Pausa' : '🎬 Registrazione in Corso...'}
             </h2>
             <p className="text-xl font-mono text-red-600 mb-4">
               {formatTime(elapsedTime)}
@@ -138,15 +188,43 @@ export function RecordWorkflow() {
             <p className="text-gray-600 mb-8 max-w-md mx-auto">
               Esegui le azioni che vuoi automatizzare.
               Ogni click, digitazione e azione viene registrata.
+              Premi Ctrl+Shift+F9 per mettere in pausa o riprendere.
             </p>
-            <Button
-              onClick={handleStopRecording}
-              variant="danger"
-              className="px-8 py-3 text-lg"
-            >
-              <Square className="w-5 h-5" />
-              Ferma Registrazione
-            </Button>
+            <div className="max-w-md mx-auto mb-6 flex gap-2 items-end">
+              <div className="flex-1">
+                <Input
+                  label="Marker"
+                  placeholder="es: compilazione_dati_cliente"
+                  value={markerLabel}
+                  onChange={(e) => setMarkerLabel(e.target.value)}
+                />
+              </div>
+              <Button onClick={handleAddMarker} variant="secondary">
+                <Flag className="w-5 h-5" />
+                Aggiungi
+              </Button>
+            </div>
+            {notice && (
+              <p className="text-sm text-gray-600 mb-4">{notice}</p>
+            )}
+            <div className="flex justify-center gap-4">
+              <Button onClick={handleTogglePause} variant="secondary" className="px-6 py-3 text-lg">
+                {isPaused ? <Play className="w-5 h-5" /> : <Pause className="w-5 h-5" />}
+                {isPaused ? 'Riprendi' : 'Pausa'}
+              </Button>
+              <Button onClick={handleUndo} variant="secondary" className="px-6 py-3 text-lg">
+                <Undo2 className="w-5 h-5" />
+                Annulla Ultima Azione
+              </Button>
+              <Button
+                onClick={handleStopRecording}
+                variant="danger"
+                className="px-8 py-3 text-lg"
+              >
+                <Square className="w-5 h-5" />
+                Ferma Registrazione
+              </Button>
+            </div>
           </div>
         )}
 
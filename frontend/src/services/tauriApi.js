@@ -53,6 +53,78 @@ export async function saveWorkflow(fileName) {
   }
 }
 
+/**
+ * Mette in pausa la registrazione corrente
+ * @returns {Promise<string>}
+ */
+export async function pauseRecording() {
+  try {
+    const result = await invoke('pause_recording');
+    return result;
+  } catch (error) {
+    console.error('Errore pausa registrazione:', error);
+    throw error;
+  }
+}
+
+/**
+ * Riprende la registrazione in pausa
+ * @returns {Promise<string>}
+ */
+export async function resumeRecording() {
+  try {
+    const result = await invoke('resume_recording');
+    return result;
+  } catch (error) {
+    console.error('Errore ripresa registrazione:', error);
+    throw error;
+  }
+}
+
+/**
+ * Verifica se la registrazione è in pausa (anche tramite hotkey Ctrl+Shift+F9)
+ * @returns {Promise<boolean>}
+ */
+export async function isPaused() {
+  try {
+    const result = await invoke('is_paused');
+    return result;
+  } catch (error) {
+    console.error('Errore verifica pausa:', error);
+    throw error;
+  }
+}
+
+/**
+ * Aggiunge un marker: le azioni successive formano un gruppo con questo nome
+ * @param {string} label - Nome del marker
+ * @returns {Promise<string>}
+ */
+export async function addMarker(label) {
+  try {
+    const result = await invoke('add_marker', { label });
+    return result;
+  } catch (error) {
+    console.error('Errore aggiunta marker:', error);
+    throw error;
+  }
+}
+
+/**
+ * Annulla le ultime azioni registrate
+ * @param {number} count - Numero di azioni da annullare
+ * @returns {Promise<number>} - Numero di azioni annullate
+ */
+export async function undoLastActions(count = 1) {
+  try {
+    const result = await invoke('undo_last_actions', { count });
+    return result;
+  } catch (error) {
+    console.error('Errore annullamento azioni:', error);
+    throw error;
+  }
+}
+
 /**
  * Verifica se una registrazione è in corso
  * @returns {Promise<boolean>}
@@ -115,6 +115,65 @@ async fn save_workflow(
     }
 }
 
+#[tauri::command]
+async fn pause_recording(state: State<'_, RecorderState>) -> Result<String, String> {
+    info!("⏸️  Pausa registrazione");
+
+    let recorder_guard = state.recorder.lock().map_err(|e| e.to_string())?;
+    let rec = recorder_guard
+        .as_ref()
+        .ok_or("Nessuna registrazione in corso")?;
+    rec.pause();
+
+    Ok("Registrazione in pausa".to_string())
+}
+
+#[tauri::command]
+async fn resume_recording(state: State<'_, RecorderState>) -> Result<String, String> {
+    info!("▶️  Ripresa registrazione");
+
+    let recorder_guard = state.recorder.lock().map_err(|e| e.to_string())?;
+    let rec = recorder_guard
+        .as_ref()
+        .ok_or("Nessuna registrazione in corso")?;
+    rec.resume();
+
+    Ok("Registrazione ripresa".to_string())
+}
+
+#[tauri::command]
+async fn is_paused(state: State<'_, RecorderState>) -> Result<bool, String> {
+    let recorder_guard = state.recorder.lock().map_err(|e| e.to_string())?;
+    Ok(recorder_guard.as_ref().is_some_and(|rec| rec.is_paused()))
+}
+
+#[tauri::command]
+async fn add_marker(label: String, state: State<'_, RecorderState>) -> Result<String, String> {
+    info!("🏷️  Aggiunta marker: {}", label);
+
+    let recorder_guard = state.recorder.lock().map_err(|e| e.to_string())?;
+    let rec = recorder_guard
+        .as_ref()
+        .ok_or("Nessuna registrazione in corso")?;
+    rec.add_marker(label.clone()).map_err(|e| e.to_string())?;
+
+    Ok(format!("Marker '{}' aggiunto", label))
+}
+
+#[tauri::command]
+async fn undo_last_actions(
+    count: usize,
+    state: State<'_, RecorderState>,
+) -> Result<usize, String> {
+    info!("↩️  Annullamento ultime {} azioni", count);
+
+    let recorder_guard = state.recorder.lock().map_err(|e| e.to_string())?;
+    let rec = recorder_guard
+        .as_ref()
+        .ok_or("Nessuna registrazione in corso")?;
+    rec.undo_last_actions(count).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 async fn is_recording(state: State<'_, RecorderState>) -> Result<bool, String> {
     let recording = state.recording.lock().map_err(|e| e.to_string())?;
@@ -295,6 +354,11 @@ fn main() {
             start_recording,
             stop_recording,
             save_workflow,
+            pause_recording,
+            resume_recording,
+            is_paused,
+            add_marker,
+            undo_last_actions,
             is_recording,
             list_workflows,
             get_workflow_info,
@@ -0,0 +1,109 @@
+use anyhow::{bail, Context, Result};
+use clap::{Args, Subcommand};
+use colored::*;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use terminator_mcp_agent::audit::{entry_hash, GENESIS_HASH};
+
+#[derive(Debug, Subcommand)]
+pub enum AuditCommand {
+    /// Check the hash chain of an MCP agent audit log
+    Verify(VerifyArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct VerifyArgs {
+    /// Audit log directory (checks rotated files and audit.jsonl in order) or a single .jsonl file
+    path: PathBuf,
+}
+
+impl AuditCommand {
+    pub fn execute(&self) -> Result<()> {
+        match self {
+            AuditCommand::Verify(args) => verify(&args.path),
+        }
+    }
+}
+
+fn verify(path: &Path) -> Result<()> {
+    let files = chain_files(path)?;
+    if files.is_empty() {
+        bail!("no audit log files found in {}", path.display());
+    }
+
+    let mut expected: Option<(u64, String)> = None;
+    let mut first_seq = None;
+    let mut entries = 0u64;
+    for file in &files {
+        let content = fs::read_to_string(file)
+            .with_context(|| format!("failed to read {}", file.display()))?;
+        for (index, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let location = format!("{}:{}", file.display(), index + 1);
+            let entry: Value = serde_json::from_str(line)
+                .with_context(|| format!("{location}: entry is not valid JSON"))?;
+            let seq = entry["seq"]
+                .as_u64()
+                .with_context(|| format!("{location}: entry has no seq"))?;
+            let prev_hash = entry["prev_hash"].as_str().unwrap_or_default();
+            let hash = entry["hash"].as_str().unwrap_or_default();
+
+            match &expected {
+                Some((last_seq, last_hash)) => {
+                    if seq != last_seq + 1 {
+                        bail!("{location}: expected seq {} but found {seq} (entries removed or reordered)", last_seq + 1);
+                    }
+                    if prev_hash != last_hash {
+                        bail!("{location}: prev_hash does not match the hash of entry {last_seq}");
+                    }
+                }
+                None => {
+                    if seq == 1 && prev_hash != GENESIS_HASH {
+                        bail!("{location}: first entry does not start from the genesis hash");
+                    }
+                    first_seq = Some(seq);
+                }
+            }
+
+            let computed = entry_hash(&entry);
+            if hash != computed {
+                bail!("{location}: entry {seq} was modified (hash mismatch)");
+            }
+            expected = Some((seq, computed));
+            entries += 1;
+        }
+    }
+
+    let Some((last_seq, last_hash)) = expected else {
+        bail!("audit log at {} has no entries", path.display());
+    };
+    println!(
+        "{} {} entries verified across {} file(s) (seq {}..{})",
+        "✅".green(),
+        entries,
+        files.len(),
+        first_seq.unwrap_or(1),
+        last_seq
+    );
+    if first_seq != Some(1) {
+        println!(
+            "{} chain starts at seq {}; earlier entries were not checked",
+            "⚠️".yellow(),
+            first_seq.unwrap_or_default()
+        );
+    }
+    println!("   Last hash: {}", last_hash.dimmed());
+    Ok(())
+}
+
+/// Files of the chain in write order: rotated `audit-*.jsonl`, then `audit.jsonl`.
+fn chain_files(path: &Path) -> Result<Vec<PathBuf>> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    terminator_mcp_agent::audit::chain_files(path)
+        .with_context(|| format!("failed to read {}", path.display()))
+}
@@ -1 +1,5 @@
+#[cfg(feature = "agent-tools")]
+pub mod audit;
 pub mod setup;
+#[cfg(feature = "agent-tools")]
+pub mod workflow;
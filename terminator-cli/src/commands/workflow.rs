@@ -0,0 +1,279 @@
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand};
+use colored::*;
+use std::fs;
+use std::path::PathBuf;
+use terminator_mcp_agent::workflow_codegen::{generate_script, load_workflow, ScriptLanguage};
+use terminator_mcp_agent::workflow_converter::write_storyboard;
+use terminator_mcp_agent::workflow_lint::{lint_workflow, Diagnostic, Severity};
+use terminator_mcp_agent::workflow_replay::{compare_reports, run_workflow_test, GOLDEN_FILE};
+use terminator_mcp_agent::workflow_schema::workflow_schema;
+
+#[derive(Debug, Subcommand)]
+pub enum WorkflowCommand {
+    /// Print the JSON Schema of workflow files (steps, and the arguments of every tool)
+    Schema(SchemaArgs),
+    /// Statically check workflow files without running them
+    Lint(LintArgs),
+    /// Replay a workflow against recorded UI-tree fixtures and compare with the golden report
+    Test(TestArgs),
+    /// Generate a Rust, Python or TypeScript script from a recording or workflow file
+    Codegen(CodegenArgs),
+    /// Render a recording as an HTML storyboard of its actions and screenshots
+    Report(ReportArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct SchemaArgs {
+    /// Write the schema to this file instead of stdout
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct LintArgs {
+    /// Workflow YAML files to check
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+
+    /// Print diagnostics as JSON instead of text
+    #[arg(long)]
+    json: bool,
+
+    /// Exit with an error when there are warnings
+    #[arg(long)]
+    deny_warnings: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct TestArgs {
+    /// Workflow YAML file to replay
+    file: PathBuf,
+
+    /// Directory of fixtures recorded with 'terminator mcp run --capture-fixtures'
+    #[arg(long)]
+    fixtures: PathBuf,
+
+    /// JSON object with input values for workflow variables
+    #[arg(long)]
+    inputs: Option<String>,
+
+    /// Overwrite the golden report with this run's report
+    #[arg(long)]
+    update_golden: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct CodegenArgs {
+    /// Recording saved by 'record_workflow' (JSON) or workflow file (YAML/JSON)
+    file: PathBuf,
+
+    /// Language of the generated script: rust, python or typescript
+    #[arg(short, long, default_value = "typescript")]
+    lang: ScriptLanguage,
+
+    /// Write the script to this file instead of stdout
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct ReportArgs {
+    /// Recording saved by 'record_workflow' (JSON)
+    file: PathBuf,
+
+    /// Write the page to this file instead of next to the recording (screenshot
+    /// files are referenced relative to the recording)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+impl WorkflowCommand {
+    /// Runs the command; `Ok(false)` means the workflows have problems.
+    pub fn execute(&self) -> Result<bool> {
+        match self {
+            WorkflowCommand::Schema(args) => schema(args),
+            WorkflowCommand::Lint(args) => lint(args),
+            WorkflowCommand::Test(args) => test(args),
+            WorkflowCommand::Codegen(args) => codegen(args),
+            WorkflowCommand::Report(args) => report(args),
+        }
+    }
+}
+
+fn schema(args: &SchemaArgs) -> Result<bool> {
+    let schema = serde_json::to_string_pretty(&workflow_schema())?;
+    match &args.output {
+        Some(path) => {
+            fs::write(path, schema + "\n")
+                .with_context(|| format!("failed to write {}", path.display()))?;
+            eprintln!("{} schema written to {}", "✅".green(), path.display());
+        }
+        None => println!("{schema}"),
+    }
+    Ok(true)
+}
+
+fn lint(args: &LintArgs) -> Result<bool> {
+    let mut errors = 0;
+    let mut warnings = 0;
+    let mut report = Vec::new();
+    for file in &args.files {
+        let source = fs::read_to_string(file)
+            .with_context(|| format!("failed to read {}", file.display()))?;
+        let diagnostics = lint_workflow(&source);
+        for diagnostic in &diagnostics {
+            match diagnostic.severity {
+                Severity::Error => errors += 1,
+                Severity::Warning => warnings += 1,
+            }
+            if !args.json {
+                print_diagnostic(file, diagnostic);
+            }
+        }
+        if args.json {
+            report.push(serde_json::json!({
+                "file": file.display().to_string(),
+                "diagnostics": diagnostics,
+            }));
+        }
+    }
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else if errors == 0 && warnings == 0 {
+        println!(
+            "{} {} workflow file(s) checked, no problems found",
+            "✅".green(),
+            args.files.len()
+        );
+    } else {
+        println!(
+            "{} file(s) checked: {} error(s), {} warning(s)",
+            args.files.len(),
+            errors,
+            warnings
+        );
+    }
+    Ok(errors == 0 && (warnings == 0 || !args.deny_warnings))
+}
+
+fn test(args: &TestArgs) -> Result<bool> {
+    let inputs = args
+        .inputs
+        .as_deref()
+        .map(serde_json::from_str)
+        .transpose()
+        .context("invalid JSON in --inputs")?;
+    let report = tokio::runtime::Runtime::new()?.block_on(run_workflow_test(
+        &args.file,
+        &args.fixtures,
+        inputs,
+    ))?;
+
+    for call in report["calls"].as_array().into_iter().flatten() {
+        let mark = if call["status"] == "success" {
+            "✓".green()
+        } else {
+            "✗".red()
+        };
+        let step = call["step_id"]
+            .as_str()
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("#{}", call["step_index"]));
+        let actions = call["actions"].as_array().map_or(0, Vec::len);
+        let mut line = format!(
+            "{mark} {step} {} ({actions} action(s))",
+            call["tool_name"].as_str().unwrap_or_default()
+        );
+        if call["missing_fixture"] == true {
+            line.push_str(&format!(" {}", "[no fixture]".yellow()));
+        }
+        if let Some(error) = call["error"].as_str() {
+            line.push_str(&format!(": {}", error.dimmed()));
+        }
+        println!("  {line}");
+    }
+
+    let golden_path = args.fixtures.join(GOLDEN_FILE);
+    if args.update_golden || !golden_path.exists() {
+        fs::write(&golden_path, serde_json::to_string_pretty(&report)? + "\n")
+            .with_context(|| format!("failed to write {}", golden_path.display()))?;
+        println!(
+            "{} replay finished with status {}; golden report written to {}",
+            "✅".green(),
+            report["status"],
+            golden_path.display()
+        );
+        return Ok(true);
+    }
+
+    let golden: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(&golden_path)
+            .with_context(|| format!("failed to read {}", golden_path.display()))?,
+    )
+    .with_context(|| format!("invalid JSON in {}", golden_path.display()))?;
+    let differences = compare_reports(&golden, &report);
+    if differences.is_empty() {
+        println!("{} replay matches {}", "✅".green(), golden_path.display());
+        return Ok(true);
+    }
+    println!(
+        "{} replay differs from {}:",
+        "❌".red(),
+        golden_path.display()
+    );
+    for difference in &differences {
+        println!("    {difference}");
+    }
+    println!("Run again with --update-golden if the change is intended.");
+    Ok(false)
+}
+
+fn codegen(args: &CodegenArgs) -> Result<bool> {
+    let workflow = tokio::runtime::Runtime::new()?.block_on(load_workflow(&args.file))?;
+    if workflow.steps.is_empty() {
+        anyhow::bail!("{} has no steps to generate code from", args.file.display());
+    }
+    let script = generate_script(&workflow, args.lang);
+    match &args.output {
+        Some(path) => {
+            fs::write(path, script)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+            eprintln!(
+                "{} {} script with {} step(s) written to {}",
+                "✅".green(),
+                args.lang.as_str(),
+                workflow.steps.len(),
+                path.display()
+            );
+        }
+        None => print!("{script}"),
+    }
+    Ok(true)
+}
+
+fn report(args: &ReportArgs) -> Result<bool> {
+    let path = write_storyboard(&args.file, args.output.as_deref())
+        .with_context(|| format!("failed to render {}", args.file.display()))?;
+    eprintln!("{} Storyboard written to {}", "✅".green(), path.display());
+    Ok(true)
+}
+
+fn print_diagnostic(file: &std::path::Path, diagnostic: &Diagnostic) {
+    let severity = match diagnostic.severity {
+        Severity::Error => "error".red().bold(),
+        Severity::Warning => "warning".yellow().bold(),
+    };
+    println!(
+        "{}:{}:{}: {}: {}",
+        file.display(),
+        diagnostic.line,
+        diagnostic.column,
+        severity,
+        diagnostic.message
+    );
+    if !diagnostic.path.is_empty() {
+        println!("    {} {}", "at".dimmed(), diagnostic.path.dimmed());
+    }
+}
@@ -167,6 +167,10 @@ struct McpRunArgs {
     /// Example: --inputs '{"user":"john","count":5}'
     #[clap(long)]
     inputs: Option<String>,
+
+    /// Record a UI-tree fixture per step into this directory, for 'terminator workflow test'
+    #[clap(long)]
+    capture_fixtures: Option<PathBuf>,
 }
 
 #[derive(Subcommand)]
@@ -214,6 +218,14 @@ enum Commands {
     Mcp(McpCommands),
     /// Setup Terminator environment (Chrome extension, SDKs, dependencies)
     Setup(commands::setup::SetupCommand),
+    /// Inspect MCP agent audit logs
+    #[cfg(feature = "agent-tools")]
+    #[command(subcommand)]
+    Audit(commands::audit::AuditCommand),
+    /// Export the workflow JSON Schema or lint workflow files
+    #[cfg(feature = "agent-tools")]
+    #[command(subcommand)]
+    Workflow(commands::workflow::WorkflowCommand),
 }
 
 fn main() {
@@ -250,6 +262,22 @@ fn main() {
             full_release(&args.level.to_string());
         }
         Commands::Mcp(mcp_cmd) => handle_mcp_command(mcp_cmd),
+        #[cfg(feature = "agent-tools")]
+        Commands::Audit(audit_cmd) => {
+            if let Err(e) = audit_cmd.execute() {
+                eprintln!("❌ Audit verification failed: {e:#}");
+                std::process::exit(1);
+            }
+        }
+        #[cfg(feature = "agent-tools")]
+        Commands::Workflow(workflow_cmd) => match workflow_cmd.execute() {
+            Ok(true) => {}
+            Ok(false) => std::process::exit(1),
+            Err(e) => {
+                eprintln!("❌ Workflow command failed: {e:#}");
+                std::process::exit(1);
+            }
+        },
         Commands::Setup(setup_cmd) => {
             // Setup command doesn't require project root
             tokio::runtime::Builder::new_multi_thread()
@@ -1393,6 +1421,10 @@ async fn run_workflow(transport: mcp_client::Transport, args: McpRunArgs) -> any
             }
         }
 
+        if let Some(dir) = &args.capture_fixtures {
+            workflow_args.insert("capture_fixtures".to_string(), capture_dir_value(dir)?);
+        }
+
         let workflow_str = serde_json::to_string(&workflow_args)?;
         info!("Sending workflow_args to MCP: {}", workflow_str);
         workflow_str
@@ -1410,6 +1442,11 @@ async fn run_workflow(transport: mcp_client::Transport, args: McpRunArgs) -> any
                 }
             }
         }
+        if let Some(dir) = &args.capture_fixtures {
+            if let Some(obj) = workflow_val.as_object_mut() {
+                obj.insert("capture_fixtures".to_string(), capture_dir_value(dir)?);
+            }
+        }
 
         // For remote sources, send the entire parsed content
         serde_json::to_string(&workflow_val)?
@@ -1679,6 +1716,10 @@ async fn run_workflow_once(
             }
         }
 
+        if let Some(dir) = &args.capture_fixtures {
+            workflow_args.insert("capture_fixtures".to_string(), capture_dir_value(dir)?);
+        }
+
         let workflow_str = serde_json::to_string(&workflow_args)?;
         info!("Sending workflow_args to MCP: {}", workflow_str);
         workflow_str
@@ -1696,6 +1737,11 @@ async fn run_workflow_once(
                 }
             }
         }
+        if let Some(dir) = &args.capture_fixtures {
+            if let Some(obj) = workflow_val.as_object_mut() {
+                obj.insert("capture_fixtures".to_string(), capture_dir_value(dir)?);
+            }
+        }
 
         // For remote sources, send the entire parsed content
         serde_json::to_string(&workflow_val)?
@@ -1740,6 +1786,13 @@ async fn run_workflow_once(
     Ok(())
 }
 
+/// The fixtures directory as an absolute path, since the MCP server may run elsewhere
+fn capture_dir_value(dir: &Path) -> anyhow::Result<Value> {
+    let dir = std::path::absolute(dir)
+        .with_context(|| format!("Failed to resolve path: {}", dir.display()))?;
+    Ok(Value::String(dir.display().to_string()))
+}
+
 fn determine_input_type(input: &str, specified_type: InputType) -> InputType {
     match specified_type {
         InputType::Auto => {
@@ -1,9 +1,16 @@
 use anyhow::Result;
 use rmcp::{
-    model::{CallToolRequestParam, ClientCapabilities, ClientInfo, Implementation},
+    model::{
+        CallToolRequestParam, ClientCapabilities, ClientInfo, Implementation, LoggingLevel,
+        LoggingMessageNotificationParam, ProgressNotificationParam,
+    },
     object,
-    transport::{StreamableHttpClientTransport, TokioChildProcess},
-    ServiceExt,
+    service::{NotificationContext, RoleClient},
+    transport::{
+        streamable_http_client::StreamableHttpClientTransportConfig, StreamableHttpClientTransport,
+        TokioChildProcess,
+    },
+    ClientHandler, ServiceExt,
 };
 use std::io::{self, Write};
 use std::time::Duration;
@@ -82,6 +89,16 @@ fn find_executable(name: &str) -> Option<String> {
     Some(name.to_string())
 }
 
+/// Streamable HTTP transport for `url`, sending the bearer token from
+/// `MCP_AUTH_TOKEN` when the server was started with `--auth-token`.
+fn http_transport(url: &str) -> StreamableHttpClientTransport<reqwest::Client> {
+    let mut config = StreamableHttpClientTransportConfig::with_uri(url);
+    if let Ok(token) = std::env::var("MCP_AUTH_TOKEN") {
+        config = config.auth_header(token);
+    }
+    StreamableHttpClientTransport::from_config(config)
+}
+
 pub async fn interactive_chat(transport: Transport) -> Result<()> {
     println!("🤖 Terminator MCP Chat Client");
     println!("=============================");
@@ -89,7 +106,7 @@ pub async fn interactive_chat(transport: Transport) -> Result<()> {
     match transport {
         Transport::Http(url) => {
             println!("Connecting to: {url}");
-            let transport = StreamableHttpClientTransport::from_uri(url.as_str());
+            let transport = http_transport(&url);
             let client_info = ClientInfo {
                 protocol_version: Default::default(),
                 capabilities: ClientCapabilities::default(),
@@ -430,7 +447,7 @@ pub async fn execute_command(
     match transport {
         Transport::Http(url) => {
             info!("Connecting to server: {}", url);
-            let transport = StreamableHttpClientTransport::from_uri(url.as_str());
+            let transport = http_transport(&url);
             let client_info = ClientInfo {
                 protocol_version: Default::default(),
                 capabilities: ClientCapabilities::default(),
@@ -638,7 +655,6 @@ fn init_logging() {
 }
 
 // Helper function to parse step start logs
-#[allow(dead_code)]
 fn parse_step_log(line: &str) -> Option<(String, String, String)> {
     // Parse lines like: "Step 0 BEGIN tool='open_application' id='open_notepad' ..."
     if let Some(step_idx) = line.find("Step ") {
@@ -672,7 +688,6 @@ fn parse_step_log(line: &str) -> Option<(String, String, String)> {
 }
 
 // Helper function to parse step end logs
-#[allow(dead_code)]
 fn parse_step_end_log(line: &str) -> Option<(String, String)> {
     // Parse lines like: "Step 0 END tool='open_application' id='open_notepad' status=success"
     if let Some(step_idx) = line.find("Step ") {
@@ -689,6 +704,107 @@ fn parse_step_end_log(line: &str) -> Option<(String, String)> {
     None
 }
 
+/// Client handler for `execute_sequence` runs that prints the server's progress and
+/// log notifications as they arrive.
+#[derive(Clone)]
+struct WorkflowProgressRenderer {
+    show_progress: bool,
+    /// Steps are already printed from the agent's OTLP spans by the telemetry receiver
+    steps_from_telemetry: bool,
+}
+
+impl ClientHandler for WorkflowProgressRenderer {
+    async fn on_progress(
+        &self,
+        params: ProgressNotificationParam,
+        context: NotificationContext<RoleClient>,
+    ) {
+        use colored::Colorize;
+
+        if !self.show_progress || self.steps_from_telemetry {
+            return;
+        }
+        // The agent describes the step in `_meta`; the message is only for display
+        let Some(step) = context.meta.0.get("terminator/step") else {
+            if let Some(message) = params.message {
+                println!("  {} {message}", "•".dimmed());
+            }
+            return;
+        };
+        let status = step["status"].as_str().unwrap_or_default();
+        let icon = match status {
+            "running" => "▶".blue(),
+            "success" => "✓".green(),
+            "skipped" => "⏭".yellow(),
+            _ => "✗".red(),
+        };
+        let id = step["id"]
+            .as_str()
+            .map(|id| format!(" [{id}]"))
+            .unwrap_or_default();
+        let mut line = format!(
+            "Step {}/{}{id} {}: {status}",
+            step["index"].as_u64().unwrap_or(0) + 1,
+            step["total"].as_u64().unwrap_or(0),
+            step["tool"].as_str().unwrap_or("?"),
+        );
+        if let Some(duration_ms) = step["duration_ms"].as_u64() {
+            line.push_str(&format!(" in {duration_ms}ms"));
+        }
+        let (attempt, max_attempts) = (
+            step["attempt"].as_u64().unwrap_or(1),
+            step["max_attempts"].as_u64().unwrap_or(1),
+        );
+        if attempt > 1 {
+            line.push_str(&format!(" (attempt {attempt}/{max_attempts})"));
+        }
+        println!("  {icon} {line}");
+    }
+
+    async fn on_logging_message(
+        &self,
+        params: LoggingMessageNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        use colored::Colorize;
+
+        if !self.show_progress {
+            return;
+        }
+        let message = params
+            .data
+            .get("message")
+            .and_then(|m| m.as_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| params.data.to_string());
+        // Step boundaries are already shown from progress notifications
+        let is_step_boundary = (message.contains(" BEGIN ") && parse_step_log(&message).is_some())
+            || (message.contains(" END ") && parse_step_end_log(&message).is_some());
+        if is_step_boundary {
+            return;
+        }
+        let line = format!("    │ {message}");
+        match params.level {
+            LoggingLevel::Warning => println!("{}", line.yellow()),
+            LoggingLevel::Info | LoggingLevel::Notice | LoggingLevel::Debug => {
+                println!("{}", line.dimmed())
+            }
+            _ => println!("{}", line.red()),
+        }
+    }
+
+    fn get_info(&self) -> ClientInfo {
+        ClientInfo {
+            protocol_version: Default::default(),
+            capabilities: ClientCapabilities::default(),
+            client_info: Implementation {
+                name: "terminator-cli".to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+            },
+        }
+    }
+}
+
 pub async fn natural_language_chat(transport: Transport) -> Result<()> {
     println!("🤖 Terminator Natural Language Chat Client");
     println!("==========================================");
@@ -709,7 +825,7 @@ pub async fn natural_language_chat(transport: Transport) -> Result<()> {
     let service = match transport {
         Transport::Http(url) => {
             println!("Connecting to MCP server: {url}");
-            let transport = StreamableHttpClientTransport::from_uri(url.as_str());
+            let transport = http_transport(&url);
             let client_info = ClientInfo {
                 protocol_version: Default::default(),
                 capabilities: ClientCapabilities::default(),
@@ -999,40 +1115,23 @@ pub async fn execute_command_with_progress_and_retry(
     use colored::Colorize;
     use tracing::debug;
 
-    // Start telemetry receiver if showing progress for workflows
-    let telemetry_handle = if show_progress && tool == "execute_sequence" {
-        match crate::telemetry_receiver::start_telemetry_receiver().await {
-            Ok(handle) => {
-                debug!("Started telemetry receiver on port 4318");
-                Some(handle)
-            }
-            Err(e) => {
-                debug!("Failed to start telemetry receiver: {}", e);
-                None
-            }
-        }
-    } else {
-        None
-    };
-
     // Special handling for execute_sequence to capture full result
     if tool == "execute_sequence" {
         match transport {
             Transport::Http(url) => {
                 debug!("Connecting to server: {}", url);
-                let transport = StreamableHttpClientTransport::from_uri(url.as_str());
-                let client_info = ClientInfo {
-                    protocol_version: Default::default(),
-                    capabilities: ClientCapabilities::default(),
-                    client_info: Implementation {
-                        name: "terminator-cli".to_string(),
-                        version: env!("CARGO_PKG_VERSION").to_string(),
-                    },
-                };
+                let transport = http_transport(&url);
 
                 // Connection setup - no retry here as StreamableHttpClientTransport doesn't support cloning
                 // Retries will be handled at the tool call level
-                let service = client_info.serve(transport).await?;
+                // rmcp attaches a progress token to every request, so the server streams
+                // step progress and logs back as notifications
+                let service = WorkflowProgressRenderer {
+                    show_progress,
+                    steps_from_telemetry: false,
+                }
+                .serve(transport)
+                .await?;
 
                 let arguments = if let Some(args_str) = args {
                     serde_json::from_str::<serde_json::Value>(&args_str)
@@ -1128,12 +1227,6 @@ pub async fn execute_command_with_progress_and_retry(
                                 serde_json::from_str::<serde_json::Value>(&text.text)
                             {
                                 service.cancel().await?;
-
-                                // Stop telemetry receiver if it was started
-                                if let Some(handle) = telemetry_handle {
-                                    handle.abort();
-                                }
-
                                 return Ok(json_result);
                             }
                         }
@@ -1142,11 +1235,6 @@ pub async fn execute_command_with_progress_and_retry(
 
                 service.cancel().await?;
 
-                // Stop telemetry receiver if it was started
-                if let Some(handle) = telemetry_handle {
-                    handle.abort();
-                }
-
                 Ok(json!({"status": "unknown", "message": "No parseable result from workflow"}))
             }
             Transport::Stdio(command) => {
@@ -1169,17 +1257,32 @@ pub async fn execute_command_with_progress_and_retry(
                     }
                 }
 
-                // Enable telemetry if showing progress
-                if show_progress {
-                    cmd.env("OTEL_EXPORTER_OTLP_ENDPOINT", "http://localhost:4318");
-                    cmd.env("OTEL_SERVICE_NAME", "terminator-mcp");
-                    cmd.env("ENABLE_TELEMETRY", "true");
-                }
+                // Start telemetry receiver if showing progress for workflows
+                let telemetry_handle = if show_progress {
+                    match crate::telemetry_receiver::start_telemetry_receiver().await {
+                        Ok(handle) => {
+                            debug!("Started telemetry receiver on port 4318");
+                            cmd.env("OTEL_EXPORTER_OTLP_ENDPOINT", "http://localhost:4318");
+                            cmd.env("OTEL_SERVICE_NAME", "terminator-mcp");
+                            cmd.env("ENABLE_TELEMETRY", "true");
+                            Some(handle)
+                        }
+                        Err(e) => {
+                            debug!("Failed to start telemetry receiver: {}", e);
+                            None
+                        }
+                    }
+                } else {
+                    None
+                };
 
-                // For now, just use the standard transport without stderr parsing
-                // TODO: Add proper step streaming once MCP protocol supports it
                 let transport = TokioChildProcess::new(cmd)?;
-                let service = ().serve(transport).await?;
+                let service = WorkflowProgressRenderer {
+                    show_progress,
+                    steps_from_telemetry: telemetry_handle.is_some(),
+                }
+                .serve(transport)
+                .await?;
 
                 let arguments = if let Some(args_str) = args {
                     // Parse workflow to show initial progress
@@ -32,6 +32,10 @@ pub struct WorkflowResult {
     pub duration_ms: Option<u64>,
     /// Number of steps executed
     pub executed_steps: Option<usize>,
+    /// Results of the workflow's `on_failure` cleanup steps (if they ran)
+    pub on_failure_results: Option<Vec<Value>>,
+    /// Results of the workflow's `finally` steps (if any)
+    pub finally_results: Option<Vec<Value>>,
 }
 
 impl WorkflowResult {
@@ -142,6 +146,16 @@ impl WorkflowResult {
             .and_then(|e| e.as_u64())
             .map(|n| n as usize);
 
+        // Cleanup blocks are reported separately from the main step results
+        let on_failure_results = response
+            .get("on_failure_results")
+            .and_then(|r| r.as_array())
+            .cloned();
+        let finally_results = response
+            .get("finally_results")
+            .and_then(|r| r.as_array())
+            .cloned();
+
         Ok(WorkflowResult {
             execution_status,
             success,
@@ -152,6 +166,8 @@ impl WorkflowResult {
             validation,
             duration_ms,
             executed_steps,
+            on_failure_results,
+            finally_results,
         })
     }
 
@@ -246,6 +262,31 @@ impl WorkflowResult {
             println!("{} {}", "⚠️  Error:".yellow(), error);
         }
 
+        // Display cleanup block results if present
+        if self.on_failure_results.is_some() || self.finally_results.is_some() {
+            println!("{}", "─".repeat(60));
+            println!("🧹 Cleanup:");
+            for (block, results) in [
+                ("on_failure", &self.on_failure_results),
+                ("finally", &self.finally_results),
+            ] {
+                for result in results.iter().flatten() {
+                    let tool = result
+                        .get("tool_name")
+                        .and_then(|t| t.as_str())
+                        .unwrap_or("unknown");
+                    let status = result
+                        .get("status")
+                        .and_then(|s| s.as_str())
+                        .unwrap_or("unknown");
+                    println!("   • {block}: {tool} ({status})");
+                    if let Some(error) = result.get("error").and_then(|e| e.as_str()) {
+                        println!("     {}", error.red());
+                    }
+                }
+            }
+        }
+
         println!("{}", "═".repeat(60));
         println!();
     }
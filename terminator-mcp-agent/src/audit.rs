@@ -0,0 +1,537 @@
+//! Tamper-evident audit log of tool calls (`--audit-log <DIR>`).
+//!
+//! Every tool invocation, including the steps of `execute_sequence`, is appended to
+//! `<DIR>/audit.jsonl` as one JSON object per line. Each entry carries the hash of
+//! the entry before it (`prev_hash`) and its own `hash`, a SHA-256 over the entry's
+//! canonical JSON (keys sorted, `hash` left out), so editing, removing or reordering
+//! lines breaks the chain. When the file grows past the size limit it is renamed to
+//! `audit-<last seq>-<timestamp>.jsonl` and the chain continues in a fresh `audit.jsonl`.
+//!
+//! `terminator audit verify <DIR|FILE>` checks the chain.
+
+use chrono::Utc;
+use rmcp::model::{CallToolResult, RawContent};
+use rmcp::ErrorData as McpError;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::warn;
+
+/// `prev_hash` of the first entry in a chain.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+const CURRENT_FILE: &str = "audit.jsonl";
+const REDACTED: &str = "[REDACTED]";
+
+/// Arguments that carry the user's data (typed text, scripts, values) rather than
+/// parameters, per tool. They never reach the log, whatever their name.
+const SENSITIVE_ARGUMENTS: &[(&str, &[&str])] = &[
+    ("type_into_element", &["text_to_type"]),
+    ("set_value", &["value"]),
+    ("run_command", &["run", "env"]),
+    ("execute_browser_script", &["script", "env"]),
+    ("debug_eval", &["expression"]),
+    ("debug_set_var", &["value"]),
+];
+
+/// Argument keys whose values never reach the log (matched case-insensitively as substrings).
+const SECRET_KEYS: &[&str] = &[
+    "password",
+    "passwd",
+    "secret",
+    "token",
+    "api_key",
+    "apikey",
+    "authorization",
+    "credential",
+    "private_key",
+    "cookie",
+];
+
+/// One line of the audit log.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntry {
+    pub seq: u64,
+    pub timestamp: String,
+    pub identity: String,
+    pub request_id: String,
+    pub tool: String,
+    /// Call arguments with secret-looking values and the tool's sensitive arguments
+    /// replaced by `[REDACTED]`.
+    pub arguments: Value,
+    /// The UI element the tool resolved, when its result reports one.
+    pub element: Option<Value>,
+    /// `success`, `error` or `denied` (rejected by the tool policy).
+    pub outcome: String,
+    pub error: Option<String>,
+    pub duration_ms: u64,
+    /// SHA-256 of the first image returned by the tool, if any.
+    pub screenshot_sha256: Option<String>,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+/// What a single tool call contributes to its audit entry.
+pub struct AuditRecord<'a> {
+    pub identity: String,
+    pub request_id: String,
+    pub tool: &'a str,
+    pub arguments: &'a Value,
+    pub result: &'a Result<CallToolResult, McpError>,
+    pub duration: Duration,
+}
+
+struct State {
+    file: File,
+    size: u64,
+    seq: u64,
+    last_hash: String,
+}
+
+/// Append-only, hash-chained JSONL sink shared by every session of the server.
+pub struct AuditLog {
+    dir: PathBuf,
+    max_bytes: u64,
+    state: Mutex<State>,
+}
+
+impl AuditLog {
+    /// Open (or resume) the log in `dir`, rotating `audit.jsonl` once it exceeds `max_bytes`.
+    pub fn open(dir: impl Into<PathBuf>, max_bytes: u64) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+
+        // Resume the chain from the newest entry, which may sit in a rotated file
+        let mut files = rotated_files(&dir)?;
+        files.push(dir.join(CURRENT_FILE));
+        let mut seq = 0;
+        let mut last_hash = GENESIS_HASH.to_string();
+        for path in files.iter().rev() {
+            if let Some(last) = last_entry(path)? {
+                seq = last["seq"].as_u64().unwrap_or(0);
+                last_hash = last["hash"].as_str().unwrap_or(GENESIS_HASH).to_string();
+                break;
+            }
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(CURRENT_FILE))?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            dir,
+            max_bytes,
+            state: Mutex::new(State {
+                file,
+                size,
+                seq,
+                last_hash,
+            }),
+        })
+    }
+
+    /// Append an entry for a finished tool call. Failures are logged, never returned,
+    /// so a full disk can't break automation.
+    pub fn record(&self, record: AuditRecord<'_>) {
+        if let Err(e) = self.append(record) {
+            warn!("Failed to write audit log entry: {}", e);
+        }
+    }
+
+    fn append(&self, record: AuditRecord<'_>) -> io::Result<()> {
+        let (outcome, error) = outcome(record.result);
+        let (element, screenshot_sha256) = match record.result {
+            Ok(result) => (resolved_element(result), screenshot_hash(result)),
+            Err(_) => (None, None),
+        };
+
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.size >= self.max_bytes {
+            self.rotate(&mut state)?;
+        }
+
+        let mut entry = AuditEntry {
+            seq: state.seq + 1,
+            timestamp: Utc::now().to_rfc3339(),
+            identity: record.identity,
+            request_id: record.request_id,
+            tool: record.tool.to_string(),
+            arguments: redact_arguments(record.tool, record.arguments),
+            element,
+            outcome: outcome.to_string(),
+            error,
+            duration_ms: record.duration.as_millis() as u64,
+            screenshot_sha256,
+            prev_hash: state.last_hash.clone(),
+            hash: String::new(),
+        };
+        entry.hash = entry_hash(&serde_json::to_value(&entry)?);
+
+        let mut line = serde_json::to_string(&entry)?;
+        line.push('\n');
+        state.file.write_all(line.as_bytes())?;
+        state.file.flush()?;
+        state.size += line.len() as u64;
+        state.seq = entry.seq;
+        state.last_hash = entry.hash;
+        Ok(())
+    }
+
+    fn rotate(&self, state: &mut State) -> io::Result<()> {
+        // The last sequence number keeps names unique and in chain order
+        let rotated = self.dir.join(format!(
+            "audit-{:012}-{}.jsonl",
+            state.seq,
+            Utc::now().format("%Y%m%dT%H%M%S%.3fZ")
+        ));
+        if rotated.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already exists", rotated.display()),
+            ));
+        }
+        fs::rename(self.dir.join(CURRENT_FILE), rotated)?;
+        state.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.dir.join(CURRENT_FILE))?;
+        state.size = 0;
+        Ok(())
+    }
+}
+
+/// Files of the chain in `dir` in write order: rotated files, then `audit.jsonl`.
+pub fn chain_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = rotated_files(dir)?;
+    let current = dir.join(CURRENT_FILE);
+    if current.is_file() {
+        files.push(current);
+    }
+    Ok(files)
+}
+
+/// Rotated files in `dir`, oldest first.
+fn rotated_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with("audit-") && n.ends_with(".jsonl"))
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+fn last_entry(path: &Path) -> io::Result<Option<Value>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let mut last = None;
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            last = Some(line);
+        }
+    }
+    Ok(last.and_then(|line| serde_json::from_str(&line).ok()))
+}
+
+/// SHA-256 (hex) of an entry's canonical JSON with the `hash` field removed.
+pub fn entry_hash(entry: &Value) -> String {
+    let mut entry = entry.clone();
+    if let Some(obj) = entry.as_object_mut() {
+        obj.remove("hash");
+    }
+    let mut canonical = String::new();
+    write_canonical(&entry, &mut canonical);
+    hex(ring::digest::digest(&ring::digest::SHA256, canonical.as_bytes()).as_ref())
+}
+
+/// Compact JSON with object keys sorted, independent of serde_json's map ordering.
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            out.push('{');
+            for (i, key) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(&map[key], out);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        scalar => out.push_str(&scalar.to_string()),
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Copy of the arguments of a `tool` call with secret-looking keys and the tool's
+/// sensitive arguments masked. Steps nested in the arguments (`execute_sequence`,
+/// `debug_start`) are masked for their own tool.
+pub fn redact_arguments(tool: &str, arguments: &Value) -> Value {
+    let mut redacted = redact(arguments);
+    mask_sensitive_arguments(tool, &mut redacted);
+    redacted
+}
+
+fn mask_sensitive_arguments(tool: &str, arguments: &mut Value) {
+    let tool = tool
+        .strip_prefix("mcp_terminator-mcp-agent_")
+        .unwrap_or(tool);
+    let sensitive = SENSITIVE_ARGUMENTS
+        .iter()
+        .find(|(name, _)| *name == tool)
+        .map(|(_, keys)| *keys)
+        .unwrap_or_default();
+    if let Value::Object(map) = arguments {
+        for (key, value) in map.iter_mut() {
+            if sensitive.contains(&key.as_str()) && !value.is_null() {
+                *value = json!(REDACTED);
+            }
+        }
+    }
+    mask_nested_steps(arguments);
+}
+
+/// Mask the arguments of every `{"tool_name": ..., "arguments": ...}` step in `value`.
+fn mask_nested_steps(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            let step_tool = map
+                .get("tool_name")
+                .and_then(Value::as_str)
+                .map(str::to_string);
+            for (key, nested) in map.iter_mut() {
+                match (&step_tool, key.as_str()) {
+                    (Some(tool), "arguments") => mask_sensitive_arguments(tool, nested),
+                    _ => mask_nested_steps(nested),
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(mask_nested_steps),
+        _ => {}
+    }
+}
+
+/// Copy of `value` with secret-looking keys masked, at any depth.
+pub fn redact(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| {
+                    let lower = key.to_lowercase();
+                    let value = if SECRET_KEYS.iter().any(|secret| lower.contains(secret)) {
+                        json!(REDACTED)
+                    } else {
+                        redact(value)
+                    };
+                    (key.clone(), value)
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(redact).collect()),
+        other => other.clone(),
+    }
+}
+
+fn outcome(result: &Result<CallToolResult, McpError>) -> (&'static str, Option<String>) {
+    match result {
+        Ok(result) if result.is_error == Some(true) => ("error", None),
+        Ok(_) => ("success", None),
+        Err(e) => {
+            let denied = e
+                .data
+                .as_ref()
+                .is_some_and(|data| data["error_type"] == "policy_denied");
+            (
+                if denied { "denied" } else { "error" },
+                Some(e.message.to_string()),
+            )
+        }
+    }
+}
+
+/// Role, name, app, window and bounds of the element a tool reported acting on.
+fn resolved_element(result: &CallToolResult) -> Option<Value> {
+    result.content.iter().find_map(|content| {
+        let RawContent::Text(text) = &content.raw else {
+            return None;
+        };
+        let parsed: Value = serde_json::from_str(&text.text).ok()?;
+        let element = parsed.get("element").filter(|e| e.is_object())?;
+        Some(json!({
+            "role": element.get("role"),
+            "name": element.get("name"),
+            "application": element.get("application"),
+            "window_title": element.get("window_title"),
+            "bounds": element.get("bounds"),
+        }))
+    })
+}
+
+fn screenshot_hash(result: &CallToolResult) -> Option<String> {
+    use base64::Engine;
+
+    result.content.iter().find_map(|content| {
+        let RawContent::Image(image) = &content.raw else {
+            return None;
+        };
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(&image.data)
+            .ok()?;
+        Some(hex(
+            ring::digest::digest(&ring::digest::SHA256, &bytes).as_ref()
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::Content;
+
+    fn record<'a>(
+        tool: &'a str,
+        arguments: &'a Value,
+        result: &'a Result<CallToolResult, McpError>,
+    ) -> AuditRecord<'a> {
+        AuditRecord {
+            identity: "ci".to_string(),
+            request_id: "7".to_string(),
+            tool,
+            arguments,
+            result,
+            duration: Duration::from_millis(5),
+        }
+    }
+
+    fn read_entries(path: &Path) -> Vec<Value> {
+        fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_entries_are_chained_and_redacted() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = AuditLog::open(dir.path(), u64::MAX).unwrap();
+        let args =
+            json!({"selector": "role:Edit", "env": {"API_TOKEN": "abc"}, "password": "hunter2"});
+        let clicked = Ok(CallToolResult::success(vec![Content::json(json!({
+            "element": {"role": "Button", "name": "OK", "application": "app", "window_title": "w", "bounds": {"x": 1}}
+        }))
+        .unwrap()]));
+        log.record(record("click_element", &args, &clicked));
+        let denied = Err(McpError::invalid_request(
+            "Tool call denied by policy",
+            Some(json!({"error_type": "policy_denied"})),
+        ));
+        log.record(record("run_command", &json!({}), &denied));
+
+        let entries = read_entries(&dir.path().join(CURRENT_FILE));
+        assert_eq!(entries[0]["prev_hash"], GENESIS_HASH);
+        assert_eq!(entries[0]["arguments"]["password"], REDACTED);
+        assert_eq!(entries[0]["arguments"]["env"]["API_TOKEN"], REDACTED);
+        assert_eq!(entries[0]["arguments"]["selector"], "role:Edit");
+        assert_eq!(entries[0]["element"]["name"], "OK");
+        assert_eq!(entries[1]["outcome"], "denied");
+        assert_eq!(entries[1]["prev_hash"], entries[0]["hash"]);
+        for entry in &entries {
+            assert_eq!(entry["hash"].as_str().unwrap(), entry_hash(entry));
+        }
+
+        let mut tampered = entries[0].clone();
+        tampered["tool"] = json!("get_applications");
+        assert_ne!(tampered["hash"].as_str().unwrap(), entry_hash(&tampered));
+    }
+
+    #[test]
+    fn test_sensitive_arguments_are_redacted_per_tool() {
+        let typed = redact_arguments(
+            "type_into_element",
+            &json!({"selector": "role:Edit", "text_to_type": "hunter2"}),
+        );
+        assert_eq!(typed["text_to_type"], REDACTED);
+        assert_eq!(typed["selector"], "role:Edit");
+        assert_eq!(
+            redact_arguments("mcp_terminator-mcp-agent_run_command", &json!({"run": "x"}))["run"],
+            REDACTED
+        );
+        // The same key is kept for tools where it is a parameter
+        assert_eq!(
+            redact_arguments("set_range_value", &json!({"value": 5}))["value"],
+            5
+        );
+
+        let sequence = redact_arguments(
+            "execute_sequence",
+            &json!({"steps": [
+                {"tool_name": "type_into_element", "arguments": {"text_to_type": "hunter2"}},
+                {"group_name": "g", "steps": [
+                    {"tool_name": "run_command", "arguments": {"run": "curl -u me:pw"}}
+                ]},
+                {"tool_name": "click_element", "arguments": {"selector": "name:OK"}}
+            ]}),
+        );
+        assert_eq!(sequence["steps"][0]["arguments"]["text_to_type"], REDACTED);
+        assert_eq!(
+            sequence["steps"][1]["steps"][0]["arguments"]["run"],
+            REDACTED
+        );
+        assert_eq!(sequence["steps"][2]["arguments"]["selector"], "name:OK");
+    }
+
+    #[test]
+    fn test_rotation_and_reopen_continue_the_chain() {
+        let dir = tempfile::tempdir().unwrap();
+        let ok = Ok(CallToolResult::success(vec![]));
+        {
+            let log = AuditLog::open(dir.path(), 1).unwrap();
+            log.record(record("a", &json!({}), &ok));
+            log.record(record("b", &json!({}), &ok));
+        }
+        let rotated = rotated_files(dir.path()).unwrap();
+        assert_eq!(rotated.len(), 1);
+        let first = read_entries(&rotated[0]);
+        assert!(rotated[0]
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with("audit-000000000001-"));
+
+        let log = AuditLog::open(dir.path(), u64::MAX).unwrap();
+        log.record(record("c", &json!({}), &ok));
+        let current = read_entries(&dir.path().join(CURRENT_FILE));
+        assert_eq!(current[0]["prev_hash"], first[0]["hash"]);
+        assert_eq!(current[1]["seq"], 3);
+        assert_eq!(current[1]["prev_hash"], current[0]["hash"]);
+    }
+}
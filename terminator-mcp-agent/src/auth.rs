@@ -0,0 +1,251 @@
+//! Bearer-token authentication for the HTTP and SSE transports.
+//!
+//! Tokens come from `--auth-token` and the `MCP_AUTH_TOKENS` environment variable,
+//! each written as `name:token` (or a bare `token`). Every authenticated request
+//! carries an [`AuthIdentity`] naming the token that was used; it reaches tool
+//! handlers through the HTTP request parts rmcp stores on the `RequestContext`.
+
+use anyhow::{anyhow, Result};
+use axum::{
+    body::Body,
+    extract::State,
+    http::{header, request::Parts, Method, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use rmcp::service::{RequestContext, RoleServer};
+use std::future::Future;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// Environment variable holding comma-separated `name:token` pairs.
+pub const AUTH_TOKENS_ENV: &str = "MCP_AUTH_TOKENS";
+
+/// Paths that stay reachable without a token (load balancer and CI health probes).
+const PUBLIC_PATHS: &[&str] = &["/health"];
+
+/// The named token a request was authenticated with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthIdentity {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AuthConfig {
+    /// (name, token) pairs.
+    tokens: Vec<(String, String)>,
+}
+
+impl AuthConfig {
+    /// Build the token table from CLI values and the `MCP_AUTH_TOKENS` value.
+    /// Bare tokens are named `token-<n>` in the order they were given.
+    pub fn from_sources(cli_tokens: &[String], env_tokens: Option<&str>) -> Result<Self> {
+        let entries = cli_tokens
+            .iter()
+            .map(String::as_str)
+            .chain(env_tokens.into_iter().flat_map(|v| v.split(',')))
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty());
+
+        let mut tokens: Vec<(String, String)> = Vec::new();
+        for (index, entry) in entries.enumerate() {
+            let (name, token) = match entry.split_once(':') {
+                Some((name, token)) => (name.trim().to_string(), token.trim().to_string()),
+                None => (format!("token-{}", index + 1), entry.to_string()),
+            };
+            if name.is_empty() || token.is_empty() {
+                return Err(anyhow!(
+                    "Invalid auth token entry '{}': expected 'name:token'",
+                    entry
+                ));
+            }
+            if tokens.iter().any(|(existing, _)| *existing == name) {
+                return Err(anyhow!("Duplicate auth token name '{}'", name));
+            }
+            tokens.push((name, token));
+        }
+        Ok(Self { tokens })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.tokens.is_empty()
+    }
+
+    pub fn token_names(&self) -> impl Iterator<Item = &str> {
+        self.tokens.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// All tokens as a `MCP_AUTH_TOKENS` value, so a child process can be given
+    /// them without putting them on its command line.
+    pub fn to_env_value(&self) -> String {
+        self.tokens
+            .iter()
+            .map(|(name, token)| format!("{name}:{token}"))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// Find the identity for a presented token. Every configured token is compared
+    /// in constant time so the response time doesn't reveal which one was close.
+    pub fn authenticate(&self, presented: &str) -> Option<AuthIdentity> {
+        let mut matched = None;
+        for (name, token) in &self.tokens {
+            if constant_time_eq(token.as_bytes(), presented.as_bytes()) {
+                matched = Some(AuthIdentity { name: name.clone() });
+            }
+        }
+        matched
+    }
+}
+
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn unauthorized(message: &str) -> Response {
+    let body = serde_json::json!({
+        "error": {
+            "code": 401,
+            "message": message,
+        }
+    });
+    (
+        StatusCode::UNAUTHORIZED,
+        [(header::WWW_AUTHENTICATE, "Bearer")],
+        Json(body),
+    )
+        .into_response()
+}
+
+/// axum middleware requiring `Authorization: Bearer <token>` on every route except
+/// the public health probe and CORS preflights.
+pub async fn require_bearer_token(
+    State(config): State<Arc<AuthConfig>>,
+    mut req: Request<Body>,
+    next: Next,
+) -> Response {
+    if req.method() == Method::OPTIONS || PUBLIC_PATHS.contains(&req.uri().path()) {
+        return next.run(req).await;
+    }
+
+    let presented = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| {
+            v.strip_prefix("Bearer ")
+                .or_else(|| v.strip_prefix("bearer "))
+        })
+        .map(str::trim);
+
+    let Some(presented) = presented else {
+        debug!(
+            "Rejected {} {}: missing bearer token",
+            req.method(),
+            req.uri()
+        );
+        return unauthorized("Missing bearer token");
+    };
+    let Some(identity) = config.authenticate(presented) else {
+        warn!(
+            "Rejected {} {}: invalid bearer token",
+            req.method(),
+            req.uri()
+        );
+        return unauthorized("Invalid bearer token");
+    };
+
+    debug!(
+        "Authenticated {} {} as '{}'",
+        req.method(),
+        req.uri(),
+        identity.name
+    );
+    req.extensions_mut().insert(identity);
+    next.run(req).await
+}
+
+/// Identity of the client that sent an MCP request, if the transport authenticated it.
+pub fn identity_from_context(context: &RequestContext<RoleServer>) -> Option<AuthIdentity> {
+    context
+        .extensions
+        .get::<AuthIdentity>()
+        .cloned()
+        .or_else(|| {
+            context
+                .extensions
+                .get::<Parts>()
+                .and_then(|parts| parts.extensions.get::<AuthIdentity>())
+                .cloned()
+        })
+}
+
+tokio::task_local! {
+    static CURRENT_IDENTITY: AuthIdentity;
+}
+
+/// Run `fut` with `identity` as the current identity, so telemetry spans started
+/// inside it are attributed to the caller.
+pub async fn with_identity<F: Future>(identity: Option<AuthIdentity>, fut: F) -> F::Output {
+    match identity {
+        Some(identity) => CURRENT_IDENTITY.scope(identity, fut).await,
+        None => fut.await,
+    }
+}
+
+/// Name of the identity the current task is running for.
+pub fn current_identity() -> Option<String> {
+    CURRENT_IDENTITY
+        .try_with(|identity| identity.name.clone())
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_sources() {
+        let config = AuthConfig::from_sources(
+            &["ci:abc123".to_string(), "bare-token".to_string()],
+            Some("ops:s3cret, alice:xyz"),
+        )
+        .unwrap();
+        let names: Vec<_> = config.token_names().collect();
+        assert_eq!(names, ["ci", "token-2", "ops", "alice"]);
+
+        assert_eq!(config.authenticate("s3cret").unwrap().name, "ops");
+        assert_eq!(config.authenticate("bare-token").unwrap().name, "token-2");
+        assert!(config.authenticate("s3cre").is_none());
+        assert!(config.authenticate("").is_none());
+
+        let passed_on = AuthConfig::from_sources(&[], Some(&config.to_env_value())).unwrap();
+        assert_eq!(passed_on.token_names().collect::<Vec<_>>(), names);
+        assert_eq!(
+            passed_on.authenticate("bare-token").unwrap().name,
+            "token-2"
+        );
+
+        assert!(!AuthConfig::from_sources(&[], None).unwrap().is_enabled());
+        assert!(AuthConfig::from_sources(&["ci:".to_string()], None).is_err());
+        assert!(AuthConfig::from_sources(&["a:1".to_string()], Some("a:2")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_current_identity_scope() {
+        assert_eq!(current_identity(), None);
+        let identity = AuthIdentity {
+            name: "ci".to_string(),
+        };
+        let seen = with_identity(Some(identity), async { current_identity() }).await;
+        assert_eq!(seen.as_deref(), Some("ci"));
+        assert_eq!(
+            with_identity(None, async { current_identity() }).await,
+            None
+        );
+    }
+}
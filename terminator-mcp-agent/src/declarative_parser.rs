@@ -0,0 +1,763 @@
+//! Rust-native declarative output parser.
+//!
+//! An alternative to the JavaScript `output_parser` that needs no Node.js runtime.
+//! Rows are located in the UI tree with the usual selector syntax (or with a
+//! JSONPath into the sequence result), and each field is mapped from an attribute
+//! or path, optionally narrowed by a regex capture and coerced to a type.
+
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use terminator::Selector;
+
+/// Declarative parser definition, used via `output: { declarative: ... }`.
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct DeclarativeParser {
+    /// Selector that scopes the search (may match the tree root itself). Defaults to the whole tree.
+    #[serde(default)]
+    pub root: Option<String>,
+    /// Selector for repeated rows inside `root`, or a `$` JSONPath into the sequence result.
+    /// When omitted the result is a single object built from `root`.
+    #[serde(default)]
+    pub rows: Option<String>,
+    /// Output field name -> mapping. A string is shorthand for an attribute name
+    /// (`name`), a JSONPath into the sequence result (`$.results[0].result`) or a
+    /// path relative to the row (`@.attributes.value`).
+    #[serde(default)]
+    pub fields: BTreeMap<String, FieldSpec>,
+    /// Rows with a null value for any of these fields are dropped.
+    #[serde(default)]
+    pub required: Vec<String>,
+    /// Group rows by this field into `[{ <field>: key, items: [...] }]`.
+    #[serde(default)]
+    pub group_by: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(untagged)]
+pub enum FieldSpec {
+    Short(String),
+    Full(FieldMapping),
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct FieldMapping {
+    /// Selector for a descendant of the row; the first match is used.
+    #[serde(default)]
+    pub selector: Option<String>,
+    /// Attribute to read (`name`, `value`, `role`, `id`, `is_toggled`, a property, ...). Defaults to `name`.
+    #[serde(default)]
+    pub attribute: Option<String>,
+    /// JSONPath: `$...` reads the sequence result, `@...` reads the row (or selected) node.
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Regex applied to the value; the first capture group (or whole match) is kept.
+    #[serde(default)]
+    pub regex: Option<String>,
+    #[serde(default, rename = "type")]
+    pub value_type: Option<FieldType>,
+    /// Value used when the field resolves to null.
+    #[serde(default)]
+    pub default: Option<Value>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldType {
+    String,
+    Number,
+    Integer,
+    Boolean,
+}
+
+/// Run a declarative parser against an (optional) UI tree and the full sequence result.
+/// Returns `{ "result": ... }`, the same shape the JavaScript parser produces.
+pub fn run_declarative_parser(
+    parser: &DeclarativeParser,
+    ui_tree: Option<&Value>,
+    sequence_result: &Value,
+) -> Result<Value> {
+    if let Some(Value::String(_)) = ui_tree {
+        return Err(anyhow!(
+            "The declarative parser needs a JSON UI tree; capture it with tree_output_format: verbose_json"
+        ));
+    }
+
+    let fields = parser
+        .fields
+        .iter()
+        .map(|(name, spec)| CompiledField::compile(name, spec))
+        .collect::<Result<Vec<_>>>()?;
+
+    let tree = ui_tree.map(FlatTree::build);
+    let scope = match (&parser.root, &tree) {
+        (Some(root), Some(tree)) => select(tree, &[0], &parse_selector(root)?, true)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("Root selector '{}' matched nothing in the UI tree", root))?,
+        (Some(root), None) => {
+            return Err(anyhow!(
+                "Root selector '{}' given but no UI tree was found in the results",
+                root
+            ))
+        }
+        (None, _) => 0,
+    };
+
+    let result = match &parser.rows {
+        None => {
+            let row = tree.as_ref().map(|tree| (tree, scope));
+            Value::Object(map_row(&fields, row, sequence_result)?)
+        }
+        Some(rows) if rows.starts_with('$') => {
+            let mut mapped = Vec::new();
+            for value in json_path(sequence_result, rows)?.0 {
+                let row_tree = FlatTree::build(value);
+                mapped.push(map_row(&fields, Some((&row_tree, 0)), sequence_result)?);
+            }
+            finish_rows(parser, mapped)
+        }
+        Some(rows) => {
+            let tree = tree.as_ref().ok_or_else(|| {
+                anyhow!(
+                    "Row selector '{}' given but no UI tree was found in the results",
+                    rows
+                )
+            })?;
+            let mut mapped = Vec::new();
+            for index in select(tree, &[scope], &parse_selector(rows)?, false)? {
+                mapped.push(map_row(&fields, Some((tree, index)), sequence_result)?);
+            }
+            finish_rows(parser, mapped)
+        }
+    };
+
+    Ok(json!({ "result": result }))
+}
+
+fn finish_rows(parser: &DeclarativeParser, rows: Vec<Map<String, Value>>) -> Value {
+    let rows: Vec<_> = rows
+        .into_iter()
+        .filter(|row| {
+            parser
+                .required
+                .iter()
+                .all(|field| !row.get(field).unwrap_or(&Value::Null).is_null())
+        })
+        .collect();
+
+    let Some(group_by) = &parser.group_by else {
+        return Value::Array(rows.into_iter().map(Value::Object).collect());
+    };
+
+    // Groups keep the order in which their key was first seen.
+    let mut groups: Vec<(Value, Vec<Value>)> = Vec::new();
+    for row in rows {
+        let key = row.get(group_by).cloned().unwrap_or(Value::Null);
+        match groups.iter_mut().find(|(existing, _)| *existing == key) {
+            Some((_, items)) => items.push(Value::Object(row)),
+            None => groups.push((key, vec![Value::Object(row)])),
+        }
+    }
+    Value::Array(
+        groups
+            .into_iter()
+            .map(|(key, items)| {
+                let mut group = Map::new();
+                group.insert(group_by.clone(), key);
+                group.insert("items".to_string(), Value::Array(items));
+                Value::Object(group)
+            })
+            .collect(),
+    )
+}
+
+fn map_row(
+    fields: &[CompiledField],
+    row: Option<(&FlatTree, usize)>,
+    sequence_result: &Value,
+) -> Result<Map<String, Value>> {
+    let mut out = Map::new();
+    for field in fields {
+        out.insert(field.name.clone(), field.extract(row, sequence_result)?);
+    }
+    Ok(out)
+}
+
+struct CompiledField {
+    name: String,
+    selector: Option<Selector>,
+    attribute: String,
+    path: Option<String>,
+    regex: Option<Regex>,
+    value_type: Option<FieldType>,
+    default: Option<Value>,
+}
+
+impl CompiledField {
+    fn compile(name: &str, spec: &FieldSpec) -> Result<Self> {
+        let mapping = match spec {
+            FieldSpec::Short(s) if s.starts_with('$') || s.starts_with('@') => FieldMapping {
+                path: Some(s.clone()),
+                ..Default::default()
+            },
+            FieldSpec::Short(s) => FieldMapping {
+                attribute: Some(s.clone()),
+                ..Default::default()
+            },
+            FieldSpec::Full(mapping) => mapping.clone(),
+        };
+        if let Some(path) = &mapping.path {
+            if !path.starts_with('$') && !path.starts_with('@') {
+                return Err(anyhow!(
+                    "Field '{}': path '{}' must start with '$' (sequence result) or '@' (row)",
+                    name,
+                    path
+                ));
+            }
+        }
+        let regex = mapping
+            .regex
+            .as_deref()
+            .map(Regex::new)
+            .transpose()
+            .map_err(|e| anyhow!("Field '{}': invalid regex: {}", name, e))?;
+        Ok(Self {
+            name: name.to_string(),
+            selector: mapping
+                .selector
+                .as_deref()
+                .map(parse_selector)
+                .transpose()?,
+            attribute: mapping.attribute.unwrap_or_else(|| "name".to_string()),
+            path: mapping.path,
+            regex,
+            value_type: mapping.value_type,
+            default: mapping.default,
+        })
+    }
+
+    fn extract(&self, row: Option<(&FlatTree, usize)>, sequence_result: &Value) -> Result<Value> {
+        let raw = match (&self.path, &self.selector) {
+            (Some(path), None) if path.starts_with('$') => {
+                collapse(json_path(sequence_result, path)?)
+            }
+            _ => {
+                let Some((tree, mut index)) = row else {
+                    return Err(anyhow!(
+                        "Field '{}' reads the UI tree but no UI tree was found in the results",
+                        self.name
+                    ));
+                };
+                if let Some(selector) = &self.selector {
+                    match select(tree, &[index], selector, false)?.first() {
+                        Some(found) => index = *found,
+                        None => return Ok(self.finish(Value::Null)),
+                    }
+                }
+                let node = tree.nodes[index];
+                match &self.path {
+                    Some(path) if path.starts_with('$') => {
+                        collapse(json_path(sequence_result, path)?)
+                    }
+                    Some(path) => collapse(json_path(node, path)?),
+                    None => attribute(node, &self.attribute)
+                        .cloned()
+                        .unwrap_or(Value::Null),
+                }
+            }
+        };
+        Ok(self.finish(raw))
+    }
+
+    fn finish(&self, raw: Value) -> Value {
+        let mut value = raw;
+        if let Some(regex) = &self.regex {
+            value = match scalar_to_string(&value) {
+                Some(text) => regex
+                    .captures(&text)
+                    .and_then(|caps| caps.get(1).or_else(|| caps.get(0)))
+                    .map(|m| Value::String(m.as_str().to_string()))
+                    .unwrap_or(Value::Null),
+                None => Value::Null,
+            };
+        }
+        if let Some(value_type) = self.value_type {
+            value = coerce(&value, value_type);
+        }
+        match (&value, &self.default) {
+            (Value::Null, Some(default)) => default.clone(),
+            _ => value,
+        }
+    }
+}
+
+/// A single JSONPath match is returned as-is; wildcard/recursive paths return an array.
+fn collapse((matches, definite): (Vec<&Value>, bool)) -> Value {
+    if definite {
+        matches.first().map(|v| (*v).clone()).unwrap_or(Value::Null)
+    } else {
+        Value::Array(matches.into_iter().cloned().collect())
+    }
+}
+
+fn scalar_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// Coerce a value to the requested type; values that cannot be coerced become null.
+pub fn coerce(value: &Value, value_type: FieldType) -> Value {
+    match value_type {
+        FieldType::String => scalar_to_string(value)
+            .map(Value::String)
+            .unwrap_or(Value::Null),
+        FieldType::Number | FieldType::Integer => {
+            let number = match value {
+                Value::Number(n) => n.as_f64(),
+                Value::String(s) => parse_number(s),
+                _ => None,
+            };
+            match number {
+                Some(n) if value_type == FieldType::Integer => json!(n.trunc() as i64),
+                Some(n) => json!(n),
+                None => Value::Null,
+            }
+        }
+        FieldType::Boolean => match value {
+            Value::Bool(b) => Value::Bool(*b),
+            Value::Number(n) => Value::Bool(n.as_f64() != Some(0.0)),
+            Value::String(s) => match s.trim().to_lowercase().as_str() {
+                "true" | "yes" | "y" | "1" | "on" | "checked" | "selected" => Value::Bool(true),
+                "false" | "no" | "n" | "0" | "off" | "unchecked" | "" => Value::Bool(false),
+                _ => Value::Null,
+            },
+            _ => Value::Null,
+        },
+    }
+}
+
+/// Parse numbers as they appear in UIs: "$1,234.50", "42 items", "-3".
+fn parse_number(text: &str) -> Option<f64> {
+    let start = text.find(|c: char| c.is_ascii_digit() || c == '-' || c == '.')?;
+    let cleaned: String = text[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || matches!(c, '-' | '.' | ','))
+        .filter(|c| *c != ',')
+        .collect();
+    cleaned.parse().ok()
+}
+
+/// Read an attribute from a UI node, accepting both the `get_window_tree` shape
+/// (`{ id, attributes: { role, name, properties } }`) and flat serialized elements.
+fn attribute<'a>(node: &'a Value, name: &str) -> Option<&'a Value> {
+    let attrs = &node["attributes"];
+    [
+        attrs.get(name),
+        node.get(name),
+        attrs["properties"].get(name),
+    ]
+    .into_iter()
+    .flatten()
+    .find(|v| !v.is_null())
+}
+
+fn attribute_str(node: &Value, name: &str) -> Option<String> {
+    attribute(node, name).and_then(scalar_to_string)
+}
+
+fn contains_ci(haystack: Option<String>, needle: &str) -> bool {
+    haystack.is_some_and(|h| h.to_lowercase().contains(&needle.to_lowercase()))
+}
+
+fn parse_selector(raw: &str) -> Result<Selector> {
+    match Selector::from(raw) {
+        Selector::Invalid(reason) => Err(anyhow!("Invalid selector '{}': {}", raw, reason)),
+        selector => Ok(selector),
+    }
+}
+
+/// Pre-order flattening of a UI tree so descendant and parent lookups are index based.
+struct FlatTree<'a> {
+    nodes: Vec<&'a Value>,
+    parents: Vec<Option<usize>>,
+    /// Exclusive end of each node's subtree in `nodes`.
+    ends: Vec<usize>,
+}
+
+impl<'a> FlatTree<'a> {
+    fn build(root: &'a Value) -> Self {
+        fn visit<'a>(tree: &mut FlatTree<'a>, node: &'a Value, parent: Option<usize>) {
+            let index = tree.nodes.len();
+            tree.nodes.push(node);
+            tree.parents.push(parent);
+            tree.ends.push(index + 1);
+            if let Some(children) = node["children"].as_array() {
+                for child in children {
+                    visit(tree, child, Some(index));
+                }
+            }
+            tree.ends[index] = tree.nodes.len();
+        }
+        let mut tree = FlatTree {
+            nodes: Vec::new(),
+            parents: Vec::new(),
+            ends: Vec::new(),
+        };
+        visit(&mut tree, root, None);
+        tree
+    }
+}
+
+/// Resolve a selector against the tree, starting from `scope`. Each part of a chain
+/// searches the descendants of the previous part's matches; `nth:`, `..` and a
+/// chained `has:` act on the current matches. With `include_self`, the scope nodes
+/// themselves can match.
+fn select(
+    tree: &FlatTree,
+    scope: &[usize],
+    selector: &Selector,
+    include_self: bool,
+) -> Result<Vec<usize>> {
+    let parts = match selector {
+        Selector::Chain(parts) => parts.as_slice(),
+        single => std::slice::from_ref(single),
+    };
+
+    let mut current = scope.to_vec();
+    let mut include_self = include_self;
+    for (position, part) in parts.iter().enumerate() {
+        current = match part {
+            Selector::Nth(n) => {
+                let index = if *n < 0 {
+                    current.len().checked_sub(n.unsigned_abs() as usize)
+                } else {
+                    Some(*n as usize)
+                };
+                index
+                    .and_then(|i| current.get(i).copied())
+                    .into_iter()
+                    .collect()
+            }
+            Selector::Has(inner) if position > 0 => {
+                let mut kept = Vec::new();
+                for index in current {
+                    if !select(tree, &[index], inner, false)?.is_empty() {
+                        kept.push(index);
+                    }
+                }
+                kept
+            }
+            Selector::Parent => {
+                let mut parents: Vec<usize> =
+                    current.iter().filter_map(|i| tree.parents[*i]).collect();
+                parents.dedup();
+                parents
+            }
+            _ => {
+                let mut found = Vec::new();
+                for &start in &current {
+                    let from = if include_self { start } else { start + 1 };
+                    for index in from..tree.ends[start] {
+                        if node_matches(tree, index, part)? {
+                            found.push(index);
+                        }
+                    }
+                }
+                // Subtrees of nested scope nodes overlap; keep document order without duplicates.
+                found.sort_unstable();
+                found.dedup();
+                found
+            }
+        };
+        include_self = false;
+    }
+    Ok(current)
+}
+
+fn node_matches(tree: &FlatTree, index: usize, selector: &Selector) -> Result<bool> {
+    let node = tree.nodes[index];
+    Ok(match selector {
+        Selector::Role { role, name } => {
+            attribute_str(node, "role").is_some_and(|r| r.eq_ignore_ascii_case(role))
+                && name
+                    .as_deref()
+                    .is_none_or(|name| contains_ci(attribute_str(node, "name"), name))
+        }
+        Selector::Id(id) => {
+            attribute_str(node, "id").as_deref() == Some(id.trim_start_matches('#'))
+        }
+        Selector::Name(name) => contains_ci(attribute_str(node, "name"), name),
+        Selector::Text(text) => ["text", "name", "value"]
+            .iter()
+            .any(|attr| contains_ci(attribute_str(node, attr), text)),
+        Selector::NativeId(id) => ["AutomationId", "automation_id"]
+            .iter()
+            .any(|attr| attribute_str(node, attr).as_deref() == Some(id.as_str())),
+        Selector::ClassName(class) => ["ClassName", "class_name"]
+            .iter()
+            .any(|attr| attribute_str(node, attr).as_deref() == Some(class.as_str())),
+        Selector::LocalizedRole(role) => {
+            attribute_str(node, "localized_role").is_some_and(|r| r.eq_ignore_ascii_case(role))
+        }
+        Selector::Attributes(expected) => expected
+            .iter()
+            .all(|(key, value)| attribute_str(node, key).as_deref() == Some(value.as_str())),
+        Selector::Visible(visible) => {
+            let has_area = attribute(node, "bounds")
+                .and_then(|b| b.as_array())
+                .is_some_and(|b| {
+                    b.len() == 4
+                        && b[2].as_f64().unwrap_or(0.0) > 0.0
+                        && b[3].as_f64().unwrap_or(0.0) > 0.0
+                });
+            has_area == *visible
+        }
+        Selector::Has(inner) => !select(tree, &[index], inner, false)?.is_empty(),
+        Selector::Chain(_) => {
+            return Err(anyhow!("Nested selector chains are not supported"));
+        }
+        other => {
+            return Err(anyhow!(
+                "Selector {} is not supported by the declarative parser",
+                other
+            ));
+        }
+    })
+}
+
+enum PathToken {
+    Key(String),
+    Index(i64),
+    Wildcard,
+    Recursive(String),
+}
+
+/// Evaluate a small JSONPath subset: `$`/`@`, `.key`, `['key']`, `[n]` (negative from
+/// the end), `[*]`/`.*` and `..key`. Also reports whether the path is definite (no
+/// wildcard or recursive segment), i.e. names at most one value.
+pub fn json_path<'a>(root: &'a Value, path: &str) -> Result<(Vec<&'a Value>, bool)> {
+    let tokens = tokenize_path(path)?;
+    let definite = tokens
+        .iter()
+        .all(|t| matches!(t, PathToken::Key(_) | PathToken::Index(_)));
+
+    let mut current = vec![root];
+    for token in &tokens {
+        let mut next = Vec::new();
+        for value in current {
+            match token {
+                PathToken::Key(key) => next.extend(value.get(key.as_str())),
+                PathToken::Index(i) => {
+                    if let Some(items) = value.as_array() {
+                        let index = if *i < 0 {
+                            items.len().checked_sub(i.unsigned_abs() as usize)
+                        } else {
+                            Some(*i as usize)
+                        };
+                        next.extend(index.and_then(|i| items.get(i)));
+                    }
+                }
+                PathToken::Wildcard => match value {
+                    Value::Array(items) => next.extend(items.iter()),
+                    Value::Object(map) => next.extend(map.values()),
+                    _ => {}
+                },
+                PathToken::Recursive(key) => collect_recursive(value, key, &mut next),
+            }
+        }
+        current = next;
+    }
+    Ok((current, definite))
+}
+
+fn collect_recursive<'a>(value: &'a Value, key: &str, out: &mut Vec<&'a Value>) {
+    match value {
+        Value::Object(map) => {
+            if let Some(found) = map.get(key) {
+                out.push(found);
+            }
+            for child in map.values() {
+                collect_recursive(child, key, out);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                collect_recursive(item, key, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn tokenize_path(path: &str) -> Result<Vec<PathToken>> {
+    let invalid = |reason: &str| anyhow!("Invalid path '{}': {}", path, reason);
+    let rest = path
+        .strip_prefix('$')
+        .or_else(|| path.strip_prefix('@'))
+        .ok_or_else(|| invalid("must start with '$' or '@'"))?;
+
+    let is_key_char = |c: char| c.is_alphanumeric() || c == '_' || c == '-';
+    let chars: Vec<char> = rest.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '.' if chars.get(i + 1) == Some(&'.') => {
+                let start = i + 2;
+                let end = start
+                    + chars[start..]
+                        .iter()
+                        .take_while(|c| is_key_char(**c))
+                        .count();
+                if end == start {
+                    return Err(invalid("'..' must be followed by a key"));
+                }
+                tokens.push(PathToken::Recursive(chars[start..end].iter().collect()));
+                i = end;
+            }
+            '.' if chars.get(i + 1) == Some(&'*') => {
+                tokens.push(PathToken::Wildcard);
+                i += 2;
+            }
+            '.' => {
+                let start = i + 1;
+                let end = start
+                    + chars[start..]
+                        .iter()
+                        .take_while(|c| is_key_char(**c))
+                        .count();
+                if end == start {
+                    return Err(invalid("'.' must be followed by a key"));
+                }
+                tokens.push(PathToken::Key(chars[start..end].iter().collect()));
+                i = end;
+            }
+            '[' => {
+                let close = chars[i..]
+                    .iter()
+                    .position(|c| *c == ']')
+                    .map(|p| i + p)
+                    .ok_or_else(|| invalid("unclosed '['"))?;
+                let inner: String = chars[i + 1..close].iter().collect();
+                let inner = inner.trim();
+                let token = if inner == "*" {
+                    PathToken::Wildcard
+                } else if let Some(quoted) = inner
+                    .strip_prefix('\'')
+                    .and_then(|s| s.strip_suffix('\''))
+                    .or_else(|| inner.strip_prefix('"').and_then(|s| s.strip_suffix('"')))
+                {
+                    PathToken::Key(quoted.to_string())
+                } else {
+                    PathToken::Index(
+                        inner
+                            .parse()
+                            .map_err(|_| invalid(&format!("bad index '{inner}'")))?,
+                    )
+                };
+                tokens.push(token);
+                i = close + 1;
+            }
+            c => return Err(invalid(&format!("unexpected '{c}'"))),
+        }
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_path() {
+        let data = json!({
+            "results": [
+                {"step_id": "a", "result": {"count": 3}},
+                {"step_id": "b", "result": {"count": 5, "items": ["x", "y"]}}
+            ]
+        });
+        let (values, definite) = json_path(&data, "$.results[1].result.count").unwrap();
+        assert!(definite);
+        assert_eq!(values, vec![&json!(5)]);
+
+        let (values, _) = json_path(&data, "$.results[-1]['step_id']").unwrap();
+        assert_eq!(values, vec![&json!("b")]);
+
+        let (values, definite) = json_path(&data, "$.results[*].step_id").unwrap();
+        assert!(!definite);
+        assert_eq!(values, vec![&json!("a"), &json!("b")]);
+
+        let (values, _) = json_path(&data, "$..count").unwrap();
+        assert_eq!(values, vec![&json!(3), &json!(5)]);
+
+        assert!(json_path(&data, "results").is_err());
+        assert!(json_path(&data, "$.results[x]").is_err());
+    }
+
+    #[test]
+    fn test_coerce() {
+        assert_eq!(
+            coerce(&json!("$1,234.50"), FieldType::Number),
+            json!(1234.5)
+        );
+        assert_eq!(coerce(&json!("42 items"), FieldType::Integer), json!(42));
+        assert_eq!(coerce(&json!("-3.9"), FieldType::Integer), json!(-3));
+        assert_eq!(coerce(&json!("n/a"), FieldType::Number), Value::Null);
+        assert_eq!(coerce(&json!("Yes"), FieldType::Boolean), json!(true));
+        assert_eq!(coerce(&json!("off"), FieldType::Boolean), json!(false));
+        assert_eq!(coerce(&json!(7), FieldType::String), json!("7"));
+        assert_eq!(coerce(&json!({"a": 1}), FieldType::String), Value::Null);
+    }
+
+    #[test]
+    fn test_selectors_on_flat_tree() {
+        let tree_json = json!({
+            "attributes": {"role": "Window", "name": "Orders"},
+            "children": [
+                {"attributes": {"role": "List"}, "children": [
+                    {"id": "1", "attributes": {"role": "ListItem", "name": "First"}},
+                    {"id": "2", "attributes": {"role": "ListItem", "name": "Second"},
+                     "children": [{"attributes": {"role": "Image"}}]}
+                ]}
+            ]
+        });
+        let tree = FlatTree::build(&tree_json);
+        let names = |indices: Vec<usize>| -> Vec<String> {
+            indices
+                .into_iter()
+                .map(|i| attribute_str(tree.nodes[i], "name").unwrap_or_default())
+                .collect()
+        };
+
+        let sel = parse_selector("role:ListItem").unwrap();
+        assert_eq!(
+            names(select(&tree, &[0], &sel, false).unwrap()),
+            ["First", "Second"]
+        );
+
+        let sel = parse_selector("role:List >> role:ListItem >> nth:-1").unwrap();
+        assert_eq!(names(select(&tree, &[0], &sel, false).unwrap()), ["Second"]);
+
+        let sel = parse_selector("has:role:Image").unwrap();
+        assert_eq!(
+            names(select(&tree, &[0], &sel, false).unwrap()),
+            ["", "Second"]
+        );
+
+        let sel = parse_selector("#1").unwrap();
+        assert_eq!(names(select(&tree, &[0], &sel, false).unwrap()), ["First"]);
+
+        let sel = parse_selector("role:window|orders").unwrap();
+        assert!(select(&tree, &[0], &sel, false).unwrap().is_empty());
+        assert_eq!(names(select(&tree, &[0], &sel, true).unwrap()), ["Orders"]);
+
+        assert!(parse_selector("bogus").is_err());
+    }
+}
@@ -0,0 +1,466 @@
+//! Named desktop targets for the HTTP transport (`--target NAME=DISPLAY`).
+//!
+//! The accessibility engine, launched applications and input all follow the
+//! process environment, so each target runs in its own agent process started with
+//! that target's display. The front server proxies `/targets/{name}/...` to it,
+//! which binds MCP sessions to the target they were opened on. Each worker has its
+//! own request queue (`MCP_MAX_CONCURRENT` applies per target), so automations on
+//! different targets run side by side.
+
+use axum::body::Body;
+use axum::http::{HeaderMap, Method, StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use futures::StreamExt;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::process::Command;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+/// Set in target worker processes to the name of their target.
+pub const TARGET_ENV: &str = "TERMINATOR_MCP_TARGET";
+/// Set in target worker processes to the pid of the agent that started them.
+const PARENT_PID_ENV: &str = "TERMINATOR_MCP_PARENT_PID";
+const PARENT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+const WORKER_STOP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Headers that describe a single hop and must not be forwarded.
+const HOP_BY_HOP_HEADERS: &[&str] = &[
+    "connection",
+    "host",
+    "keep-alive",
+    "proxy-connection",
+    "transfer-encoding",
+    "upgrade",
+    "content-length",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TargetDisplay {
+    /// An X display such as `:1` (e.g. started with `Xvfb :1`)
+    X11(String),
+    /// A Windows virtual display (see `platforms/windows/virtual_display.rs`)
+    WindowsVirtual,
+    /// An Xvfb display the worker starts itself (`--headless-linux`)
+    Headless,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DesktopTarget {
+    pub name: String,
+    pub display: TargetDisplay,
+}
+
+impl DesktopTarget {
+    /// Parse `NAME=:N` (X display), `NAME=headless` (Xvfb started by the worker)
+    /// or `NAME=virtual` (Windows virtual display).
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (name, display) = spec.split_once('=').ok_or_else(|| {
+            format!("target '{spec}' must look like NAME=:1, NAME=headless or NAME=virtual")
+        })?;
+        let name = name.trim();
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(format!(
+                "target name '{name}' may only contain letters, digits, '-' and '_'"
+            ));
+        }
+        let display = display.trim();
+        let display = if display.eq_ignore_ascii_case("virtual") {
+            TargetDisplay::WindowsVirtual
+        } else if display.eq_ignore_ascii_case("headless") {
+            TargetDisplay::Headless
+        } else if display.starts_with(':')
+            && display[1..]
+                .split('.')
+                .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+        {
+            TargetDisplay::X11(display.to_string())
+        } else {
+            return Err(format!(
+                "target '{name}' has display '{display}'; expected an X display like :1, 'headless' or 'virtual'"
+            ));
+        };
+        Ok(Self {
+            name: name.to_string(),
+            display,
+        })
+    }
+
+    /// Reject displays this platform can't drive.
+    pub fn check_platform(&self) -> Result<(), String> {
+        match self.display {
+            TargetDisplay::X11(_) | TargetDisplay::Headless if !cfg!(target_os = "linux") => {
+                Err(format!(
+                    "target '{}': X displays are only supported on Linux",
+                    self.name
+                ))
+            }
+            TargetDisplay::WindowsVirtual if !cfg!(target_os = "windows") => Err(format!(
+                "target '{}': virtual displays are only supported on Windows",
+                self.name
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn display_label(&self) -> String {
+        match &self.display {
+            TargetDisplay::X11(display) => display.clone(),
+            TargetDisplay::WindowsVirtual => "virtual".to_string(),
+            TargetDisplay::Headless => "headless".to_string(),
+        }
+    }
+
+    /// Environment for the worker process.
+    fn env(&self) -> Vec<(&'static str, String)> {
+        let mut env = vec![(TARGET_ENV, self.name.clone())];
+        match &self.display {
+            TargetDisplay::X11(display) => env.push(("DISPLAY", display.clone())),
+            TargetDisplay::WindowsVirtual => env.push(("TERMINATOR_HEADLESS", "true".to_string())),
+            // The worker sets DISPLAY and its session bus once Xvfb is up
+            TargetDisplay::Headless => {}
+        }
+        env
+    }
+}
+
+struct TargetWorker {
+    target: DesktopTarget,
+    port: u16,
+}
+
+/// The worker processes of all configured targets.
+pub struct TargetRegistry {
+    workers: BTreeMap<String, TargetWorker>,
+    client: reqwest::Client,
+    shutdown: CancellationToken,
+    supervisors: tokio::sync::Mutex<Vec<tokio::task::JoinHandle<()>>>,
+}
+
+impl TargetRegistry {
+    /// Start one worker per target. `worker_args` are passed to every worker after the
+    /// transport/host/port options (policy, ...) and `worker_env` is set in its
+    /// environment (auth tokens, which must not show up in process listings);
+    /// `audit_dir` gets a per-target subdirectory so each worker keeps its own hash chain.
+    pub async fn start(
+        targets: Vec<DesktopTarget>,
+        worker_args: Vec<OsString>,
+        worker_env: Vec<(String, String)>,
+        audit_dir: Option<&Path>,
+    ) -> anyhow::Result<Self> {
+        let exe = std::env::current_exe()?;
+        let shutdown = CancellationToken::new();
+        let mut workers = BTreeMap::new();
+        let mut supervisors = Vec::new();
+        check_virtual_targets(&targets).map_err(anyhow::Error::msg)?;
+        for target in targets {
+            target.check_platform().map_err(anyhow::Error::msg)?;
+            if workers.contains_key(&target.name) {
+                anyhow::bail!("target '{}' is defined more than once", target.name);
+            }
+            let port = free_port()?;
+            let mut args: Vec<OsString> = vec![
+                "-t".into(),
+                "http".into(),
+                "--host".into(),
+                "127.0.0.1".into(),
+                "--port".into(),
+                port.to_string().into(),
+            ];
+            if target.display == TargetDisplay::Headless {
+                args.push("--headless-linux".into());
+            }
+            args.extend(worker_args.iter().cloned());
+            if let Some(dir) = audit_dir {
+                args.push("--audit-log".into());
+                args.push(dir.join(&target.name).into_os_string());
+            }
+
+            let mut command = match (&target.display, find_in_path("dbus-run-session")) {
+                // A private session bus gives the display its own accessibility bus
+                (TargetDisplay::X11(_), Some(dbus_run_session)) => {
+                    let mut command = Command::new(dbus_run_session);
+                    command.arg("--").arg(&exe);
+                    command
+                }
+                _ => Command::new(&exe),
+            };
+            command.args(&args).kill_on_drop(true);
+            for (key, value) in target.env() {
+                command.env(key, value);
+            }
+            command.envs(worker_env.iter().map(|(key, value)| (key, value)));
+            // A wrapper like dbus-run-session doesn't pass signals on, so the worker
+            // also exits on its own once this process is gone
+            command.env(PARENT_PID_ENV, std::process::id().to_string());
+            let mut child = command.spawn().map_err(|e| {
+                anyhow::anyhow!("failed to start worker for target '{}': {e}", target.name)
+            })?;
+            info!(
+                "Target '{}' ({}) served by worker pid {:?} on 127.0.0.1:{}",
+                target.name,
+                target.display_label(),
+                child.id(),
+                port
+            );
+
+            let name = target.name.clone();
+            let stop = shutdown.clone();
+            supervisors.push(tokio::spawn(async move {
+                tokio::select! {
+                    status = child.wait() => {
+                        warn!("Worker for target '{}' exited: {:?}", name, status);
+                    }
+                    _ = stop.cancelled() => stop_worker(&mut child).await,
+                }
+            }));
+            workers.insert(target.name.clone(), TargetWorker { target, port });
+        }
+        Ok(Self {
+            workers,
+            client: reqwest::Client::new(),
+            shutdown,
+            supervisors: tokio::sync::Mutex::new(supervisors),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.workers.is_empty()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.workers.keys().map(String::as_str)
+    }
+
+    /// Stop all workers and wait for them to exit.
+    pub async fn shutdown(&self) {
+        self.shutdown.cancel();
+        let supervisors = std::mem::take(&mut *self.supervisors.lock().await);
+        for supervisor in supervisors {
+            let _ = supervisor.await;
+        }
+    }
+
+    /// Per-target `/status` of every worker, keyed by target name.
+    pub async fn statuses(&self) -> BTreeMap<String, Value> {
+        let mut statuses = BTreeMap::new();
+        for (name, worker) in &self.workers {
+            let url = format!("http://127.0.0.1:{}/status", worker.port);
+            let mut status = match self
+                .client
+                .get(&url)
+                .timeout(Duration::from_secs(2))
+                .send()
+                .await
+            {
+                Ok(response) => response
+                    .json::<Value>()
+                    .await
+                    .unwrap_or_else(|e| json!({"busy": true, "error": e.to_string()})),
+                Err(e) => json!({"busy": true, "error": format!("worker unreachable: {e}")}),
+            };
+            status["display"] = worker.target.display_label().into();
+            statuses.insert(name.clone(), status);
+        }
+        statuses
+    }
+
+    /// Forward a request for `/targets/{name}/{path}` to the target's worker,
+    /// streaming the response back (tool results arrive as SSE).
+    pub async fn forward(
+        &self,
+        name: &str,
+        path: &str,
+        method: Method,
+        uri: &Uri,
+        headers: HeaderMap,
+        body: Body,
+    ) -> Response {
+        let Some(worker) = self.workers.get(name) else {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(json!({
+                    "error": format!("Unknown target '{name}'"),
+                    "targets": self.names().collect::<Vec<_>>(),
+                })),
+            )
+                .into_response();
+        };
+        let mut url = format!("http://127.0.0.1:{}/{}", worker.port, path);
+        if let Some(query) = uri.query() {
+            url.push('?');
+            url.push_str(query);
+        }
+
+        let mut request = self
+            .client
+            .request(method, &url)
+            .body(reqwest::Body::wrap_stream(body.into_data_stream()));
+        for (key, value) in headers.iter() {
+            if !HOP_BY_HOP_HEADERS.contains(&key.as_str()) {
+                request = request.header(key, value);
+            }
+        }
+
+        match request.send().await {
+            Ok(upstream) => {
+                let mut response = Response::builder().status(upstream.status());
+                for (key, value) in upstream.headers() {
+                    if !HOP_BY_HOP_HEADERS.contains(&key.as_str()) {
+                        response = response.header(key, value);
+                    }
+                }
+                response
+                    .body(Body::from_stream(
+                        upstream
+                            .bytes_stream()
+                            .map(|chunk| chunk.map_err(std::io::Error::other)),
+                    ))
+                    .unwrap_or_else(|e| (StatusCode::BAD_GATEWAY, e.to_string()).into_response())
+            }
+            Err(e) => (
+                StatusCode::BAD_GATEWAY,
+                Json(json!({
+                    "error": format!("Target '{name}' is unavailable: {e}"),
+                    "target": name,
+                })),
+            )
+                .into_response(),
+        }
+    }
+}
+
+impl Drop for TargetRegistry {
+    fn drop(&mut self) {
+        self.shutdown.cancel();
+    }
+}
+
+/// Ask a worker to exit so it can stop its own children (e.g. a headless display),
+/// and kill it if it is still running after a few seconds.
+async fn stop_worker(child: &mut tokio::process::Child) {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        unsafe {
+            libc::kill(pid as libc::pid_t, libc::SIGTERM);
+        }
+        if tokio::time::timeout(WORKER_STOP_TIMEOUT, child.wait())
+            .await
+            .is_ok()
+        {
+            return;
+        }
+    }
+    let _ = child.kill().await;
+}
+
+/// Resolves when the agent that started this worker has exited; never for other processes.
+pub async fn parent_exited() {
+    let Some(pid) = std::env::var(PARENT_PID_ENV)
+        .ok()
+        .and_then(|pid| pid.parse::<usize>().ok())
+    else {
+        return std::future::pending().await;
+    };
+    let pid = sysinfo::Pid::from(pid);
+    let mut system = sysinfo::System::new();
+    loop {
+        tokio::time::sleep(PARENT_POLL_INTERVAL).await;
+        system.refresh_processes(sysinfo::ProcessesToUpdate::Some(&[pid]), true);
+        if system.process(pid).is_none() {
+            warn!("Parent agent (pid {}) exited, shutting down", pid);
+            return;
+        }
+    }
+}
+
+/// A Windows virtual display is the session's one interactive desktop, not a separate
+/// one, so two virtual targets would drive the same input with separate request
+/// queues. Allow at most one.
+fn check_virtual_targets(targets: &[DesktopTarget]) -> Result<(), String> {
+    let virtual_targets: Vec<&str> = targets
+        .iter()
+        .filter(|target| target.display == TargetDisplay::WindowsVirtual)
+        .map(|target| target.name.as_str())
+        .collect();
+    if virtual_targets.len() > 1 {
+        return Err(format!(
+            "targets {} all use the virtual display; only one virtual target is supported",
+            virtual_targets.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+fn free_port() -> std::io::Result<u16> {
+    Ok(std::net::TcpListener::bind("127.0.0.1:0")?
+        .local_addr()?
+        .port())
+}
+
+fn find_in_path(program: &str) -> Option<PathBuf> {
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths)
+        .map(|dir| dir.join(program))
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_targets() {
+        assert_eq!(
+            DesktopTarget::parse("ci-1=:1").unwrap(),
+            DesktopTarget {
+                name: "ci-1".to_string(),
+                display: TargetDisplay::X11(":1".to_string())
+            }
+        );
+        assert_eq!(
+            DesktopTarget::parse("vd=virtual").unwrap().display,
+            TargetDisplay::WindowsVirtual
+        );
+        assert_eq!(
+            DesktopTarget::parse("x=:2.0").unwrap().display_label(),
+            ":2.0"
+        );
+        assert!(DesktopTarget::parse(":1").is_err());
+        assert!(DesktopTarget::parse("a/b=:1").is_err());
+        assert!(DesktopTarget::parse("a=1").is_err());
+        assert!(DesktopTarget::parse("a=:").is_err());
+    }
+
+    #[test]
+    fn test_only_one_virtual_target() {
+        let targets = |specs: &[&str]| -> Vec<DesktopTarget> {
+            specs
+                .iter()
+                .map(|s| DesktopTarget::parse(s).unwrap())
+                .collect()
+        };
+        assert!(check_virtual_targets(&targets(&["vd=virtual", "ci=:1", "hl=headless"])).is_ok());
+        assert!(check_virtual_targets(&targets(&["a=virtual", "b=virtual"])).is_err());
+    }
+
+    #[test]
+    fn test_worker_environment() {
+        let env = DesktopTarget::parse("ci=:3").unwrap().env();
+        assert!(env.contains(&(TARGET_ENV, "ci".to_string())));
+        assert!(env.contains(&("DISPLAY", ":3".to_string())));
+        let env = DesktopTarget::parse("vd=virtual").unwrap().env();
+        assert!(env.contains(&("TERMINATOR_HEADLESS", "true".to_string())));
+        let env = DesktopTarget::parse("hl=headless").unwrap().env();
+        assert_eq!(env, vec![(TARGET_ENV, "hl".to_string())]);
+    }
+}
@@ -191,3 +191,110 @@ fn compare_values_smart(lhs: &Value, rhs_str: &str) -> bool {
         _ => false,
     }
 }
+
+/// Statically checks that `expression` uses the grammar understood by [`evaluate`],
+/// without evaluating it. Returns the variable paths it references, or a description
+/// of the first construct that `evaluate` would silently treat as `false`.
+pub fn check(expression: &str) -> Result<Vec<String>, String> {
+    let normalized = normalize_expression(expression);
+    let mut references = Vec::new();
+    check_internal(&normalized, &mut references)?;
+    Ok(references)
+}
+
+fn check_internal(expression: &str, references: &mut Vec<String>) -> Result<(), String> {
+    let expr = expression.trim();
+    if expr.is_empty() {
+        return Err("empty expression".to_string());
+    }
+
+    if let Some(inner_expr) = expr.strip_prefix('!') {
+        return check_internal(inner_expr, references);
+    }
+
+    if let Some(pos) = expr.find("&&") {
+        check_internal(&expr[..pos], references)?;
+        return check_internal(&expr[pos + 2..], references);
+    }
+
+    if let Some(pos) = expr.find("||") {
+        check_internal(&expr[..pos], references)?;
+        return check_internal(&expr[pos + 2..], references);
+    }
+
+    if let Some((func_name, args_str)) = expr.split_once('(') {
+        let func_name = func_name.trim();
+        if is_variable_path(func_name) && args_str.ends_with(')') {
+            let args_str = &args_str[..args_str.len() - 1];
+            return match func_name {
+                "always" if args_str.trim().is_empty() => Ok(()),
+                "always" => Err("always() takes no arguments".to_string()),
+                "contains" | "startsWith" | "endsWith" => {
+                    let args: Vec<&str> = args_str.split(',').map(|s| s.trim()).collect();
+                    if args.len() != 2 {
+                        return Err(format!(
+                            "{func_name}() takes exactly 2 arguments, got {}",
+                            args.len()
+                        ));
+                    }
+                    if !is_variable_path(args[0]) {
+                        return Err(format!(
+                            "first argument of {func_name}() must be a variable, got '{}'",
+                            args[0]
+                        ));
+                    }
+                    references.push(args[0].to_string());
+                    Ok(())
+                }
+                _ => Err(format!(
+                    "unknown function '{func_name}' (supported: always, contains, startsWith, endsWith)"
+                )),
+            };
+        }
+    }
+
+    let binary = if let Some(pos) = expr.find("==") {
+        Some((&expr[..pos], "==", &expr[pos + 2..]))
+    } else {
+        expr.find("!=")
+            .map(|pos| (&expr[..pos], "!=", &expr[pos + 2..]))
+    };
+    if let Some((var_path, op, raw_rhs)) = binary {
+        let var_path = var_path.trim();
+        let raw_rhs = raw_rhs.trim();
+        if !is_variable_path(var_path) {
+            return Err(format!(
+                "left-hand side of '{op}' must be a variable, got '{var_path}'"
+            ));
+        }
+        let quoted = raw_rhs.len() >= 2
+            && ((raw_rhs.starts_with('\'') && raw_rhs.ends_with('\''))
+                || (raw_rhs.starts_with('"') && raw_rhs.ends_with('"')));
+        if raw_rhs != "true" && raw_rhs != "false" && !quoted {
+            return Err(format!(
+                "right-hand side of '{op}' must be true, false or a quoted string, got '{raw_rhs}'"
+            ));
+        }
+        references.push(var_path.to_string());
+        return Ok(());
+    }
+
+    if expr == "true" || expr == "false" {
+        return Ok(());
+    }
+
+    if is_variable_path(expr) {
+        references.push(expr.to_string());
+        return Ok(());
+    }
+
+    Err(format!("cannot parse expression '{expr}'"))
+}
+
+fn is_variable_path(s: &str) -> bool {
+    !s.is_empty()
+        && !s.starts_with('.')
+        && !s.ends_with('.')
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+}
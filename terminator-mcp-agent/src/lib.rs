@@ -1,4 +1,8 @@
+pub mod audit;
+pub mod auth;
 pub mod cancellation;
+pub mod declarative_parser;
+pub mod desktop_targets;
 pub mod duration_parser;
 pub mod expression_eval;
 pub mod helpers;
@@ -6,17 +10,31 @@ pub mod log_capture;
 pub mod mcp_converter;
 pub mod mcp_types;
 pub mod output_parser;
+pub mod policy;
 pub mod prompt;
+pub mod recording_stream;
+pub mod request_queue;
+pub mod retry;
 pub mod scripting_engine;
+pub mod sequence_progress;
 pub mod server;
+pub mod server_debugger;
+pub mod server_resources;
 pub mod server_sequence;
 pub mod server_workflow_files;
 pub mod telemetry;
+pub mod tls;
 pub mod tree_formatter;
 pub mod utils;
 pub mod vcredist_check;
+pub mod workflow_assertions;
+pub mod workflow_codegen;
 pub mod workflow_converter;
 pub mod workflow_events;
+pub mod workflow_lint;
+pub mod workflow_parameterize;
+pub mod workflow_replay;
+pub mod workflow_schema;
 
 // Re-export the extract_content_json function for testing
 pub use server::extract_content_json;
@@ -1,7 +1,10 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+use tracing::span::{Attributes, Id};
 use tracing::{Event, Subscriber};
+use tracing_subscriber::registry::LookupSpan;
 use tracing_subscriber::{layer::Context, Layer};
 
 /// A single log entry captured during tool execution
@@ -13,6 +16,23 @@ pub struct LogEntry {
     pub message: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub fields: Option<serde_json::Map<String, serde_json::Value>>,
+    /// The MCP request whose span the event was logged in, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// The `CallId` of that span, which unlike the request id is unique across sessions
+    #[serde(skip)]
+    pub call_id: Option<String>,
+}
+
+/// Identifies a tool call across sessions: request ids are numbered by each client.
+/// Stored in the request extensions and recorded as the `call_id` of its span.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallId(pub String);
+
+impl CallId {
+    pub fn random() -> Self {
+        Self(uuid::Uuid::new_v4().to_string())
+    }
 }
 
 /// Thread-safe log capture mechanism for collecting logs during tool execution
@@ -21,6 +41,7 @@ pub struct LogCapture {
     logs: Arc<Mutex<Vec<LogEntry>>>,
     capture_enabled: Arc<Mutex<bool>>,
     max_entries: usize,
+    live: broadcast::Sender<LogEntry>,
 }
 
 impl LogCapture {
@@ -30,6 +51,7 @@ impl LogCapture {
             logs: Arc::new(Mutex::new(Vec::new())),
             capture_enabled: Arc::new(Mutex::new(false)),
             max_entries,
+            live: broadcast::channel(256).0,
         }
     }
 
@@ -54,8 +76,21 @@ impl LogCapture {
         *self.capture_enabled.lock().unwrap()
     }
 
+    /// Receive every log entry from now on, independently of `start_capture`
+    pub fn subscribe(&self) -> broadcast::Receiver<LogEntry> {
+        self.live.subscribe()
+    }
+
+    fn has_subscribers(&self) -> bool {
+        self.live.receiver_count() > 0
+    }
+
     /// Add a log entry to the buffer (internal use)
     fn add_log(&self, entry: LogEntry) {
+        if self.has_subscribers() {
+            let _ = self.live.send(entry.clone());
+        }
+
         // Quick check without lock first
         if !self.is_capturing() {
             return;
@@ -89,13 +124,42 @@ impl LogCaptureLayer {
     }
 }
 
+/// The `request_id` and `call_id` fields of a span, kept in its extensions so events
+/// inside it can be attributed to their request.
+struct RequestIdTag {
+    request_id: String,
+    call_id: Option<String>,
+}
+
 impl<S> Layer<S> for LogCaptureLayer
 where
-    S: Subscriber,
+    S: Subscriber + for<'a> LookupSpan<'a>,
 {
-    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
-        // Skip if not capturing
-        if !self.capture.is_capturing() {
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if attrs.metadata().fields().field("request_id").is_none() {
+            return;
+        }
+        let mut visitor = FieldVisitor::default();
+        attrs.record(&mut visitor);
+        let as_string = |value: serde_json::Value| match value {
+            serde_json::Value::String(value) => value,
+            other => other.to_string(),
+        };
+        let Some(request_id) = visitor.fields.remove("request_id").map(as_string) else {
+            return;
+        };
+        let call_id = visitor.fields.remove("call_id").map(as_string);
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(RequestIdTag {
+                request_id,
+                call_id,
+            });
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        // Skip if nobody is capturing or listening
+        if !self.capture.is_capturing() && !self.capture.has_subscribers() {
             return;
         }
 
@@ -103,6 +167,15 @@ where
         let mut visitor = FieldVisitor::default();
         event.record(&mut visitor);
 
+        let tag = ctx.event_scope(event).and_then(|scope| {
+            scope.into_iter().find_map(|span| {
+                span.extensions()
+                    .get::<RequestIdTag>()
+                    .map(|tag| (tag.request_id.clone(), tag.call_id.clone()))
+            })
+        });
+        let (request_id, call_id) = tag.unzip();
+
         // Build log entry
         let entry = LogEntry {
             timestamp: Utc::now(),
@@ -114,6 +187,8 @@ where
             } else {
                 Some(visitor.fields)
             },
+            request_id,
+            call_id: call_id.flatten(),
         };
 
         // Add to capture buffer
@@ -2,16 +2,17 @@ use anyhow::Result;
 use axum::middleware::Next;
 use axum::{
     body::Body,
-    extract::State,
-    http::{Method, Request, StatusCode},
-    response::IntoResponse,
-    routing::get,
+    extract::{Path, State},
+    http::{HeaderMap, Method, Request, StatusCode, Uri},
+    response::{IntoResponse, Response},
+    routing::{any, delete, get},
     Json, Router,
 };
 use chrono::{DateTime, Utc};
 use clap::{Parser, ValueEnum};
+use futures::StreamExt;
 use rmcp::{
-    transport::sse_server::SseServer,
+    transport::sse_server::{SseServer, SseServerConfig},
     transport::stdio,
     transport::streamable_http_server::{
         session::local::LocalSessionManager, StreamableHttpService,
@@ -19,19 +20,26 @@ use rmcp::{
     ServiceExt,
 };
 use std::{
+    future::Future,
     net::SocketAddr,
-    sync::{
-        atomic::{AtomicUsize, Ordering},
-        Arc, Mutex,
-    },
-    time::SystemTime,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
 };
 use sysinfo::{ProcessesToUpdate, System};
+use terminator_mcp_agent::audit::AuditLog;
+use terminator_mcp_agent::auth::{require_bearer_token, AuthConfig, AUTH_TOKENS_ENV};
 use terminator_mcp_agent::cancellation::RequestManager;
+use terminator_mcp_agent::desktop_targets::{DesktopTarget, TargetRegistry, TARGET_ENV};
+use terminator_mcp_agent::policy::ToolPolicy;
+use terminator_mcp_agent::request_queue::{QueueError, RequestQueue, SharedPermit};
 use terminator_mcp_agent::server;
+use terminator_mcp_agent::tls::{load_server_config, TlsListener};
 use terminator_mcp_agent::utils::init_logging;
+use tokio_rustls::rustls::ServerConfig;
+use tokio_util::sync::CancellationToken;
 use tower_http::cors::CorsLayer;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
 #[derive(Parser, Debug)]
@@ -56,6 +64,51 @@ struct Args {
     /// Enable CORS for HTTP and SSE transports
     #[arg(long)]
     cors: bool,
+
+    /// Require this bearer token on HTTP and SSE requests, as `name:token` (repeatable).
+    /// More tokens can be given in MCP_AUTH_TOKENS as comma-separated `name:token` pairs.
+    #[arg(long = "auth-token", value_name = "NAME:TOKEN")]
+    auth_tokens: Vec<String>,
+
+    /// PEM certificate chain to serve HTTPS with (requires --tls-key)
+    #[arg(long, requires = "tls_key")]
+    tls_cert: Option<PathBuf>,
+
+    /// PEM private key for --tls-cert
+    #[arg(long, requires = "tls_cert")]
+    tls_key: Option<PathBuf>,
+
+    /// PEM CA bundle; clients must present a certificate signed by it (mutual TLS)
+    #[arg(long, requires = "tls_cert")]
+    client_ca: Option<PathBuf>,
+
+    /// YAML/JSON tool permission policy (allow/deny lists, read-only mode, argument guards)
+    #[arg(long, value_name = "PATH")]
+    policy: Option<PathBuf>,
+
+    /// Folder served as `terminator://workflows/...` resources; they are disabled without it
+    #[arg(long, value_name = "DIR")]
+    workflows_root: Option<PathBuf>,
+
+    /// Directory for the hash-chained audit log of every tool call (audit.jsonl)
+    #[arg(long, value_name = "DIR")]
+    audit_log: Option<PathBuf>,
+
+    /// Rotate audit.jsonl once it grows past this many megabytes
+    #[arg(long, value_name = "MB", default_value = "100", requires = "audit_log")]
+    audit_max_size_mb: u64,
+
+    /// Serve a named desktop target at /targets/NAME/mcp (HTTP only, repeatable):
+    /// `NAME=:1` for an X display (e.g. Xvfb), `NAME=headless` for an Xvfb display started
+    /// for the target, `NAME=virtual` for a Windows virtual display. A virtual display is
+    /// the session's interactive desktop, so at most one virtual target is allowed
+    #[arg(long = "target", value_name = "NAME=DISPLAY", value_parser = DesktopTarget::parse)]
+    targets: Vec<DesktopTarget>,
+
+    /// Start and supervise Xvfb, a private D-Bus session bus and AT-SPI, and automate
+    /// that display (Linux only)
+    #[arg(long)]
+    headless_linux: bool,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
@@ -142,13 +195,41 @@ fn kill_previous_mcp_instances() {
     }
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
+fn main() -> Result<()> {
     let args = Args::parse();
 
-    // Kill any previous MCP instances before starting
-    kill_previous_mcp_instances();
+    // Kill any previous MCP instances before starting. Target workers are started by
+    // a running agent and must not kill it or their sibling workers.
+    if std::env::var_os(TARGET_ENV).is_none() {
+        kill_previous_mcp_instances();
+    }
+
+    // The headless session points DISPLAY & co. at Xvfb, which is only sound while the
+    // process is single-threaded: start it before the runtime and stop it after.
+    #[cfg(target_os = "linux")]
+    let headless = if args.headless_linux {
+        Some(terminator::HeadlessSession::start(
+            terminator::HeadlessConfig::default(),
+        )?)
+    } else {
+        None
+    };
+    #[cfg(not(target_os = "linux"))]
+    if args.headless_linux {
+        anyhow::bail!("--headless-linux is only supported on Linux");
+    }
 
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?;
+    let result = runtime.block_on(run(args));
+    drop(runtime);
+    #[cfg(target_os = "linux")]
+    drop(headless);
+    result
+}
+
+async fn run(args: Args) -> Result<()> {
     // Install panic hook to prevent stdout corruption (used by other MCP servers)
     std::panic::set_hook(Box::new(|panic_info| {
         // CRITICAL: Never write to stdout during panic - it corrupts the JSON-RPC stream
@@ -231,13 +312,90 @@ async fn main() -> Result<()> {
         tracing::info!("CORS enabled for web transports");
     }
 
+    let auth = Arc::new(AuthConfig::from_sources(
+        &args.auth_tokens,
+        std::env::var(AUTH_TOKENS_ENV).ok().as_deref(),
+    )?);
+    let tls_config = match (&args.tls_cert, &args.tls_key) {
+        (Some(cert), Some(key)) => Some(load_server_config(cert, key, args.client_ca.as_deref())?),
+        _ => None,
+    };
+    let policy = match &args.policy {
+        Some(path) => {
+            let policy = ToolPolicy::load(path)?;
+            info!(
+                "Tool policy loaded from {}{}",
+                path.display(),
+                if policy.is_read_only() {
+                    " (read-only mode)"
+                } else {
+                    ""
+                }
+            );
+            policy
+        }
+        None => ToolPolicy::default(),
+    };
+    let audit = match &args.audit_log {
+        Some(dir) => {
+            let audit = AuditLog::open(dir, args.audit_max_size_mb.saturating_mul(1024 * 1024))?;
+            info!("Audit log: {}", dir.join("audit.jsonl").display());
+            Some(Arc::new(audit))
+        }
+        None => None,
+    };
+    if !args.targets.is_empty() && args.transport != TransportMode::Http {
+        anyhow::bail!("--target is only supported with the HTTP transport");
+    }
+    #[cfg(target_os = "linux")]
+    if let Some(headless) = terminator::platforms::linux_headless::active_status() {
+        info!(
+            "Headless session on display {} ({}), session bus {}",
+            headless.display, headless.screen, headless.bus_address
+        );
+    }
+    let scheme = if tls_config.is_some() {
+        "https"
+    } else {
+        "http"
+    };
+
+    if args.transport == TransportMode::Stdio {
+        if auth.is_enabled() || tls_config.is_some() {
+            info!("Authentication and TLS settings only apply to the HTTP and SSE transports");
+        }
+    } else {
+        if auth.is_enabled() {
+            let names: Vec<_> = auth.token_names().collect();
+            info!("Bearer token authentication enabled ({})", names.join(", "));
+        } else if !is_loopback_host(&args.host) {
+            warn!(
+                "No --auth-token configured: anyone who can reach {}:{} can control this desktop",
+                args.host, args.port
+            );
+        }
+        if tls_config.is_some() {
+            info!(
+                "TLS enabled{}",
+                if args.client_ca.is_some() {
+                    " with client certificate verification"
+                } else {
+                    ""
+                }
+            );
+        }
+    }
+
     match args.transport {
         TransportMode::Stdio => {
             tracing::info!("Starting stdio transport...");
 
             // Initialize with error recovery (pattern used by other MCP servers)
             let desktop = match server::DesktopWrapper::new_with_log_capture(log_capture.clone()) {
-                Ok(d) => d,
+                Ok(d) => d
+                    .with_policy(policy)
+                    .with_audit_log(audit)
+                    .with_workflows_root(args.workflows_root.clone()),
                 Err(e) => {
                     tracing::error!("Failed to initialize desktop wrapper: {}", e);
                     eprintln!("Fatal: Failed to initialize MCP server: {e}");
@@ -268,7 +426,7 @@ async fn main() -> Result<()> {
         }
         TransportMode::Sse => {
             let addr: SocketAddr = format!("{}:{}", args.host, args.port).parse()?;
-            tracing::info!("Starting SSE server on http://{}", addr);
+            tracing::info!("Starting SSE server on {}://{}", scheme, addr);
 
             if args.cors {
                 error!("SSE transport does not support CORS");
@@ -281,31 +439,57 @@ async fn main() -> Result<()> {
                 return Ok(());
             }
 
-            let desktop = server::DesktopWrapper::new_with_log_capture(log_capture.clone())?;
-            let ct = SseServer::serve(addr)
-                .await?
-                .with_service(move || desktop.clone());
+            let desktop = server::DesktopWrapper::new_with_log_capture(log_capture.clone())?
+                .with_policy(policy)
+                .with_audit_log(audit)
+                .with_workflows_root(args.workflows_root.clone());
+            let (sse_server, mut sse_router) = SseServer::new(SseServerConfig {
+                bind: addr,
+                sse_path: "/sse".to_string(),
+                post_path: "/message".to_string(),
+                ct: CancellationToken::new(),
+                sse_keep_alive: None,
+            });
+            let ct = sse_server.with_service(move || desktop.for_new_session());
 
-            info!("SSE server running on http://{addr}");
+            if auth.is_enabled() {
+                sse_router = sse_router.layer(axum::middleware::from_fn_with_state(
+                    auth.clone(),
+                    require_bearer_token,
+                ));
+            }
+
+            info!("SSE server running on {scheme}://{addr}");
             info!("Connect your MCP client to:");
-            info!("  SSE endpoint: http://{addr}/sse");
-            info!("  Message endpoint: http://{addr}/message");
+            info!("  SSE endpoint: {scheme}://{addr}/sse");
+            info!("  Message endpoint: {scheme}://{addr}/message");
             info!("Press Ctrl+C to stop");
 
-            tokio::signal::ctrl_c().await?;
-            ct.cancel();
+            // Open SSE streams only end once their sessions are cancelled
+            serve_router(addr, sse_router, tls_config, async move {
+                shutdown_signal().await;
+                ct.cancel();
+            })
+            .await?;
             tracing::info!("Shutting down SSE server");
         }
         TransportMode::Http => {
             let addr: SocketAddr = format!("{}:{}", args.host, args.port).parse()?;
-            tracing::info!("Starting streamable HTTP server on http://{}", addr);
+            tracing::info!("Starting streamable HTTP server on {}://{}", scheme, addr);
 
             // Lazy-initialize DesktopWrapper on first /mcp use so that /health can succeed on CI
             let service = StreamableHttpService::new(
                 {
                     let log_capture = log_capture.clone();
+                    let workflows_root = args.workflows_root.clone();
                     move || {
                         server::DesktopWrapper::new_with_log_capture(log_capture.clone())
+                            .map(|desktop| {
+                                desktop
+                                    .with_policy(policy.clone())
+                                    .with_audit_log(audit.clone())
+                                    .with_workflows_root(workflows_root.clone())
+                            })
                             .map_err(|e| std::io::Error::other(e.to_string()))
                     }
                 },
@@ -313,73 +497,173 @@ async fn main() -> Result<()> {
                 Default::default(),
             );
 
+            // Each target gets a worker agent with the same policy and tokens
+            let mut worker_args: Vec<std::ffi::OsString> = Vec::new();
+            if let Some(path) = &args.policy {
+                worker_args.push("--policy".into());
+                worker_args.push(path.clone().into_os_string());
+            }
+            if let Some(root) = &args.workflows_root {
+                worker_args.push("--workflows-root".into());
+                worker_args.push(root.clone().into_os_string());
+            }
+            if args.audit_log.is_some() {
+                worker_args.push("--audit-max-size-mb".into());
+                worker_args.push(args.audit_max_size_mb.to_string().into());
+            }
+            let targets = Arc::new(
+                TargetRegistry::start(
+                    args.targets.clone(),
+                    worker_args,
+                    // Tokens go through the environment; arguments are visible to every user
+                    vec![(AUTH_TOKENS_ENV.to_string(), auth.to_env_value())],
+                    args.audit_log.as_deref(),
+                )
+                .await?,
+            );
+
             // Busy-aware concurrency state with request tracking
             #[derive(Clone)]
             struct AppState {
-                active_requests: Arc<AtomicUsize>,
+                queue: RequestQueue,
                 last_activity: Arc<Mutex<String>>, // ISO-8601
-                max_concurrent: usize,
                 request_manager: RequestManager,
+                default_queue_timeout_ms: u64,
+                targets: Arc<TargetRegistry>,
             }
 
-            let max_concurrent = std::env::var("MCP_MAX_CONCURRENT")
-                .ok()
-                .and_then(|s| s.parse::<usize>().ok())
-                .unwrap_or(1);
+            let env_number =
+                |name: &str| std::env::var(name).ok().and_then(|s| s.parse::<u64>().ok());
+            let max_concurrent = env_number("MCP_MAX_CONCURRENT").unwrap_or(1) as usize;
+            let max_queued = env_number("MCP_MAX_QUEUE_DEPTH").unwrap_or(64) as usize;
+            let default_queue_timeout_ms = env_number("MCP_QUEUE_TIMEOUT_MS").unwrap_or(0);
+            if default_queue_timeout_ms > 0 {
+                info!(
+                    "Request queueing enabled: up to {} requests wait at most {}ms",
+                    max_queued, default_queue_timeout_ms
+                );
+            }
 
             let app_state = AppState {
-                active_requests: Arc::new(AtomicUsize::new(0)),
+                queue: RequestQueue::new(max_concurrent, max_queued),
                 last_activity: Arc::new(Mutex::new(chrono::Utc::now().to_rfc3339())),
-                max_concurrent,
                 request_manager: RequestManager::new(),
+                default_queue_timeout_ms,
+                targets: targets.clone(),
             };
 
-            async fn status_handler(State(state): State<AppState>) -> impl IntoResponse {
-                let active = state.active_requests.load(Ordering::SeqCst);
-                let busy = active >= state.max_concurrent;
+            fn status_body(state: &AppState) -> serde_json::Value {
+                let active = state.queue.active();
+                let queued = state.queue.queued();
                 let last_activity = state
                     .last_activity
                     .lock()
                     .map(|s| s.clone())
                     .unwrap_or_else(|_| chrono::Utc::now().to_rfc3339());
+                serde_json::json!({
+                    "busy": active >= state.queue.max_concurrent(),
+                    "activeRequests": active,
+                    "maxConcurrent": state.queue.max_concurrent(),
+                    "queueDepth": queued.len(),
+                    "queuedRequests": queued,
+                    "lastActivity": last_activity,
+                })
+            }
+
+            fn touch_activity(state: &AppState) {
+                if let Ok(mut ts) = state.last_activity.lock() {
+                    *ts = chrono::Utc::now().to_rfc3339();
+                }
+            }
+
+            async fn status_handler(State(state): State<AppState>) -> impl IntoResponse {
+                let mut body = status_body(&state);
+                // With targets, the host is busy only when no desktop has a free slot
+                let mut busy = body["busy"] == true;
+                if !state.targets.is_empty() {
+                    let targets = state.targets.statuses().await;
+                    busy = busy && targets.values().all(|status| status["busy"] == true);
+                    body["targets"] = serde_json::json!(targets);
+                }
                 let code = if busy {
                     StatusCode::SERVICE_UNAVAILABLE
                 } else {
                     StatusCode::OK
                 };
+                (code, Json(body))
+            }
+
+            async fn cancel_handler(
+                State(state): State<AppState>,
+                Path(request_id): Path<String>,
+            ) -> impl IntoResponse {
+                let cancelled = state.request_manager.cancel_request(&request_id).await;
+                let code = if cancelled {
+                    StatusCode::OK
+                } else {
+                    StatusCode::NOT_FOUND
+                };
+                (
+                    code,
+                    Json(serde_json::json!({"requestId": request_id, "cancelled": cancelled})),
+                )
+            }
+
+            async fn target_handler(
+                State(state): State<AppState>,
+                Path((name, rest)): Path<(String, String)>,
+                method: Method,
+                uri: Uri,
+                headers: HeaderMap,
+                body: Body,
+            ) -> Response {
+                state
+                    .targets
+                    .forward(&name, &rest, method, &uri, headers, body)
+                    .await
+            }
+
+            fn cancelled_response(request_id: &str) -> Response {
                 let body = serde_json::json!({
-                    "busy": busy,
-                    "activeRequests": active,
-                    "maxConcurrent": state.max_concurrent,
-                    "lastActivity": last_activity,
+                    "error": {
+                        "code": -32001,
+                        "message": format!("Request {} was cancelled", request_id)
+                    }
                 });
-                (code, Json(body))
+                (StatusCode::REQUEST_TIMEOUT, Json(body)).into_response()
+            }
+
+            /// Releases a request's queue slot and cancellation registration when dropped.
+            struct RequestGuard {
+                permit: SharedPermit,
+                request_id: String,
+                state: AppState,
+            }
+
+            impl Drop for RequestGuard {
+                fn drop(&mut self) {
+                    drop(self.permit.take());
+                    touch_activity(&self.state);
+                    let manager = self.state.request_manager.clone();
+                    let request_id = std::mem::take(&mut self.request_id);
+                    tokio::spawn(async move { manager.unregister(&request_id).await });
+                }
             }
 
             async fn mcp_gate(
                 State(state): State<AppState>,
-                req: Request<Body>,
+                mut req: Request<Body>,
                 next: Next,
             ) -> impl IntoResponse {
                 if req.method() == Method::POST {
-                    let active = state.active_requests.load(Ordering::SeqCst);
-                    if active >= state.max_concurrent {
-                        let last_activity = state
-                            .last_activity
-                            .lock()
-                            .map(|s| s.clone())
-                            .unwrap_or_else(|_| chrono::Utc::now().to_rfc3339());
-                        let body = serde_json::json!({
-                            "busy": true,
-                            "activeRequests": active,
-                            "maxConcurrent": state.max_concurrent,
-                            "lastActivity": last_activity,
-                        });
-                        return (StatusCode::SERVICE_UNAVAILABLE, Json(body)).into_response();
-                    }
-
                     // Extract request ID from headers or generate one
                     let headers = req.headers();
+                    let header_number = |name: &str| {
+                        headers
+                            .get(name)
+                            .and_then(|v| v.to_str().ok())
+                            .and_then(|s| s.trim().parse::<i64>().ok())
+                    };
                     let request_id = headers
                         .get("x-request-id")
                         .and_then(|v| v.to_str().ok())
@@ -387,58 +671,93 @@ async fn main() -> Result<()> {
                         .unwrap_or_else(|| Uuid::new_v4().to_string());
 
                     // Extract timeout from headers
-                    let timeout_ms = headers
-                        .get("x-request-timeout-ms")
-                        .and_then(|v| v.to_str().ok())
-                        .and_then(|s| s.parse::<u64>().ok())
+                    let timeout_ms = header_number("x-request-timeout-ms")
+                        .map(|ms| ms.max(0) as u64)
                         .or_else(|| {
                             std::env::var("MCP_DEFAULT_TIMEOUT_MS")
                                 .ok()
                                 .and_then(|s| s.parse::<u64>().ok())
                         });
+                    // Without a queue timeout a busy server answers 503 right away
+                    let queue_timeout_ms = header_number("x-request-queue-timeout-ms")
+                        .map(|ms| ms.max(0) as u64)
+                        .unwrap_or(state.default_queue_timeout_ms);
+                    let priority = header_number("x-request-priority")
+                        .map(|p| p.clamp(i32::MIN as i64, i32::MAX as i64) as i32)
+                        .unwrap_or(0);
 
                     debug!(
                         "Processing request {} with timeout {:?}ms",
                         request_id, timeout_ms
                     );
 
-                    // Register the request with cancellation support
+                    // Register the request with cancellation support. Registering before
+                    // queueing lets queued requests be cancelled and time out too.
                     let context = state
                         .request_manager
                         .register(request_id.clone(), timeout_ms)
                         .await;
 
-                    state.active_requests.fetch_add(1, Ordering::SeqCst);
-                    if let Ok(mut ts) = state.last_activity.lock() {
-                        *ts = chrono::Utc::now().to_rfc3339();
-                    }
-
-                    // Clone for cleanup
-                    let request_id_cleanup = request_id.clone();
-                    let manager_cleanup = state.request_manager.clone();
-                    let state_cleanup = state.clone();
+                    let permit = match state
+                        .queue
+                        .acquire(
+                            &request_id,
+                            priority,
+                            Duration::from_millis(queue_timeout_ms),
+                            &context.cancellation_token,
+                        )
+                        .await
+                    {
+                        Ok(permit) => permit,
+                        Err(QueueError::Cancelled) => {
+                            debug!("Queued request {} was cancelled", request_id);
+                            state.request_manager.unregister(&request_id).await;
+                            return cancelled_response(&request_id);
+                        }
+                        Err(e) => {
+                            state.request_manager.unregister(&request_id).await;
+                            let mut body = status_body(&state);
+                            body["busy"] = true.into();
+                            body["reason"] = match e {
+                                QueueError::QueueFull => "queue_full",
+                                QueueError::TimedOut(_) => "queue_timeout",
+                                _ => "busy",
+                            }
+                            .into();
+                            return (StatusCode::SERVICE_UNAVAILABLE, Json(body)).into_response();
+                        }
+                    };
+                    touch_activity(&state);
+                    let permit = SharedPermit::new(permit);
+                    req.extensions_mut().insert(permit.clone());
 
                     // Execute the request with cancellation support
                     let response = tokio::select! {
                         res = next.run(req) => res,
                         _ = context.cancellation_token.cancelled() => {
                             debug!("Request {} was cancelled", request_id);
-                            let body = serde_json::json!({
-                                "error": {
-                                    "code": -32001,
-                                    "message": format!("Request {} was cancelled", request_id)
-                                }
-                            });
-                            (StatusCode::REQUEST_TIMEOUT, Json(body)).into_response()
+                            cancelled_response(&request_id)
                         }
                     };
 
-                    // Cleanup
-                    manager_cleanup.unregister(&request_id_cleanup).await;
-                    state_cleanup.active_requests.fetch_sub(1, Ordering::SeqCst);
-                    if let Ok(mut ts) = state_cleanup.last_activity.lock() {
-                        *ts = chrono::Utc::now().to_rfc3339();
-                    }
+                    // Streamable HTTP sends the tool result in an SSE body, so the slot and
+                    // the registration are held until that body ends or the request is cancelled
+                    // (a debug session may take the slot over to keep running after that).
+                    let guard = RequestGuard {
+                        permit,
+                        request_id,
+                        state: state.clone(),
+                    };
+                    let cancelled = context.cancellation_token.clone().cancelled_owned();
+                    let (parts, body) = response.into_parts();
+                    let body =
+                        Body::from_stream(body.into_data_stream().take_until(cancelled).map(
+                            move |chunk| {
+                                let _ = &guard;
+                                chunk
+                            },
+                        ));
+                    let response = Response::from_parts(parts, body);
 
                     return response;
                 }
@@ -455,33 +774,46 @@ async fn main() -> Result<()> {
                 .route("/", get(root_handler))
                 .route("/health", get(health_check))
                 .route("/status", get(status_handler))
+                .route("/requests/{request_id}", delete(cancel_handler))
+                .route("/targets/{name}/{*rest}", any(target_handler))
                 .nest("/mcp", mcp_router)
                 .with_state(app_state.clone());
 
+            // Added before CORS so preflight requests are answered without a token
+            if auth.is_enabled() {
+                router = router.layer(axum::middleware::from_fn_with_state(
+                    auth.clone(),
+                    require_bearer_token,
+                ));
+            }
+
             if args.cors {
                 router = router.layer(CorsLayer::permissive());
             }
 
-            let tcp_listener = tokio::net::TcpListener::bind(addr).await?;
-
-            info!("Streamable HTTP server running on http://{addr}");
+            info!("Streamable HTTP server running on {scheme}://{addr}");
             if args.cors {
                 info!("CORS enabled - accessible from web browsers");
             }
             info!("Available endpoints:");
-            info!("  Root (endpoint list): http://{addr}/");
-            info!("  MCP client endpoint: http://{addr}/mcp");
-            info!("  Status endpoint: http://{addr}/status");
-            info!("  Health check: http://{addr}/health");
+            info!("  Root (endpoint list): {scheme}://{addr}/");
+            info!("  MCP client endpoint: {scheme}://{addr}/mcp");
+            info!("  Status endpoint: {scheme}://{addr}/status");
+            info!("  Cancel request: DELETE {scheme}://{addr}/requests/<request-id>");
+            info!("  Health check: {scheme}://{addr}/health");
+            for name in targets.names() {
+                info!("  Target '{name}' MCP endpoint: {scheme}://{addr}/targets/{name}/mcp");
+            }
             info!("Press Ctrl+C to stop");
 
-            axum::serve(tcp_listener, router)
-                .with_graceful_shutdown(async move {
-                    tokio::signal::ctrl_c().await.ok();
-                    info!("Received shutdown signal, cancelling active requests...");
-                    app_state.request_manager.cancel_all().await;
-                })
-                .await?;
+            serve_router(addr, router, tls_config, async move {
+                shutdown_signal().await;
+                info!("Received shutdown signal, cancelling active requests...");
+                app_state.request_manager.cancel_all().await;
+                app_state.targets.shutdown().await;
+            })
+            .await?;
+            targets.shutdown().await;
 
             tracing::info!("Shutting down HTTP server");
         }
@@ -493,6 +825,60 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// Serve `router` on `addr`, over TLS when a server config is given.
+async fn serve_router(
+    addr: SocketAddr,
+    router: Router,
+    tls_config: Option<ServerConfig>,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> Result<()> {
+    let tcp_listener = tokio::net::TcpListener::bind(addr).await?;
+    match tls_config {
+        Some(config) => {
+            axum::serve(TlsListener::new(tcp_listener, config)?, router)
+                .with_graceful_shutdown(shutdown)
+                .await?
+        }
+        None => {
+            axum::serve(tcp_listener, router)
+                .with_graceful_shutdown(shutdown)
+                .await?
+        }
+    }
+    Ok(())
+}
+
+/// Resolves on Ctrl+C, on SIGTERM (Unix) so service managers shut down cleanly too,
+/// and in target workers when the agent that started them is gone.
+async fn shutdown_signal() {
+    let terminate = async {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+            match signal(SignalKind::terminate()) {
+                Ok(mut terminate) => {
+                    terminate.recv().await;
+                    return;
+                }
+                Err(e) => warn!("Failed to listen for SIGTERM: {}", e),
+            }
+        }
+        std::future::pending::<()>().await
+    };
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => {}
+        _ = terminate => {}
+        _ = terminator_mcp_agent::desktop_targets::parent_exited() => {}
+    }
+}
+
+fn is_loopback_host(host: &str) -> bool {
+    host == "localhost"
+        || host
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
 async fn root_handler() -> impl axum::response::IntoResponse {
     (
         axum::http::StatusCode::OK,
@@ -504,7 +890,9 @@ async fn root_handler() -> impl axum::response::IntoResponse {
                 "/": "This endpoint - lists available endpoints",
                 "/mcp": "MCP protocol endpoint - connect your MCP client here",
                 "/health": "Health check endpoint - returns server status",
-                "/status": "Status endpoint - shows active requests and concurrency info"
+                "/status": "Status endpoint - shows active requests, queued requests and concurrency info",
+                "/requests/{request_id}": "DELETE to cancel a running or queued request",
+                "/targets/{name}/mcp": "MCP endpoint of a desktop target started with --target"
             },
             "usage": {
                 "mcp_client": "Connect your MCP client to: /mcp",
@@ -1,8 +1,10 @@
+use crate::workflow_assertions::AssertionConfig;
 use crate::workflow_events::{
     ClickEvent, EnhancedUIElement, McpToolStep, TextInputCompletedEvent, WorkflowEvent,
 };
 use anyhow::Result;
 use serde_json::json;
+use terminator_workflow_recorder::PostProcessConfig;
 use tracing::{debug, warn};
 
 /// Configuration for MCP conversion behavior
@@ -18,6 +20,10 @@ pub struct ConversionConfig {
     pub validate_during_recording: bool,
     /// Whether to prefer browser scripts over UI automation for browser interactions
     pub prefer_browser_scripts: bool,
+    /// Passes run over the recorded events before they are converted
+    pub postprocess: PostProcessConfig,
+    /// Checks of the recorded UI state added after the converted steps
+    pub assertions: AssertionConfig,
 }
 
 impl Default for ConversionConfig {
@@ -28,6 +34,8 @@ impl Default for ConversionConfig {
             max_fallback_strategies: 3,
             validate_during_recording: false, // Expensive, off by default
             prefer_browser_scripts: true,     // NEW: Default to browser scripts
+            postprocess: PostProcessConfig::default(),
+            assertions: AssertionConfig::default(),
         }
     }
 }
@@ -1517,6 +1525,30 @@ impl McpConverter {
         let mut sequence = Vec::new();
         let mut notes = Vec::new();
 
+        // Item picked from a combo box (see the recorder's CollapseDropdownSelection pass)
+        if event.field_type.eq_ignore_ascii_case("combobox") {
+            sequence.push(McpToolStep {
+                tool_name: "select_option".to_string(),
+                arguments: json!({
+                    "selector": self.generate_text_field_selector(event),
+                    "option_name": event.text_value
+                }),
+                description: Some(format!(
+                    "Select '{}' in {}",
+                    event.text_value,
+                    event.field_name.as_deref().unwrap_or("dropdown")
+                )),
+                timeout_ms: Some(5000),
+                continue_on_error: Some(false),
+                delay_ms: Some(200),
+            });
+            return Ok(TextInputStrategy {
+                sequence,
+                semantic_action: "select_option".to_string(),
+                fallbacks: vec![],
+            });
+        }
+
         // Analyze UI pattern if context is available
         if let Some(context) = ui_context {
             if context.interaction_context.ui_pattern == "dropdown" {
@@ -1,7 +1,8 @@
+use crate::declarative_parser::{run_declarative_parser, DeclarativeParser};
 use crate::scripting_engine::execute_javascript_with_nodejs;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{json, Value};
 
 /// JavaScript-based parser definition
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -20,6 +21,27 @@ pub struct OutputParserDefinition {
     /// Simplified alias for 'javascript_code' - inspired by GitHub Actions syntax
     /// Use this for inline JavaScript code instead of javascript_code
     pub run: Option<String>,
+    /// Rust-native declarative parser (selectors, JSONPath, regex, type coercion).
+    /// Runs without Node.js; use instead of JavaScript code.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub declarative: Option<DeclarativeParser>,
+}
+
+/// The `run_command` arguments a JavaScript parser amounts to, so the tool policy can be
+/// checked before it runs in Node.js. `None` for declarative parsers, which don't.
+pub fn script_arguments(parser_def_val: &Value) -> Option<Value> {
+    if let Some(code) = parser_def_val.as_str() {
+        return Some(json!({"engine": "javascript", "run": code}));
+    }
+    let parser_def: OutputParserDefinition = serde_json::from_value(parser_def_val.clone()).ok()?;
+    match (
+        parser_def.javascript_code.or(parser_def.run),
+        parser_def.javascript_file_path,
+    ) {
+        (Some(code), _) => Some(json!({"engine": "javascript", "run": code})),
+        (None, Some(path)) => Some(json!({"engine": "javascript", "script_file": path})),
+        (None, None) => None,
+    }
 }
 
 /// The main entry point for parsing tool output.
@@ -34,6 +56,7 @@ pub async fn run_output_parser(
             javascript_code: parser_def_val.as_str().map(|s| s.to_string()),
             javascript_file_path: None,
             run: None,
+            declarative: None,
         }
     } else {
         serde_json::from_value(parser_def_val.clone()).map_err(|e| {
@@ -47,6 +70,17 @@ pub async fn run_output_parser(
     // Determine the JavaScript source - support 'run' as alias for 'javascript_code'
     let javascript_code = parser_def.javascript_code.or(parser_def.run);
 
+    if let Some(declarative) = &parser_def.declarative {
+        if javascript_code.is_some() || parser_def.javascript_file_path.is_some() {
+            return Err(anyhow::anyhow!(
+                "Cannot combine 'declarative' with JavaScript ('javascript_code', 'run' or 'javascript_file_path'). Please provide only one."
+            ));
+        }
+        let ui_tree =
+            find_ui_tree_in_results(tool_output, parser_def.ui_tree_source_step_id.as_deref())?;
+        return run_declarative_parser(declarative, ui_tree.as_ref(), tool_output).map(Some);
+    }
+
     let user_javascript_code = match (javascript_code, parser_def.javascript_file_path) {
         (Some(code), None) => {
             // Inline JavaScript provided (via javascript_code or run)
@@ -65,7 +99,7 @@ pub async fn run_output_parser(
         }
         (None, None) => {
             return Err(anyhow::anyhow!(
-                "Must provide either 'javascript_code'/'run' (inline JavaScript) or 'javascript_file_path' (path to JavaScript file)."
+                "Must provide either 'javascript_code'/'run' (inline JavaScript), 'javascript_file_path' (path to JavaScript file) or 'declarative'."
             ));
         }
     };
@@ -217,6 +251,9 @@ fn find_ui_tree_in_results(tool_output: &Value, step_id: Option<&str>) -> Result
                     if let Some(content) = result_obj.get("content") {
                         if let Some(content_array) = content.as_array() {
                             for content_item in content_array.iter().rev() {
+                                if let Some(ui_tree) = content_item.get("ui_tree") {
+                                    return Ok(Some(ui_tree.clone()));
+                                }
                                 if let Some(text) = content_item.get("text") {
                                     if let Some(text_str) = text.as_str() {
                                         if let Ok(parsed_json) =
@@ -242,7 +279,22 @@ fn find_ui_tree_in_results(tool_output: &Value, step_id: Option<&str>) -> Result
 #[cfg(test)]
 mod tests {
     use super::*;
-    use serde_json::json;
+
+    #[test]
+    fn test_script_arguments() {
+        assert_eq!(
+            script_arguments(&json!("return [];")),
+            Some(json!({"engine": "javascript", "run": "return [];"}))
+        );
+        assert_eq!(
+            script_arguments(&json!({"javascript_file_path": "parse.js"})),
+            Some(json!({"engine": "javascript", "script_file": "parse.js"}))
+        );
+        assert_eq!(
+            script_arguments(&json!({"declarative": {"fields": {}}})),
+            None
+        );
+    }
 
     #[test]
     fn test_javascript_parser() {
@@ -297,6 +349,7 @@ mod tests {
                 .to_string(),
             ),
             javascript_file_path: None,
+            declarative: None,
         };
         // Note: This test would require an async runtime to execute JavaScript
         // For now, we'll just verify the parser definition structure is correct
@@ -327,6 +380,7 @@ mod tests {
                 .to_string(),
             ),
             javascript_file_path: None,
+            declarative: None,
         };
 
         // Verify parser definition structure
@@ -377,6 +431,7 @@ mod tests {
                 .to_string(),
             ),
             javascript_file_path: None,
+            declarative: None,
         };
 
         // Verify parser definition structure
@@ -421,6 +476,7 @@ mod tests {
                 .to_string(),
             ),
             javascript_file_path: None,
+            declarative: None,
         };
 
         json!({
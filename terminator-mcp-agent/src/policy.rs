@@ -0,0 +1,447 @@
+//! Tool permission policy loaded from `--policy <file>`.
+//!
+//! A policy decides, before a tool runs, whether the call is allowed. It can deny
+//! or allow tools by name, restrict the server to read-only tools, and guard the
+//! arguments of the tools that reach outside the UI tree (`run_command`,
+//! `open_application`, `navigate_browser`). Without a policy every call is allowed.
+//!
+//! ```yaml
+//! read_only: false
+//! deny: ["execute_browser_script", "close_element"]
+//! run_command:
+//!   engines: ["python", "shell"]
+//!   allowed_commands: ["^git status$", "^Get-Process"]
+//!   denied_commands: ["Remove-Item", "rm -rf"]
+//! open_application:
+//!   apps: ["notepad*", "calc*"]
+//! navigate_browser:
+//!   urls: ["https://*.example.com/*"]
+//! ```
+
+use anyhow::{anyhow, Context, Result};
+use regex::{Regex, RegexBuilder};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Phrase the tool descriptions use to mark tools that never change UI state.
+pub const READ_ONLY_MARKER: &str = "read-only operation";
+
+/// On-disk policy format (YAML or JSON).
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PolicyFile {
+    /// Only allow tools documented as read-only operations.
+    #[serde(default)]
+    pub read_only: bool,
+    /// Tool name patterns (`*` and `?` wildcards). When set, other tools are denied.
+    #[serde(default)]
+    pub allow: Option<Vec<String>>,
+    /// Tool name patterns that are always denied, even if allowed elsewhere.
+    #[serde(default)]
+    pub deny: Vec<String>,
+    #[serde(default)]
+    pub run_command: Option<RunCommandPolicy>,
+    #[serde(default)]
+    pub open_application: Option<OpenApplicationPolicy>,
+    #[serde(default)]
+    pub navigate_browser: Option<NavigateBrowserPolicy>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RunCommandPolicy {
+    /// Allowed `engine` values. Plain shell commands (no engine) count as `shell`.
+    #[serde(default)]
+    pub engines: Option<Vec<String>>,
+    /// Regexes; the command (or script file path) must match at least one.
+    #[serde(default)]
+    pub allowed_commands: Option<Vec<String>>,
+    /// Regexes; a command matching any of them is denied.
+    #[serde(default)]
+    pub denied_commands: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OpenApplicationPolicy {
+    /// Application name patterns (`*` and `?` wildcards, case-insensitive).
+    pub apps: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NavigateBrowserPolicy {
+    /// URL patterns (`*` and `?` wildcards, case-insensitive).
+    pub urls: Vec<String>,
+}
+
+/// Why a tool call was refused.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyDenial {
+    pub tool_name: String,
+    /// The policy rule that refused the call, e.g. `deny` or `run_command.engines`.
+    pub rule: &'static str,
+    pub reason: String,
+}
+
+impl PolicyDenial {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "error_type": "policy_denied",
+            "tool_name": self.tool_name,
+            "rule": self.rule,
+            "reason": self.reason,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct CommandRules {
+    engines: Option<HashSet<String>>,
+    allowed: Option<Vec<Regex>>,
+    denied: Vec<Regex>,
+}
+
+/// A compiled policy. The default policy allows everything.
+#[derive(Debug, Clone, Default)]
+pub struct ToolPolicy {
+    read_only: bool,
+    read_only_tools: HashSet<String>,
+    allow: Option<Vec<Regex>>,
+    deny: Vec<Regex>,
+    run_command: Option<CommandRules>,
+    apps: Option<Vec<Regex>>,
+    urls: Option<Vec<Regex>>,
+}
+
+impl ToolPolicy {
+    /// Load and compile a policy file. `.json` files are parsed as JSON, anything else as YAML.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read policy file '{}'", path.display()))?;
+        let file: PolicyFile = if path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+        {
+            serde_json::from_str(&text)
+                .with_context(|| format!("Invalid policy file '{}'", path.display()))?
+        } else {
+            serde_yaml::from_str(&text)
+                .with_context(|| format!("Invalid policy file '{}'", path.display()))?
+        };
+        Self::compile(file)
+    }
+
+    pub fn compile(file: PolicyFile) -> Result<Self> {
+        let globs = |patterns: &[String], case_insensitive: bool| {
+            patterns
+                .iter()
+                .map(|p| glob_to_regex(p, case_insensitive))
+                .collect::<Result<Vec<_>>>()
+        };
+        let regexes = |patterns: &[String]| {
+            patterns
+                .iter()
+                .map(|p| Regex::new(p).with_context(|| format!("Invalid command pattern '{p}'")))
+                .collect::<Result<Vec<_>>>()
+        };
+
+        let run_command = match file.run_command {
+            Some(rules) => Some(CommandRules {
+                engines: rules
+                    .engines
+                    .map(|engines| engines.iter().map(|e| canonical_engine(e)).collect()),
+                allowed: rules.allowed_commands.as_deref().map(regexes).transpose()?,
+                denied: regexes(&rules.denied_commands)?,
+            }),
+            None => None,
+        };
+
+        Ok(Self {
+            read_only: file.read_only,
+            read_only_tools: HashSet::new(),
+            allow: file.allow.as_deref().map(|p| globs(p, false)).transpose()?,
+            deny: globs(&file.deny, false)?,
+            run_command,
+            apps: file
+                .open_application
+                .map(|rules| globs(&rules.apps, true))
+                .transpose()?,
+            urls: file
+                .navigate_browser
+                .map(|rules| globs(&rules.urls, true))
+                .transpose()?,
+        })
+    }
+
+    /// Register the tools that `read_only` mode keeps available.
+    pub fn with_read_only_tools<I, S>(mut self, tools: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.read_only_tools = tools.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Whether the tool may be called at all, ignoring argument guards. Used to hide
+    /// denied tools from `tools/list`.
+    pub fn permits_tool(&self, tool_name: &str) -> bool {
+        self.check_name(tool_name).is_ok()
+    }
+
+    /// Check a tool call against the policy.
+    pub fn check(&self, tool_name: &str, arguments: &Value) -> Result<(), PolicyDenial> {
+        self.check_name(tool_name)?;
+        let deny = |rule, reason: String| {
+            Err(PolicyDenial {
+                tool_name: tool_name.to_string(),
+                rule,
+                reason,
+            })
+        };
+        let arg = |key: &str| arguments.get(key).and_then(Value::as_str);
+
+        match tool_name {
+            "run_command" => {
+                let Some(rules) = &self.run_command else {
+                    return Ok(());
+                };
+                let engine = arg("engine")
+                    .map(canonical_engine)
+                    .unwrap_or_else(|| "shell".to_string());
+                if let Some(engines) = &rules.engines {
+                    if !engines.contains(&engine) {
+                        return deny(
+                            "run_command.engines",
+                            format!("Engine '{engine}' is not in the allowed engines"),
+                        );
+                    }
+                }
+                let command = arg("run").or_else(|| arg("script_file")).unwrap_or("");
+                if let Some(pattern) = rules.denied.iter().find(|re| re.is_match(command)) {
+                    return deny(
+                        "run_command.denied_commands",
+                        format!("Command matches denied pattern '{}'", pattern.as_str()),
+                    );
+                }
+                if let Some(allowed) = &rules.allowed {
+                    if !allowed.iter().any(|re| re.is_match(command)) {
+                        return deny(
+                            "run_command.allowed_commands",
+                            "Command does not match any allowed pattern".to_string(),
+                        );
+                    }
+                }
+            }
+            "open_application" => {
+                if let Some(apps) = &self.apps {
+                    let app = arg("app_name").unwrap_or("");
+                    if !apps.iter().any(|re| re.is_match(app)) {
+                        return deny(
+                            "open_application.apps",
+                            format!("Application '{app}' is not in the allowed apps"),
+                        );
+                    }
+                }
+            }
+            "navigate_browser" => {
+                if let Some(urls) = &self.urls {
+                    let url = arg("url").unwrap_or("");
+                    if !urls.iter().any(|re| re.is_match(url)) {
+                        return deny(
+                            "navigate_browser.urls",
+                            format!("URL '{url}' does not match any allowed pattern"),
+                        );
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn check_name(&self, tool_name: &str) -> Result<(), PolicyDenial> {
+        let deny = |rule, reason: &str| {
+            Err(PolicyDenial {
+                tool_name: tool_name.to_string(),
+                rule,
+                reason: reason.to_string(),
+            })
+        };
+        if self.deny.iter().any(|re| re.is_match(tool_name)) {
+            return deny("deny", "Tool is on the policy deny list");
+        }
+        if self.read_only && !self.read_only_tools.contains(tool_name) {
+            return deny(
+                "read_only",
+                "Server is in read-only mode and this tool can change UI state",
+            );
+        }
+        if let Some(allow) = &self.allow {
+            if !allow.iter().any(|re| re.is_match(tool_name)) {
+                return deny("allow", "Tool is not on the policy allow list");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// One name per `run_command` engine, so a rule written for an alias (e.g. `node`)
+/// covers the others (`bun`, `js`, `javascript`) too.
+fn canonical_engine(engine: &str) -> String {
+    let engine = engine.to_ascii_lowercase();
+    match engine.as_str() {
+        "node" | "bun" | "javascript" | "js" => "javascript".to_string(),
+        "typescript" | "ts" => "typescript".to_string(),
+        "python" | "py" => "python".to_string(),
+        _ => engine,
+    }
+}
+
+/// Compile a `*`/`?` wildcard pattern into an anchored regex.
+fn glob_to_regex(pattern: &str, case_insensitive: bool) -> Result<Regex> {
+    let mut re = String::from("^");
+    for c in pattern.chars() {
+        match c {
+            '*' => re.push_str(".*"),
+            '?' => re.push('.'),
+            c => re.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    re.push('$');
+    RegexBuilder::new(&re)
+        .case_insensitive(case_insensitive)
+        .build()
+        .map_err(|e| anyhow!("Invalid pattern '{}': {}", pattern, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(yaml: &str) -> ToolPolicy {
+        ToolPolicy::compile(serde_yaml::from_str(yaml).unwrap())
+            .unwrap()
+            .with_read_only_tools(["get_window_tree", "get_applications"])
+    }
+
+    #[test]
+    fn test_tool_name_rules() {
+        let allow_all = ToolPolicy::default();
+        assert!(allow_all
+            .check("run_command", &json!({"run": "rm -rf /"}))
+            .is_ok());
+
+        let p = policy("allow: ['get_*', 'click_element', 'close_element']\ndeny: ['close_*']");
+        assert!(p.permits_tool("get_window_tree"));
+        assert!(p.permits_tool("click_element"));
+        assert_eq!(
+            p.check("close_element", &json!({})).unwrap_err().rule,
+            "deny"
+        );
+        assert_eq!(
+            p.check("run_command", &json!({})).unwrap_err().rule,
+            "allow"
+        );
+
+        let p = policy("read_only: true");
+        assert!(p.permits_tool("get_applications"));
+        assert_eq!(
+            p.check("click_element", &json!({})).unwrap_err().rule,
+            "read_only"
+        );
+    }
+
+    #[test]
+    fn test_argument_guards() {
+        let p = policy(
+            r#"
+run_command:
+  engines: [python, shell]
+  allowed_commands: ['^git status$', '^scripts/']
+  denied_commands: ['rm -rf']
+open_application:
+  apps: ['notepad*']
+navigate_browser:
+  urls: ['https://*.example.com/*']
+"#,
+        );
+        let rule = |tool: &str, args: Value| p.check(tool, &args).err().map(|d| d.rule);
+
+        assert_eq!(rule("run_command", json!({"run": "git status"})), None);
+        assert_eq!(
+            rule(
+                "run_command",
+                json!({"engine": "python", "script_file": "scripts/a.py"})
+            ),
+            None
+        );
+        assert_eq!(
+            rule(
+                "run_command",
+                json!({"engine": "Node", "run": "git status"})
+            ),
+            Some("run_command.engines")
+        );
+        assert_eq!(
+            rule("run_command", json!({"engine": "py", "run": "git status"})),
+            None
+        );
+        assert_eq!(
+            rule("run_command", json!({"run": "git status; rm -rf /"})),
+            Some("run_command.denied_commands")
+        );
+        assert_eq!(
+            rule("run_command", json!({"run": "git push"})),
+            Some("run_command.allowed_commands")
+        );
+
+        assert_eq!(
+            rule("open_application", json!({"app_name": "Notepad.exe"})),
+            None
+        );
+        assert_eq!(
+            rule("open_application", json!({"app_name": "cmd"})),
+            Some("open_application.apps")
+        );
+        assert_eq!(
+            rule(
+                "navigate_browser",
+                json!({"url": "https://app.example.com/login"})
+            ),
+            None
+        );
+        assert_eq!(
+            rule(
+                "navigate_browser",
+                json!({"url": "https://example.com.evil.io/"})
+            ),
+            Some("navigate_browser.urls")
+        );
+    }
+
+    #[test]
+    fn test_engine_aliases() {
+        let p = policy("run_command:\n  engines: [node]");
+        for engine in ["node", "bun", "js", "JavaScript"] {
+            assert!(p.check("run_command", &json!({"engine": engine})).is_ok());
+        }
+        assert!(p.check("run_command", &json!({"engine": "ts"})).is_err());
+        assert!(p.check("run_command", &json!({"run": "ls"})).is_err());
+    }
+
+    #[test]
+    fn test_invalid_policy() {
+        assert!(serde_yaml::from_str::<PolicyFile>("readonly: true").is_err());
+        let bad_regex: PolicyFile =
+            serde_yaml::from_str("run_command:\n  denied_commands: ['(']").unwrap();
+        assert!(ToolPolicy::compile(bad_regex).is_err());
+    }
+}
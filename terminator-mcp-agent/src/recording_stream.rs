@@ -0,0 +1,544 @@
+//! Live stream of the events of an active recording, served as Server-Sent Events on
+//! a local port (`record_workflow` with `stream_events: true`).
+//!
+//! Each stream has a random token, included in its URL as `?token=` (or sent as
+//! `Authorization: Bearer <token>`); requests without it get 401. Requests whose
+//! `Host` is not `127.0.0.1` or `localhost` get 403, so a web page can't reach the
+//! stream through DNS rebinding.
+//!
+//! `GET /events` sends a `workflow_event` message per recorded event, with the
+//! `SerializableWorkflowEvent` (without its screenshot) and the MCP steps it converts
+//! to. Query parameters filter what a client receives:
+//!
+//! - `types=Click,TextInputCompleted` — only these event types (all by default)
+//! - `raw=true` — also raw keyboard and mouse events (left out by default)
+//! - `steps=false` — leave out the converted steps
+//!
+//! Each client has a buffer of `STREAM_BUFFER` events. A client that falls further
+//! behind never slows the recording down: it skips the oldest events and receives a
+//! `lagged` message with the number of events it missed. If the stream itself falls
+//! behind the recorder, every client gets a `lagged` message.
+
+use crate::auth::constant_time_eq;
+use crate::mcp_converter::{ConversionConfig, McpConverter};
+use crate::workflow_converter::event_type_name;
+use crate::workflow_events::{McpToolStep, WorkflowEvent};
+use axum::extract::{Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use terminator_workflow_recorder::SerializableWorkflowEvent;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+/// Events buffered for each client before it starts skipping them
+pub const STREAM_BUFFER: usize = 256;
+
+/// A recorded event as sent to stream clients
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamedEvent {
+    /// Position of the event in the stream, starting at 1
+    pub sequence: u64,
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    pub event_type: &'static str,
+    pub event: SerializableWorkflowEvent,
+    /// Steps the event converts to on its own. The workflow returned when the recording
+    /// stops is converted after clean-up, so it can differ.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub steps: Vec<McpToolStep>,
+    #[serde(skip)]
+    raw: bool,
+}
+
+/// Query parameters of `GET /events`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct StreamFilter {
+    /// Comma-separated event types, e.g. `Click,TextInputCompleted`
+    pub types: Option<String>,
+    /// Include raw keyboard and mouse events
+    pub raw: bool,
+    /// Include the converted steps
+    pub steps: bool,
+}
+
+/// Credentials of `GET /events`, when not sent as a bearer token
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+struct StreamAccess {
+    token: Option<String>,
+}
+
+/// What the stream hands to its clients
+#[derive(Debug)]
+enum StreamMessage {
+    Event(StreamedEvent),
+    /// The stream fell behind the recorder and missed this many events
+    Lagged(u64),
+}
+
+impl Default for StreamFilter {
+    fn default() -> Self {
+        Self {
+            types: None,
+            raw: false,
+            steps: true,
+        }
+    }
+}
+
+impl StreamFilter {
+    pub fn matches(&self, event: &StreamedEvent) -> bool {
+        if event.raw && !self.raw {
+            return false;
+        }
+        match &self.types {
+            Some(types) => types
+                .split(',')
+                .any(|t| t.trim().eq_ignore_ascii_case(event.event_type)),
+            None => true,
+        }
+    }
+
+    fn message(&self, event: &StreamedEvent) -> Event {
+        let mut data = json!(event);
+        if !self.steps {
+            if let Some(data) = data.as_object_mut() {
+                data.remove("steps");
+            }
+        }
+        Event::default()
+            .event("workflow_event")
+            .id(event.sequence.to_string())
+            .data(data.to_string())
+    }
+}
+
+/// Serves the events of a recording until stopped or dropped, or until the recording
+/// ends.
+pub struct RecordingStreamServer {
+    addr: SocketAddr,
+    token: String,
+    tx: broadcast::Sender<Arc<StreamMessage>>,
+    shutdown: CancellationToken,
+}
+
+impl RecordingStreamServer {
+    /// Stream the events received on `events` from `127.0.0.1:port` (a free port for 0),
+    /// converting them to MCP steps with `config`.
+    pub async fn start(
+        events: broadcast::Receiver<WorkflowEvent>,
+        port: u16,
+        config: ConversionConfig,
+    ) -> std::io::Result<Self> {
+        let listener = tokio::net::TcpListener::bind(("127.0.0.1", port)).await?;
+        let addr = listener.local_addr()?;
+        let token = uuid::Uuid::new_v4().simple().to_string();
+        let shutdown = CancellationToken::new();
+        let (tx, _) = broadcast::channel(STREAM_BUFFER);
+
+        tokio::spawn(forward_events(
+            events,
+            tx.clone(),
+            McpConverter::with_config(config),
+            shutdown.clone(),
+        ));
+        let router = Router::new()
+            .route("/events", get(stream_events))
+            .with_state(StreamState {
+                tx: tx.clone(),
+                token: token.clone(),
+                port: addr.port(),
+                shutdown: shutdown.clone(),
+            });
+        let stopped = shutdown.clone().cancelled_owned();
+        tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, router)
+                .with_graceful_shutdown(stopped)
+                .await
+            {
+                warn!("Recording stream server failed: {}", e);
+            }
+        });
+
+        info!("Streaming recorded events on http://{}/events", addr);
+        Ok(Self {
+            addr,
+            token,
+            tx,
+            shutdown,
+        })
+    }
+
+    /// URL of the stream, including its token
+    pub fn url(&self) -> String {
+        format!("http://{}/events?token={}", self.addr, self.token)
+    }
+
+    /// Number of connected clients
+    pub fn client_count(&self) -> usize {
+        self.tx.receiver_count()
+    }
+
+    /// Stop serving; the streams of connected clients end.
+    pub fn stop(&self) {
+        self.shutdown.cancel();
+    }
+}
+
+impl Drop for RecordingStreamServer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[derive(Clone)]
+struct StreamState {
+    tx: broadcast::Sender<Arc<StreamMessage>>,
+    token: String,
+    port: u16,
+    shutdown: CancellationToken,
+}
+
+impl StreamState {
+    /// Reject requests for another host or without the stream's token.
+    fn authorize(&self, headers: &HeaderMap, access: &StreamAccess) -> Result<(), Response> {
+        let host = headers.get(header::HOST).and_then(|v| v.to_str().ok());
+        if !host.is_some_and(|host| self.is_local_host(host)) {
+            warn!("Rejected recording stream request for host {:?}", host);
+            return Err((StatusCode::FORBIDDEN, "Unexpected Host header").into_response());
+        }
+
+        let presented = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| {
+                v.strip_prefix("Bearer ")
+                    .or_else(|| v.strip_prefix("bearer "))
+            })
+            .map(str::trim)
+            .or(access.token.as_deref());
+        match presented {
+            Some(token) if constant_time_eq(token.as_bytes(), self.token.as_bytes()) => Ok(()),
+            _ => {
+                warn!("Rejected recording stream request without a valid token");
+                Err((
+                    StatusCode::UNAUTHORIZED,
+                    [(header::WWW_AUTHENTICATE, "Bearer")],
+                    "Missing or invalid stream token",
+                )
+                    .into_response())
+            }
+        }
+    }
+
+    fn is_local_host(&self, host: &str) -> bool {
+        let (name, port) = match host.rsplit_once(':') {
+            Some((name, port)) => (name, port.parse().ok()),
+            None => (host, Some(80)),
+        };
+        port == Some(self.port) && (name == "127.0.0.1" || name.eq_ignore_ascii_case("localhost"))
+    }
+}
+
+/// Converts each recorded event once and hands it to all clients.
+async fn forward_events(
+    mut events: broadcast::Receiver<WorkflowEvent>,
+    tx: broadcast::Sender<Arc<StreamMessage>>,
+    converter: McpConverter,
+    shutdown: CancellationToken,
+) {
+    let mut sequence = 0;
+    loop {
+        let mut event = tokio::select! {
+            _ = shutdown.cancelled() => return,
+            received = events.recv() => match received {
+                Ok(event) => event,
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("Recording stream fell behind and skipped {} events", skipped);
+                    let _ = tx.send(Arc::new(StreamMessage::Lagged(skipped)));
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            },
+        };
+        sequence += 1;
+
+        let raw = event.is_raw_input();
+        let steps = if raw {
+            Vec::new()
+        } else {
+            match converter.convert_event(&event, None).await {
+                Ok(result) => result.primary_sequence,
+                Err(e) => {
+                    debug!("Failed to convert streamed event {}: {}", sequence, e);
+                    Vec::new()
+                }
+            }
+        };
+        let event_type = event_type_name(&event);
+        // Screenshots stay in the recording; streaming them would cost a few hundred
+        // kilobytes of base64 per event.
+        event.metadata_mut().screenshot = None;
+        // Serializing reads the element's properties through the accessibility API.
+        let event = match tokio::task::spawn_blocking(move || {
+            SerializableWorkflowEvent::from(&event)
+        })
+        .await
+        {
+            Ok(event) => event,
+            Err(e) => {
+                debug!("Failed to serialize streamed event {}: {}", sequence, e);
+                continue;
+            }
+        };
+        let streamed = StreamedEvent {
+            sequence,
+            timestamp: chrono::Utc::now().timestamp_millis() as u64,
+            event_type,
+            event,
+            steps,
+            raw,
+        };
+        // Sending fails only while no client is connected.
+        let _ = tx.send(Arc::new(StreamMessage::Event(streamed)));
+    }
+    info!("Recording ended, closing the event stream");
+    shutdown.cancel();
+}
+
+async fn stream_events(
+    State(state): State<StreamState>,
+    headers: HeaderMap,
+    Query(access): Query<StreamAccess>,
+    Query(filter): Query<StreamFilter>,
+) -> Response {
+    if let Err(rejection) = state.authorize(&headers, &access) {
+        return rejection;
+    }
+    Sse::new(subscribe(&state, filter))
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+/// The messages `filter` lets through for a new client
+fn subscribe(
+    state: &StreamState,
+    filter: StreamFilter,
+) -> impl Stream<Item = Result<Event, Infallible>> {
+    let lagged = |skipped: u64| {
+        Event::default()
+            .event("lagged")
+            .data(json!({ "skipped": skipped }).to_string())
+    };
+    let filter = Arc::new(filter);
+    futures::stream::unfold(state.tx.subscribe(), move |mut rx| {
+        let filter = Arc::clone(&filter);
+        async move {
+            loop {
+                let message = match rx.recv().await {
+                    Ok(message) => match message.as_ref() {
+                        StreamMessage::Event(event) if filter.matches(event) => {
+                            filter.message(event)
+                        }
+                        StreamMessage::Event(_) => continue,
+                        StreamMessage::Lagged(skipped) => lagged(*skipped),
+                    },
+                    Err(RecvError::Lagged(skipped)) => lagged(skipped),
+                    Err(RecvError::Closed) => return None,
+                };
+                return Some((Ok(message), rx));
+            }
+        }
+    })
+    .take_until(state.shutdown.clone().cancelled_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workflow_events::{ButtonInteractionType, ClickEvent, EventMetadata, KeyboardEvent};
+
+    fn click(text: &str) -> WorkflowEvent {
+        WorkflowEvent::Click(ClickEvent {
+            element_text: text.to_string(),
+            interaction_type: ButtonInteractionType::Click,
+            element_role: "Button".to_string(),
+            was_enabled: true,
+            click_position: None,
+            element_description: None,
+            child_text_content: vec![],
+            relative_position: None,
+            metadata: EventMetadata::empty(),
+        })
+    }
+
+    fn key(key_code: u32) -> WorkflowEvent {
+        WorkflowEvent::Keyboard(KeyboardEvent {
+            key_code,
+            is_key_down: true,
+            ctrl_pressed: false,
+            alt_pressed: false,
+            shift_pressed: false,
+            win_pressed: false,
+            character: None,
+            scan_code: None,
+            metadata: EventMetadata::empty(),
+        })
+    }
+
+    /// Reads `workflow_event` and `lagged` messages as `(event name, data)` pairs.
+    async fn read_messages(url: &str, count: usize) -> Vec<(String, serde_json::Value)> {
+        let response = reqwest::get(url).await.unwrap();
+        assert_eq!(
+            response.headers()["content-type"],
+            "text/event-stream",
+            "unexpected response from {url}"
+        );
+        let mut body = response.bytes_stream();
+        let mut text = String::new();
+        let mut messages = Vec::new();
+        while messages.len() < count {
+            let chunk = body.next().await.unwrap().unwrap();
+            text.push_str(&String::from_utf8_lossy(&chunk));
+            while let Some(end) = text.find("\n\n") {
+                let block: String = text.drain(..end + 2).collect();
+                let field = |name: &str| {
+                    block
+                        .lines()
+                        .find_map(|line| line.strip_prefix(name))
+                        .map(|value| value.trim().to_string())
+                };
+                if let (Some(event), Some(data)) = (field("event:"), field("data:")) {
+                    messages.push((event, serde_json::from_str(&data).unwrap()));
+                }
+            }
+        }
+        messages
+    }
+
+    #[test]
+    fn test_filter() {
+        let streamed = |event: &WorkflowEvent| StreamedEvent {
+            sequence: 1,
+            timestamp: 0,
+            event_type: event_type_name(event),
+            event: SerializableWorkflowEvent::from(event),
+            steps: Vec::new(),
+            raw: event.is_raw_input(),
+        };
+        let clicked = streamed(&click("OK"));
+        let pressed = streamed(&key(65));
+
+        let filter = StreamFilter::default();
+        assert!(filter.matches(&clicked) && !filter.matches(&pressed));
+
+        let filter: StreamFilter =
+            serde_json::from_value(json!({ "types": "keyboard, Hotkey", "raw": true })).unwrap();
+        assert!(!filter.matches(&clicked) && filter.matches(&pressed));
+        assert!(filter.steps);
+    }
+
+    #[tokio::test]
+    async fn test_streams_filtered_events_with_steps() {
+        let (tx, rx) = broadcast::channel(16);
+        let server = RecordingStreamServer::start(rx, 0, ConversionConfig::default())
+            .await
+            .unwrap();
+        let url = format!("{}&types=Click", server.url());
+        let client = tokio::spawn(async move { read_messages(&url, 2).await });
+
+        // Wait for the client to subscribe: events sent before are not replayed.
+        while server.client_count() == 0 {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        for event in [click("Open"), key(65), click("Save")] {
+            tx.send(event).unwrap();
+        }
+
+        let messages = client.await.unwrap();
+        let names: Vec<_> = messages
+            .iter()
+            .map(|(name, data)| {
+                (
+                    name.as_str(),
+                    data["event"]["Click"]["element_text"].clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("workflow_event", json!("Open")),
+                ("workflow_event", json!("Save"))
+            ]
+        );
+        assert_eq!(messages[1].1["sequence"], 3);
+        assert_eq!(messages[0].1["event_type"], "Click");
+        assert_eq!(messages[0].1["steps"][0]["tool_name"], "click_element");
+
+        server.stop();
+    }
+
+    #[tokio::test]
+    async fn test_rejects_requests_without_token_or_for_other_hosts() {
+        let (_tx, rx) = broadcast::channel(16);
+        let server = RecordingStreamServer::start(rx, 0, ConversionConfig::default())
+            .await
+            .unwrap();
+        let (base, token) = server
+            .url()
+            .split_once("?token=")
+            .map(|(base, token)| (base.to_string(), token.to_string()))
+            .unwrap();
+        let client = reqwest::Client::new();
+        let status = |request: reqwest::RequestBuilder| async move {
+            request.send().await.unwrap().status().as_u16()
+        };
+
+        assert_eq!(status(client.get(&base)).await, 401);
+        assert_eq!(status(client.get(format!("{base}?token=wrong"))).await, 401);
+        assert_eq!(
+            status(client.get(server.url()).header("Host", "attacker.example")).await,
+            403
+        );
+        assert_eq!(status(client.get(&base).bearer_auth(&token)).await, 200);
+
+        server.stop();
+    }
+
+    #[tokio::test]
+    async fn test_tells_clients_when_the_stream_falls_behind_the_recorder() {
+        let (tx, rx) = broadcast::channel(2);
+        let server = RecordingStreamServer::start(rx, 0, ConversionConfig::default())
+            .await
+            .unwrap();
+        let url = server.url();
+        let client = tokio::spawn(async move { read_messages(&url, 3).await });
+        while server.client_count() == 0 {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+
+        // The stream only reads once this task yields, by then the first event is gone.
+        for event in [click("Open"), click("Edit"), click("Save")] {
+            tx.send(event).unwrap();
+        }
+
+        let messages = client.await.unwrap();
+        assert_eq!(messages[0], ("lagged".to_string(), json!({ "skipped": 1 })));
+        assert_eq!(messages[1].1["event"]["Click"]["element_text"], "Edit");
+        assert_eq!(messages[2].1["sequence"], 2);
+
+        server.stop();
+    }
+}
@@ -0,0 +1,334 @@
+//! Admission queue for `POST /mcp` on the HTTP transport.
+//!
+//! At most `max_concurrent` requests run at once. Callers that opt in to queueing
+//! wait for a slot instead of getting an immediate 503; waiting requests are
+//! admitted by priority (higher first) and then in arrival order.
+
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+use tokio_util::sync::CancellationToken;
+use tracing::debug;
+
+/// Why a request was not admitted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueueError {
+    /// No free slot and the caller did not ask to wait.
+    Busy,
+    /// The queue already holds `max_queued` requests.
+    QueueFull,
+    /// No slot freed up within the queue timeout.
+    TimedOut(Duration),
+    /// The request was cancelled while it was waiting.
+    Cancelled,
+}
+
+/// A waiting request as reported by `/status`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueuedRequest {
+    pub request_id: String,
+    /// 1-based position; position 1 is admitted next.
+    pub position: usize,
+    pub priority: i32,
+    pub waited_ms: u64,
+}
+
+struct Waiter {
+    seq: u64,
+    request_id: String,
+    priority: i32,
+    enqueued_at: Instant,
+    admit: oneshot::Sender<()>,
+}
+
+#[derive(Default)]
+struct Inner {
+    active: usize,
+    next_seq: u64,
+    /// Kept sorted in admission order.
+    waiting: Vec<Waiter>,
+}
+
+impl Inner {
+    /// Hand free slots to waiting requests, skipping any whose caller went away.
+    fn admit_waiting(&mut self, max_concurrent: usize) {
+        while self.active < max_concurrent && !self.waiting.is_empty() {
+            let waiter = self.waiting.remove(0);
+            self.active += 1;
+            if waiter.admit.send(()).is_err() {
+                self.active -= 1;
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RequestQueue {
+    max_concurrent: usize,
+    max_queued: usize,
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl RequestQueue {
+    pub fn new(max_concurrent: usize, max_queued: usize) -> Self {
+        Self {
+            max_concurrent: max_concurrent.max(1),
+            max_queued,
+            inner: Arc::new(Mutex::new(Inner::default())),
+        }
+    }
+
+    pub fn max_concurrent(&self) -> usize {
+        self.max_concurrent
+    }
+
+    pub fn active(&self) -> usize {
+        self.lock().active
+    }
+
+    /// Waiting requests in admission order.
+    pub fn queued(&self) -> Vec<QueuedRequest> {
+        self.lock()
+            .waiting
+            .iter()
+            .enumerate()
+            .map(|(index, waiter)| QueuedRequest {
+                request_id: waiter.request_id.clone(),
+                position: index + 1,
+                priority: waiter.priority,
+                waited_ms: waiter.enqueued_at.elapsed().as_millis() as u64,
+            })
+            .collect()
+    }
+
+    /// Take a slot, waiting up to `queue_timeout` behind higher-priority and earlier
+    /// requests. A zero timeout fails fast with [`QueueError::Busy`]. The slot is
+    /// released when the returned permit is dropped.
+    pub async fn acquire(
+        &self,
+        request_id: &str,
+        priority: i32,
+        queue_timeout: Duration,
+        cancellation_token: &CancellationToken,
+    ) -> Result<QueuePermit, QueueError> {
+        let (seq, admitted) = {
+            let mut inner = self.lock();
+            if inner.active < self.max_concurrent && inner.waiting.is_empty() {
+                inner.active += 1;
+                return Ok(self.permit());
+            }
+            if queue_timeout.is_zero() {
+                return Err(QueueError::Busy);
+            }
+            if inner.waiting.len() >= self.max_queued {
+                return Err(QueueError::QueueFull);
+            }
+
+            let seq = inner.next_seq;
+            inner.next_seq += 1;
+            let (admit, admitted) = oneshot::channel();
+            // Insert after every waiter with the same or higher priority (FIFO within a priority)
+            let index = inner
+                .waiting
+                .iter()
+                .position(|w| w.priority < priority)
+                .unwrap_or(inner.waiting.len());
+            inner.waiting.insert(
+                index,
+                Waiter {
+                    seq,
+                    request_id: request_id.to_string(),
+                    priority,
+                    enqueued_at: Instant::now(),
+                    admit,
+                },
+            );
+            debug!(
+                "Queued request {} (priority {}, position {})",
+                request_id,
+                priority,
+                index + 1
+            );
+            (seq, admitted)
+        };
+
+        // Leaves the queue if this future is dropped (e.g. the client disconnects)
+        let mut ticket = Ticket {
+            queue: self,
+            seq,
+            admitted,
+        };
+        let error = tokio::select! {
+            result = &mut ticket.admitted => match result {
+                Ok(()) => return Ok(self.permit()),
+                Err(_) => QueueError::Cancelled,
+            },
+            _ = tokio::time::sleep(queue_timeout) => QueueError::TimedOut(queue_timeout),
+            _ = cancellation_token.cancelled() => QueueError::Cancelled,
+        };
+        drop(ticket);
+        Err(error)
+    }
+
+    fn permit(&self) -> QueuePermit {
+        QueuePermit {
+            queue: self.clone(),
+        }
+    }
+
+    fn release(&self) {
+        let mut inner = self.lock();
+        inner.active = inner.active.saturating_sub(1);
+        inner.admit_waiting(self.max_concurrent);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A running slot; dropping it admits the next waiting request.
+pub struct QueuePermit {
+    queue: RequestQueue,
+}
+
+impl QueuePermit {
+    /// The queue this slot belongs to.
+    pub fn queue(&self) -> &RequestQueue {
+        &self.queue
+    }
+}
+
+/// A request's slot, stored in its HTTP extensions so a handler whose work outlives
+/// the response (a debug session) can take it over. The gate releases it with the
+/// response unless it was taken.
+#[derive(Clone)]
+pub struct SharedPermit(Arc<Mutex<Option<QueuePermit>>>);
+
+impl SharedPermit {
+    pub fn new(permit: QueuePermit) -> Self {
+        Self(Arc::new(Mutex::new(Some(permit))))
+    }
+
+    pub fn take(&self) -> Option<QueuePermit> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).take()
+    }
+}
+
+impl Drop for QueuePermit {
+    fn drop(&mut self) {
+        self.queue.release();
+    }
+}
+
+struct Ticket<'a> {
+    queue: &'a RequestQueue,
+    seq: u64,
+    admitted: oneshot::Receiver<()>,
+}
+
+impl Drop for Ticket<'_> {
+    fn drop(&mut self) {
+        let mut inner = self.queue.lock();
+        if let Some(index) = inner.waiting.iter().position(|w| w.seq == self.seq) {
+            inner.waiting.remove(index);
+            return;
+        }
+        drop(inner);
+        // Admitted between giving up and getting here: hand the slot back
+        if self.admitted.try_recv().is_ok() {
+            self.queue.release();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WAIT: Duration = Duration::from_secs(5);
+
+    #[tokio::test]
+    async fn test_priority_then_fifo_order() {
+        let queue = RequestQueue::new(1, 10);
+        let token = CancellationToken::new();
+        let running = queue.acquire("a", 0, WAIT, &token).await.unwrap();
+        assert_eq!(
+            queue.acquire("x", 0, Duration::ZERO, &token).await.err(),
+            Some(QueueError::Busy)
+        );
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        for (id, priority) in [("low", 0), ("high", 5), ("low2", 0), ("high2", 5)] {
+            let queue = queue.clone();
+            let token = token.clone();
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                let permit = queue.acquire(id, priority, WAIT, &token).await.unwrap();
+                tx.send(id).unwrap();
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                drop(permit);
+            });
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let order: Vec<_> = queue.queued().into_iter().map(|q| q.request_id).collect();
+        assert_eq!(order, ["high", "high2", "low", "low2"]);
+        assert_eq!(queue.queued()[2].position, 3);
+
+        drop(running);
+        let mut admitted = Vec::new();
+        for _ in 0..4 {
+            admitted.push(rx.recv().await.unwrap());
+        }
+        assert_eq!(admitted, ["high", "high2", "low", "low2"]);
+    }
+
+    #[tokio::test]
+    async fn test_timeout_cancel_and_full() {
+        let queue = RequestQueue::new(1, 1);
+        let token = CancellationToken::new();
+        let running = queue.acquire("a", 0, WAIT, &token).await.unwrap();
+
+        let err = queue
+            .acquire("b", 0, Duration::from_millis(20), &token)
+            .await
+            .err();
+        assert_eq!(err, Some(QueueError::TimedOut(Duration::from_millis(20))));
+        assert!(queue.queued().is_empty());
+
+        let cancel = CancellationToken::new();
+        let waiting = {
+            let queue = queue.clone();
+            let cancel = cancel.clone();
+            tokio::spawn(async move { queue.acquire("c", 0, WAIT, &cancel).await.err() })
+        };
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(
+            queue.acquire("d", 0, WAIT, &token).await.err(),
+            Some(QueueError::QueueFull)
+        );
+        cancel.cancel();
+        assert_eq!(waiting.await.unwrap(), Some(QueueError::Cancelled));
+
+        drop(running);
+        assert_eq!(queue.active(), 0);
+        assert!(queue.acquire("e", 0, Duration::ZERO, &token).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_shared_permit_handover() {
+        let queue = RequestQueue::new(1, 1);
+        let token = CancellationToken::new();
+        let shared = SharedPermit::new(queue.acquire("a", 0, WAIT, &token).await.unwrap());
+
+        let taken = shared.clone().take();
+        assert!(taken.is_some());
+        assert!(shared.take().is_none(), "only one holder gets the slot");
+        assert_eq!(queue.active(), 1);
+        drop(taken);
+        assert_eq!(queue.active(), 0);
+    }
+}
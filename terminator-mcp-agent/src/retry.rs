@@ -0,0 +1,268 @@
+use crate::utils::{BackoffStrategy, RetryErrorClass, RetryPolicy, ToolCall};
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+const DEFAULT_INITIAL_DELAY: Duration = Duration::from_millis(500);
+const DEFAULT_MAX_DELAY: Duration = Duration::from_secs(30);
+
+impl RetryErrorClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RetryErrorClass::ElementNotFound => "element_not_found",
+            RetryErrorClass::Timeout => "timeout",
+            RetryErrorClass::NotEnabled => "not_enabled",
+            RetryErrorClass::Obscured => "obscured",
+            RetryErrorClass::ScriptError => "script_error",
+        }
+    }
+}
+
+/// A step's retry behaviour with defaults applied and durations parsed.
+#[derive(Debug, Clone)]
+pub struct ResolvedRetry {
+    pub max_attempts: u32,
+    pub backoff: BackoffStrategy,
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    pub on: Option<Vec<RetryErrorClass>>,
+    pub before_retry: Vec<ToolCall>,
+}
+
+impl ResolvedRetry {
+    /// Resolve a step's `retry:` block, falling back to the legacy `retries` count
+    /// (fixed 500ms delay, retry on any failure) when no block is given.
+    pub fn resolve(policy: Option<&RetryPolicy>, retries: Option<u32>) -> Result<Self> {
+        let Some(policy) = policy else {
+            return Ok(Self {
+                max_attempts: retries.unwrap_or(0) + 1,
+                backoff: BackoffStrategy::Fixed,
+                initial_delay: DEFAULT_INITIAL_DELAY,
+                max_delay: DEFAULT_MAX_DELAY,
+                on: None,
+                before_retry: Vec::new(),
+            });
+        };
+
+        let max_attempts = policy
+            .max_attempts
+            .or(retries.map(|r| r + 1))
+            .unwrap_or(DEFAULT_MAX_ATTEMPTS);
+        if max_attempts == 0 {
+            return Err(anyhow!("max_attempts must be at least 1"));
+        }
+
+        let parse = |value: Option<&String>, field: &str, default: Duration| match value {
+            Some(raw) => crate::duration_parser::parse_duration(raw)
+                .map(Duration::from_millis)
+                .map_err(|e| anyhow!("Invalid {field} '{raw}': {e}")),
+            None => Ok(default),
+        };
+        let initial_delay = parse(
+            policy.initial_delay.as_ref(),
+            "initial_delay",
+            DEFAULT_INITIAL_DELAY,
+        )?;
+        let max_delay = parse(policy.max_delay.as_ref(), "max_delay", DEFAULT_MAX_DELAY)?;
+
+        Ok(Self {
+            max_attempts,
+            backoff: policy.backoff.unwrap_or_default(),
+            initial_delay,
+            max_delay: max_delay.max(initial_delay),
+            on: policy.on.clone(),
+            before_retry: policy.before_retry.clone().unwrap_or_default(),
+        })
+    }
+
+    /// Delay to wait before retry number `retry` (1 for the first retry).
+    pub fn delay_for(&self, retry: u32) -> Duration {
+        let exponential = || {
+            let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+            self.initial_delay
+                .saturating_mul(factor)
+                .min(self.max_delay)
+        };
+        match self.backoff {
+            BackoffStrategy::Fixed => self.initial_delay.min(self.max_delay),
+            BackoffStrategy::Exponential => exponential(),
+            BackoffStrategy::Jitter => {
+                let ceiling = exponential().as_millis() as u64;
+                Duration::from_millis(random_u64() % (ceiling + 1))
+            }
+        }
+    }
+
+    /// Whether a failure of the given class should be retried. Unclassified failures
+    /// are only retried when the policy does not restrict classes.
+    pub fn should_retry(&self, class: Option<RetryErrorClass>) -> bool {
+        match (&self.on, class) {
+            (None, _) => true,
+            (Some(on), Some(class)) => on.contains(&class),
+            (Some(_), None) => false,
+        }
+    }
+}
+
+/// Classify a failed step (or group) result into a retryable error class.
+pub fn classify_failure(result: &Value) -> Option<RetryErrorClass> {
+    // Groups report their inner results; the last failing one decides.
+    if let Some(inner) = result.get("results").and_then(|r| r.as_array()) {
+        return inner.iter().rev().find_map(classify_failure);
+    }
+
+    if result["timed_out"] == true {
+        return Some(RetryErrorClass::Timeout);
+    }
+
+    let error = result["error"].as_str().unwrap_or_default().to_lowercase();
+    if error.contains("not found") || error.contains("unable to find") {
+        Some(RetryErrorClass::ElementNotFound)
+    } else if error.contains("timed out") || error.contains("timeout") {
+        Some(RetryErrorClass::Timeout)
+    } else if error.contains("not enabled") || error.contains("disabled") {
+        Some(RetryErrorClass::NotEnabled)
+    } else if error.contains("obscured") || error.contains("covered by") {
+        Some(RetryErrorClass::Obscured)
+    } else if !error.is_empty()
+        && matches!(
+            result["tool_name"].as_str(),
+            Some("run_command" | "execute_browser_script")
+        )
+    {
+        Some(RetryErrorClass::ScriptError)
+    } else {
+        None
+    }
+}
+
+fn random_u64() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos(),
+    );
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn policy(backoff: BackoffStrategy) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: Some(5),
+            backoff: Some(backoff),
+            initial_delay: Some("100ms".to_string()),
+            max_delay: Some("1s".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_legacy_retries() {
+        let retry = ResolvedRetry::resolve(None, Some(2)).unwrap();
+        assert_eq!(retry.max_attempts, 3);
+        assert_eq!(retry.delay_for(1), Duration::from_millis(500));
+        assert!(retry.should_retry(None));
+
+        let no_retry = ResolvedRetry::resolve(None, None).unwrap();
+        assert_eq!(no_retry.max_attempts, 1);
+    }
+
+    #[test]
+    fn test_backoff_delays() {
+        let fixed = ResolvedRetry::resolve(Some(&policy(BackoffStrategy::Fixed)), None).unwrap();
+        assert_eq!(fixed.delay_for(4), Duration::from_millis(100));
+
+        let exp =
+            ResolvedRetry::resolve(Some(&policy(BackoffStrategy::Exponential)), None).unwrap();
+        assert_eq!(exp.delay_for(1), Duration::from_millis(100));
+        assert_eq!(exp.delay_for(3), Duration::from_millis(400));
+        assert_eq!(exp.delay_for(10), Duration::from_secs(1));
+
+        let jitter = ResolvedRetry::resolve(Some(&policy(BackoffStrategy::Jitter)), None).unwrap();
+        for retry in 1..6 {
+            assert!(jitter.delay_for(retry) <= exp.delay_for(retry));
+        }
+    }
+
+    #[test]
+    fn test_invalid_policy() {
+        let bad_delay = RetryPolicy {
+            initial_delay: Some("soon".to_string()),
+            ..Default::default()
+        };
+        assert!(ResolvedRetry::resolve(Some(&bad_delay), None).is_err());
+
+        let zero = RetryPolicy {
+            max_attempts: Some(0),
+            ..Default::default()
+        };
+        assert!(ResolvedRetry::resolve(Some(&zero), None).is_err());
+    }
+
+    #[test]
+    fn test_classify_failure() {
+        let cases = [
+            (
+                json!({"tool_name": "click_element", "error": "Element not found: role:Button"}),
+                Some(RetryErrorClass::ElementNotFound),
+            ),
+            (
+                json!({"tool_name": "click_element", "error": "Step timed out after 100ms", "timed_out": true}),
+                Some(RetryErrorClass::Timeout),
+            ),
+            (
+                json!({"tool_name": "click_element", "error": "Element is not enabled: Submit"}),
+                Some(RetryErrorClass::NotEnabled),
+            ),
+            (
+                json!({"tool_name": "click_element", "error": "Element is obscured by another element: Save"}),
+                Some(RetryErrorClass::Obscured),
+            ),
+            (
+                json!({"tool_name": "run_command", "error": "ReferenceError: x is not defined"}),
+                Some(RetryErrorClass::ScriptError),
+            ),
+            (
+                json!({"tool_name": "press_key", "error": "Permission denied"}),
+                None,
+            ),
+        ];
+        for (result, expected) in cases {
+            assert_eq!(classify_failure(&result), expected, "{result}");
+        }
+
+        let group = json!({"group_name": "g", "status": "partial_success", "results": [
+            {"tool_name": "click_element", "status": "success"},
+            {"tool_name": "click_element", "status": "error", "error": "Element not found: x"}
+        ]});
+        assert_eq!(
+            classify_failure(&group),
+            Some(RetryErrorClass::ElementNotFound)
+        );
+    }
+
+    #[test]
+    fn test_retry_on_filter() {
+        let retry = ResolvedRetry::resolve(
+            Some(&RetryPolicy {
+                on: Some(vec![RetryErrorClass::ElementNotFound]),
+                ..Default::default()
+            }),
+            None,
+        )
+        .unwrap();
+        assert_eq!(retry.max_attempts, DEFAULT_MAX_ATTEMPTS);
+        assert!(retry.should_retry(Some(RetryErrorClass::ElementNotFound)));
+        assert!(!retry.should_retry(Some(RetryErrorClass::Timeout)));
+        assert!(!retry.should_retry(None));
+    }
+}
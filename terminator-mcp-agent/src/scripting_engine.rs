@@ -1168,6 +1168,10 @@ console.log('[Node.js Wrapper] Current working directory:', process.cwd());
                             libc::kill(-(pid as i32), libc::SIGTERM);
                         }
                     }
+                    // The child is not guaranteed to lead its own group, so kill it directly too
+                    if let Err(e) = child.kill().await {
+                        debug!("[Node.js] Child process already exited: {}", e);
+                    }
                 }
                 return Err(McpError::internal_error(
                     "Execution cancelled by user",
@@ -1760,6 +1764,7 @@ console.log('[TypeScript] Current working directory:', process.cwd());
 /// Execute Python using system interpreter with terminator.py bindings available
 pub async fn execute_python_with_bindings(
     script: String,
+    cancellation_token: Option<tokio_util::sync::CancellationToken>,
     working_dir: Option<PathBuf>,
 ) -> Result<serde_json::Value, McpError> {
     use std::process::Stdio;
@@ -1921,8 +1926,27 @@ asyncio.run(__runner__())
         child.id()
     );
 
-    // Try waiting for the process to complete with timeout
-    match tokio::time::timeout(std::time::Duration::from_secs(10), child.wait_with_output()).await {
+    // Try waiting for the process to complete with timeout, bailing out early on cancellation
+    let wait_result = tokio::select! {
+        res = tokio::time::timeout(std::time::Duration::from_secs(10), child.wait_with_output()) => res,
+        _ = async {
+            if let Some(ref ct) = cancellation_token {
+                ct.cancelled().await
+            } else {
+                // Never resolves if no cancellation token
+                std::future::pending::<()>().await
+            }
+        } => {
+            // Dropping the wait future kills the interpreter thanks to kill_on_drop
+            warn!("[Python] Execution cancelled, terminating child process");
+            return Err(McpError::internal_error(
+                "Python execution cancelled",
+                Some(json!({"code": -32001, "reason": "cancelled"})),
+            ));
+        }
+    };
+
+    match wait_result {
         Ok(Ok(output)) => {
             let stdout_str = String::from_utf8_lossy(&output.stdout);
             let stderr_str = String::from_utf8_lossy(&output.stderr);
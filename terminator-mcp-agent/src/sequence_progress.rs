@@ -0,0 +1,282 @@
+//! Live reporting for `execute_sequence`.
+//!
+//! When the caller's request carries a progress token, every step emits an MCP
+//! `notifications/progress` as it starts and finishes, with the step described in
+//! `_meta["terminator/step"]`, and the request's own log lines are forwarded as
+//! `notifications/message` until the sequence returns. Log lines are matched on the
+//! request id and the `CallId` of the tool call, since each client numbers its requests
+//! independently.
+
+use crate::log_capture::{CallId, LogCapture, LogEntry};
+use rmcp::model::{
+    LoggingLevel, LoggingMessageNotificationParam, Meta, ProgressNotification,
+    ProgressNotificationParam, ProgressToken, RequestId, ServerNotification,
+};
+use rmcp::service::{Peer, RoleServer};
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio_util::sync::CancellationToken;
+use tracing::debug;
+
+/// The step a notification is about.
+pub struct ProgressStep<'a> {
+    /// 0-based index into the flattened step list (main steps, then troubleshooting).
+    pub index: usize,
+    pub id: Option<&'a str>,
+    /// Tool name, or group name for a tool group.
+    pub tool: &'a str,
+}
+
+pub struct SequenceProgress {
+    peer: Peer<RoleServer>,
+    token: Option<ProgressToken>,
+    total: usize,
+    /// Steps reported as finished so far; grows past `total` when jumps loop.
+    finished: usize,
+    log_forwarder: Option<CancellationToken>,
+}
+
+impl SequenceProgress {
+    pub fn new(
+        peer: Peer<RoleServer>,
+        token: Option<ProgressToken>,
+        total_steps: usize,
+        log_capture: Option<&LogCapture>,
+        request_id: &RequestId,
+        call_id: Option<&CallId>,
+    ) -> Self {
+        // Without a call id, the request's lines can't be told from another session's.
+        let log_forwarder = match (&token, log_capture, call_id) {
+            (Some(_), Some(capture), Some(call_id)) => Some(spawn_log_forwarder(
+                peer.clone(),
+                capture,
+                request_id.to_string(),
+                call_id.clone(),
+            )),
+            _ => None,
+        };
+        Self {
+            peer,
+            token,
+            total: total_steps,
+            finished: 0,
+            log_forwarder,
+        }
+    }
+
+    /// Report that an attempt of `step` is about to run. `attempt` is 0-based.
+    pub async fn step_started(&self, step: &ProgressStep<'_>, attempt: u32, max_attempts: u32) {
+        let mut message = format!("{}: running", self.describe(step));
+        if attempt > 0 {
+            message.push_str(&format!(" (attempt {}/{max_attempts})", attempt + 1));
+        }
+        let mut details = self.step_details(step, "running");
+        details["attempt"] = json!(attempt + 1);
+        details["max_attempts"] = json!(max_attempts);
+        self.send(running_progress(self.finished, attempt), message, details)
+            .await;
+    }
+
+    /// Report the outcome of `step` after all of its attempts.
+    pub async fn step_finished(
+        &mut self,
+        step: &ProgressStep<'_>,
+        status: &str,
+        duration: Duration,
+        attempts: u32,
+    ) {
+        let mut message = format!(
+            "{}: {status} in {}ms",
+            self.describe(step),
+            duration.as_millis()
+        );
+        if attempts > 1 {
+            message.push_str(&format!(" after {attempts} attempts"));
+        }
+        let mut details = self.step_details(step, status);
+        details["attempt"] = json!(attempts);
+        details["duration_ms"] = json!(duration.as_millis() as u64);
+        self.finished += 1;
+        self.send(self.finished as f64, message, details).await;
+    }
+
+    /// The structured form of a notification, for clients that render steps themselves.
+    fn step_details(&self, step: &ProgressStep<'_>, status: &str) -> Value {
+        json!({
+            "index": step.index,
+            "total": self.total.max(step.index + 1),
+            "id": step.id,
+            "tool": step.tool,
+            "status": status,
+        })
+    }
+
+    fn describe(&self, step: &ProgressStep<'_>) -> String {
+        let id = step.id.map(|id| format!(" [{id}]")).unwrap_or_default();
+        format!(
+            "Step {}/{}{id} {}",
+            step.index + 1,
+            self.total.max(step.index + 1),
+            step.tool
+        )
+    }
+
+    async fn send(&self, progress: f64, message: String, details: Value) {
+        let Some(token) = &self.token else {
+            return;
+        };
+        let total = self.total.max(progress.ceil() as usize);
+        let mut notification = ProgressNotification::new(ProgressNotificationParam {
+            progress_token: token.clone(),
+            progress,
+            total: Some(total as f64),
+            message: Some(message),
+        });
+        let mut meta = Meta::new();
+        meta.0.insert("terminator/step".to_string(), details);
+        notification.extensions.insert(meta);
+        if let Err(e) = self
+            .peer
+            .send_notification(ServerNotification::ProgressNotification(notification))
+            .await
+        {
+            debug!("Failed to send progress notification: {}", e);
+        }
+    }
+}
+
+impl Drop for SequenceProgress {
+    fn drop(&mut self) {
+        if let Some(forwarder) = self.log_forwarder.take() {
+            forwarder.cancel();
+        }
+    }
+}
+
+/// Progress value while an attempt is running: halfway into the step, then
+/// creeping towards its end on each retry so the value never goes backwards.
+fn running_progress(finished: usize, attempt: u32) -> f64 {
+    finished as f64 + 1.0 - 0.5f64.powi(attempt.min(30) as i32 + 1)
+}
+
+fn logging_level(level: &str) -> Option<LoggingLevel> {
+    match level {
+        "ERROR" => Some(LoggingLevel::Error),
+        "WARN" => Some(LoggingLevel::Warning),
+        "INFO" => Some(LoggingLevel::Info),
+        // Debug and trace output is too chatty to push to the client
+        _ => None,
+    }
+}
+
+fn forwards(entry: &LogEntry, request_id: &str, call_id: &CallId) -> bool {
+    entry.request_id.as_deref() == Some(request_id)
+        && entry.call_id.as_deref() == Some(call_id.0.as_str())
+        && !entry.target.starts_with("rmcp")
+}
+
+/// Forward the info-and-above log lines of request `request_id` to the client until the
+/// returned token is cancelled. Lines of other requests and sessions are skipped, and so
+/// are rmcp's own events so sending a notification can't feed back into the stream.
+fn spawn_log_forwarder(
+    peer: Peer<RoleServer>,
+    capture: &LogCapture,
+    request_id: String,
+    call_id: CallId,
+) -> CancellationToken {
+    let stop = CancellationToken::new();
+    let mut logs = capture.subscribe();
+    let stopped = stop.clone();
+    tokio::spawn(async move {
+        loop {
+            let entry: LogEntry = tokio::select! {
+                _ = stopped.cancelled() => break,
+                received = logs.recv() => match received {
+                    Ok(entry) => entry,
+                    Err(RecvError::Lagged(skipped)) => {
+                        debug!("Log forwarder dropped {} entries", skipped);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+            };
+            if !forwards(&entry, &request_id, &call_id) {
+                continue;
+            }
+            let Some(level) = logging_level(&entry.level) else {
+                continue;
+            };
+            let notification = LoggingMessageNotificationParam {
+                level,
+                logger: Some(entry.target.clone()),
+                data: serde_json::to_value(&entry).unwrap_or_default(),
+            };
+            if peer.notify_logging_message(notification).await.is_err() {
+                break;
+            }
+        }
+    });
+    stop
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_running_progress_increases_across_retries() {
+        let values: Vec<f64> = (0..4).map(|attempt| running_progress(2, attempt)).collect();
+        assert_eq!(values[0], 2.5);
+        assert!(values.windows(2).all(|w| w[0] < w[1]));
+        assert!(values.iter().all(|v| *v < 3.0));
+    }
+
+    #[test]
+    fn test_forwards_only_the_requests_own_logs() {
+        let call = CallId("a".to_string());
+        let entry = |request_id: Option<&str>, call_id: &str, target: &str| LogEntry {
+            timestamp: chrono::Utc::now(),
+            level: "INFO".to_string(),
+            target: target.to_string(),
+            message: "Step 1 BEGIN".to_string(),
+            fields: None,
+            request_id: request_id.map(str::to_string),
+            call_id: Some(call_id.to_string()),
+        };
+        assert!(forwards(
+            &entry(Some("7"), "a", "terminator_mcp_agent"),
+            "7",
+            &call
+        ));
+        assert!(!forwards(
+            &entry(Some("8"), "a", "terminator_mcp_agent"),
+            "7",
+            &call
+        ));
+        assert!(!forwards(
+            &entry(None, "a", "terminator_mcp_agent"),
+            "7",
+            &call
+        ));
+        assert!(!forwards(
+            &entry(Some("7"), "a", "rmcp::service"),
+            "7",
+            &call
+        ));
+        // The same request id in another session
+        assert!(!forwards(
+            &entry(Some("7"), "b", "terminator_mcp_agent"),
+            "7",
+            &call
+        ));
+    }
+
+    #[test]
+    fn test_logging_level_skips_debug_output() {
+        assert_eq!(logging_level("WARN"), Some(LoggingLevel::Warning));
+        assert_eq!(logging_level("INFO"), Some(LoggingLevel::Info));
+        assert_eq!(logging_level("DEBUG"), None);
+        assert_eq!(logging_level("TRACE"), None);
+    }
+}
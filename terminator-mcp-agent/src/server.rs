@@ -5,13 +5,14 @@ use crate::utils::find_and_execute_with_retry_with_fallback;
 pub use crate::utils::DesktopWrapper;
 use crate::utils::{
     get_timeout, ActionHighlightConfig, ActivateElementArgs, ClickElementArgs, CloseElementArgs,
-    DelayArgs, ExecuteBrowserScriptArgs, ExecuteSequenceArgs, ExportWorkflowSequenceArgs,
-    GetApplicationsArgs, GetFocusedWindowTreeArgs, GetWindowTreeArgs, GlobalKeyArgs,
-    HighlightElementArgs, ImportWorkflowSequenceArgs, LocatorArgs, MaximizeWindowArgs,
-    MinimizeWindowArgs, MouseDragArgs, NavigateBrowserArgs, OpenApplicationArgs, PressKeyArgs,
-    RecordWorkflowArgs, RunCommandArgs, ScrollElementArgs, SelectOptionArgs, SetRangeValueArgs,
-    SetSelectedArgs, SetToggledArgs, SetValueArgs, SetZoomArgs, StopHighlightingArgs,
-    TypeIntoElementArgs, ValidateElementArgs, WaitForElementArgs,
+    DebugEvalArgs, DebugSessionArgs, DebugSetVarArgs, DebugStartArgs, DelayArgs,
+    ExecuteBrowserScriptArgs, ExecuteSequenceArgs, ExportWorkflowSequenceArgs, GetApplicationsArgs,
+    GetFocusedWindowTreeArgs, GetWindowTreeArgs, GlobalKeyArgs, HighlightElementArgs,
+    ImportWorkflowSequenceArgs, LocatorArgs, MaximizeWindowArgs, MinimizeWindowArgs, MouseDragArgs,
+    NavigateBrowserArgs, OpenApplicationArgs, PressKeyArgs, RecordWorkflowArgs, RunCommandArgs,
+    ScrollElementArgs, SelectOptionArgs, SetRangeValueArgs, SetSelectedArgs, SetToggledArgs,
+    SetValueArgs, SetZoomArgs, StopHighlightingArgs, TypeIntoElementArgs, ValidateElementArgs,
+    WaitForElementArgs,
 };
 use futures::StreamExt;
 use image::{ExtendedColorType, ImageEncoder};
@@ -20,17 +21,19 @@ use rmcp::handler::server::wrapper::Parameters;
 use rmcp::model::{
     CallToolResult, Content, Implementation, ProtocolVersion, ServerCapabilities, ServerInfo,
 };
+use rmcp::tool_router;
 use rmcp::{tool, ErrorData as McpError, ServerHandler};
-use rmcp::{tool_handler, tool_router};
 use serde_json::json;
 use std::io::Cursor;
 use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use terminator::{AutomationError, Browser, Desktop, Selector, UIElement};
-use terminator_workflow_recorder::{PerformanceMode, WorkflowRecorder, WorkflowRecorderConfig};
+use terminator_workflow_recorder::{
+    PerformanceMode, PiiMaskingConfig, WorkflowRecorder, WorkflowRecorderConfig,
+};
 use tokio::sync::Mutex;
-use tracing::{info, warn};
+use tracing::{info, warn, Instrument};
 
 // New imports for image encoding
 use base64::{engine::general_purpose, Engine as _};
@@ -108,7 +111,7 @@ async fn capture_monitor_screenshots(desktop: &Desktop) -> Vec<Content> {
 }
 
 /// Convert RGBA image data to PNG format
-fn rgba_to_png(
+pub(crate) fn rgba_to_png(
     rgba_data: &[u8],
     width: u32,
     height: u32,
@@ -381,7 +384,15 @@ impl DesktopWrapper {
             }
         };
 
-        Ok(Self {
+        Ok(Self::with_desktop(desktop, log_capture))
+    }
+
+    /// A server driving `desktop`, e.g. one backed by recorded fixtures.
+    pub fn with_desktop(
+        desktop: Desktop,
+        log_capture: Option<crate::log_capture::LogCapture>,
+    ) -> Self {
+        Self {
             desktop: Arc::new(desktop),
             tool_router: Self::tool_router(),
             request_manager: crate::cancellation::RequestManager::new(),
@@ -390,12 +401,131 @@ impl DesktopWrapper {
             log_capture,
             current_workflow_dir: Arc::new(Mutex::new(None)),
             current_scripts_base_path: Arc::new(Mutex::new(None)),
+            debug_sessions: Arc::new(Mutex::new(std::collections::HashMap::new())),
+            policy: Arc::new(crate::policy::ToolPolicy::default()),
+            resource_subscriptions: Arc::new(Mutex::new(Default::default())),
+            audit: None,
+            fixture_replay: None,
+            recording_stream: Arc::new(Mutex::new(None)),
+            workflows_root: None,
+        }
+    }
+
+    /// Answer the tool calls of `execute_sequence` from `session` (see `workflow_replay`).
+    pub fn with_fixture_replay(
+        mut self,
+        session: Arc<crate::workflow_replay::FixtureSession>,
+    ) -> Self {
+        self.fixture_replay = Some(session);
+        self
+    }
+
+    /// Serve `terminator://workflows/...` resources from `root` only.
+    pub fn with_workflows_root(mut self, root: Option<std::path::PathBuf>) -> Self {
+        self.workflows_root = root;
+        self
+    }
+
+    /// A server for another client session of the same desktop, with its own
+    /// resource subscriptions.
+    pub fn for_new_session(&self) -> Self {
+        Self {
+            resource_subscriptions: Arc::new(Mutex::new(Default::default())),
+            ..self.clone()
+        }
+    }
+
+    /// Enforce `policy` on every tool call, including the steps of `execute_sequence`.
+    pub fn with_policy(mut self, policy: crate::policy::ToolPolicy) -> Self {
+        let read_only_tools = self
+            .tool_router
+            .list_all()
+            .into_iter()
+            .filter(|tool| {
+                tool.description
+                    .as_deref()
+                    .is_some_and(|d| d.contains(crate::policy::READ_ONLY_MARKER))
+            })
+            .map(|tool| tool.name.to_string());
+        self.policy = Arc::new(policy.with_read_only_tools(read_only_tools));
+        self
+    }
+
+    /// Every tool this server exposes, with its input schema, without creating a desktop.
+    pub fn tool_definitions() -> Vec<rmcp::model::Tool> {
+        Self::tool_router().list_all()
+    }
+
+    /// Whether `tool_name` takes a `timeout_ms` argument
+    pub(crate) fn accepts_timeout_ms(tool_name: &str) -> bool {
+        static TOOLS: std::sync::OnceLock<std::collections::HashSet<String>> =
+            std::sync::OnceLock::new();
+        TOOLS
+            .get_or_init(|| {
+                Self::tool_definitions()
+                    .into_iter()
+                    .filter(|tool| {
+                        tool.input_schema
+                            .get("properties")
+                            .and_then(|properties| properties.get("timeout_ms"))
+                            .is_some()
+                    })
+                    .map(|tool| tool.name.to_string())
+                    .collect()
+            })
+            .contains(tool_name)
+    }
+
+    /// Record every tool call, including the steps of `execute_sequence`, in `audit`.
+    pub fn with_audit_log(mut self, audit: Option<Arc<crate::audit::AuditLog>>) -> Self {
+        self.audit = audit;
+        self
+    }
+
+    pub(crate) fn record_audit(
+        &self,
+        request_id: &rmcp::model::RequestId,
+        tool_name: &str,
+        arguments: &serde_json::Value,
+        result: &Result<CallToolResult, McpError>,
+        duration: Duration,
+    ) {
+        if let Some(audit) = &self.audit {
+            audit.record(crate::audit::AuditRecord {
+                identity: crate::auth::current_identity()
+                    .unwrap_or_else(|| "anonymous".to_string()),
+                request_id: request_id.to_string(),
+                tool: tool_name,
+                arguments,
+                result,
+                duration,
+            });
+        }
+    }
+
+    /// Check a call against the tool policy; denials are audit-logged and returned
+    /// as a structured `invalid_request` error.
+    pub(crate) fn enforce_policy(
+        &self,
+        tool_name: &str,
+        arguments: &serde_json::Value,
+    ) -> Result<(), McpError> {
+        self.policy.check(tool_name, arguments).map_err(|denial| {
+            warn!(
+                target: "audit",
+                tool = %denial.tool_name,
+                rule = denial.rule,
+                identity = %crate::auth::current_identity().unwrap_or_else(|| "anonymous".to_string()),
+                "Tool call denied by policy: {}",
+                denial.reason
+            );
+            McpError::invalid_request("Tool call denied by policy", Some(denial.to_json()))
         })
     }
 
     /// Create TreeBuildConfig based on include_detailed_attributes parameter
     /// Defaults to comprehensive attributes for LLM usage if include_detailed_attributes is not specified
-    fn create_tree_config(
+    pub(crate) fn create_tree_config(
         include_detailed_attributes: Option<bool>,
     ) -> terminator::platforms::TreeBuildConfig {
         let include_detailed = include_detailed_attributes.unwrap_or(true);
@@ -2179,6 +2309,7 @@ const count = (typeof retry_count !== 'undefined') ? parseInt(retry_count) : 0;
 
                 let execution_result = scripting_engine::execute_python_with_bindings(
                     final_script,
+                    cancellation_token,
                     script_working_dir,
                 )
                 .await?;
@@ -2448,21 +2579,37 @@ const count = (typeof retry_count !== 'undefined') ? parseInt(retry_count) : 0;
             (None, Some(unix_cmd))
         };
 
-        let output = self
+        // Dropping the command future kills the shell, so a cancelled or timed out step
+        // doesn't leave it running
+        let command = self
             .desktop
-            .run_command(windows_cmd.as_deref(), unix_cmd.as_deref())
-            .await
-            .map_err(|e| {
-                McpError::internal_error(
-                    "Failed to run command",
-                    Some(json!({
-                        "reason": e.to_string(),
-                        "command": run_str,
-                        "shell": args.shell,
-                        "working_directory": args.working_directory
-                    })),
-                )
-            })?;
+            .run_command(windows_cmd.as_deref(), unix_cmd.as_deref());
+        let output = tokio::select! {
+            output = command => output,
+            _ = async {
+                match cancellation_token {
+                    Some(ref ct) => ct.cancelled().await,
+                    None => std::future::pending::<()>().await,
+                }
+            } => {
+                warn!("[run_command] Shell command cancelled, terminating it");
+                return Err(McpError::internal_error(
+                    "Shell command cancelled",
+                    Some(json!({"code": -32001, "reason": "cancelled", "command": run_str})),
+                ));
+            }
+        }
+        .map_err(|e| {
+            McpError::internal_error(
+                "Failed to run command",
+                Some(json!({
+                    "reason": e.to_string(),
+                    "command": run_str,
+                    "shell": args.shell,
+                    "working_directory": args.working_directory
+                })),
+            )
+        })?;
 
         span.set_status(true, None);
         span.end();
@@ -3017,7 +3164,7 @@ const count = (typeof retry_count !== 'undefined') ? parseInt(retry_count) : 0;
         let condition_lower = args.condition.to_lowercase();
 
         // For the "exists" condition, we can use the standard wait
-        if condition_lower == "exists" {
+        if condition_lower == "exists" && args.expected_value.is_none() {
             info!(
                 "[wait_for_element] Waiting for element to exist: selector='{}', timeout={:?}",
                 args.selector, timeout
@@ -3082,8 +3229,9 @@ const count = (typeof retry_count !== 'undefined') ? parseInt(retry_count) : 0;
             }
         }
 
-        // For other conditions (visible, enabled, focused), we need to poll
+        // For other conditions (visible, enabled, focused) and expected values, we need to poll
         let start_time = std::time::Instant::now();
+        let mut last_value: Option<String> = None;
         let timeout_duration = timeout.unwrap_or(std::time::Duration::from_millis(5000));
         info!(
             "[wait_for_element] Polling for condition '{}' on selector='{}' with timeout {:?}",
@@ -3093,11 +3241,19 @@ const count = (typeof retry_count !== 'undefined') ? parseInt(retry_count) : 0;
         loop {
             // Check if we've exceeded the timeout
             if start_time.elapsed() > timeout_duration {
-                let timeout_msg = format!(
-                    "Timeout waiting for element to be {} within {}ms",
-                    args.condition,
-                    timeout_duration.as_millis()
-                );
+                let timeout_msg = match &args.expected_value {
+                    Some(expected) => format!(
+                        "Timeout waiting for element value to be '{}' within {}ms (last value: {:?})",
+                        expected,
+                        timeout_duration.as_millis(),
+                        last_value
+                    ),
+                    None => format!(
+                        "Timeout waiting for element to be {} within {}ms",
+                        args.condition,
+                        timeout_duration.as_millis()
+                    ),
+                };
                 info!(
                     "[wait_for_element] Timeout exceeded for selector='{}', condition='{}', waited {}ms",
                     args.selector, args.condition, start_time.elapsed().as_millis()
@@ -3107,6 +3263,8 @@ const count = (typeof retry_count !== 'undefined') ? parseInt(retry_count) : 0;
                     Some(json!({
                         "selector": args.selector,
                         "condition": args.condition,
+                        "expected_value": args.expected_value,
+                        "last_value": last_value,
                         "timeout_ms": args.timeout_ms.unwrap_or(5000),
                         "elapsed_ms": start_time.elapsed().as_millis()
                     })),
@@ -3125,6 +3283,7 @@ const count = (typeof retry_count !== 'undefined') ? parseInt(retry_count) : 0;
                     );
                     // Element exists, now check the specific condition
                     let condition_met = match condition_lower.as_str() {
+                        "exists" => true,
                         "visible" => {
                             let v = element.is_visible().unwrap_or(false);
                             info!(
@@ -3160,8 +3319,22 @@ const count = (typeof retry_count !== 'undefined') ? parseInt(retry_count) : 0;
                             ));
                         }
                     };
+                    let value_matches = match &args.expected_value {
+                        Some(expected) => {
+                            let value = element
+                                .get_value()
+                                .ok()
+                                .flatten()
+                                .or_else(|| element.name())
+                                .unwrap_or_default();
+                            let matches = value.trim() == expected.trim();
+                            last_value = Some(value);
+                            matches
+                        }
+                        None => true,
+                    };
 
-                    if condition_met {
+                    if condition_met && value_matches {
                         info!(
                             "[wait_for_element] Condition '{}' met for selector='{}' after {}ms",
                             args.condition,
@@ -3175,6 +3348,7 @@ const count = (typeof retry_count !== 'undefined') ? parseInt(retry_count) : 0;
                             "condition": args.condition,
                             "condition_met": true,
                             "selector": args.selector,
+                            "expected_value": args.expected_value,
                             "timeout_ms": args.timeout_ms.unwrap_or(5000),
                             "elapsed_ms": start_time.elapsed().as_millis(),
                             "timestamp": chrono::Utc::now().to_rfc3339()
@@ -4283,7 +4457,7 @@ const count = (typeof retry_count !== 'undefined') ? parseInt(retry_count) : 0;
     }
 
     #[tool(
-        description = "Records a user's UI interactions into a reusable workflow file. Use action: 'start' to begin recording and 'stop' to end and save the workflow. This allows a human to demonstrate a task for the AI to learn."
+        description = "Records a user's UI interactions into a reusable workflow file. Use action: 'start' to begin recording and 'stop' to end and save the workflow. While recording, 'pause'/'resume' skip interruptions, 'marker' (with marker_label) starts a named group of steps, and 'undo' (with undo_count) drops the last recorded actions. This allows a human to demonstrate a task for the AI to learn."
     )]
     pub async fn record_workflow(
         &self,
@@ -4310,7 +4484,7 @@ const count = (typeof retry_count !== 'undefined') ? parseInt(retry_count) : 0;
                     )
                 })?;
 
-                let config = if args.low_energy_mode.unwrap_or(false) {
+                let mut config = if args.low_energy_mode.unwrap_or(false) {
                     // This uses a config optimized for performance, which importantly disables
                     // text input completion tracking, a feature the user found caused lag.
                     PerformanceMode::low_energy_config()
@@ -4320,9 +4494,32 @@ const count = (typeof retry_count !== 'undefined') ? parseInt(retry_count) : 0;
                         ..WorkflowRecorderConfig::default()
                     }
                 };
+                if args.mask_pii.unwrap_or(false) {
+                    config.pii_masking = PiiMaskingConfig::all();
+                }
+                config.event_screenshots.enabled = args.capture_screenshots.unwrap_or(false);
 
                 let mut recorder = WorkflowRecorder::new(workflow_name.clone(), config);
 
+                // Subscribe before starting so that no event is missed
+                let stream = if args.stream_events.unwrap_or(false) {
+                    let stream = crate::recording_stream::RecordingStreamServer::start(
+                        recorder.subscribe(),
+                        args.stream_port.unwrap_or(0),
+                        Default::default(),
+                    )
+                    .await
+                    .map_err(|e| {
+                        McpError::internal_error(
+                            "Failed to start the recording event stream",
+                            Some(json!({ "error": e.to_string(), "port": args.stream_port })),
+                        )
+                    })?;
+                    Some(stream)
+                } else {
+                    None
+                };
+
                 // Start highlighting task if enabled
                 if let Some(ref highlight_config) = args.highlight_mode {
                     if highlight_config.enabled {
@@ -4439,6 +4636,10 @@ const count = (typeof retry_count !== 'undefined') ? parseInt(retry_count) : 0;
                     "workflow_name": workflow_name,
                     "message": "Recording started. Perform the UI actions you want to record. Call this tool again with action: 'stop' to finish."
                 });
+                if let Some(stream) = stream {
+                    response["stream_url"] = json!(stream.url());
+                    *self.recording_stream.lock().await = Some(stream);
+                }
 
                 // Add highlighting status to response
                 if let Some(ref highlight_config) = args.highlight_mode {
@@ -4463,7 +4664,78 @@ const count = (typeof retry_count !== 'undefined') ? parseInt(retry_count) : 0;
                     .await,
                 ))
             }
+            "pause" | "resume" | "marker" | "undo" => {
+                let recorder = recorder_guard.as_ref().ok_or_else(|| {
+                    McpError::invalid_params(
+                        "No recording is currently in progress. Please start a recording first.",
+                        None,
+                    )
+                })?;
+
+                let mut response = json!({
+                    "status": "success",
+                    "action": args.action,
+                });
+                match args.action.as_str() {
+                    "pause" => recorder.pause(),
+                    "resume" => recorder.resume(),
+                    "marker" => {
+                        let label = args.marker_label.ok_or_else(|| {
+                            McpError::invalid_params(
+                                "`marker_label` is required to add a marker.",
+                                None,
+                            )
+                        })?;
+                        recorder.add_marker(label.clone()).map_err(|e| {
+                            McpError::internal_error(
+                                "Failed to add marker",
+                                Some(json!({ "error": e.to_string() })),
+                            )
+                        })?;
+                        response["marker_label"] = json!(label);
+                    }
+                    _ => {
+                        let removed = recorder
+                            .undo_last_actions(args.undo_count.unwrap_or(1))
+                            .map_err(|e| {
+                                McpError::internal_error(
+                                    "Failed to undo recorded actions",
+                                    Some(json!({ "error": e.to_string() })),
+                                )
+                            })?;
+                        response["undone_actions"] = json!(removed);
+                    }
+                }
+                response["paused"] = json!(recorder.is_paused());
+
+                span.set_status(true, None);
+                span.end();
+
+                Ok(CallToolResult::success(vec![Content::json(response)?]))
+            }
             "stop" => {
+                let script_language = args
+                    .output_format
+                    .as_deref()
+                    .filter(|format| !format.eq_ignore_ascii_case("mcp"))
+                    .map(str::parse::<crate::workflow_codegen::ScriptLanguage>)
+                    .transpose()
+                    .map_err(|e| {
+                        McpError::invalid_params(
+                            "Invalid output_format. Must be 'mcp', 'rust', 'python' or 'typescript'.",
+                            Some(json!({ "error": e, "provided_output_format": args.output_format })),
+                        )
+                    })?;
+                let assertions = crate::workflow_assertions::AssertionConfig::from_kinds(
+                    args.assertions.as_deref().unwrap_or_default(),
+                )
+                .map_err(|e| {
+                    McpError::invalid_params(
+                        "Invalid assertions. Must be 'field_values', 'window_titles', 'dialogs' or 'all'.",
+                        Some(json!({ "error": e, "provided_assertions": args.assertions })),
+                    )
+                })?;
+
                 let mut recorder = recorder_guard.take().ok_or_else(|| {
                     McpError::invalid_params(
                         "No recording is currently in progress. Please start a recording first.",
@@ -4477,10 +4749,22 @@ const count = (typeof retry_count !== 'undefined') ? parseInt(retry_count) : 0;
                         Some(json!({ "error": e.to_string() })),
                     )
                 })?;
+                if let Some(stream) = self.recording_stream.lock().await.take() {
+                    stream.stop();
+                }
 
-                let workflow_name = {
+                let (workflow_name, proposed_variables, has_screenshots) = {
                     let workflow = recorder.workflow.lock().unwrap();
-                    workflow.name.clone()
+                    let proposed_variables = if args.parameterize.unwrap_or(false) {
+                        crate::workflow_parameterize::propose_variables(&workflow)
+                    } else {
+                        Vec::new()
+                    };
+                    let has_screenshots = workflow
+                        .events
+                        .iter()
+                        .any(|e| e.event.metadata().screenshot.is_some());
+                    (workflow.name.clone(), proposed_variables, has_screenshots)
                 };
 
                 let file_name = args.file_path.unwrap_or_else(|| {
@@ -4514,15 +4798,44 @@ const count = (typeof retry_count !== 'undefined') ? parseInt(retry_count) : 0;
                     )
                 })?;
 
+                let storyboard_path = if has_screenshots {
+                    crate::workflow_converter::write_storyboard(&file_path, None)
+                        .map_err(|e| warn!("Failed to write storyboard: {}", e))
+                        .ok()
+                } else {
+                    None
+                };
+
                 // Convert the recorded workflow to MCP sequences
-                let mcp_workflow = match crate::workflow_converter::load_and_convert_workflow(
+                let mut generated_script = None;
+                let config = crate::mcp_converter::ConversionConfig {
+                    assertions,
+                    ..Default::default()
+                };
+                let conversion = crate::workflow_converter::load_and_convert_workflow_with_config(
                     file_path.to_str().unwrap_or_default(),
-                )
-                .await
-                {
-                    Ok(mcp_workflow) => {
+                    config,
+                );
+                let mcp_workflow = match conversion.await {
+                    Ok(mut mcp_workflow) => {
                         info!("Successfully converted workflow to MCP sequences");
 
+                        if let Some(language) = script_language {
+                            generated_script = Some(json!({
+                                "language": language.as_str(),
+                                "code": crate::workflow_codegen::generate_script(
+                                    &mcp_workflow,
+                                    language
+                                ),
+                            }));
+                        }
+
+                        // Scripts keep the recorded values; the workflow takes them as inputs.
+                        let variables = crate::workflow_parameterize::apply_variables(
+                            &mut mcp_workflow.steps,
+                            proposed_variables,
+                        );
+
                         // Return null if no steps were converted
                         if mcp_workflow.steps.is_empty() {
                             info!("No convertible events found in workflow");
@@ -4532,10 +4845,21 @@ const count = (typeof retry_count !== 'undefined') ? parseInt(retry_count) : 0;
                             let mut workflow_obj = json!({
                                 "tool_name": "execute_sequence",
                                 "arguments": {
-                                    "items": mcp_workflow.steps
+                                    "items": mcp_workflow.sequence_items()
                                 }
                             });
 
+                            if !variables.is_empty() {
+                                let (definitions, inputs) =
+                                    crate::workflow_parameterize::variables_and_inputs(&variables);
+                                workflow_obj["arguments"]["variables"] = definitions;
+                                workflow_obj["arguments"]["inputs"] = inputs;
+                            }
+
+                            if !mcp_workflow.annotations.is_empty() {
+                                workflow_obj["annotations"] = json!(mcp_workflow.annotations);
+                            }
+
                             // Add conversion_notes at the root level if they exist
                             if let Some(metadata) = &mcp_workflow.metadata {
                                 if !metadata.conversion_notes.is_empty() {
@@ -4561,6 +4885,12 @@ const count = (typeof retry_count !== 'undefined') ? parseInt(retry_count) : 0;
 
                 // Add MCP workflow if conversion was successful, otherwise null
                 response["mcp_workflow"] = mcp_workflow.unwrap_or(serde_json::Value::Null);
+                if let Some(script) = generated_script {
+                    response["script"] = script;
+                }
+                if let Some(path) = storyboard_path {
+                    response["storyboard_path"] = json!(path.to_string_lossy());
+                }
 
                 span.set_status(true, None);
                 span.end();
@@ -4575,7 +4905,7 @@ const count = (typeof retry_count !== 'undefined') ? parseInt(retry_count) : 0;
                 ))
             }
             _ => Err(McpError::invalid_params(
-                "Invalid action. Must be 'start' or 'stop'.",
+                "Invalid action. Must be 'start', 'stop', 'pause', 'resume', 'marker' or 'undo'.",
                 Some(json!({ "provided_action": args.action })),
             )),
         }
@@ -4632,6 +4962,82 @@ const count = (typeof retry_count !== 'undefined') ? parseInt(retry_count) : 0;
             .await;
     }
 
+    #[tool(
+        description = "Starts an interactive debug session for a workflow. Accepts the same fields as execute_sequence plus 'breakpoints' (step ids), 'breakpoint_conditions' (expressions evaluated against the env before each step) and 'stop_on_entry' (default true). Execution suspends before matching steps. Returns the session_id, the step it is paused before and the current env; position updates are also streamed as 'debugger' log notifications. Use debug_step, debug_continue, debug_pause, debug_eval, debug_set_var and debug_stop to control the session."
+    )]
+    pub async fn debug_start(
+        &self,
+        peer: Peer<RoleServer>,
+        request_context: RequestContext<RoleServer>,
+        Parameters(args): Parameters<DebugStartArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug_start_impl(peer, request_context, args).await
+    }
+
+    #[tool(
+        description = "Executes the step a paused debug session is suspended before, then pauses again before the next step. Returns the new position and env, or the final result if the workflow finished."
+    )]
+    pub async fn debug_step(
+        &self,
+        request_context: RequestContext<RoleServer>,
+        Parameters(args): Parameters<DebugSessionArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug_step_impl(request_context, args).await
+    }
+
+    #[tool(
+        description = "Resumes a paused debug session until the next breakpoint, pause request or the end of the workflow. Returns the new position and env, or the final result."
+    )]
+    pub async fn debug_continue(
+        &self,
+        request_context: RequestContext<RoleServer>,
+        Parameters(args): Parameters<DebugSessionArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug_continue_impl(request_context, args).await
+    }
+
+    #[tool(
+        description = "Pauses a running debug session before its next step. The step currently executing is allowed to finish."
+    )]
+    pub async fn debug_pause(
+        &self,
+        request_context: RequestContext<RoleServer>,
+        Parameters(args): Parameters<DebugSessionArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug_pause_impl(request_context, args).await
+    }
+
+    #[tool(
+        description = "Evaluates an expression against the live env of a paused debug session. Variable paths (e.g. 'env.invoice_id') return their value; conditions (e.g. 'login_status == \"success\"') return true or false."
+    )]
+    pub async fn debug_eval(
+        &self,
+        Parameters(args): Parameters<DebugEvalArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug_eval_impl(args).await
+    }
+
+    #[tool(
+        description = "Sets an env variable in a paused debug session. Later steps see the new value in conditions, {{variable}} substitutions and scripts."
+    )]
+    pub async fn debug_set_var(
+        &self,
+        Parameters(args): Parameters<DebugSetVarArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug_set_var_impl(args).await
+    }
+
+    #[tool(
+        description = "Stops a debug session, cancelling the running step if any. Workflow finally blocks still run. Returns the final result."
+    )]
+    pub async fn debug_stop(
+        &self,
+        request_context: RequestContext<RoleServer>,
+        Parameters(args): Parameters<DebugSessionArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        self.debug_stop_impl(request_context, args).await
+    }
+
     #[tool(
         description = "Edits workflow files using simple text find/replace operations. Works like sed - finds text patterns and replaces them, or appends content if no pattern specified."
     )]
@@ -5607,6 +6013,32 @@ Requires Chrome extension to be installed. See browser_dom_extraction.yml and de
 
 impl DesktopWrapper {
     pub(crate) async fn dispatch_tool(
+        &self,
+        peer: Peer<RoleServer>,
+        request_context: RequestContext<RoleServer>,
+        tool_name: &str,
+        arguments: &serde_json::Value,
+    ) -> Result<CallToolResult, McpError> {
+        let request_id = request_context.id.clone();
+        let started = Instant::now();
+        let result = match self.enforce_policy(tool_name, arguments) {
+            Ok(()) => {
+                self.route_tool(peer, request_context, tool_name, arguments)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        self.record_audit(
+            &request_id,
+            tool_name,
+            arguments,
+            &result,
+            started.elapsed(),
+        );
+        result
+    }
+
+    async fn route_tool(
         &self,
         _peer: Peer<RoleServer>,
         request_context: RequestContext<RoleServer>,
@@ -5715,7 +6147,16 @@ impl DesktopWrapper {
             }
             "execute_browser_script" => {
                 match serde_json::from_value::<ExecuteBrowserScriptArgs>(arguments.clone()) {
-                    Ok(args) => self.execute_browser_script(Parameters(args)).await,
+                    Ok(args) => {
+                        // Browser scripts run inside the extension, so stop waiting on cancellation
+                        tokio::select! {
+                            result = self.execute_browser_script(Parameters(args)) => result,
+                            _ = request_context.ct.cancelled() => Err(McpError::internal_error(
+                                "Browser script execution cancelled",
+                                Some(json!({"code": -32001, "reason": "cancelled"})),
+                            )),
+                        }
+                    }
                     Err(e) => Err(McpError::invalid_params(
                         "Invalid arguments for execute_browser_script",
                         Some(json!({"error": e.to_string()})),
@@ -5953,14 +6394,123 @@ impl DesktopWrapper {
     }
 }
 
-#[tool_handler]
 impl ServerHandler for DesktopWrapper {
+    async fn call_tool(
+        &self,
+        request: rmcp::model::CallToolRequestParam,
+        mut context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        // Attribute the call to the authenticated token (HTTP/SSE transports) so
+        // tools, logs and telemetry spans can see who is driving the desktop.
+        let identity = crate::auth::identity_from_context(&context);
+        let identity_name = identity
+            .as_ref()
+            .map(|identity| identity.name.clone())
+            .unwrap_or_else(|| "anonymous".to_string());
+        if let Some(identity) = &identity {
+            context.extensions.insert(identity.clone());
+        }
+        let call_id = crate::log_capture::CallId::random();
+        context.extensions.insert(call_id.clone());
+
+        let span = tracing::info_span!(
+            "tool_call",
+            tool = %request.name,
+            identity = %identity_name,
+            request_id = %context.id,
+            call_id = %call_id.0
+        );
+        span.in_scope(|| info!("Handling tool call"));
+        let arguments = request
+            .arguments
+            .clone()
+            .map(serde_json::Value::Object)
+            .unwrap_or(serde_json::Value::Null);
+        let tool_name = request.name.clone();
+        let request_id = context.id.clone();
+        let tcc = rmcp::handler::server::tool::ToolCallContext::new(self, request, context);
+        crate::auth::with_identity(identity, async move {
+            let started = Instant::now();
+            let result = match self.enforce_policy(&tool_name, &arguments) {
+                Ok(()) => self.tool_router.call(tcc).await,
+                Err(e) => Err(e),
+            };
+            self.record_audit(
+                &request_id,
+                &tool_name,
+                &arguments,
+                &result,
+                started.elapsed(),
+            );
+            result
+        })
+        .instrument(span)
+        .await
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<rmcp::model::PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<rmcp::model::ListToolsResult, McpError> {
+        let mut tools = self.tool_router.list_all();
+        tools.retain(|tool| self.policy.permits_tool(&tool.name));
+        Ok(rmcp::model::ListToolsResult::with_all_items(tools))
+    }
+
+    async fn list_resources(
+        &self,
+        _request: Option<rmcp::model::PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<rmcp::model::ListResourcesResult, McpError> {
+        self.list_resources_impl().await
+    }
+
+    async fn list_resource_templates(
+        &self,
+        _request: Option<rmcp::model::PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<rmcp::model::ListResourceTemplatesResult, McpError> {
+        Ok(self.list_resource_templates_impl())
+    }
+
+    async fn read_resource(
+        &self,
+        request: rmcp::model::ReadResourceRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<rmcp::model::ReadResourceResult, McpError> {
+        let identity = crate::auth::identity_from_context(&context);
+        crate::auth::with_identity(
+            identity,
+            self.read_resource_checked(&request.uri, &context.id),
+        )
+        .await
+    }
+
+    async fn subscribe(
+        &self,
+        request: rmcp::model::SubscribeRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<(), McpError> {
+        self.subscribe_resource(request.uri, context.peer).await
+    }
+
+    async fn unsubscribe(
+        &self,
+        request: rmcp::model::UnsubscribeRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<(), McpError> {
+        self.unsubscribe_resource(&request.uri).await
+    }
+
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
             protocol_version: ProtocolVersion::LATEST,
             capabilities: ServerCapabilities::builder()
                 .enable_tools()
                 .enable_logging()
+                .enable_resources()
+                .enable_resources_subscribe()
                 .build(),
             server_info: Implementation::from_build_env(),
             instructions: Some(crate::prompt::get_server_instructions().to_string()),
@@ -0,0 +1,572 @@
+use crate::expression_eval;
+use crate::request_queue::{QueuePermit, RequestQueue, SharedPermit};
+use crate::server::extract_content_json;
+use crate::utils::{
+    DebugEvalArgs, DebugSessionArgs, DebugSetVarArgs, DebugStartArgs, DesktopWrapper, SequenceStep,
+};
+use rmcp::model::{CallToolResult, Content, LoggingLevel, LoggingMessageNotificationParam};
+use rmcp::service::{Peer, RequestContext, RoleServer};
+use rmcp::ErrorData as McpError;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, MutexGuard};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot, watch, Mutex};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+/// How long a paused session waits for a command by default. A client that goes away
+/// without stopping its session would otherwise keep it, and its workflow, forever.
+pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DebugStatus {
+    Starting,
+    Running,
+    Paused,
+    Finished,
+}
+
+/// Where a debug session is and what its env looks like. Sent to the client as the
+/// result of every debug tool and streamed as a `debugger` log notification.
+#[derive(Debug, Clone, Serialize)]
+pub struct DebugSnapshot {
+    pub session_id: String,
+    pub status: DebugStatus,
+    /// Index of the step the session is suspended before (paused) or executing (running).
+    pub step_index: Option<usize>,
+    pub step_id: Option<String>,
+    pub step_name: Option<String>,
+    pub pause_reason: Option<String>,
+    pub env: Value,
+    /// Final `execute_sequence` response once the session has finished.
+    pub result: Option<Value>,
+    #[serde(skip)]
+    revision: u64,
+}
+
+enum DebugCommand {
+    Step,
+    Continue,
+    Eval {
+        expression: String,
+        reply: oneshot::Sender<Value>,
+    },
+    SetVar {
+        name: String,
+        value: Value,
+        reply: oneshot::Sender<Value>,
+    },
+}
+
+/// The HTTP queue slot a running session holds after the request that started or
+/// resumed it has returned.
+#[derive(Default)]
+struct SessionSlot {
+    queue: Option<RequestQueue>,
+    permit: Option<QueuePermit>,
+}
+
+/// A workflow run that can be suspended between steps.
+///
+/// The executing sequence owns the env; while it is suspended in [`Self::pause_point`] it
+/// serves commands from the debug tools, so evaluations and edits see the live env.
+///
+/// Over HTTP the workflow keeps the queue slot of the request that started or resumed
+/// it until it pauses or finishes, so `/status` reports the desktop as busy meanwhile.
+/// A paused session gives the slot back, otherwise the debug tools that resume it
+/// could not be admitted.
+pub struct DebugSession {
+    pub id: String,
+    breakpoints: HashSet<String>,
+    conditions: Vec<String>,
+    step_mode: AtomicBool,
+    pause_requested: AtomicBool,
+    commands: mpsc::UnboundedSender<DebugCommand>,
+    command_rx: Mutex<mpsc::UnboundedReceiver<DebugCommand>>,
+    state: watch::Sender<DebugSnapshot>,
+    idle_timeout: Duration,
+    slot: std::sync::Mutex<SessionSlot>,
+    pub cancellation: CancellationToken,
+}
+
+impl DebugSession {
+    pub fn new(
+        id: String,
+        breakpoints: Vec<String>,
+        conditions: Vec<String>,
+        stop_on_entry: bool,
+        idle_timeout: Duration,
+    ) -> Self {
+        let (commands, command_rx) = mpsc::unbounded_channel();
+        let (state, _) = watch::channel(DebugSnapshot {
+            session_id: id.clone(),
+            status: DebugStatus::Starting,
+            step_index: None,
+            step_id: None,
+            step_name: None,
+            pause_reason: None,
+            env: json!({}),
+            result: None,
+            revision: 0,
+        });
+        Self {
+            id,
+            breakpoints: breakpoints.into_iter().collect(),
+            conditions,
+            step_mode: AtomicBool::new(stop_on_entry),
+            pause_requested: AtomicBool::new(false),
+            commands,
+            command_rx: Mutex::new(command_rx),
+            state,
+            idle_timeout,
+            slot: Default::default(),
+            cancellation: CancellationToken::new(),
+        }
+    }
+
+    fn slot(&self) -> MutexGuard<'_, SessionSlot> {
+        self.slot.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Take over the queue slot of `request_context`'s HTTP request, if it has one.
+    fn hold_slot(&self, request_context: &RequestContext<RoleServer>) {
+        let Some(permit) = request_context
+            .extensions
+            .get::<axum::http::request::Parts>()
+            .and_then(|parts| parts.extensions.get::<SharedPermit>())
+            .and_then(SharedPermit::take)
+        else {
+            return;
+        };
+        let mut slot = self.slot();
+        slot.queue = Some(permit.queue().clone());
+        slot.permit = Some(permit);
+    }
+
+    fn release_slot(&self) {
+        self.slot().permit = None;
+    }
+
+    /// Wait for a slot again, for a session that stops while paused without a request
+    /// to take one from (idle timeout).
+    async fn reacquire_slot(&self) {
+        let Some(queue) = self.slot().queue.clone() else {
+            return;
+        };
+        match queue
+            .acquire(&self.id, 0, self.idle_timeout, &CancellationToken::new())
+            .await
+        {
+            Ok(permit) => self.slot().permit = Some(permit),
+            Err(e) => warn!(
+                "Debug session {} runs its cleanup without a queue slot: {:?}",
+                self.id, e
+            ),
+        }
+    }
+
+    pub fn snapshot(&self) -> DebugSnapshot {
+        self.state.borrow().clone()
+    }
+
+    fn publish(&self, update: impl FnOnce(&mut DebugSnapshot)) {
+        self.state.send_modify(|snapshot| {
+            update(snapshot);
+            snapshot.revision += 1;
+        });
+    }
+
+    async fn notify(&self, peer: &Peer<RoleServer>) {
+        let data = serde_json::to_value(self.snapshot()).unwrap_or_default();
+        if let Err(e) = peer
+            .notify_logging_message(LoggingMessageNotificationParam {
+                level: LoggingLevel::Info,
+                logger: Some("debugger".to_string()),
+                data,
+            })
+            .await
+        {
+            warn!("Failed to stream debug position to client: {}", e);
+        }
+    }
+
+    /// Called by the sequence before every step. Suspends while a breakpoint, pause or
+    /// single-step applies, and returns `false` when the session was stopped or sat
+    /// paused without a command for its idle timeout.
+    pub(crate) async fn pause_point(
+        &self,
+        step_index: usize,
+        step: Option<&SequenceStep>,
+        execution_context_map: &mut serde_json::Map<String, Value>,
+        peer: &Peer<RoleServer>,
+    ) -> bool {
+        if self.cancellation.is_cancelled() {
+            return false;
+        }
+
+        let step_id = step.and_then(|s| s.id.clone());
+        let step_name = step.and_then(|s| s.tool_name.clone().or_else(|| s.group_name.clone()));
+        let reason = if self.step_mode.load(Ordering::SeqCst) {
+            Some("step".to_string())
+        } else if self.pause_requested.load(Ordering::SeqCst) {
+            Some("pause".to_string())
+        } else if step_id
+            .as_ref()
+            .is_some_and(|id| self.breakpoints.contains(id))
+        {
+            Some("breakpoint".to_string())
+        } else {
+            let context = DesktopWrapper::create_flattened_execution_context(execution_context_map);
+            self.conditions
+                .iter()
+                .find(|condition| expression_eval::evaluate(condition, &context))
+                .map(|condition| format!("condition: {condition}"))
+        };
+
+        let Some(reason) = reason else {
+            self.publish(|s| {
+                s.status = DebugStatus::Running;
+                s.step_index = Some(step_index);
+                s.step_id = step_id;
+                s.step_name = step_name;
+            });
+            return true;
+        };
+
+        info!(
+            "Debug session {} paused before step {} ({})",
+            self.id, step_index, reason
+        );
+        self.pause_requested.store(false, Ordering::SeqCst);
+        self.release_slot();
+        let env = execution_context_map
+            .get("env")
+            .cloned()
+            .unwrap_or_default();
+        self.publish(|s| {
+            s.status = DebugStatus::Paused;
+            s.step_index = Some(step_index);
+            s.step_id = step_id;
+            s.step_name = step_name;
+            s.pause_reason = Some(reason);
+            s.env = env;
+        });
+        self.notify(peer).await;
+
+        let mut commands = self.command_rx.lock().await;
+        loop {
+            let command = tokio::select! {
+                _ = self.cancellation.cancelled() => return false,
+                _ = tokio::time::sleep(self.idle_timeout) => {
+                    warn!(
+                        "Debug session {} received no command for {:?}, stopping it",
+                        self.id, self.idle_timeout
+                    );
+                    self.cancellation.cancel();
+                    self.reacquire_slot().await;
+                    return false;
+                }
+                command = commands.recv() => command,
+            };
+            match command {
+                None => return false,
+                Some(DebugCommand::Step) => {
+                    self.step_mode.store(true, Ordering::SeqCst);
+                    break;
+                }
+                Some(DebugCommand::Continue) => {
+                    self.step_mode.store(false, Ordering::SeqCst);
+                    break;
+                }
+                Some(DebugCommand::Eval { expression, reply }) => {
+                    let context =
+                        DesktopWrapper::create_flattened_execution_context(execution_context_map);
+                    let value = expression_eval::get_value(expression.trim(), &context)
+                        .cloned()
+                        .unwrap_or_else(|| json!(expression_eval::evaluate(&expression, &context)));
+                    let _ = reply.send(value);
+                }
+                Some(DebugCommand::SetVar { name, value, reply }) => {
+                    if let Some(env_map) = execution_context_map
+                        .get_mut("env")
+                        .and_then(|env| env.as_object_mut())
+                    {
+                        env_map.insert(name, value);
+                    }
+                    let env = execution_context_map
+                        .get("env")
+                        .cloned()
+                        .unwrap_or_default();
+                    self.publish(|s| s.env = env.clone());
+                    let _ = reply.send(env);
+                }
+            }
+        }
+
+        self.publish(|s| {
+            s.status = DebugStatus::Running;
+            s.pause_reason = None;
+        });
+        true
+    }
+
+    fn finish(&self, result: Value) {
+        self.release_slot();
+        self.publish(|s| {
+            s.status = DebugStatus::Finished;
+            s.pause_reason = None;
+            s.result = Some(result);
+        });
+    }
+
+    /// Wait until the session pauses or finishes after `revision`.
+    async fn wait_for_stop(
+        &self,
+        revision: u64,
+        request_context: &RequestContext<RoleServer>,
+    ) -> Result<DebugSnapshot, McpError> {
+        let mut state = self.state.subscribe();
+        tokio::select! {
+            _ = request_context.ct.cancelled() => Err(McpError::internal_error(
+                "Request cancelled while waiting for the debug session",
+                Some(json!({"session_id": self.id})),
+            )),
+            stopped = state.wait_for(|s| {
+                s.revision > revision
+                    && matches!(s.status, DebugStatus::Paused | DebugStatus::Finished)
+            }) => Ok(stopped.map(|s| s.clone()).unwrap_or_else(|_| self.snapshot())),
+        }
+    }
+
+    fn ensure_paused(&self) -> Result<u64, McpError> {
+        let snapshot = self.state.borrow();
+        if snapshot.status != DebugStatus::Paused {
+            return Err(McpError::invalid_params(
+                format!(
+                    "Debug session is not paused (status: {:?})",
+                    snapshot.status
+                ),
+                Some(json!({"session_id": self.id})),
+            ));
+        }
+        Ok(snapshot.revision)
+    }
+
+    fn send(&self, command: DebugCommand) -> Result<(), McpError> {
+        self.commands.send(command).map_err(|_| {
+            McpError::invalid_params(
+                "Debug session has finished",
+                Some(json!({"session_id": self.id})),
+            )
+        })
+    }
+
+    async fn resume(
+        &self,
+        command: DebugCommand,
+        request_context: &RequestContext<RoleServer>,
+    ) -> Result<DebugSnapshot, McpError> {
+        let revision = self.ensure_paused()?;
+        self.hold_slot(request_context);
+        self.send(command)?;
+        self.wait_for_stop(revision, request_context).await
+    }
+
+    async fn request<T>(
+        &self,
+        command: impl FnOnce(oneshot::Sender<T>) -> DebugCommand,
+    ) -> Result<T, McpError> {
+        self.ensure_paused()?;
+        let (reply, response) = oneshot::channel();
+        self.send(command(reply))?;
+        response.await.map_err(|_| {
+            McpError::internal_error(
+                "Debug session ended before answering",
+                Some(json!({"session_id": self.id})),
+            )
+        })
+    }
+}
+
+fn snapshot_result(snapshot: &DebugSnapshot) -> Result<CallToolResult, McpError> {
+    Ok(CallToolResult::success(vec![Content::json(snapshot)?]))
+}
+
+impl DesktopWrapper {
+    async fn get_debug_session(&self, session_id: &str) -> Result<Arc<DebugSession>, McpError> {
+        self.debug_sessions
+            .lock()
+            .await
+            .get(session_id)
+            .cloned()
+            .ok_or_else(|| {
+                McpError::invalid_params(
+                    format!("Unknown or finished debug session '{session_id}'"),
+                    Some(json!({"session_id": session_id})),
+                )
+            })
+    }
+
+    pub async fn debug_start_impl(
+        &self,
+        peer: Peer<RoleServer>,
+        request_context: RequestContext<RoleServer>,
+        args: DebugStartArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let idle_timeout = match args.idle_timeout.as_deref() {
+            Some(raw) => crate::duration_parser::parse_duration(raw)
+                .map(Duration::from_millis)
+                .map_err(|e| {
+                    McpError::invalid_params(
+                        format!("Invalid idle_timeout '{raw}': {e}"),
+                        Some(json!({"idle_timeout": raw})),
+                    )
+                })?,
+            None => DEFAULT_IDLE_TIMEOUT,
+        };
+        let session = Arc::new(DebugSession::new(
+            uuid::Uuid::new_v4().to_string(),
+            args.breakpoints.unwrap_or_default(),
+            args.breakpoint_conditions.unwrap_or_default(),
+            args.stop_on_entry.unwrap_or(true),
+            idle_timeout,
+        ));
+        self.debug_sessions
+            .lock()
+            .await
+            .insert(session.id.clone(), session.clone());
+        info!("Started debug session {}", session.id);
+
+        // The session outlives this request, so it runs under its own cancellation token
+        let mut session_context = request_context.clone();
+        session_context.ct = session.cancellation.clone();
+
+        let server = self.clone();
+        let task_session = session.clone();
+        let identity = crate::auth::identity_from_context(&request_context);
+        session.hold_slot(&request_context);
+        tokio::spawn(async move {
+            let result = crate::auth::with_identity(
+                identity,
+                server.execute_sequence_with_debugger(
+                    peer.clone(),
+                    session_context,
+                    args.workflow,
+                    Some(task_session.clone()),
+                ),
+            )
+            .await;
+            let result = match result {
+                Ok(result) => result
+                    .content
+                    .first()
+                    .and_then(|content| extract_content_json(content).ok())
+                    .unwrap_or_default(),
+                // A stopped session reports the results of its cleanup blocks here
+                Err(e) => json!({"status": "error", "error": e.message, "data": e.data}),
+            };
+            task_session.finish(result);
+            task_session.notify(&peer).await;
+            server.debug_sessions.lock().await.remove(&task_session.id);
+            info!("Debug session {} finished", task_session.id);
+        });
+
+        let snapshot = session.wait_for_stop(0, &request_context).await?;
+        snapshot_result(&snapshot)
+    }
+
+    pub async fn debug_step_impl(
+        &self,
+        request_context: RequestContext<RoleServer>,
+        args: DebugSessionArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let session = self.get_debug_session(&args.session_id).await?;
+        let snapshot = session.resume(DebugCommand::Step, &request_context).await?;
+        snapshot_result(&snapshot)
+    }
+
+    pub async fn debug_continue_impl(
+        &self,
+        request_context: RequestContext<RoleServer>,
+        args: DebugSessionArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let session = self.get_debug_session(&args.session_id).await?;
+        let snapshot = session
+            .resume(DebugCommand::Continue, &request_context)
+            .await?;
+        snapshot_result(&snapshot)
+    }
+
+    pub async fn debug_pause_impl(
+        &self,
+        request_context: RequestContext<RoleServer>,
+        args: DebugSessionArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let session = self.get_debug_session(&args.session_id).await?;
+        let snapshot = session.snapshot();
+        if snapshot.status != DebugStatus::Running {
+            return snapshot_result(&snapshot);
+        }
+        // Takes effect before the next step; the running step is not interrupted
+        session.pause_requested.store(true, Ordering::SeqCst);
+        let snapshot = session
+            .wait_for_stop(snapshot.revision, &request_context)
+            .await?;
+        snapshot_result(&snapshot)
+    }
+
+    pub async fn debug_eval_impl(&self, args: DebugEvalArgs) -> Result<CallToolResult, McpError> {
+        let session = self.get_debug_session(&args.session_id).await?;
+        let expression = args.expression;
+        let value = session
+            .request(|reply| DebugCommand::Eval {
+                expression: expression.clone(),
+                reply,
+            })
+            .await?;
+        Ok(CallToolResult::success(vec![Content::json(json!({
+            "session_id": session.id,
+            "expression": expression,
+            "value": value,
+        }))?]))
+    }
+
+    pub async fn debug_set_var_impl(
+        &self,
+        args: DebugSetVarArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let session = self.get_debug_session(&args.session_id).await?;
+        let name = args.name;
+        let value = args.value;
+        let env = session
+            .request(|reply| DebugCommand::SetVar {
+                name: name.clone(),
+                value,
+                reply,
+            })
+            .await?;
+        info!("Debug session {} set env.{}", session.id, name);
+        Ok(CallToolResult::success(vec![Content::json(json!({
+            "session_id": session.id,
+            "name": name,
+            "env": env,
+        }))?]))
+    }
+
+    pub async fn debug_stop_impl(
+        &self,
+        request_context: RequestContext<RoleServer>,
+        args: DebugSessionArgs,
+    ) -> Result<CallToolResult, McpError> {
+        let session = self.get_debug_session(&args.session_id).await?;
+        let revision = session.snapshot().revision;
+        session.hold_slot(&request_context);
+        session.cancellation.cancel();
+        let snapshot = session.wait_for_stop(revision, &request_context).await?;
+        snapshot_result(&snapshot)
+    }
+}
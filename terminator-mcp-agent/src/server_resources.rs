@@ -0,0 +1,638 @@
+//! MCP resources: read-only views of desktop state that clients can read (and
+//! subscribe to) without spending tool calls.
+//!
+//! - `terminator://applications` — running applications (JSON)
+//! - `terminator://window/{pid}/tree` — compact YAML UI tree of a window
+//!   (`terminator://window/focused/tree` for whichever window has focus)
+//! - `terminator://workflows/{path}` — workflow YAML files in a folder (JSON), or one file,
+//!   relative to the configured workflows root
+//! - `terminator://monitors/{id}/screenshot` — PNG screenshot of a monitor
+//!
+//! Reads are checked against the tool policy and audit-logged as the tool that returns
+//! the same data. Subscribed clients get `notifications/resources/updated` when the
+//! focused window changes, for every subscribed resource that change affects.
+
+use crate::server::rgba_to_png;
+use crate::server_workflow_files::scan_yaml_files;
+use crate::tree_formatter::format_ui_node_as_compact_yaml;
+use crate::utils::DesktopWrapper;
+use base64::{engine::general_purpose, Engine as _};
+use rmcp::model::{
+    AnnotateAble, ListResourceTemplatesResult, ListResourcesResult, RawResource,
+    RawResourceTemplate, ReadResourceResult, ResourceContents, ResourceUpdatedNotificationParam,
+};
+use rmcp::service::{Peer, RoleServer};
+use rmcp::ErrorData as McpError;
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+pub const APPLICATIONS_URI: &str = "terminator://applications";
+pub const FOCUSED_TREE_URI: &str = "terminator://window/focused/tree";
+
+/// How often the focus watcher samples the focused window while anything is subscribed.
+const FOCUS_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResourceUri {
+    Applications,
+    /// `None` means the focused window.
+    WindowTree(Option<u32>),
+    Workflows(String),
+    MonitorScreenshot(String),
+}
+
+impl ResourceUri {
+    pub fn parse(uri: &str) -> Result<Self, McpError> {
+        let not_found =
+            || McpError::resource_not_found("Unknown resource URI", Some(json!({ "uri": uri })));
+        let rest = uri.strip_prefix("terminator://").ok_or_else(not_found)?;
+
+        if rest == "applications" {
+            return Ok(Self::Applications);
+        }
+        if let Some(window) = rest
+            .strip_prefix("window/")
+            .and_then(|r| r.strip_suffix("/tree"))
+        {
+            return match window {
+                "focused" => Ok(Self::WindowTree(None)),
+                pid => pid
+                    .parse()
+                    .map(|pid| Self::WindowTree(Some(pid)))
+                    .map_err(|_| not_found()),
+            };
+        }
+        if let Some(path) = rest.strip_prefix("workflows/") {
+            let path = percent_decode(path).ok_or_else(not_found)?;
+            if path.is_empty() {
+                return Err(not_found());
+            }
+            return Ok(Self::Workflows(path));
+        }
+        if let Some(id) = rest
+            .strip_prefix("monitors/")
+            .and_then(|r| r.strip_suffix("/screenshot"))
+        {
+            let id = percent_decode(id).ok_or_else(not_found)?;
+            if !id.is_empty() {
+                return Ok(Self::MonitorScreenshot(id));
+            }
+        }
+        Err(not_found())
+    }
+
+    /// The tool that returns the same data, with matching arguments, so that reads
+    /// go through the same policy check and audit log as that tool.
+    pub fn matching_tool(&self) -> (&'static str, Value) {
+        match self {
+            Self::Applications => ("get_applications", json!({"include_tree": false})),
+            Self::WindowTree(Some(pid)) => ("get_window_tree", json!({"pid": pid})),
+            Self::WindowTree(None) => ("get_focused_window_tree", json!({})),
+            Self::Workflows(path) if path.ends_with(".yml") || path.ends_with(".yaml") => {
+                ("import_workflow_sequence", json!({"file_path": path}))
+            }
+            Self::Workflows(path) => ("import_workflow_sequence", json!({"folder_path": path})),
+            Self::MonitorScreenshot(_) => (
+                "get_applications",
+                json!({"include_tree": false, "include_monitor_screenshots": true}),
+            ),
+        }
+    }
+}
+
+/// Resolve `path` inside `root`, rejecting anything (`..`, absolute paths, symlinks)
+/// that ends up outside of it.
+fn resolve_in_root(root: &Path, path: &str) -> Result<PathBuf, String> {
+    let root = root
+        .canonicalize()
+        .map_err(|e| format!("Workflows root {}: {}", root.display(), e))?;
+    let resolved = root
+        .join(path.trim_start_matches(['/', '\\']))
+        .canonicalize()
+        .map_err(|e| e.to_string())?;
+    if !resolved.starts_with(&root) {
+        return Err("Path is outside of the workflows root".to_string());
+    }
+    Ok(resolved)
+}
+
+/// URIs whose content changes when focus moves from `old_pid` to `new_pid`.
+fn focus_change_affects(uri: &str, old_pid: Option<u32>, new_pid: Option<u32>) -> bool {
+    match ResourceUri::parse(uri) {
+        Ok(ResourceUri::Applications)
+        | Ok(ResourceUri::WindowTree(None))
+        | Ok(ResourceUri::MonitorScreenshot(_)) => true,
+        Ok(ResourceUri::WindowTree(Some(pid))) => Some(pid) == old_pid || Some(pid) == new_pid,
+        _ => false,
+    }
+}
+
+fn percent_decode(input: &str) -> Option<String> {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = input.get(i + 1..i + 3)?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+/// Subscriptions of one client session. Every session's server gets its own (see
+/// `DesktopWrapper::for_new_session`), so sessions never see or cancel each other's.
+#[derive(Debug, Default)]
+pub struct ResourceSubscriptions {
+    uris: HashSet<String>,
+    /// Cancels the focus watcher; set while at least one URI is subscribed.
+    watcher: Option<CancellationToken>,
+}
+
+/// A resource read as the result of its matching tool, for the audit log.
+fn as_tool_result(result: &ReadResourceResult) -> rmcp::model::CallToolResult {
+    let content = result
+        .contents
+        .iter()
+        .map(|contents| match contents {
+            ResourceContents::TextResourceContents { text, .. } => {
+                rmcp::model::Content::text(text.clone())
+            }
+            ResourceContents::BlobResourceContents {
+                blob, mime_type, ..
+            } => rmcp::model::Content::image(blob.clone(), mime_type.clone().unwrap_or_default()),
+        })
+        .collect();
+    rmcp::model::CallToolResult::success(content)
+}
+
+fn resource(uri: &str, name: &str, description: &str, mime_type: &str) -> rmcp::model::Resource {
+    RawResource {
+        uri: uri.to_string(),
+        name: name.to_string(),
+        description: Some(description.to_string()),
+        mime_type: Some(mime_type.to_string()),
+        size: None,
+    }
+    .no_annotation()
+}
+
+fn template(
+    uri_template: &str,
+    name: &str,
+    description: &str,
+    mime_type: &str,
+) -> rmcp::model::ResourceTemplate {
+    RawResourceTemplate {
+        uri_template: uri_template.to_string(),
+        name: name.to_string(),
+        description: Some(description.to_string()),
+        mime_type: Some(mime_type.to_string()),
+    }
+    .no_annotation()
+}
+
+fn text_contents(uri: &str, mime_type: &str, text: String) -> ReadResourceResult {
+    ReadResourceResult {
+        contents: vec![ResourceContents::TextResourceContents {
+            uri: uri.to_string(),
+            mime_type: Some(mime_type.to_string()),
+            text,
+            meta: None,
+        }],
+    }
+}
+
+fn internal_error(message: &'static str, uri: &str, e: impl ToString) -> McpError {
+    McpError::internal_error(message, Some(json!({"uri": uri, "reason": e.to_string()})))
+}
+
+impl DesktopWrapper {
+    pub async fn list_resources_impl(&self) -> Result<ListResourcesResult, McpError> {
+        let mut resources = vec![
+            resource(
+                APPLICATIONS_URI,
+                "applications",
+                "Running applications with their PIDs and focus state",
+                "application/json",
+            ),
+            resource(
+                FOCUSED_TREE_URI,
+                "focused-window-tree",
+                "Compact YAML UI tree of the focused window",
+                "application/yaml",
+            ),
+        ];
+        match self.desktop.list_monitors().await {
+            Ok(monitors) => resources.extend(monitors.iter().map(|monitor| {
+                resource(
+                    &format!("terminator://monitors/{}/screenshot", monitor.id),
+                    &format!("screenshot-{}", monitor.name),
+                    &format!(
+                        "Screenshot of monitor '{}' ({}x{}{})",
+                        monitor.name,
+                        monitor.width,
+                        monitor.height,
+                        if monitor.is_primary { ", primary" } else { "" }
+                    ),
+                    "image/png",
+                )
+            })),
+            Err(e) => warn!("Failed to list monitors for resources: {}", e),
+        }
+        Ok(ListResourcesResult::with_all_items(resources))
+    }
+
+    pub fn list_resource_templates_impl(&self) -> ListResourceTemplatesResult {
+        ListResourceTemplatesResult::with_all_items(vec![
+            template(
+                "terminator://window/{pid}/tree",
+                "window-tree",
+                "Compact YAML UI tree of the window owned by a process",
+                "application/yaml",
+            ),
+            template(
+                "terminator://workflows/{path}",
+                "workflows",
+                "Workflow YAML files in a folder (JSON listing), or the content of one workflow file. Percent-encode the path if needed.",
+                "application/json",
+            ),
+            template(
+                "terminator://monitors/{id}/screenshot",
+                "monitor-screenshot",
+                "PNG screenshot of a monitor",
+                "image/png",
+            ),
+        ])
+    }
+
+    /// Read `uri` on behalf of request `request_id`: checked against the policy and
+    /// recorded in the audit log like a call of the matching tool.
+    pub async fn read_resource_checked(
+        &self,
+        uri: &str,
+        request_id: &rmcp::model::RequestId,
+    ) -> Result<ReadResourceResult, McpError> {
+        let (tool_name, mut arguments) = ResourceUri::parse(uri)?.matching_tool();
+        arguments["resource_uri"] = json!(uri);
+        let started = Instant::now();
+        let result = match self.enforce_policy(tool_name, &arguments) {
+            Ok(()) => self.read_resource_impl(uri).await,
+            Err(e) => Err(e),
+        };
+        if self.audit.is_some() {
+            let audited = match &result {
+                Ok(read) => Ok(as_tool_result(read)),
+                Err(e) => Err(e.clone()),
+            };
+            self.record_audit(
+                request_id,
+                tool_name,
+                &arguments,
+                &audited,
+                started.elapsed(),
+            );
+        }
+        result
+    }
+
+    pub async fn read_resource_impl(&self, uri: &str) -> Result<ReadResourceResult, McpError> {
+        match ResourceUri::parse(uri)? {
+            ResourceUri::Applications => {
+                let args = crate::utils::GetApplicationsArgs {
+                    include_tree: Some(false),
+                    tree_max_depth: None,
+                    tree_from_selector: None,
+                    include_detailed_attributes: None,
+                    include_monitor_screenshots: None,
+                };
+                let result = self
+                    .get_applications(rmcp::handler::server::wrapper::Parameters(args))
+                    .await?;
+                let applications = result
+                    .content
+                    .first()
+                    .and_then(|content| crate::server::extract_content_json(content).ok())
+                    .and_then(|value| value.get("applications").cloned())
+                    .unwrap_or_else(|| json!([]));
+                let text = serde_json::to_string_pretty(&applications)
+                    .map_err(|e| internal_error("Failed to serialize applications", uri, e))?;
+                Ok(text_contents(uri, "application/json", text))
+            }
+            ResourceUri::WindowTree(pid) => {
+                let desktop = self.desktop.clone();
+                let config = Self::create_tree_config(None);
+                let tree = tokio::task::spawn_blocking(move || {
+                    let pid = match pid {
+                        Some(pid) => pid,
+                        None => desktop
+                            .focused_element()
+                            .map_err(|e| e.to_string())?
+                            .process_id()
+                            .map_err(|e| e.to_string())?,
+                    };
+                    desktop
+                        .get_window_tree(pid, None, Some(config))
+                        .map_err(|e| e.to_string())
+                })
+                .await
+                .map_err(|e| internal_error("Window tree task failed", uri, e))?
+                .map_err(|e| {
+                    McpError::resource_not_found(
+                        "Failed to get window tree",
+                        Some(json!({"uri": uri, "reason": e})),
+                    )
+                })?;
+                Ok(text_contents(
+                    uri,
+                    "application/yaml",
+                    format_ui_node_as_compact_yaml(&tree, 0),
+                ))
+            }
+            ResourceUri::Workflows(path) => {
+                let root = self.workflows_root.as_deref().ok_or_else(|| {
+                    McpError::resource_not_found(
+                        "Workflow resources are disabled; start the server with --workflows-root",
+                        Some(json!({"uri": uri})),
+                    )
+                })?;
+                let resolved = resolve_in_root(root, &path).map_err(|reason| {
+                    McpError::resource_not_found(
+                        "Workflow resource not found",
+                        Some(json!({"uri": uri, "path": path, "reason": reason})),
+                    )
+                })?;
+                if resolved.is_dir() {
+                    let files = scan_yaml_files(&resolved.to_string_lossy())?;
+                    let text = serde_json::to_string_pretty(&json!({
+                        "folder_path": path,
+                        "files": files,
+                        "count": files.len(),
+                    }))
+                    .map_err(|e| internal_error("Failed to serialize workflow list", uri, e))?;
+                    return Ok(text_contents(uri, "application/json", text));
+                }
+                let is_yaml = resolved
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .is_some_and(|ext| ext == "yaml" || ext == "yml");
+                if !is_yaml {
+                    return Err(McpError::resource_not_found(
+                        "Workflow resource must be a folder or a .yml/.yaml file",
+                        Some(json!({"uri": uri, "path": path})),
+                    ));
+                }
+                let text = std::fs::read_to_string(&resolved).map_err(|e| {
+                    McpError::resource_not_found(
+                        "Failed to read workflow file",
+                        Some(json!({"uri": uri, "path": path, "reason": e.to_string()})),
+                    )
+                })?;
+                Ok(text_contents(uri, "application/yaml", text))
+            }
+            ResourceUri::MonitorScreenshot(id) => {
+                let monitor = self.desktop.get_monitor_by_id(&id).await.map_err(|e| {
+                    McpError::resource_not_found(
+                        "Monitor not found",
+                        Some(json!({"uri": uri, "monitor_id": id, "reason": e.to_string()})),
+                    )
+                })?;
+                let screenshot = self
+                    .desktop
+                    .capture_monitor(&monitor)
+                    .await
+                    .map_err(|e| internal_error("Failed to capture monitor", uri, e))?;
+                let png = rgba_to_png(&screenshot.image_data, screenshot.width, screenshot.height)
+                    .map_err(|e| internal_error("Failed to encode screenshot", uri, e))?;
+                Ok(ReadResourceResult {
+                    contents: vec![ResourceContents::BlobResourceContents {
+                        uri: uri.to_string(),
+                        mime_type: Some("image/png".to_string()),
+                        blob: general_purpose::STANDARD.encode(png),
+                        meta: None,
+                    }],
+                })
+            }
+        }
+    }
+
+    pub async fn subscribe_resource(
+        &self,
+        uri: String,
+        peer: Peer<RoleServer>,
+    ) -> Result<(), McpError> {
+        ResourceUri::parse(&uri)?;
+        let mut subscriptions = self.resource_subscriptions.lock().await;
+        subscriptions.uris.insert(uri.clone());
+        if subscriptions.watcher.is_none() {
+            subscriptions.watcher = Some(self.spawn_focus_watcher(peer));
+        }
+        info!("Subscribed to resource {}", uri);
+        Ok(())
+    }
+
+    pub async fn unsubscribe_resource(&self, uri: &str) -> Result<(), McpError> {
+        let mut subscriptions = self.resource_subscriptions.lock().await;
+        subscriptions.uris.remove(uri);
+        if subscriptions.uris.is_empty() {
+            if let Some(watcher) = subscriptions.watcher.take() {
+                watcher.cancel();
+            }
+        }
+        info!("Unsubscribed from resource {}", uri);
+        Ok(())
+    }
+
+    /// Poll the focused window and notify subscribers when it changes. Stops when
+    /// cancelled or when the client can no longer be reached.
+    fn spawn_focus_watcher(&self, peer: Peer<RoleServer>) -> CancellationToken {
+        let token = CancellationToken::new();
+        let cancelled = token.clone();
+        let desktop = self.desktop.clone();
+        let subscriptions = self.resource_subscriptions.clone();
+
+        let focused_window = move || {
+            let desktop = desktop.clone();
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    let element = desktop.focused_element().ok()?;
+                    Some((element.process_id().ok()?, element.window_title()))
+                })
+                .await
+                .ok()
+                .flatten()
+            }
+        };
+
+        tokio::spawn(async move {
+            let mut last = focused_window().await;
+            'watch: loop {
+                tokio::select! {
+                    _ = cancelled.cancelled() => break,
+                    _ = tokio::time::sleep(FOCUS_POLL_INTERVAL) => {}
+                }
+                if peer.is_transport_closed() {
+                    debug!("Stopping focus watcher, client session closed");
+                    break;
+                }
+                let current = focused_window().await;
+                if current == last {
+                    continue;
+                }
+                debug!("Focused window changed: {:?} -> {:?}", last, current);
+                let (old_pid, new_pid) = (
+                    last.as_ref().map(|(pid, _)| *pid),
+                    current.as_ref().map(|(pid, _)| *pid),
+                );
+                last = current;
+
+                let uris: Vec<String> = subscriptions
+                    .lock()
+                    .await
+                    .uris
+                    .iter()
+                    .filter(|uri| focus_change_affects(uri, old_pid, new_pid))
+                    .cloned()
+                    .collect();
+                for uri in uris {
+                    if let Err(e) = peer
+                        .notify_resource_updated(ResourceUpdatedNotificationParam { uri })
+                        .await
+                    {
+                        debug!("Stopping focus watcher, client unreachable: {}", e);
+                        break 'watch;
+                    }
+                }
+            }
+            // Nobody is left to notify, so forget the session's subscriptions
+            if !cancelled.is_cancelled() {
+                let mut subscriptions = subscriptions.lock().await;
+                subscriptions.uris.clear();
+                subscriptions.watcher = None;
+            }
+        });
+        token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_resource_uris() {
+        assert_eq!(
+            ResourceUri::parse("terminator://applications").unwrap(),
+            ResourceUri::Applications
+        );
+        assert_eq!(
+            ResourceUri::parse("terminator://window/4242/tree").unwrap(),
+            ResourceUri::WindowTree(Some(4242))
+        );
+        assert_eq!(
+            ResourceUri::parse(FOCUSED_TREE_URI).unwrap(),
+            ResourceUri::WindowTree(None)
+        );
+        assert_eq!(
+            ResourceUri::parse("terminator://workflows/C:%5Cflows%20dir").unwrap(),
+            ResourceUri::Workflows("C:\\flows dir".to_string())
+        );
+        assert_eq!(
+            ResourceUri::parse("terminator://workflows//home/ci/flows/login.yml").unwrap(),
+            ResourceUri::Workflows("/home/ci/flows/login.yml".to_string())
+        );
+        assert_eq!(
+            ResourceUri::parse("terminator://monitors/65537/screenshot").unwrap(),
+            ResourceUri::MonitorScreenshot("65537".to_string())
+        );
+        for bad in [
+            "file:///etc/passwd",
+            "terminator://window/abc/tree",
+            "terminator://workflows/",
+            "terminator://monitors//screenshot",
+            "terminator://workflows/%zz",
+        ] {
+            assert!(ResourceUri::parse(bad).is_err(), "{bad} should not parse");
+        }
+    }
+
+    #[test]
+    fn test_workflow_paths_stay_in_root() {
+        let root = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        std::fs::create_dir(root.path().join("flows")).unwrap();
+        std::fs::write(root.path().join("flows/login.yml"), "steps: []").unwrap();
+        std::fs::write(outside.path().join("secret.yml"), "steps: []").unwrap();
+
+        let resolved = resolve_in_root(root.path(), "flows/login.yml").unwrap();
+        assert!(resolved.ends_with("flows/login.yml"));
+        assert!(resolve_in_root(root.path(), "/flows").unwrap().is_dir());
+
+        let escape = format!(
+            "flows/../../{}/secret.yml",
+            outside.path().file_name().unwrap().to_string_lossy()
+        );
+        for path in [
+            "../secret.yml",
+            escape.as_str(),
+            &outside.path().join("secret.yml").to_string_lossy(),
+        ] {
+            assert!(resolve_in_root(root.path(), path).is_err(), "{path}");
+        }
+    }
+
+    #[test]
+    fn test_resources_match_tools() {
+        assert_eq!(
+            ResourceUri::WindowTree(Some(7)).matching_tool(),
+            ("get_window_tree", json!({"pid": 7}))
+        );
+        assert_eq!(
+            ResourceUri::Workflows("flows/login.yml".to_string()).matching_tool(),
+            (
+                "import_workflow_sequence",
+                json!({"file_path": "flows/login.yml"})
+            )
+        );
+    }
+
+    #[test]
+    fn test_focus_change_affects() {
+        let (old, new) = (Some(10), Some(20));
+        assert!(focus_change_affects(APPLICATIONS_URI, old, new));
+        assert!(focus_change_affects(FOCUSED_TREE_URI, old, new));
+        assert!(focus_change_affects(
+            "terminator://window/10/tree",
+            old,
+            new
+        ));
+        assert!(focus_change_affects(
+            "terminator://window/20/tree",
+            old,
+            new
+        ));
+        assert!(!focus_change_affects(
+            "terminator://window/30/tree",
+            old,
+            new
+        ));
+        assert!(focus_change_affects(
+            "terminator://monitors/1/screenshot",
+            old,
+            new
+        ));
+        assert!(!focus_change_affects(
+            "terminator://workflows/flows",
+            old,
+            new
+        ));
+    }
+}
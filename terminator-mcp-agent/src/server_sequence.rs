@@ -1,16 +1,96 @@
 use crate::helpers::substitute_variables;
 use crate::output_parser;
+use crate::retry::{classify_failure, ResolvedRetry};
+use crate::sequence_progress::{ProgressStep, SequenceProgress};
 use crate::server::extract_content_json;
+use crate::server_debugger::DebugSession;
 use crate::telemetry::{StepSpan, WorkflowSpan};
-use crate::utils::{DesktopWrapper, ExecuteSequenceArgs, SequenceItem, ToolCall, ToolGroup};
+use crate::utils::{
+    DesktopWrapper, ExecuteSequenceArgs, SequenceItem, SequenceStep, ToolCall, ToolGroup,
+};
 use rmcp::model::{CallToolResult, Content};
 use rmcp::service::{Peer, RequestContext, RoleServer};
 use rmcp::ErrorData as McpError;
 use serde_json::{json, Value};
 use std::path::{Path, PathBuf};
-use std::time::Duration;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tracing::{debug, info, warn};
 
+/// Grace period given to cancellation-aware tools (e.g. script engines killing their
+/// child process) to wind down after a step timeout fires.
+const STEP_TIMEOUT_GRACE: Duration = Duration::from_millis(500);
+
+/// Timeout of `on_failure`/`finally` steps without one, unless `cleanup_timeout` is set
+const DEFAULT_CLEANUP_STEP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Time an `on_failure`/`finally` block may run past the workflow deadline, or after the
+/// request was cancelled
+const CLEANUP_GRACE: Duration = Duration::from_secs(30);
+
+/// Parse an optional human-readable timeout. Malformed values are rejected up front
+/// so a typo can't silently leave a step unbounded.
+fn parse_timeout(value: Option<&str>, location: &str) -> Result<Option<Duration>, McpError> {
+    match value {
+        Some(raw) => crate::duration_parser::parse_duration(raw)
+            .map(|ms| Some(Duration::from_millis(ms)))
+            .map_err(|e| {
+                McpError::invalid_params(
+                    format!("Invalid timeout '{raw}' for {location}: {e}"),
+                    Some(json!({"timeout": raw, "location": location})),
+                )
+            }),
+        None => Ok(None),
+    }
+}
+
+/// The tighter of a local timeout and the time left before an outer deadline.
+fn effective_timeout(timeout: Option<Duration>, deadline: Option<Instant>) -> Option<Duration> {
+    let remaining = deadline.map(|d| d.saturating_duration_since(Instant::now()));
+    match (timeout, remaining) {
+        (Some(local), Some(remaining)) => Some(local.min(remaining)),
+        (local, remaining) => local.or(remaining),
+    }
+}
+
+/// Resolve a step's retry policy, rejecting malformed durations up front like timeouts.
+fn parse_retry(step: &SequenceStep) -> Result<ResolvedRetry, McpError> {
+    let location = step
+        .id
+        .as_deref()
+        .or(step.tool_name.as_deref())
+        .or(step.group_name.as_deref())
+        .unwrap_or_default();
+    let retry = ResolvedRetry::resolve(step.retry.as_ref(), step.retries).map_err(|e| {
+        McpError::invalid_params(
+            format!("Invalid retry policy for step '{location}': {e}"),
+            Some(json!({"retry": step.retry, "location": location})),
+        )
+    })?;
+    for s in &retry.before_retry {
+        parse_timeout(
+            s.timeout.as_deref(),
+            &format!("before_retry step '{}'", s.tool_name),
+        )?;
+    }
+    Ok(retry)
+}
+
+/// The id and error of the step that made `result` fail, looking inside group results.
+fn failure_details(result: &Value) -> (Value, Value) {
+    let inner = result
+        .get("results")
+        .and_then(|r| r.as_array())
+        .or_else(|| result.as_array());
+    let failed = inner
+        .and_then(|inner| inner.iter().rev().find(|r| r.get("error").is_some()))
+        .unwrap_or(result);
+    (
+        failed.get("step_id").cloned().unwrap_or(Value::Null),
+        failed.get("error").cloned().unwrap_or(Value::Null),
+    )
+}
+
 impl DesktopWrapper {
     // Get the state file path for a workflow
     async fn get_state_file_path(workflow_url: &str) -> Option<PathBuf> {
@@ -101,7 +181,7 @@ impl DesktopWrapper {
     /// are available both under 'env.' prefix and directly at the top level.
     /// This enables conditions to access env variables directly without the 'env.' prefix,
     /// matching the behavior of script execution.
-    fn create_flattened_execution_context(
+    pub(crate) fn create_flattened_execution_context(
         execution_context_map: &serde_json::Map<String, serde_json::Value>,
     ) -> serde_json::Value {
         let mut flattened_map = execution_context_map.clone();
@@ -130,7 +210,20 @@ impl DesktopWrapper {
         &self,
         peer: Peer<RoleServer>,
         request_context: RequestContext<RoleServer>,
+        args: ExecuteSequenceArgs,
+    ) -> Result<CallToolResult, McpError> {
+        self.execute_sequence_with_debugger(peer, request_context, args, None)
+            .await
+    }
+
+    /// Runs a sequence, optionally under a debug session that can suspend it before
+    /// each step (see [`DebugSession::pause_point`]).
+    pub async fn execute_sequence_with_debugger(
+        &self,
+        peer: Peer<RoleServer>,
+        mut request_context: RequestContext<RoleServer>,
         mut args: ExecuteSequenceArgs,
+        debug_session: Option<Arc<DebugSession>>,
     ) -> Result<CallToolResult, McpError> {
         // Validate that either URL or steps are provided
         if args.url.is_none() && args.steps.as_ref().map(|s| s.is_empty()).unwrap_or(true) {
@@ -360,6 +453,9 @@ impl DesktopWrapper {
             if args.output.is_none() {
                 args.output = remote_workflow.output;
             }
+            if args.timeout.is_none() {
+                args.timeout = remote_workflow.timeout;
+            }
         }
 
         // Set the scripts_base_path for file resolution in run_command and execute_browser_script
@@ -381,6 +477,16 @@ impl DesktopWrapper {
             );
         }
 
+        let workflow_timeout = parse_timeout(args.timeout.as_deref(), "workflow")?;
+        let cleanup_step_timeout = parse_timeout(args.cleanup_timeout.as_deref(), "cleanup")?
+            .unwrap_or(DEFAULT_CLEANUP_STEP_TIMEOUT);
+        for s in args.on_failure.iter().chain(args.finally.iter()).flatten() {
+            parse_timeout(
+                s.timeout.as_deref(),
+                &format!("cleanup step '{}'", s.tool_name),
+            )?;
+        }
+
         // Handle backward compatibility: 'continue' is opposite of 'stop_on_error'
         let stop_on_error = if let Some(continue_exec) = args.r#continue {
             !continue_exec // continue=true means stop_on_error=false
@@ -671,6 +777,7 @@ impl DesktopWrapper {
 
         // Convert flattened SequenceStep to internal SequenceItem representation
         let mut sequence_items = Vec::new();
+        let mut retry_plans = Vec::new();
         let empty_steps = Vec::new();
         let steps = args.steps.as_ref().unwrap_or(&empty_steps);
         for step in steps {
@@ -688,31 +795,36 @@ impl DesktopWrapper {
                     step.delay_ms
                 };
 
+                parse_timeout(step.timeout.as_deref(), &format!("step '{tool_name}'"))?;
+
                 let tool_call = ToolCall {
                     tool_name: tool_name.clone(),
                     arguments: step.arguments.clone().unwrap_or(serde_json::json!({})),
                     continue_on_error: step.continue_on_error,
                     delay_ms,
                     id: step.id.clone(),
+                    timeout: step.timeout.clone(),
                 };
                 SequenceItem::Tool { tool_call }
             } else if let Some(group_name) = &step.group_name {
+                parse_timeout(step.timeout.as_deref(), &format!("group '{group_name}'"))?;
+                for s in step
+                    .steps
+                    .iter()
+                    .chain(step.on_failure.iter())
+                    .chain(step.finally.iter())
+                    .flatten()
+                {
+                    parse_timeout(s.timeout.as_deref(), &format!("step '{}'", s.tool_name))?;
+                }
+
                 let tool_group = ToolGroup {
                     group_name: group_name.clone(),
-                    steps: step
-                        .steps
-                        .clone()
-                        .unwrap_or_default()
-                        .into_iter()
-                        .map(|s| ToolCall {
-                            tool_name: s.tool_name,
-                            arguments: s.arguments,
-                            continue_on_error: s.continue_on_error,
-                            delay_ms: s.delay_ms,
-                            id: s.id,
-                        })
-                        .collect(),
+                    steps: step.steps.clone().unwrap_or_default(),
                     skippable: step.skippable,
+                    timeout: step.timeout.clone(),
+                    on_failure: step.on_failure.clone(),
+                    finally: step.finally.clone(),
                 };
                 SequenceItem::Group { tool_group }
             } else {
@@ -722,6 +834,7 @@ impl DesktopWrapper {
                 ));
             };
             sequence_items.push(item);
+            retry_plans.push(parse_retry(step)?);
         }
 
         // Add troubleshooting steps to the sequence (they won't execute unless jumped to via fallback_id)
@@ -745,31 +858,36 @@ impl DesktopWrapper {
                         step.delay_ms
                     };
 
+                    parse_timeout(step.timeout.as_deref(), &format!("step '{tool_name}'"))?;
+
                     let tool_call = ToolCall {
                         tool_name: tool_name.clone(),
                         arguments: step.arguments.clone().unwrap_or(serde_json::json!({})),
                         continue_on_error: step.continue_on_error,
                         delay_ms,
                         id: step.id.clone(),
+                        timeout: step.timeout.clone(),
                     };
                     SequenceItem::Tool { tool_call }
                 } else if let Some(group_name) = &step.group_name {
+                    parse_timeout(step.timeout.as_deref(), &format!("group '{group_name}'"))?;
+                    for s in step
+                        .steps
+                        .iter()
+                        .chain(step.on_failure.iter())
+                        .chain(step.finally.iter())
+                        .flatten()
+                    {
+                        parse_timeout(s.timeout.as_deref(), &format!("step '{}'", s.tool_name))?;
+                    }
+
                     let tool_group = ToolGroup {
                         group_name: group_name.clone(),
-                        steps: step
-                            .steps
-                            .clone()
-                            .unwrap_or_default()
-                            .into_iter()
-                            .map(|s| ToolCall {
-                                tool_name: s.tool_name,
-                                arguments: s.arguments,
-                                continue_on_error: s.continue_on_error,
-                                delay_ms: s.delay_ms,
-                                id: s.id,
-                            })
-                            .collect(),
+                        steps: step.steps.clone().unwrap_or_default(),
                         skippable: step.skippable,
+                        timeout: step.timeout.clone(),
+                        on_failure: step.on_failure.clone(),
+                        finally: step.finally.clone(),
                     };
                     SequenceItem::Group { tool_group }
                 } else {
@@ -779,6 +897,7 @@ impl DesktopWrapper {
                     ));
                 };
                 sequence_items.push(item);
+                retry_plans.push(parse_retry(step)?);
             }
         }
 
@@ -790,7 +909,42 @@ impl DesktopWrapper {
         let mut sequence_had_errors = false;
         let mut critical_error_occurred = false;
         let mut used_fallback = false; // Track if any fallback was used
+        let mut workflow_timed_out = false;
+        let mut cancelled = false;
+        let mut last_failure: Option<(Value, Value)> = None;
         let start_time = chrono::Utc::now();
+        let mut progress = SequenceProgress::new(
+            peer.clone(),
+            request_context.meta.get_progress_token(),
+            sequence_items.len(),
+            self.log_capture.as_ref(),
+            &request_context.id,
+            request_context
+                .extensions
+                .get::<crate::log_capture::CallId>(),
+        );
+        if let Some(dir) = &args.capture_fixtures {
+            if self.fixture_replay.is_some() {
+                warn!("Ignoring capture_fixtures while replaying fixtures");
+            } else {
+                self.enforce_policy("capture_fixtures", &json!({"capture_fixtures": dir}))?;
+                let session =
+                    crate::workflow_replay::FixtureSession::capture(dir).map_err(|e| {
+                        McpError::invalid_params(
+                            format!("Failed to create fixture directory: {e}"),
+                            Some(json!({"capture_fixtures": dir})),
+                        )
+                    })?;
+                info!("Capturing UI-tree fixtures into {dir}");
+                request_context
+                    .extensions
+                    .insert(crate::workflow_replay::FixtureCapture(Arc::new(session)));
+            }
+        }
+        let workflow_deadline = workflow_timeout.map(|timeout| {
+            info!("Workflow timeout set to {:?}", timeout);
+            Instant::now() + timeout
+        });
 
         let mut current_index: usize = start_from_index;
         let max_iterations = sequence_items.len() * 10; // Prevent infinite fallback loops
@@ -851,13 +1005,22 @@ impl DesktopWrapper {
         {
             iterations += 1;
 
-            // Check if the request has been cancelled
+            // Check if the request has been cancelled; cleanup blocks still run below
             if request_context.ct.is_cancelled() {
                 warn!("Request cancelled by user, stopping sequence execution");
-                return Err(McpError::internal_error(
-                    "Request cancelled by user",
-                    Some(json!({"code": -32001, "reason": "user_cancelled"})),
-                ));
+                cancelled = true;
+                sequence_had_errors = true;
+                break;
+            }
+
+            if workflow_deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                warn!(
+                    "Workflow timeout exceeded before step {}, stopping sequence",
+                    current_index
+                );
+                workflow_timed_out = true;
+                sequence_had_errors = true;
+                break;
             }
 
             // Get the original step from either main steps or troubleshooting steps
@@ -871,11 +1034,11 @@ impl DesktopWrapper {
             if let Some(step) = original_step {
                 if let Some(tool_name) = &step.tool_name {
                     info!(
-                        "Step {} BEGIN tool='{}' id='{}' retries={} if_expr={:?} fallback_id={:?} jumps={}",
+                        "Step {} BEGIN tool='{}' id='{}' max_attempts={} if_expr={:?} fallback_id={:?} jumps={}",
                         current_index,
                         tool_name,
                         step.id.as_deref().unwrap_or(""),
-                        step.retries.unwrap_or(0),
+                        retry_plans[current_index].max_attempts,
                         step.r#if,
                         step.fallback_id,
                         step.jumps.as_ref().map(|j| j.len()).unwrap_or(0)
@@ -891,15 +1054,38 @@ impl DesktopWrapper {
                 }
             }
 
+            if let Some(session) = &debug_session {
+                if !session
+                    .pause_point(
+                        current_index,
+                        original_step,
+                        &mut execution_context_map,
+                        &peer,
+                    )
+                    .await
+                {
+                    warn!("Debug session stopped before step {}", current_index);
+                    cancelled = true;
+                    sequence_had_errors = true;
+                    break;
+                }
+            }
+
             // Extract values from the step if it exists
-            let (if_expr, retries, fallback_id_opt) = if let Some(step) = original_step {
-                (
-                    step.r#if.clone(),
-                    step.retries.unwrap_or(0),
-                    step.fallback_id.clone(),
-                )
+            let (if_expr, fallback_id_opt) = if let Some(step) = original_step {
+                (step.r#if.clone(), step.fallback_id.clone())
             } else {
-                (None, 0, None)
+                (None, None)
+            };
+            let retry_plan = retry_plans[current_index].clone();
+            let step_label = match &sequence_items[current_index] {
+                SequenceItem::Tool { tool_call } => tool_call.tool_name.clone(),
+                SequenceItem::Group { tool_group } => tool_group.group_name.clone(),
+            };
+            let progress_step = ProgressStep {
+                index: current_index,
+                id: original_step.and_then(|s| s.id.as_deref()),
+                tool: &step_label,
             };
 
             let is_always_step = if_expr.as_deref().is_some_and(|s| s.trim() == "always()");
@@ -911,6 +1097,9 @@ impl DesktopWrapper {
                     "status": "skipped",
                     "reason": "Skipped due to a previous unrecoverable error in the sequence."
                 }));
+                progress
+                    .step_finished(&progress_step, "skipped", Duration::ZERO, 0)
+                    .await;
                 current_index += 1;
                 continue;
             }
@@ -931,6 +1120,9 @@ impl DesktopWrapper {
                         "status": "skipped",
                         "reason": format!("if_expr not met: {}", cond_str)
                     }));
+                    progress
+                        .step_finished(&progress_step, "skipped", Duration::ZERO, 0)
+                        .await;
                     current_index += 1;
                     continue;
                 }
@@ -940,8 +1132,22 @@ impl DesktopWrapper {
             let mut final_result = json!(null);
             let mut step_error_occurred = false;
             let total_steps = sequence_items.len();
+            let critical_error_before = critical_error_occurred;
+            let sequence_errors_before = sequence_had_errors;
+            let step_started_at = Instant::now();
+            let mut attempts_made = 0;
+
+            'attempts: for attempt in 0..retry_plan.max_attempts {
+                progress
+                    .step_started(&progress_step, attempt, retry_plan.max_attempts)
+                    .await;
+                attempts_made = attempt + 1;
+                // Only the outcome of the last attempt counts
+                critical_error_occurred = critical_error_before;
+                sequence_had_errors = sequence_errors_before;
+                step_error_occurred = false;
+                let mut attempt_span: Option<StepSpan> = None;
 
-            for attempt in 0..=retries {
                 let item = &mut sequence_items[current_index];
                 match item {
                     SequenceItem::Tool { tool_call } => {
@@ -1020,8 +1226,12 @@ impl DesktopWrapper {
                             ],
                         );
 
+                        let step_timeout = effective_timeout(
+                            parse_timeout(tool_call.timeout.as_deref(), "step").unwrap_or(None),
+                            workflow_deadline,
+                        );
                         let (result, error_occurred) = self
-                            .execute_single_tool(
+                            .execute_single_tool_with_timeout(
                                 peer.clone(),
                                 request_context.clone(),
                                 &tool_call.tool_name,
@@ -1030,6 +1240,7 @@ impl DesktopWrapper {
                                 current_index,
                                 include_detailed,
                                 original_step.and_then(|s| s.id.as_deref()),
+                                step_timeout,
                             )
                             .await;
 
@@ -1116,7 +1327,12 @@ impl DesktopWrapper {
                                 None
                             },
                         );
-                        step_span.end();
+                        if success {
+                            step_span.end();
+                        } else {
+                            // Kept open so a retry can be recorded on it
+                            attempt_span = Some(step_span);
+                        }
 
                         // Add workflow event for step completion
                         workflow_span.add_event(
@@ -1436,17 +1652,46 @@ impl DesktopWrapper {
                         let mut group_had_errors = false;
                         let mut group_results = Vec::new();
                         let is_skippable = tool_group.skippable.unwrap_or(false);
+                        let group_deadline = effective_timeout(
+                            parse_timeout(tool_group.timeout.as_deref(), "group").unwrap_or(None),
+                            workflow_deadline,
+                        )
+                        .map(|timeout| Instant::now() + timeout);
 
                         for (step_index, step_tool_call) in tool_group.steps.iter_mut().enumerate()
                         {
+                            if group_deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                                warn!(
+                                    "Group '{}' timed out before step {}",
+                                    tool_group.group_name, step_index
+                                );
+                                group_results.push(json!({
+                                    "tool_name": &step_tool_call.tool_name,
+                                    "index": step_index,
+                                    "status": "error",
+                                    "error": format!("Group '{}' timed out", tool_group.group_name),
+                                    "timed_out": true,
+                                }));
+                                group_had_errors = true;
+                                if !is_skippable && fallback_id_opt.is_none() {
+                                    critical_error_occurred = true;
+                                }
+                                break;
+                            }
+
                             // Substitute variables in arguments before execution
                             let execution_context =
                                 Self::create_flattened_execution_context(&execution_context_map);
                             let mut substituted_args = step_tool_call.arguments.clone();
                             substitute_variables(&mut substituted_args, &execution_context);
 
+                            let step_timeout = effective_timeout(
+                                parse_timeout(step_tool_call.timeout.as_deref(), "step")
+                                    .unwrap_or(None),
+                                group_deadline,
+                            );
                             let (result, error_occurred) = self
-                                .execute_single_tool(
+                                .execute_single_tool_with_timeout(
                                     peer.clone(),
                                     request_context.clone(),
                                     &step_tool_call.tool_name,
@@ -1455,6 +1700,7 @@ impl DesktopWrapper {
                                     step_index,
                                     include_detailed,
                                     step_tool_call.id.as_deref(), // Use step ID if available
+                                    step_timeout,
                                 )
                                 .await;
 
@@ -1522,22 +1768,183 @@ impl DesktopWrapper {
                         }
                     }
                 }
-                if attempt < retries {
+                if workflow_deadline.is_some_and(|deadline| Instant::now() >= deadline) {
                     warn!(
-                        "Step {} failed on attempt {}/{}. Retrying...",
-                        current_index,
-                        attempt + 1,
-                        retries
+                        "Workflow timeout exceeded during step {}, not retrying",
+                        current_index
+                    );
+                    if let Some(span) = attempt_span.take() {
+                        span.end();
+                    }
+                    break;
+                }
+                if request_context.ct.is_cancelled() {
+                    warn!(
+                        "Request cancelled during step {}, not retrying",
+                        current_index
+                    );
+                    if let Some(span) = attempt_span.take() {
+                        span.end();
+                    }
+                    break;
+                }
+
+                let error_class = classify_failure(&final_result);
+                let reason = error_class.map(|c| c.as_str()).unwrap_or("other");
+                if attempt + 1 >= retry_plan.max_attempts || !retry_plan.should_retry(error_class) {
+                    if attempt + 1 < retry_plan.max_attempts {
+                        info!(
+                            "Step {} failed with '{}' error, which its retry policy does not cover",
+                            current_index, reason
+                        );
+                    }
+                    if let Some(span) = attempt_span.take() {
+                        span.end();
+                    }
+                    break;
+                }
+
+                let delay = retry_plan.delay_for(attempt + 1);
+                let delay = effective_timeout(Some(delay), workflow_deadline).unwrap_or(delay);
+                match attempt_span.take() {
+                    Some(mut span) => {
+                        span.record_retry(attempt + 1, reason, delay.as_millis() as u64);
+                        span.end();
+                    }
+                    None => workflow_span.add_event(
+                        "step.retry",
+                        vec![
+                            ("step.index", current_index.to_string()),
+                            ("retry.attempt", (attempt + 1).to_string()),
+                            ("retry.reason", reason.to_string()),
+                            ("retry.delay_ms", delay.as_millis().to_string()),
+                        ],
+                    ),
+                }
+                warn!(
+                    "Step {} failed on attempt {}/{} ({}). Retrying in {}ms...",
+                    current_index,
+                    attempt + 1,
+                    retry_plan.max_attempts,
+                    reason,
+                    delay.as_millis()
+                );
+
+                for before_step in &retry_plan.before_retry {
+                    if request_context.ct.is_cancelled() {
+                        warn!(
+                            "Request cancelled before retrying step {}, not retrying",
+                            current_index
+                        );
+                        break 'attempts;
+                    }
+                    let execution_context =
+                        Self::create_flattened_execution_context(&execution_context_map);
+                    let mut substituted_args = before_step.arguments.clone();
+                    substitute_variables(&mut substituted_args, &execution_context);
+
+                    let step_timeout = effective_timeout(
+                        parse_timeout(before_step.timeout.as_deref(), "step").unwrap_or(None),
+                        workflow_deadline,
                     );
-                    tokio::time::sleep(Duration::from_millis(500)).await; // Wait before retry
+                    let (result, _) = self
+                        .execute_single_tool_with_timeout(
+                            peer.clone(),
+                            request_context.clone(),
+                            &before_step.tool_name,
+                            &substituted_args,
+                            true,
+                            current_index,
+                            false,
+                            before_step.id.as_deref(),
+                            step_timeout,
+                        )
+                        .await;
+                    if result["status"] != "success" {
+                        warn!(
+                            "before_retry step '{}' for step {} failed: {}",
+                            before_step.tool_name,
+                            current_index,
+                            result["error"].as_str().unwrap_or("unknown error")
+                        );
+                    }
+                    if let Some(delay_ms) = before_step.delay_ms {
+                        if delay_ms > 0 {
+                            tokio::select! {
+                                _ = tokio::time::sleep(Duration::from_millis(delay_ms)) => {}
+                                _ = request_context.ct.cancelled() => {}
+                            }
+                        }
+                    }
+                }
+
+                tokio::select! {
+                    _ = tokio::time::sleep(delay) => {}
+                    _ = request_context.ct.cancelled() => {
+                        warn!(
+                            "Request cancelled while waiting to retry step {}, not retrying",
+                            current_index
+                        );
+                        break;
+                    }
+                }
+            }
+
+            // A group's cleanup blocks run once, after its last attempt
+            if let SequenceItem::Group { tool_group } = &sequence_items[current_index] {
+                let group_failure = step_error_occurred.then(|| failure_details(&final_result));
+                if let (Some(steps), Some(failure)) =
+                    (&tool_group.on_failure, group_failure.as_ref())
+                {
+                    let cleanup_results = self
+                        .run_cleanup_steps(
+                            &peer,
+                            &request_context,
+                            &format!("group '{}' on_failure", tool_group.group_name),
+                            steps,
+                            &mut execution_context_map,
+                            Some(failure),
+                            include_detailed,
+                            workflow_deadline,
+                            cleanup_step_timeout,
+                        )
+                        .await;
+                    final_result["on_failure_results"] = json!(cleanup_results);
+                }
+                if let Some(steps) = &tool_group.finally {
+                    let cleanup_results = self
+                        .run_cleanup_steps(
+                            &peer,
+                            &request_context,
+                            &format!("group '{}' finally", tool_group.group_name),
+                            steps,
+                            &mut execution_context_map,
+                            group_failure.as_ref(),
+                            include_detailed,
+                            workflow_deadline,
+                            cleanup_step_timeout,
+                        )
+                        .await;
+                    final_result["finally_results"] = json!(cleanup_results);
                 }
             }
 
+            if step_error_occurred {
+                last_failure = Some(failure_details(&final_result));
+            }
             results.push(final_result);
 
             // Decide next index based on success or fallback
             let step_succeeded = !step_error_occurred;
             let step_status_str = if step_succeeded { "success" } else { "failed" };
+            progress
+                .step_finished(
+                    &progress_step,
+                    step_status_str,
+                    step_started_at.elapsed(),
+                    attempts_made,
+                )
+                .await;
             if let Some(tool_name) = original_step.and_then(|s| s.tool_name.as_ref()) {
                 info!(
                     "Step {} END tool='{}' id='{}' status={}",
@@ -1711,6 +2118,65 @@ impl DesktopWrapper {
             warn!("Maximum iteration count reached. Possible infinite fallback loop detected.");
         }
 
+        // Workflow-level cleanup blocks. Skipped for partial runs so step-by-step
+        // debugging doesn't tear down the application state being inspected.
+        let mut on_failure_results = None;
+        let mut finally_results = None;
+        if args.end_at_step.is_some() {
+            if args.on_failure.is_some() || args.finally.is_some() {
+                info!("Skipping on_failure/finally for partial workflow execution (end_at_step specified)");
+            }
+        } else {
+            if sequence_had_errors {
+                if let Some(steps) = &args.on_failure {
+                    on_failure_results = Some(
+                        self.run_cleanup_steps(
+                            &peer,
+                            &request_context,
+                            "workflow on_failure",
+                            steps,
+                            &mut execution_context_map,
+                            last_failure.as_ref(),
+                            include_detailed,
+                            workflow_deadline,
+                            cleanup_step_timeout,
+                        )
+                        .await,
+                    );
+                }
+            }
+            if let Some(steps) = &args.finally {
+                finally_results = Some(
+                    self.run_cleanup_steps(
+                        &peer,
+                        &request_context,
+                        "workflow finally",
+                        steps,
+                        &mut execution_context_map,
+                        last_failure.as_ref().filter(|_| sequence_had_errors),
+                        include_detailed,
+                        workflow_deadline,
+                        cleanup_step_timeout,
+                    )
+                    .await,
+                );
+            }
+        }
+
+        if cancelled {
+            let mut data = json!({"code": -32001, "reason": "user_cancelled"});
+            if let Some(cleanup_results) = on_failure_results {
+                data["on_failure_results"] = json!(cleanup_results);
+            }
+            if let Some(cleanup_results) = finally_results {
+                data["finally_results"] = json!(cleanup_results);
+            }
+            return Err(McpError::internal_error(
+                "Request cancelled by user",
+                Some(data),
+            ));
+        }
+
         let total_duration = (chrono::Utc::now() - start_time).num_milliseconds();
 
         // Determine final status - simple success or failure
@@ -1734,9 +2200,18 @@ impl DesktopWrapper {
             "total_duration_ms": total_duration,
             "timestamp": chrono::Utc::now().to_rfc3339(),
             "used_fallback": used_fallback,
+            "timed_out": workflow_timed_out,
             "results": results,
             "env": execution_context_map.get("env").cloned().unwrap_or_else(|| json!({})),
         });
+        if let Some(obj) = summary.as_object_mut() {
+            if let Some(cleanup_results) = on_failure_results {
+                obj.insert("on_failure_results".to_string(), json!(cleanup_results));
+            }
+            if let Some(cleanup_results) = finally_results {
+                obj.insert("finally_results".to_string(), json!(cleanup_results));
+            }
+        }
 
         // Support both 'output_parser' (legacy) and 'output' (simplified)
         let parser_def = args.output_parser.as_ref().or(args.output.as_ref());
@@ -1760,7 +2235,21 @@ impl DesktopWrapper {
                     Self::create_flattened_execution_context(&execution_context_map);
                 substitute_variables(&mut parser_json, &execution_context);
 
-                match output_parser::run_output_parser(&parser_json, &summary).await {
+                // A JavaScript parser runs in Node.js just like `run_command` does
+                let parsed = match output_parser::script_arguments(&parser_json) {
+                    Some(script) => match self.enforce_policy("run_command", &script) {
+                        Ok(()) => output_parser::run_output_parser(&parser_json, &summary).await,
+                        Err(e) => Err(anyhow::anyhow!(
+                            "Output parser denied by policy: {}",
+                            e.data
+                                .as_ref()
+                                .and_then(|data| data["reason"].as_str())
+                                .unwrap_or(&e.message)
+                        )),
+                    },
+                    None => output_parser::run_output_parser(&parser_json, &summary).await,
+                };
+                match parsed {
                     Ok(Some(parsed_data)) => {
                         // Check if the parsed data is wrapped in a 'result' field and unwrap it
                         // This handles the case where JavaScript execution via scripting_engine returns
@@ -1803,6 +2292,7 @@ impl DesktopWrapper {
                 "had_critical_error": critical_error_occurred,
                 "had_errors": sequence_had_errors,
                 "used_fallback": used_fallback,
+                "workflow_timed_out": workflow_timed_out,
                 "executed_count": results.len(),
             });
 
@@ -1835,6 +2325,227 @@ impl DesktopWrapper {
         Ok(CallToolResult::success(contents))
     }
 
+    /// Run an `on_failure` or `finally` block. Every step runs regardless of earlier
+    /// failures, and the failing step (if any) is exposed to the block as
+    /// `env.failed_step_id` / `env.failed_step_error`.
+    ///
+    /// The block runs under its own cancellation token, so it also runs for a cancelled
+    /// request. Steps without a `timeout` get `default_step_timeout`, and once the
+    /// workflow deadline has passed or the request is cancelled, the block only has
+    /// `CLEANUP_GRACE` left; steps that would start after that are skipped.
+    #[allow(clippy::too_many_arguments)]
+    async fn run_cleanup_steps(
+        &self,
+        peer: &Peer<RoleServer>,
+        request_context: &RequestContext<RoleServer>,
+        block: &str,
+        steps: &[ToolCall],
+        execution_context_map: &mut serde_json::Map<String, Value>,
+        failure: Option<&(Value, Value)>,
+        include_detailed: bool,
+        workflow_deadline: Option<Instant>,
+        default_step_timeout: Duration,
+    ) -> Vec<Value> {
+        info!("Running {} ({} steps)", block, steps.len());
+        if let Some((failed_step_id, failed_step_error)) = failure {
+            if let Some(env_map) = execution_context_map
+                .get_mut("env")
+                .and_then(|env| env.as_object_mut())
+            {
+                env_map.insert("failed_step_id".to_string(), failed_step_id.clone());
+                env_map.insert("failed_step_error".to_string(), failed_step_error.clone());
+            }
+        }
+
+        let mut cleanup_context = request_context.clone();
+        cleanup_context.ct = tokio_util::sync::CancellationToken::new();
+        let block_deadline = (workflow_deadline.is_some() || request_context.ct.is_cancelled())
+            .then(|| {
+                let grace = Instant::now() + CLEANUP_GRACE;
+                workflow_deadline.map_or(grace, |deadline| deadline.max(grace))
+            });
+
+        let mut cleanup_results = Vec::with_capacity(steps.len());
+        for (index, step) in steps.iter().enumerate() {
+            if block_deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                warn!(
+                    "{} ran out of time, skipping step '{}'",
+                    block, step.tool_name
+                );
+                let mut skipped = json!({
+                    "tool_name": step.tool_name,
+                    "index": index,
+                    "status": "skipped",
+                    "error": format!("{block} ran out of time"),
+                    "timed_out": true,
+                });
+                if let Some(id) = &step.id {
+                    skipped["step_id"] = json!(id);
+                }
+                cleanup_results.push(skipped);
+                continue;
+            }
+            let execution_context = Self::create_flattened_execution_context(execution_context_map);
+            let mut substituted_args = step.arguments.clone();
+            substitute_variables(&mut substituted_args, &execution_context);
+
+            // Scripts read the failure from their env like any other workflow value
+            if let (Some((failed_step_id, failed_step_error)), true) = (
+                failure,
+                matches!(
+                    step.tool_name.as_str(),
+                    "run_command" | "execute_browser_script"
+                ),
+            ) {
+                if let Some(args_obj) = substituted_args.as_object_mut() {
+                    let env = args_obj.entry("env").or_insert_with(|| json!({}));
+                    if let Some(env_obj) = env.as_object_mut() {
+                        env_obj.insert("failed_step_id".to_string(), failed_step_id.clone());
+                        env_obj.insert("failed_step_error".to_string(), failed_step_error.clone());
+                    }
+                }
+            }
+
+            let (result, _) = self
+                .execute_single_tool_with_timeout(
+                    peer.clone(),
+                    cleanup_context.clone(),
+                    &step.tool_name,
+                    &substituted_args,
+                    step.continue_on_error.unwrap_or(false),
+                    index,
+                    include_detailed,
+                    step.id.as_deref(),
+                    effective_timeout(
+                        parse_timeout(step.timeout.as_deref(), "step")
+                            .unwrap_or(None)
+                            .or(Some(default_step_timeout)),
+                        block_deadline,
+                    ),
+                )
+                .await;
+            if result["status"] != "success" {
+                warn!(
+                    "{} step '{}' failed: {}",
+                    block,
+                    step.tool_name,
+                    result["error"].as_str().unwrap_or("unknown error")
+                );
+            }
+            cleanup_results.push(result);
+
+            if let Some(delay_ms) = step.delay_ms {
+                if delay_ms > 0 {
+                    tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+                }
+            }
+        }
+        cleanup_results
+    }
+
+    /// Like [`Self::execute_single_tool`], but bounded by `timeout`.
+    ///
+    /// The tool runs under its own child cancellation token that is cancelled when the
+    /// timeout fires, so script engines kill their child processes. Locator `timeout_ms`
+    /// arguments are clamped to the budget so blocking element searches stop polling too.
+    /// An expired timeout is reported as a regular tool failure.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn execute_single_tool_with_timeout(
+        &self,
+        peer: Peer<RoleServer>,
+        request_context: RequestContext<RoleServer>,
+        tool_name: &str,
+        arguments: &Value,
+        is_skippable: bool,
+        index: usize,
+        include_detailed: bool,
+        step_id: Option<&str>,
+        timeout: Option<Duration>,
+    ) -> (serde_json::Value, bool) {
+        let Some(timeout) = timeout else {
+            return self
+                .execute_single_tool(
+                    peer,
+                    request_context,
+                    tool_name,
+                    arguments,
+                    is_skippable,
+                    index,
+                    include_detailed,
+                    step_id,
+                )
+                .await;
+        };
+
+        let budget_ms = timeout.as_millis() as u64;
+        let mut bounded_args = arguments.clone();
+        // A tool left running after the step timed out must not act later, e.g. click once
+        // its locator finds the element, so its own timeout can't outlast the step's, even
+        // when the step doesn't set one.
+        let tool_name_short = tool_name
+            .strip_prefix("mcp_terminator-mcp-agent_")
+            .unwrap_or(tool_name);
+        if Self::accepts_timeout_ms(tool_name_short) {
+            if let Some(args) = bounded_args.as_object_mut() {
+                let timeout_ms = args.entry("timeout_ms").or_insert(Value::Null);
+                match timeout_ms.as_u64() {
+                    Some(ms) if ms <= budget_ms => {}
+                    _ => *timeout_ms = json!(budget_ms),
+                }
+            }
+        }
+
+        let mut step_context = request_context.clone();
+        step_context.ct = request_context.ct.child_token();
+        let step_token = step_context.ct.clone();
+
+        let tool_future = self.execute_single_tool(
+            peer,
+            step_context,
+            tool_name,
+            &bounded_args,
+            is_skippable,
+            index,
+            include_detailed,
+            step_id,
+        );
+        tokio::pin!(tool_future);
+
+        tokio::select! {
+            outcome = &mut tool_future => outcome,
+            _ = tokio::time::sleep(timeout) => {
+                warn!(
+                    "Tool '{}' at index {} timed out after {}ms, cancelling",
+                    tool_name, index, budget_ms
+                );
+                step_token.cancel();
+                // Let cancellation-aware tools tear down before the step is abandoned
+                let _ = tokio::time::timeout(STEP_TIMEOUT_GRACE, &mut tool_future).await;
+
+                let mut timeout_result = json!({
+                    "tool_name": tool_name,
+                    "index": index,
+                    "status": if is_skippable { "skipped" } else { "error" },
+                    "duration_ms": budget_ms,
+                    "error": format!("Step timed out after {budget_ms}ms"),
+                    "timed_out": true,
+                });
+                if let Some(id) = step_id {
+                    timeout_result["step_id"] = json!(id);
+                }
+                if include_detailed {
+                    if let Some(ref log_capture) = self.log_capture {
+                        let captured_logs = log_capture.stop_capture();
+                        if !captured_logs.is_empty() {
+                            timeout_result["server_logs"] = json!(captured_logs);
+                        }
+                    }
+                }
+                (timeout_result, !is_skippable)
+            }
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub async fn execute_single_tool(
         &self,
@@ -1859,10 +2570,36 @@ impl DesktopWrapper {
             }
         }
 
+        // Fixture capture for this request, or replay for the whole server (see `workflow_replay`)
+        let fixture_session = request_context
+            .extensions
+            .get::<crate::workflow_replay::FixtureCapture>()
+            .map(|capture| capture.0.clone())
+            .or_else(|| self.fixture_replay.clone());
+        let fixture_call = match &fixture_session {
+            Some(session) => Some(
+                session
+                    .begin(&self.desktop, index, step_id, tool_name_short, arguments)
+                    .await,
+            ),
+            None => None,
+        };
+        let recorded_result = fixture_session
+            .as_ref()
+            .zip(fixture_call.as_ref())
+            .and_then(|(session, call)| session.recorded_result(call));
+
         // The substitution is handled in `execute_sequence_impl`.
-        let tool_result = self
-            .dispatch_tool(peer, request_context, tool_name_short, arguments)
-            .await;
+        let tool_result = match recorded_result {
+            Some(result) => result,
+            None => {
+                self.dispatch_tool(peer, request_context, tool_name_short, arguments)
+                    .await
+            }
+        };
+        if let (Some(session), Some(call)) = (&fixture_session, fixture_call) {
+            session.finish(call, &tool_result).await;
+        }
 
         let (processed_result, error_occurred) = match tool_result {
             Ok(result) => {
@@ -1,5 +1,5 @@
 // Local helper replicated from server.rs to avoid cross-module privacy issues
-fn scan_yaml_files(folder_path: &str) -> Result<Vec<serde_json::Value>, McpError> {
+pub(crate) fn scan_yaml_files(folder_path: &str) -> Result<Vec<serde_json::Value>, McpError> {
     let mut files = Vec::new();
 
     let dir = std::fs::read_dir(folder_path).map_err(|e| {
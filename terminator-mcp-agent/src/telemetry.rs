@@ -47,6 +47,9 @@ mod with_telemetry {
                 .with_kind(SpanKind::Server)
                 .start(&tracer);
             span.set_attribute(KeyValue::new("workflow.name", name.to_string()));
+            if let Some(identity) = crate::auth::current_identity() {
+                span.set_attribute(KeyValue::new("auth.identity", identity));
+            }
             WorkflowSpan { span }
         }
 
@@ -103,6 +106,9 @@ mod with_telemetry {
             if let Some(id) = step_id {
                 span.set_attribute(KeyValue::new("step.id", id.to_string()));
             }
+            if let Some(identity) = crate::auth::current_identity() {
+                span.set_attribute(KeyValue::new("auth.identity", identity));
+            }
 
             StepSpan {
                 span,
@@ -124,16 +130,19 @@ mod with_telemetry {
             self.span.add_event(name.to_string(), kvs);
         }
 
-        pub fn record_retry(&mut self, attempt: u32, reason: &str) {
+        pub fn record_retry(&mut self, attempt: u32, reason: &str, delay_ms: u64) {
             self.span
                 .set_attribute(KeyValue::new("retry.attempt", attempt as i64));
             self.span
                 .set_attribute(KeyValue::new("retry.reason", reason.to_string()));
+            self.span
+                .set_attribute(KeyValue::new("retry.delay_ms", delay_ms as i64));
             self.add_event(
                 "retry",
                 vec![
                     ("attempt", attempt.to_string()),
                     ("reason", reason.to_string()),
+                    ("delay_ms", delay_ms.to_string()),
                 ],
             );
         }
@@ -444,7 +453,7 @@ mod without_telemetry {
 
         pub fn set_attribute(&mut self, _key: &str, _value: String) {}
         pub fn add_event(&mut self, _name: &str, _attributes: Vec<(&str, String)>) {}
-        pub fn record_retry(&mut self, _attempt: u32, _reason: &str) {}
+        pub fn record_retry(&mut self, _attempt: u32, _reason: &str, _delay_ms: u64) {}
         pub fn set_status(&mut self, _success: bool, _error: Option<&str>) {}
         pub fn end(self) {}
     }
@@ -75,6 +75,20 @@ pub struct DesktopWrapper {
     pub current_workflow_dir: Arc<Mutex<Option<std::path::PathBuf>>>,
     #[serde(skip)]
     pub current_scripts_base_path: Arc<Mutex<Option<String>>>,
+    #[serde(skip)]
+    pub debug_sessions: Arc<Mutex<HashMap<String, Arc<crate::server_debugger::DebugSession>>>>,
+    #[serde(skip)]
+    pub policy: Arc<crate::policy::ToolPolicy>,
+    #[serde(skip)]
+    pub resource_subscriptions: Arc<Mutex<crate::server_resources::ResourceSubscriptions>>,
+    #[serde(skip)]
+    pub audit: Option<Arc<crate::audit::AuditLog>>,
+    #[serde(skip)]
+    pub fixture_replay: Option<Arc<crate::workflow_replay::FixtureSession>>,
+    #[serde(skip)]
+    pub recording_stream: Arc<Mutex<Option<crate::recording_stream::RecordingStreamServer>>>,
+    #[serde(skip)]
+    pub workflows_root: Option<std::path::PathBuf>,
 }
 
 impl Default for DesktopWrapper {
@@ -332,6 +346,23 @@ pub struct ClickElementArgs {
     pub include_monitor_screenshots: Option<bool>,
 }
 
+/// Accepts numbers and booleans as text: a value that is only `{{variable}}` is replaced with
+/// the variable's JSON value, which is not a string for `number` and `boolean` variables.
+fn string_or_scalar<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::String(s) => Ok(s),
+        value @ (serde_json::Value::Number(_) | serde_json::Value::Bool(_)) => {
+            Ok(value.to_string())
+        }
+        other => Err(serde::de::Error::custom(format!(
+            "expected a string, got {other}"
+        ))),
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct TypeIntoElementArgs {
     #[schemars(
@@ -347,6 +378,7 @@ pub struct TypeIntoElementArgs {
     )]
     pub fallback_selectors: Option<String>,
     #[schemars(description = "The text to type into the element")]
+    #[serde(deserialize_with = "string_or_scalar")]
     pub text_to_type: String,
     #[schemars(description = "Optional timeout in milliseconds for the action (default: 3000ms)")]
     pub timeout_ms: Option<u64>,
@@ -595,6 +627,10 @@ pub struct WaitForElementArgs {
     pub fallback_selectors: Option<String>,
     #[schemars(description = "Condition to wait for: 'visible', 'enabled', 'focused', 'exists'")]
     pub condition: String,
+    #[schemars(
+        description = "Also wait until the element's value (its name, for elements without a value) equals this text. Fails with the last seen value on timeout."
+    )]
+    pub expected_value: Option<String>,
     #[schemars(description = "Optional timeout in milliseconds")]
     pub timeout_ms: Option<u64>,
     #[schemars(description = "Whether to include full UI tree in the response (verbose mode)")]
@@ -992,6 +1028,10 @@ pub struct ToolCall {
         description = "Optional unique identifier for this step. If provided, the tool's result will be stored as {step_id}_result and its status as {step_id}_status in the environment for use in subsequent steps."
     )]
     pub id: Option<String>,
+    #[schemars(
+        description = "Optional maximum duration for this tool call (e.g. '30s', '500ms'). The call is cancelled and treated as failed when it expires."
+    )]
+    pub timeout: Option<String>,
 }
 
 // Simplified structure for Gemini compatibility
@@ -1008,6 +1048,56 @@ pub struct JumpCondition {
     pub reason: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum BackoffStrategy {
+    /// Wait `initial_delay` between every attempt.
+    #[default]
+    Fixed,
+    /// Double the delay after every attempt, capped at `max_delay`.
+    Exponential,
+    /// Exponential backoff with a random delay between zero and the computed value.
+    Jitter,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum RetryErrorClass {
+    ElementNotFound,
+    Timeout,
+    NotEnabled,
+    Obscured,
+    ScriptError,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default)]
+pub struct RetryPolicy {
+    #[schemars(
+        description = "Total number of attempts including the first one (default: 3). Takes precedence over 'retries'."
+    )]
+    pub max_attempts: Option<u32>,
+    #[schemars(
+        description = "How the delay grows between attempts: 'fixed', 'exponential' or 'jitter' (default: 'fixed')."
+    )]
+    pub backoff: Option<BackoffStrategy>,
+    #[schemars(
+        description = "Delay before the first retry, e.g. '500ms', '1s' (default: '500ms')."
+    )]
+    pub initial_delay: Option<String>,
+    #[schemars(
+        description = "Upper bound for the delay between attempts, e.g. '10s' (default: '30s')."
+    )]
+    pub max_delay: Option<String>,
+    #[schemars(
+        description = "Only retry failures of these classes: element_not_found, timeout, not_enabled, obscured, script_error. Retries every failure when omitted."
+    )]
+    pub on: Option<Vec<RetryErrorClass>>,
+    #[schemars(
+        description = "Steps executed before each retry to recover the UI (e.g. press Escape, re-activate the window). Their failures are logged and ignored."
+    )]
+    pub before_retry: Option<Vec<ToolCall>>,
+}
+
 #[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default)]
 pub struct SequenceStep {
     #[schemars(description = "The name of the tool to execute (for single tool steps)")]
@@ -1031,6 +1121,10 @@ pub struct SequenceStep {
     pub r#if: Option<String>,
     #[schemars(description = "Number of times to retry this step or group on failure.")]
     pub retries: Option<u32>,
+    #[schemars(
+        description = "Optional retry policy for this step or group with backoff, error-class filtering and recovery steps. Overrides 'retries' when present."
+    )]
+    pub retry: Option<RetryPolicy>,
     #[schemars(
         description = "Optional unique identifier for this step (string). If provided, it can be a target for other steps' fallback_id. Additionally, the tool's result will be stored as {step_id}_result and its status as {step_id}_status in the environment, making it accessible to subsequent steps."
     )]
@@ -1050,6 +1144,20 @@ pub struct SequenceStep {
         description = "Simplified alias for 'delay_ms'. Supports human-readable durations like '1s', '500ms', '2m'. Defaults to milliseconds if no unit specified."
     )]
     pub delay: Option<String>,
+
+    #[schemars(
+        description = "Optional maximum duration for this step or group (e.g. '30s', '2m'). When it expires the step is cancelled, including any script child process, and counted as a failure that retries and fallback_id can handle."
+    )]
+    pub timeout: Option<String>,
+
+    #[schemars(
+        description = "Cleanup steps run when a step of the group fails (for grouped steps). The failing step is available as env.failed_step_id and env.failed_step_error."
+    )]
+    pub on_failure: Option<Vec<ToolCall>>,
+    #[schemars(
+        description = "Steps that always run after the group completes or fails, after on_failure (for grouped steps)."
+    )]
+    pub finally: Option<Vec<ToolCall>>,
 }
 
 #[derive(Debug, serde::Deserialize, serde::Serialize, Clone, Default, JsonSchema)]
@@ -1093,7 +1201,7 @@ pub struct ExecuteSequenceArgs {
 
     // Simplified aliases for common parameters (keeping originals for backward compatibility)
     #[schemars(
-        description = "Simplified alias for 'output_parser'. Processes the final tool output and extracts structured data. Supports JavaScript code or file path."
+        description = "Simplified alias for 'output_parser'. Processes the final tool output and extracts structured data. Supports JavaScript code, a file path, or a 'declarative' parser that runs without Node.js."
     )]
     pub output: Option<serde_json::Value>,
 
@@ -1122,6 +1230,70 @@ pub struct ExecuteSequenceArgs {
         description = "Optional base path for resolving script files. When script_file is used in run_command or execute_browser_script, relative paths will first be searched in this directory, then fallback to workflow directory or current directory. Useful for mounting external file sources like S3 via rclone."
     )]
     pub scripts_base_path: Option<String>,
+    #[schemars(
+        description = "Optional maximum duration for the whole workflow (e.g. '10m'). When it expires the running step is cancelled and the sequence stops as failed."
+    )]
+    pub timeout: Option<String>,
+    #[schemars(
+        description = "Cleanup steps run after the workflow fails (e.g. close the app, discard a half-filled form). The failing step is available as env.failed_step_id and env.failed_step_error."
+    )]
+    pub on_failure: Option<Vec<ToolCall>>,
+    #[schemars(
+        description = "Steps that always run after the workflow completes or fails, after on_failure. Failures here are reported but never stop the remaining cleanup."
+    )]
+    pub finally: Option<Vec<ToolCall>>,
+    #[schemars(
+        description = "Timeout of on_failure and finally steps that don't set their own (e.g. '10s'). Defaults to 30s."
+    )]
+    pub cleanup_timeout: Option<String>,
+    #[schemars(
+        description = "Directory to record a UI-tree fixture of the focused window before every step, plus each step's result, for replaying the workflow offline with 'terminator workflow test'."
+    )]
+    pub capture_fixtures: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DebugStartArgs {
+    #[serde(flatten)]
+    pub workflow: ExecuteSequenceArgs,
+    #[schemars(description = "Step ids to suspend at before they execute.")]
+    pub breakpoints: Option<Vec<String>>,
+    #[schemars(
+        description = "Expressions evaluated against the env before every step (same syntax as a step's 'if'). Execution suspends when one is true."
+    )]
+    pub breakpoint_conditions: Option<Vec<String>>,
+    #[schemars(description = "Suspend before the first step (default: true).")]
+    pub stop_on_entry: Option<bool>,
+    #[schemars(
+        description = "How long a paused session waits for a debug command before it stops itself and runs the workflow's cleanup blocks (e.g. '10m', default '30m')."
+    )]
+    pub idle_timeout: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DebugSessionArgs {
+    #[schemars(description = "Id of the debug session returned by debug_start.")]
+    pub session_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DebugEvalArgs {
+    #[schemars(description = "Id of the debug session returned by debug_start.")]
+    pub session_id: String,
+    #[schemars(
+        description = "A variable path (e.g. 'env.invoice_id', 'login_status') or a condition (e.g. \"login_status == 'success'\") evaluated against the paused workflow's env."
+    )]
+    pub expression: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DebugSetVarArgs {
+    #[schemars(description = "Id of the debug session returned by debug_start.")]
+    pub session_id: String,
+    #[schemars(description = "Name of the env variable to set.")]
+    pub name: String,
+    #[schemars(description = "New value for the variable (any JSON value).")]
+    pub value: serde_json::Value,
 }
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
@@ -1159,6 +1331,9 @@ pub struct ToolGroup {
     pub group_name: String,
     pub steps: Vec<ToolCall>,
     pub skippable: Option<bool>,
+    pub timeout: Option<String>,
+    pub on_failure: Option<Vec<ToolCall>>,
+    pub finally: Option<Vec<ToolCall>>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -1982,11 +2157,11 @@ impl Default for HighlightConfig {
 
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct RecordWorkflowArgs {
-    /// The action to perform: 'start' to begin recording, 'stop' to end and save.
+    /// The action to perform: 'start' to begin recording, 'stop' to end and save, 'pause'/'resume' to skip an interruption, 'marker' to start a named group of steps, 'undo' to drop the last recorded actions.
     pub action: String,
     /// A descriptive name for the workflow being recorded. Required when starting.
     pub workflow_name: Option<String>,
-    /// Optional file path to save the workflow. If not provided, a default path will be used.
+    /// Optional file path to save the workflow. If not provided, a default path will be used. A path ending in '.gz' (e.g. 'long.jsonl.gz') saves a compressed recording.
     pub file_path: Option<String>,
     /// Sets the recording to a low-energy mode to reduce system load, which can help prevent lag on less powerful machines.
     pub low_energy_mode: Option<bool>,
@@ -1998,4 +2173,38 @@ pub struct RecordWorkflowArgs {
         description = "Whether to include screenshots of all monitors in the response. Defaults to false."
     )]
     pub include_monitor_screenshots: Option<bool>,
+    #[schemars(
+        description = "Format of the workflow returned when stopping: 'mcp' (default) returns the execute_sequence steps only; 'rust', 'python' or 'typescript' also return an equivalent script using the terminator Rust crate, terminator.py or terminator.js."
+    )]
+    pub output_format: Option<String>,
+    #[schemars(
+        description = "When stopping, turn typed and pasted values and dropdown choices into workflow variables: the returned steps use {{inputs.<name>}} and the recorded values become the defaults. Defaults to false."
+    )]
+    pub parameterize: Option<bool>,
+    #[schemars(
+        description = "Mask emails, IBANs, credit card and phone numbers in recorded text as stable tokens such as [EMAIL_1]. Password fields are always masked. Defaults to false."
+    )]
+    pub mask_pii: Option<bool>,
+    #[schemars(
+        description = "Capture a downscaled screenshot and an element crop for each click, text input and application switch. They are saved next to the workflow file, attached to the returned steps as 'annotations', and rendered into an HTML storyboard ('storyboard_path'). Defaults to false."
+    )]
+    pub capture_screenshots: Option<bool>,
+    #[schemars(
+        description = "Label of the checkpoint added by action 'marker'. The steps recorded after it become a group of that name in the returned workflow."
+    )]
+    pub marker_label: Option<String>,
+    #[schemars(
+        description = "Number of recorded actions removed by action 'undo'. Defaults to 1."
+    )]
+    pub undo_count: Option<usize>,
+    #[schemars(
+        description = "When stopping, add wait_for_element checks of the recorded UI state after the steps: 'field_values' (a field holds the entered value), 'window_titles' (the window the user continued in is shown), 'dialogs' (a dialog that appeared is visible), or 'all'. Defaults to none."
+    )]
+    pub assertions: Option<Vec<String>>,
+    #[schemars(
+        description = "When starting, stream the recorded events live as Server-Sent Events from a local endpoint returned as 'stream_url'. Each 'workflow_event' message has the event and the MCP steps it converts to; filter with the query parameters 'types' (comma-separated event types), 'raw' (include keyboard and mouse events) and 'steps'. Clients that fall behind skip events and get a 'lagged' message. Defaults to false."
+    )]
+    pub stream_events: Option<bool>,
+    #[schemars(description = "Local port of the event stream. Defaults to a free port.")]
+    pub stream_port: Option<u16>,
 }
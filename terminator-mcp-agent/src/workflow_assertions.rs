@@ -0,0 +1,361 @@
+//! Checks derived from the recorded UI state, added to converted workflows as
+//! `wait_for_element` steps so that a replay fails where it diverges from the recording.
+
+use crate::workflow_events::{McpToolStep, RecordedEvent, WindowContext, WorkflowEvent};
+use crate::workflow_parameterize::is_mask_token;
+use serde_json::json;
+
+/// Which checks to add after the steps converted from each recorded event
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssertionConfig {
+    /// Check the value of a field after text was entered or an option was selected
+    pub field_values: bool,
+    /// Check the window title after a navigation, application switch or a click that
+    /// brought up another window
+    pub window_titles: bool,
+    /// Check that the dialog that appeared after an action is shown
+    pub dialogs: bool,
+    /// Timeout of the generated `wait_for_element` steps
+    pub timeout_ms: u64,
+}
+
+impl Default for AssertionConfig {
+    fn default() -> Self {
+        Self {
+            field_values: false,
+            window_titles: false,
+            dialogs: false,
+            timeout_ms: 5000,
+        }
+    }
+}
+
+impl AssertionConfig {
+    /// All kinds of checks enabled
+    pub fn all() -> Self {
+        Self {
+            field_values: true,
+            window_titles: true,
+            dialogs: true,
+            ..Self::default()
+        }
+    }
+
+    /// Parse a list of kinds: `field_values`, `window_titles`, `dialogs` or `all`.
+    pub fn from_kinds<S: AsRef<str>>(kinds: &[S]) -> Result<Self, String> {
+        let mut config = Self::default();
+        for kind in kinds {
+            match kind.as_ref().trim().to_lowercase().as_str() {
+                "field_values" => config.field_values = true,
+                "window_titles" => config.window_titles = true,
+                "dialogs" => config.dialogs = true,
+                "all" => config = Self::all(),
+                other => return Err(format!("unknown assertion kind '{other}'")),
+            }
+        }
+        Ok(config)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.field_values || self.window_titles || self.dialogs
+    }
+}
+
+/// `wait_for_element` steps checking the state recorded after `events[index]`, whose
+/// converted steps are `converted`.
+pub fn assertion_steps(
+    config: &AssertionConfig,
+    events: &[RecordedEvent],
+    index: usize,
+    converted: &[McpToolStep],
+) -> Vec<McpToolStep> {
+    let mut steps = Vec::new();
+    if !config.is_enabled() || converted.is_empty() {
+        return steps;
+    }
+    if config.field_values {
+        steps.extend(field_value_check(config, &events[index].event, converted));
+    }
+    match window_check(events, index) {
+        Some(WindowCheck::Title(title)) if config.window_titles => {
+            steps.push(check_step(
+                config,
+                format!("role:Window|name:contains:{title}"),
+                "exists",
+                None,
+                format!("Check that window '{title}' is shown"),
+            ));
+        }
+        Some(WindowCheck::Window(window)) if config.window_titles => {
+            let role = if window.role.is_empty() {
+                "Window"
+            } else {
+                window.role.as_str()
+            };
+            steps.push(check_step(
+                config,
+                format!("role:{role}|name:{}", window.title),
+                "exists",
+                None,
+                format!("Check that window '{}' is shown", window.title),
+            ));
+        }
+        Some(WindowCheck::Dialog(window)) if config.dialogs => {
+            steps.push(check_step(
+                config,
+                format!("role:{}|name:{}", window.role, window.title),
+                "visible",
+                None,
+                format!("Check that dialog '{}' appeared", window.title),
+            ));
+        }
+        _ => {}
+    }
+    steps
+}
+
+/// Checks the value left in the field by the last text entry or selection step.
+fn field_value_check(
+    config: &AssertionConfig,
+    event: &WorkflowEvent,
+    converted: &[McpToolStep],
+) -> Option<McpToolStep> {
+    let WorkflowEvent::TextInputCompleted(input) = event else {
+        return None;
+    };
+    let (step, value) = converted.iter().rev().find_map(|step| {
+        let value = match step.tool_name.as_str() {
+            "type_into_element" => step.arguments["text_to_type"].as_str(),
+            "select_option" => step.arguments["option_name"].as_str(),
+            _ => None,
+        }?;
+        Some((step, value))
+    })?;
+    // A masked value is not what the field holds when the workflow runs.
+    if value.is_empty() || is_mask_token(value) {
+        return None;
+    }
+    let selector = step.arguments["selector"].as_str()?;
+    let field = input.field_name.as_deref().unwrap_or(&input.field_type);
+    Some(check_step(
+        config,
+        selector.to_string(),
+        "exists",
+        Some(value),
+        format!("Check that '{field}' is '{value}'"),
+    ))
+}
+
+enum WindowCheck<'a> {
+    /// A browser tab navigated to a page with this title
+    Title(&'a str),
+    Window(&'a WindowContext),
+    Dialog(&'a WindowContext),
+}
+
+/// The window or dialog the user continued in after `events[index]`, when the action
+/// brought it up.
+fn window_check(events: &[RecordedEvent], index: usize) -> Option<WindowCheck<'_>> {
+    let event = &events[index].event;
+    let before = match event {
+        WorkflowEvent::BrowserTabNavigation(navigation) => {
+            return navigation
+                .to_title
+                .as_deref()
+                .filter(|title| usable_title(title))
+                .map(WindowCheck::Title);
+        }
+        // The switch itself is recorded in the window it switched to.
+        WorkflowEvent::ApplicationSwitch(_) => action_window(events[..index].iter().rev()),
+        WorkflowEvent::Click(_) | WorkflowEvent::BrowserClick(_) | WorkflowEvent::Hotkey(_) => {
+            event.metadata().window.as_ref()
+        }
+        _ => return None,
+    };
+    let after = action_window(events[index + 1..].iter()).or(match event {
+        WorkflowEvent::ApplicationSwitch(_) => event.metadata().window.as_ref(),
+        _ => None,
+    })?;
+    if !usable_title(&after.title) || before.is_some_and(|before| before.title == after.title) {
+        return None;
+    }
+    if after.role.to_lowercase().contains("dialog") {
+        Some(WindowCheck::Dialog(after))
+    } else {
+        Some(WindowCheck::Window(after))
+    }
+}
+
+/// Whether a window title can be checked with a `role:...|name:...` selector. Selectors
+/// have no escaping, so a title containing `|` or `>>` would be split into other parts.
+fn usable_title(title: &str) -> bool {
+    !title.is_empty() && !is_mask_token(title) && !title.contains('|') && !title.contains(">>")
+}
+
+/// Window of the first user action among `events`
+fn action_window<'a>(
+    mut events: impl Iterator<Item = &'a RecordedEvent>,
+) -> Option<&'a WindowContext> {
+    events
+        .find(|e| !e.event.is_raw_input())
+        .and_then(|e| e.event.metadata().window.as_ref())
+}
+
+fn check_step(
+    config: &AssertionConfig,
+    selector: String,
+    condition: &str,
+    expected_value: Option<&str>,
+    description: String,
+) -> McpToolStep {
+    let mut arguments = json!({
+        "selector": selector,
+        "condition": condition,
+        "timeout_ms": config.timeout_ms,
+    });
+    if let Some(value) = expected_value {
+        arguments["expected_value"] = json!(value);
+    }
+    McpToolStep {
+        tool_name: "wait_for_element".to_string(),
+        arguments,
+        description: Some(description),
+        timeout_ms: Some(config.timeout_ms),
+        continue_on_error: Some(false),
+        delay_ms: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workflow_events::{
+        ButtonInteractionType, ClickEvent, EventMetadata, FieldFocusMethod,
+        TextInputCompletedEvent, TextInputMethod,
+    };
+
+    fn click(text: &str, window: Option<(&str, &str)>) -> RecordedEvent {
+        let mut metadata = EventMetadata::empty();
+        metadata.window = window.map(|(title, role)| WindowContext {
+            title: title.to_string(),
+            role: role.to_string(),
+        });
+        RecordedEvent {
+            timestamp: 0,
+            event: WorkflowEvent::Click(ClickEvent {
+                element_text: text.to_string(),
+                interaction_type: ButtonInteractionType::Click,
+                element_role: "Button".to_string(),
+                was_enabled: true,
+                click_position: None,
+                element_description: None,
+                child_text_content: vec![],
+                relative_position: None,
+                metadata,
+            }),
+            metadata: None,
+        }
+    }
+
+    fn step(tool_name: &str, arguments: serde_json::Value) -> McpToolStep {
+        McpToolStep {
+            tool_name: tool_name.to_string(),
+            arguments,
+            description: None,
+            timeout_ms: None,
+            continue_on_error: None,
+            delay_ms: None,
+        }
+    }
+
+    #[test]
+    fn test_from_kinds() {
+        let config = AssertionConfig::from_kinds(&["field_values", "Dialogs"]).unwrap();
+        assert!(config.field_values && config.dialogs && !config.window_titles);
+        assert_eq!(
+            AssertionConfig::from_kinds(&["all"]).unwrap(),
+            AssertionConfig::all()
+        );
+        assert!(AssertionConfig::from_kinds(&["pixels"]).is_err());
+        assert!(!AssertionConfig::default().is_enabled());
+    }
+
+    #[test]
+    fn test_field_value_check_skips_masked_values() {
+        let input = |text: &str| RecordedEvent {
+            timestamp: 0,
+            event: WorkflowEvent::TextInputCompleted(TextInputCompletedEvent {
+                text_value: text.to_string(),
+                field_name: Some("Email".to_string()),
+                field_type: "Edit".to_string(),
+                input_method: TextInputMethod::Typed,
+                focus_method: FieldFocusMethod::MouseClick,
+                typing_duration_ms: 0,
+                keystroke_count: 0,
+                metadata: EventMetadata::empty(),
+            }),
+            metadata: None,
+        };
+        let typed = |text: &str| {
+            vec![step(
+                "type_into_element",
+                json!({"selector": "role:Edit|name:Email", "text_to_type": text}),
+            )]
+        };
+        let config = AssertionConfig::all();
+
+        let events = [input("a@b.c")];
+        let checks = assertion_steps(&config, &events, 0, &typed("a@b.c"));
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].tool_name, "wait_for_element");
+        assert_eq!(checks[0].arguments["selector"], "role:Edit|name:Email");
+        assert_eq!(checks[0].arguments["expected_value"], "a@b.c");
+
+        let events = [input("[EMAIL_1]")];
+        assert!(assertion_steps(&config, &events, 0, &typed("[EMAIL_1]")).is_empty());
+    }
+
+    #[test]
+    fn test_window_and_dialog_checks() {
+        let clicked = vec![step("click_element", json!({"selector": "role:Button"}))];
+        let events = [
+            click("Save", Some(("Notes - Editor", "Window"))),
+            click("No", Some(("Save changes?", "Dialog"))),
+            click("Open", Some(("Notes - Editor", "Window"))),
+            click("Cancel", Some(("Notes - Editor", "Window"))),
+        ];
+        let config = AssertionConfig::all();
+
+        let checks = assertion_steps(&config, &events, 0, &clicked);
+        assert_eq!(checks.len(), 1);
+        assert_eq!(
+            checks[0].arguments["selector"],
+            "role:Dialog|name:Save changes?"
+        );
+        assert_eq!(checks[0].arguments["condition"], "visible");
+
+        let checks = assertion_steps(&config, &events, 1, &clicked);
+        assert_eq!(
+            checks[0].arguments["selector"],
+            "role:Window|name:Notes - Editor"
+        );
+
+        // Staying in the same window needs no check.
+        assert!(assertion_steps(&config, &events, 2, &clicked).is_empty());
+
+        let dialogs_only = AssertionConfig {
+            window_titles: false,
+            ..AssertionConfig::all()
+        };
+        assert!(assertion_steps(&dialogs_only, &events, 1, &clicked).is_empty());
+
+        // Titles that would break the selector syntax are not checked.
+        for title in ["Inbox | Mail", "Home >> Settings"] {
+            let events = [
+                click("Open", Some(("Notes - Editor", "Window"))),
+                click("OK", Some((title, "Window"))),
+            ];
+            assert!(assertion_steps(&config, &events, 0, &clicked).is_empty());
+        }
+    }
+}
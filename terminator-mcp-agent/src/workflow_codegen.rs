@@ -0,0 +1,803 @@
+//! Generates standalone automation scripts from recorded or converted workflows.
+//!
+//! The MCP steps produced by [`crate::workflow_converter`] (or written by hand in a
+//! workflow file) are turned into a script against the `terminator` Rust crate,
+//! `terminator.py` or `terminator.js`. Alternative and fallback selectors become extra
+//! candidates that the generated `locate` helper tries in order.
+
+use crate::helpers::parse_alternative_selectors;
+use crate::workflow_converter::{convert_workflow_to_mcp, McpWorkflow, WorkflowMetadata};
+use crate::workflow_events::{McpToolStep, RecordedWorkflow};
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Timeout used to find an element when the step does not set one.
+const DEFAULT_LOCATE_TIMEOUT_MS: u64 = 3000;
+
+/// Language of a generated script.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptLanguage {
+    Rust,
+    Python,
+    TypeScript,
+}
+
+impl ScriptLanguage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScriptLanguage::Rust => "rust",
+            ScriptLanguage::Python => "python",
+            ScriptLanguage::TypeScript => "typescript",
+        }
+    }
+
+    /// Conventional file extension for scripts in this language.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ScriptLanguage::Rust => "rs",
+            ScriptLanguage::Python => "py",
+            ScriptLanguage::TypeScript => "ts",
+        }
+    }
+}
+
+impl FromStr for ScriptLanguage {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "rust" | "rs" => Ok(ScriptLanguage::Rust),
+            "python" | "py" => Ok(ScriptLanguage::Python),
+            "typescript" | "ts" => Ok(ScriptLanguage::TypeScript),
+            other => Err(format!(
+                "unknown script language '{other}'; expected rust, python or typescript"
+            )),
+        }
+    }
+}
+
+/// What to do with the element found by a step's selectors.
+#[derive(Debug, Clone, PartialEq)]
+enum ElementAction {
+    Click,
+    TypeText { text: String, clear: bool },
+    PressKey(String),
+    Activate,
+    Scroll { direction: String, amount: f64 },
+    BrowserScript(String),
+}
+
+/// One workflow step, reduced to the calls the generated script makes.
+#[derive(Debug, Clone, PartialEq)]
+enum Operation {
+    OnElement {
+        selectors: Vec<String>,
+        timeout_ms: u64,
+        action: ElementAction,
+    },
+    GlobalKey(String),
+    OpenUrl {
+        url: String,
+        browser: Option<String>,
+    },
+    Unsupported,
+}
+
+fn operation(step: &McpToolStep) -> Operation {
+    let args = &step.arguments;
+    let str_arg = |key: &str| args.get(key).and_then(Value::as_str).map(str::to_string);
+    let selector = str_arg("selector").filter(|s| !s.is_empty());
+
+    let action = match step.tool_name.as_str() {
+        "click_element" => ElementAction::Click,
+        "type_into_element" => ElementAction::TypeText {
+            text: str_arg("text_to_type").unwrap_or_default(),
+            clear: args
+                .get("clear_before_typing")
+                .and_then(Value::as_bool)
+                .unwrap_or(true),
+        },
+        "press_key" | "press_key_global" => match (&selector, str_arg("key")) {
+            (Some(_), Some(key)) if step.tool_name == "press_key" => ElementAction::PressKey(key),
+            (_, Some(key)) => return Operation::GlobalKey(key),
+            (_, None) => return Operation::Unsupported,
+        },
+        "activate_element" => ElementAction::Activate,
+        "scroll_element" => ElementAction::Scroll {
+            direction: str_arg("direction").unwrap_or_else(|| "down".to_string()),
+            amount: args.get("amount").and_then(Value::as_f64).unwrap_or(3.0),
+        },
+        "execute_browser_script" => match str_arg("script") {
+            Some(script) => ElementAction::BrowserScript(script),
+            None => return Operation::Unsupported,
+        },
+        "navigate_browser" => {
+            return match str_arg("url") {
+                Some(url) => Operation::OpenUrl {
+                    url,
+                    browser: str_arg("browser"),
+                },
+                None => Operation::Unsupported,
+            }
+        }
+        _ => return Operation::Unsupported,
+    };
+
+    let Some(selector) = selector else {
+        return Operation::Unsupported;
+    };
+    let mut selectors = vec![selector];
+    for key in ["alternative_selectors", "fallback_selectors"] {
+        for candidate in parse_alternative_selectors(args.get(key).and_then(Value::as_str)) {
+            if !selectors.contains(&candidate) {
+                selectors.push(candidate);
+            }
+        }
+    }
+    Operation::OnElement {
+        selectors,
+        timeout_ms: args
+            .get("timeout_ms")
+            .and_then(Value::as_u64)
+            .or(step.timeout_ms)
+            .unwrap_or(DEFAULT_LOCATE_TIMEOUT_MS),
+        action,
+    }
+}
+
+/// Generate a script in `language` that performs the steps of `workflow`.
+pub fn generate_script(workflow: &McpWorkflow, language: ScriptLanguage) -> String {
+    let name = workflow
+        .metadata
+        .as_ref()
+        .and_then(|m| m.name.as_deref())
+        .unwrap_or("workflow");
+    let mut out = Script::default();
+    match language {
+        ScriptLanguage::Rust => rust::header(&mut out, name),
+        ScriptLanguage::Python => python::header(&mut out, name),
+        ScriptLanguage::TypeScript => typescript::header(&mut out, name),
+    }
+    out.indent += 1;
+    for (index, step) in workflow.steps.iter().enumerate() {
+        out.blank();
+        let title = step.description.as_deref().unwrap_or(&step.tool_name);
+        out.comment(language, &format!("Step {}: {title}", index + 1));
+        let operation = operation(step);
+        if operation == Operation::Unsupported {
+            out.comment(
+                language,
+                &format!(
+                    "TODO: '{}' has no direct equivalent; arguments: {}",
+                    step.tool_name, step.arguments
+                ),
+            );
+            continue;
+        }
+        let continue_on_error = step.continue_on_error.unwrap_or(false);
+        match language {
+            ScriptLanguage::Rust => rust::step(&mut out, index + 1, &operation, continue_on_error),
+            ScriptLanguage::Python => {
+                python::step(&mut out, index + 1, &operation, continue_on_error)
+            }
+            ScriptLanguage::TypeScript => {
+                typescript::step(&mut out, index + 1, &operation, continue_on_error)
+            }
+        }
+        if let Some(delay_ms) = step.delay_ms.filter(|d| *d > 0) {
+            match language {
+                ScriptLanguage::Rust => out.line(&format!(
+                    "tokio::time::sleep(Duration::from_millis({delay_ms})).await;"
+                )),
+                ScriptLanguage::Python => out.line(&format!(
+                    "await asyncio.sleep({})",
+                    delay_ms as f64 / 1000.0
+                )),
+                ScriptLanguage::TypeScript => {
+                    out.line(&format!("await desktop.delay({delay_ms});"))
+                }
+            }
+        }
+    }
+    out.indent -= 1;
+    match language {
+        ScriptLanguage::Rust => rust::footer(&mut out),
+        ScriptLanguage::Python => python::footer(&mut out),
+        ScriptLanguage::TypeScript => typescript::footer(&mut out),
+    }
+    out.text
+}
+
+/// Convert a raw recording to MCP steps and generate a script from them.
+pub async fn generate_script_from_recording(
+    workflow: RecordedWorkflow,
+    language: ScriptLanguage,
+) -> Result<String> {
+    let mcp_workflow = convert_workflow_to_mcp(workflow).await?;
+    Ok(generate_script(&mcp_workflow, language))
+}
+
+/// Load a workflow for code generation: either a recording saved by `record_workflow`
+/// (JSON or `.jsonl.gz`), or a workflow file (YAML or JSON) with `steps`, optionally
+/// wrapped in an `execute_sequence` call. Groups are flattened into their steps.
+pub async fn load_workflow(path: &Path) -> Result<McpWorkflow> {
+    if let Ok(recording) = terminator_workflow_recorder::read_recording(path) {
+        return convert_workflow_to_mcp(recording).await;
+    }
+
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+
+    let mut document: Value = serde_yaml::from_str(&content)
+        .with_context(|| format!("{} is neither a recording nor a workflow", path.display()))?;
+    if document["tool_name"] == "execute_sequence" {
+        document = document["arguments"].take();
+    }
+    let items = document
+        .get("steps")
+        .or_else(|| document.get("items"))
+        .and_then(Value::as_array)
+        .with_context(|| format!("{} has no steps", path.display()))?;
+    let mut steps = Vec::new();
+    collect_steps(items, &mut steps);
+    Ok(McpWorkflow {
+        steps,
+        groups: Vec::new(),
+        annotations: Vec::new(),
+        metadata: Some(WorkflowMetadata {
+            name: document["name"].as_str().map(str::to_string),
+            description: document["description"].as_str().map(str::to_string),
+            created_at: None,
+            event_count: 0,
+            conversion_notes: Vec::new(),
+        }),
+    })
+}
+
+fn collect_steps(items: &[Value], steps: &mut Vec<McpToolStep>) {
+    for item in items {
+        if let Some(tool_name) = item["tool_name"].as_str() {
+            steps.push(McpToolStep {
+                tool_name: tool_name.to_string(),
+                arguments: item
+                    .get("arguments")
+                    .cloned()
+                    .unwrap_or_else(|| Value::Object(Default::default())),
+                description: item["description"]
+                    .as_str()
+                    .or_else(|| item["id"].as_str())
+                    .map(str::to_string),
+                timeout_ms: item["timeout_ms"].as_u64(),
+                continue_on_error: item["continue_on_error"].as_bool(),
+                delay_ms: item["delay_ms"].as_u64(),
+            });
+        } else if let Some(group) = item["steps"].as_array() {
+            collect_steps(group, steps);
+        }
+    }
+}
+
+/// Accumulates script text with the current indentation.
+#[derive(Default)]
+struct Script {
+    text: String,
+    indent: usize,
+}
+
+impl Script {
+    fn line(&mut self, line: &str) {
+        for _ in 0..self.indent {
+            self.text.push_str("    ");
+        }
+        self.text.push_str(line);
+        self.text.push('\n');
+    }
+
+    fn lines(&mut self, block: &str) {
+        for line in block.lines() {
+            if line.is_empty() {
+                self.blank();
+            } else {
+                self.line(line);
+            }
+        }
+    }
+
+    fn blank(&mut self) {
+        self.text.push('\n');
+    }
+
+    fn comment(&mut self, language: ScriptLanguage, text: &str) {
+        let marker = match language {
+            ScriptLanguage::Python => "#",
+            ScriptLanguage::Rust | ScriptLanguage::TypeScript => "//",
+        };
+        for line in text.lines() {
+            self.line(&format!("{marker} {line}"));
+        }
+    }
+}
+
+/// A double-quoted Python or TypeScript string literal; JSON escaping is valid in both.
+fn quote(s: &str) -> String {
+    serde_json::to_string(s).unwrap_or_default()
+}
+
+/// A double-quoted Rust string literal. Rust has no `\b`, `\f` or `\uXXXX` escapes, so
+/// JSON escaping won't do; `Debug` escapes control characters as `\u{..}`.
+fn rust_quote(s: &str) -> String {
+    format!("{s:?}")
+}
+
+fn quote_list(selectors: &[String], quote: fn(&str) -> String) -> String {
+    selectors
+        .iter()
+        .map(|s| quote(s))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+mod rust {
+    use super::*;
+
+    pub(super) fn header(out: &mut Script, name: &str) {
+        out.line(&format!("//! {name}"));
+        out.line("//!");
+        out.line("//! Generated from a recorded workflow. Requires the `terminator-rs` and `tokio` crates.");
+        out.lines(
+            r#"
+use std::time::Duration;
+use terminator::{AutomationError, Desktop, UIElement};
+
+/// Returns the first element matched by any of `selectors`, tried in order.
+async fn locate(
+    desktop: &Desktop,
+    selectors: &[&str],
+    timeout_ms: u64,
+) -> Result<UIElement, AutomationError> {
+    let mut last_error = None;
+    for selector in selectors {
+        match desktop
+            .locator(*selector)
+            .first(Some(Duration::from_millis(timeout_ms)))
+            .await
+        {
+            Ok(element) => return Ok(element),
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(last_error.unwrap_or_else(|| AutomationError::ElementNotFound(selectors.join(", "))))
+}
+
+#[tokio::main]
+async fn main() -> Result<(), AutomationError> {
+    let desktop = Desktop::new(false, false)?;"#,
+        );
+    }
+
+    pub(super) fn step(
+        out: &mut Script,
+        number: usize,
+        operation: &Operation,
+        continue_on_error: bool,
+    ) {
+        let mut body = Vec::new();
+        match operation {
+            Operation::OnElement {
+                selectors,
+                timeout_ms,
+                action,
+            } => {
+                body.push(format!(
+                    "let element = locate(&desktop, &[{}], {timeout_ms}).await?;",
+                    quote_list(selectors, rust_quote)
+                ));
+                match action {
+                    ElementAction::Click => body.push("element.click()?;".to_string()),
+                    ElementAction::TypeText { text, clear } => {
+                        if *clear {
+                            body.push("element.set_value(\"\")?;".to_string());
+                        }
+                        body.push(format!("element.type_text({}, false)?;", rust_quote(text)));
+                    }
+                    ElementAction::PressKey(key) => {
+                        body.push(format!("element.press_key({})?;", rust_quote(key)))
+                    }
+                    ElementAction::Activate => body.push("element.activate_window()?;".to_string()),
+                    ElementAction::Scroll { direction, amount } => body.push(format!(
+                        "element.scroll({}, {amount:?})?;",
+                        rust_quote(direction)
+                    )),
+                    ElementAction::BrowserScript(script) => {
+                        body.push(format!(
+                            "let result = element.execute_browser_script({}).await?;",
+                            rust_quote(script)
+                        ));
+                        body.push(format!(
+                            "println!(\"Step {number} script result: {{result}}\");"
+                        ));
+                    }
+                }
+            }
+            Operation::GlobalKey(key) => {
+                body.push(format!("desktop.press_key({}).await?;", rust_quote(key)));
+            }
+            Operation::OpenUrl { url, browser } => {
+                let browser = match browser {
+                    Some(b) => format!(
+                        "Some(terminator::Browser::Custom({}.to_string()))",
+                        rust_quote(b)
+                    ),
+                    None => "None".to_string(),
+                };
+                body.push(format!(
+                    "desktop.open_url({}, {browser})?;",
+                    rust_quote(url)
+                ));
+            }
+            Operation::Unsupported => {}
+        }
+
+        if continue_on_error {
+            out.line("let step = async {");
+            out.indent += 1;
+            for line in &body {
+                out.line(line);
+            }
+            out.line("Ok::<(), AutomationError>(())");
+            out.indent -= 1;
+            out.line("};");
+            out.line("if let Err(e) = step.await {");
+            out.line(&format!(
+                "    eprintln!(\"Step {number} failed, continuing: {{e}}\");"
+            ));
+            out.line("}");
+        } else {
+            for line in &body {
+                out.line(line);
+            }
+        }
+    }
+
+    pub(super) fn footer(out: &mut Script) {
+        out.blank();
+        out.line("    Ok(())");
+        out.line("}");
+    }
+}
+
+mod python {
+    use super::*;
+
+    pub(super) fn header(out: &mut Script, name: &str) {
+        out.line(&format!("\"\"\"{}", name.replace('"', "'")));
+        out.blank();
+        out.line("Generated from a recorded workflow. Requires `pip install terminator.py`.");
+        out.line("\"\"\"");
+        out.lines(
+            r#"
+import asyncio
+
+import terminator
+
+
+async def locate(desktop, selectors, timeout_ms):
+    """Returns the first element matched by any of `selectors`, tried in order."""
+    last_error = None
+    for selector in selectors:
+        try:
+            return await desktop.locator(selector).wait(timeout_ms)
+        except Exception as e:
+            last_error = e
+    raise last_error
+
+
+async def main():
+    desktop = terminator.Desktop()"#,
+        );
+    }
+
+    pub(super) fn step(
+        out: &mut Script,
+        number: usize,
+        operation: &Operation,
+        continue_on_error: bool,
+    ) {
+        let mut body = Vec::new();
+        match operation {
+            Operation::OnElement {
+                selectors,
+                timeout_ms,
+                action,
+            } => {
+                body.push(format!(
+                    "element = await locate(desktop, [{}], {timeout_ms})",
+                    quote_list(selectors, quote)
+                ));
+                match action {
+                    ElementAction::Click => body.push("element.click()".to_string()),
+                    ElementAction::TypeText { text, clear } => {
+                        if *clear {
+                            body.push("element.set_value(\"\")".to_string());
+                        }
+                        body.push(format!("element.type_text({})", quote(text)));
+                    }
+                    ElementAction::PressKey(key) => {
+                        body.push(format!("element.press_key({})", quote(key)))
+                    }
+                    ElementAction::Activate => body.push("element.activate_window()".to_string()),
+                    ElementAction::Scroll { direction, amount } => {
+                        body.push(format!("element.scroll({}, {amount:?})", quote(direction)))
+                    }
+                    ElementAction::BrowserScript(script) => {
+                        body.push(format!(
+                            "result = await element.execute_browser_script({})",
+                            quote(script)
+                        ));
+                        body.push(format!("print(\"Step {number} script result:\", result)"));
+                    }
+                }
+            }
+            Operation::GlobalKey(key) => {
+                body.push(format!("await desktop.press_key({})", quote(key)));
+            }
+            Operation::OpenUrl { url, browser } => body.push(match browser {
+                Some(b) => format!("desktop.open_url({}, {})", quote(url), quote(b)),
+                None => format!("desktop.open_url({})", quote(url)),
+            }),
+            Operation::Unsupported => {}
+        }
+
+        if continue_on_error {
+            out.line("try:");
+            out.indent += 1;
+            for line in &body {
+                out.line(line);
+            }
+            out.indent -= 1;
+            out.line("except Exception as e:");
+            out.line(&format!(
+                "    print(f\"Step {number} failed, continuing: {{e}}\")"
+            ));
+        } else {
+            for line in &body {
+                out.line(line);
+            }
+        }
+    }
+
+    pub(super) fn footer(out: &mut Script) {
+        out.lines(
+            r#"
+
+if __name__ == "__main__":
+    asyncio.run(main())"#,
+        );
+    }
+}
+
+mod typescript {
+    use super::*;
+
+    pub(super) fn header(out: &mut Script, name: &str) {
+        out.line(&format!("// {name}"));
+        out.line("//");
+        out.line("// Generated from a recorded workflow. Requires `npm install terminator.js`.");
+        out.lines(
+            r#"
+import { Desktop, Element } from 'terminator.js';
+
+/** Returns the first element matched by any of `selectors`, tried in order. */
+async function locate(desktop: Desktop, selectors: string[], timeoutMs: number): Promise<Element> {
+    let lastError: unknown;
+    for (const selector of selectors) {
+        try {
+            return await desktop.locator(selector).first(timeoutMs);
+        } catch (e) {
+            lastError = e;
+        }
+    }
+    throw lastError;
+}
+
+async function main(): Promise<void> {
+    const desktop = new Desktop();"#,
+        );
+    }
+
+    pub(super) fn step(
+        out: &mut Script,
+        number: usize,
+        operation: &Operation,
+        continue_on_error: bool,
+    ) {
+        let mut body = Vec::new();
+        match operation {
+            Operation::OnElement {
+                selectors,
+                timeout_ms,
+                action,
+            } => {
+                body.push(format!(
+                    "const element = await locate(desktop, [{}], {timeout_ms});",
+                    quote_list(selectors, quote)
+                ));
+                match action {
+                    ElementAction::Click => body.push("element.click();".to_string()),
+                    ElementAction::TypeText { text, clear } => {
+                        if *clear {
+                            body.push("element.setValue('');".to_string());
+                        }
+                        body.push(format!("element.typeText({});", quote(text)));
+                    }
+                    ElementAction::PressKey(key) => {
+                        body.push(format!("element.pressKey({});", quote(key)))
+                    }
+                    ElementAction::Activate => body.push("element.activateWindow();".to_string()),
+                    ElementAction::Scroll { direction, amount } => {
+                        body.push(format!("element.scroll({}, {amount:?});", quote(direction)))
+                    }
+                    ElementAction::BrowserScript(script) => {
+                        body.push(format!(
+                            "const result = await element.executeBrowserScript({});",
+                            quote(script)
+                        ));
+                        body.push(format!(
+                            "console.log('Step {number} script result:', result);"
+                        ));
+                    }
+                }
+            }
+            Operation::GlobalKey(key) => {
+                body.push(format!("await desktop.pressKey({});", quote(key)));
+            }
+            Operation::OpenUrl { url, browser } => body.push(match browser {
+                Some(b) => format!("desktop.openUrl({}, {});", quote(url), quote(b)),
+                None => format!("desktop.openUrl({});", quote(url)),
+            }),
+            Operation::Unsupported => {}
+        }
+
+        // Blocks keep each step's `const element` from clashing with the next one.
+        let scoped = matches!(operation, Operation::OnElement { .. });
+        if continue_on_error {
+            out.line("try {");
+        } else if scoped {
+            out.line("{");
+        }
+        let indent = continue_on_error || scoped;
+        out.indent += usize::from(indent);
+        for line in &body {
+            out.line(line);
+        }
+        out.indent -= usize::from(indent);
+        if continue_on_error {
+            out.line("} catch (e) {");
+            out.line(&format!(
+                "    console.warn('Step {number} failed, continuing:', e);"
+            ));
+        }
+        if indent {
+            out.line("}");
+        }
+    }
+
+    pub(super) fn footer(out: &mut Script) {
+        out.lines(
+            r#"}
+
+main().catch((error) => {
+    console.error(error);
+    process.exit(1);
+});"#,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn workflow() -> McpWorkflow {
+        McpWorkflow {
+            steps: vec![
+                McpToolStep {
+                    tool_name: "click_element".to_string(),
+                    arguments: json!({
+                        "selector": "role:Button|name:New",
+                        "fallback_selectors": "name:New, #42",
+                        "timeout_ms": 3000
+                    }),
+                    description: Some("Click 'New' element".to_string()),
+                    timeout_ms: Some(3000),
+                    continue_on_error: Some(false),
+                    delay_ms: Some(200),
+                },
+                McpToolStep {
+                    tool_name: "type_into_element".to_string(),
+                    arguments: json!({
+                        "selector": "role:Edit|name:User name",
+                        "text_to_type": "say \"hi\"",
+                        "clear_before_typing": false
+                    }),
+                    description: None,
+                    timeout_ms: None,
+                    continue_on_error: Some(true),
+                    delay_ms: None,
+                },
+                McpToolStep {
+                    tool_name: "press_key".to_string(),
+                    arguments: json!({"key": "{Enter}"}),
+                    description: None,
+                    timeout_ms: None,
+                    continue_on_error: None,
+                    delay_ms: None,
+                },
+                McpToolStep {
+                    tool_name: "mouse_drag".to_string(),
+                    arguments: json!({"selector": "role:Pane"}),
+                    description: None,
+                    timeout_ms: None,
+                    continue_on_error: None,
+                    delay_ms: None,
+                },
+            ],
+            metadata: None,
+            groups: Vec::new(),
+            annotations: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_rust_script_uses_fallback_selectors() {
+        let script = generate_script(&workflow(), ScriptLanguage::Rust);
+        assert!(script.contains(
+            r##"locate(&desktop, &["role:Button|name:New", "name:New", "#42"], 3000).await?;"##
+        ));
+        assert!(script.contains("element.click()?;"));
+        assert!(script.contains(r#"element.type_text("say \"hi\"", false)?;"#));
+        assert!(!script.contains("set_value"));
+        assert!(script.contains("Step 2 failed, continuing"));
+        assert!(script.contains(r#"desktop.press_key("{Enter}").await?;"#));
+        assert!(script.contains("tokio::time::sleep(Duration::from_millis(200)).await;"));
+        assert!(script.contains("// TODO: 'mouse_drag' has no direct equivalent"));
+    }
+
+    #[test]
+    fn test_python_and_typescript_scripts() {
+        let python = generate_script(&workflow(), ScriptLanguage::Python);
+        assert!(python.contains(
+            r##"element = await locate(desktop, ["role:Button|name:New", "name:New", "#42"], 3000)"##
+        ));
+        assert!(python
+            .contains("        element.type_text(\"say \\\"hi\\\"\")\n    except Exception as e:"));
+        assert!(python.contains("await asyncio.sleep(0.2)"));
+        assert!(python.contains(r#"await desktop.press_key("{Enter}")"#));
+
+        let typescript = generate_script(&workflow(), ScriptLanguage::TypeScript);
+        assert!(typescript.contains("element.click();"));
+        assert!(typescript.contains("} catch (e) {"));
+        assert!(typescript.contains(r#"await desktop.pressKey("{Enter}");"#));
+        assert!(typescript.contains("await desktop.delay(200);"));
+    }
+
+    #[test]
+    fn test_control_characters_are_escaped_per_language() {
+        let mut workflow = workflow();
+        workflow.steps[1].arguments["text_to_type"] = json!("a\u{1}\u{8}\u{c}\tb");
+        let rust = generate_script(&workflow, ScriptLanguage::Rust);
+        assert!(rust.contains(r#"element.type_text("a\u{1}\u{8}\u{c}\tb", false)?;"#));
+        let python = generate_script(&workflow, ScriptLanguage::Python);
+        assert!(python.contains(r#"element.type_text("a\u0001\b\f\tb")"#));
+    }
+
+    #[test]
+    fn test_parse_language() {
+        assert_eq!("TS".parse(), Ok(ScriptLanguage::TypeScript));
+        assert_eq!("py".parse(), Ok(ScriptLanguage::Python));
+        assert!("go".parse::<ScriptLanguage>().is_err());
+    }
+}
@@ -1,9 +1,15 @@
 //! Workflow conversion module for converting raw recorded workflows to MCP sequences
 
 use crate::mcp_converter::{ConversionConfig, McpConverter};
-use crate::workflow_events::{McpToolStep, RecordedWorkflow, WorkflowEvent};
+use crate::workflow_assertions::{assertion_steps, AssertionConfig};
+use crate::workflow_events::{
+    EventScreenshot, McpToolStep, RecordedEvent, RecordedWorkflow, WorkflowEvent, WorkflowMarker,
+};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use terminator_workflow_recorder::{PostProcessConfig, PostProcessPipeline};
 use tracing::{debug, info, warn};
 
 /// MCP-converted workflow with tool sequences
@@ -15,6 +21,130 @@ pub struct McpWorkflow {
     /// Metadata from the original workflow
     #[serde(skip_serializing_if = "Option::is_none")]
     pub metadata: Option<WorkflowMetadata>,
+
+    /// Steps recorded after each marker, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<StepGroup>,
+
+    /// Screenshots of the recorded events the steps were converted from
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<StepAnnotation>,
+}
+
+/// Screenshots of the event a step was converted from
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StepAnnotation {
+    /// Index of the first step converted from the event, in `McpWorkflow::steps`
+    pub step: usize,
+    pub screenshot: EventScreenshot,
+}
+
+/// Steps recorded after a marker, as a range of `McpWorkflow::steps`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StepGroup {
+    /// The marker label
+    pub name: String,
+    pub start: usize,
+    pub len: usize,
+}
+
+impl McpWorkflow {
+    /// The steps as `execute_sequence` items, with the steps of each group wrapped
+    /// in a `group_name` item
+    pub fn sequence_items(&self) -> Vec<serde_json::Value> {
+        let mut items = Vec::new();
+        let mut next = 0;
+        for group in &self.groups {
+            items.extend(self.steps[next..group.start].iter().map(|s| json!(s)));
+            let end = group.start + group.len;
+            items.push(json!({
+                "group_name": group.name,
+                "steps": self.steps[group.start..end],
+            }));
+            next = end;
+        }
+        items.extend(self.steps[next..].iter().map(|s| json!(s)));
+        items
+    }
+}
+
+/// Append the steps converted from `events[index]`, annotating the first one with the
+/// event's screenshots and following them with the enabled checks of the recorded state.
+fn push_steps(
+    steps: &mut Vec<McpToolStep>,
+    annotations: &mut Vec<StepAnnotation>,
+    assertions: &AssertionConfig,
+    events: &[RecordedEvent],
+    index: usize,
+    converted: Vec<McpToolStep>,
+) {
+    if let Some(screenshot) = &events[index].event.metadata().screenshot {
+        if !converted.is_empty() {
+            annotations.push(StepAnnotation {
+                step: steps.len(),
+                screenshot: screenshot.clone(),
+            });
+        }
+    }
+    let checks = assertion_steps(assertions, events, index, &converted);
+    steps.extend(converted);
+    steps.extend(checks);
+}
+
+/// Clean up the recorded events before conversion; returns the changes as conversion notes.
+fn postprocess(workflow: &mut RecordedWorkflow, config: &PostProcessConfig) -> Vec<String> {
+    let report = PostProcessPipeline::from_config(config).process(workflow);
+    if !report.is_empty() {
+        info!(
+            "Post-processing made {} changes to the recorded events",
+            report.notes().len()
+        );
+    }
+    report.notes()
+}
+
+/// Splits the converted steps at the recorded markers.
+struct MarkerGroups<'a> {
+    markers: &'a [WorkflowMarker],
+    groups: Vec<StepGroup>,
+}
+
+impl<'a> MarkerGroups<'a> {
+    fn new(markers: &'a [WorkflowMarker]) -> Self {
+        Self {
+            markers,
+            groups: Vec::new(),
+        }
+    }
+
+    /// Called before converting an event recorded at `timestamp`, when `step_count`
+    /// steps have been converted so far
+    fn advance(&mut self, timestamp: u64, step_count: usize) {
+        while let Some((marker, rest)) = self.markers.split_first() {
+            if timestamp < marker.timestamp {
+                break;
+            }
+            self.close(step_count);
+            self.groups.push(StepGroup {
+                name: marker.label.clone(),
+                start: step_count,
+                len: 0,
+            });
+            self.markers = rest;
+        }
+    }
+
+    fn close(&mut self, step_count: usize) {
+        if let Some(group) = self.groups.last_mut() {
+            group.len = step_count - group.start;
+        }
+    }
+
+    fn finish(mut self, step_count: usize) -> Vec<StepGroup> {
+        self.close(step_count);
+        self.groups.retain(|group| group.len > 0);
+        self.groups
+    }
 }
 
 /// Metadata about the workflow
@@ -29,12 +159,23 @@ pub struct WorkflowMetadata {
 
 /// Convert a raw recorded workflow to MCP tool sequences
 pub async fn convert_workflow_to_mcp(workflow: RecordedWorkflow) -> Result<McpWorkflow> {
+    convert_workflow_with_config(workflow, ConversionConfig::default()).await
+}
+
+/// Convert a workflow with custom configuration
+pub async fn convert_workflow_with_config(
+    mut workflow: RecordedWorkflow,
+    config: ConversionConfig,
+) -> Result<McpWorkflow> {
     info!("Converting workflow to MCP sequences: {}", &workflow.name);
 
-    let converter = McpConverter::new();
+    let assertions = config.assertions.clone();
+    let mut conversion_notes = postprocess(&mut workflow, &config.postprocess);
+    let converter = McpConverter::with_config(config);
     let mut mcp_steps = Vec::new();
-    let mut conversion_notes = Vec::new();
+    let mut annotations = Vec::new();
     let mut processed_indices = std::collections::HashSet::new();
+    let mut marker_groups = MarkerGroups::new(&workflow.markers);
 
     // Process each event in the workflow
     for (index, event) in workflow.events.iter().enumerate() {
@@ -42,6 +183,7 @@ pub async fn convert_workflow_to_mcp(workflow: RecordedWorkflow) -> Result<McpWo
         if processed_indices.contains(&index) {
             continue;
         }
+        marker_groups.advance(event.timestamp, mcp_steps.len());
         debug!(
             "Converting event {} of {}",
             index + 1,
@@ -86,7 +228,14 @@ pub async fn convert_workflow_to_mcp(workflow: RecordedWorkflow) -> Result<McpWo
                         .convert_event(&workflow.events[text_idx].event, None)
                         .await
                     {
-                        mcp_steps.extend(text_result.primary_sequence);
+                        push_steps(
+                            &mut mcp_steps,
+                            &mut annotations,
+                            &assertions,
+                            &workflow.events,
+                            text_idx,
+                            text_result.primary_sequence,
+                        );
                         debug!("Event {}: {}", text_idx + 1, text_result.semantic_action);
                         if !text_result.conversion_notes.is_empty() {
                             conversion_notes.extend(text_result.conversion_notes);
@@ -95,7 +244,14 @@ pub async fn convert_workflow_to_mcp(workflow: RecordedWorkflow) -> Result<McpWo
 
                     // Now add the dropdown click
                     if let Ok(click_result) = converter.convert_event(&event.event, None).await {
-                        mcp_steps.extend(click_result.primary_sequence);
+                        push_steps(
+                            &mut mcp_steps,
+                            &mut annotations,
+                            &assertions,
+                            &workflow.events,
+                            index,
+                            click_result.primary_sequence,
+                        );
                         debug!(
                             "Event {} (reordered): {}",
                             index + 1,
@@ -120,7 +276,14 @@ pub async fn convert_workflow_to_mcp(workflow: RecordedWorkflow) -> Result<McpWo
         match converter.convert_event(&event.event, None).await {
             Ok(conversion_result) => {
                 // Add the primary sequence
-                mcp_steps.extend(conversion_result.primary_sequence);
+                push_steps(
+                    &mut mcp_steps,
+                    &mut annotations,
+                    &assertions,
+                    &workflow.events,
+                    index,
+                    conversion_result.primary_sequence,
+                );
 
                 // Log semantic action for debugging
                 debug!("Event {}: {}", index + 1, conversion_result.semantic_action);
@@ -146,8 +309,11 @@ pub async fn convert_workflow_to_mcp(workflow: RecordedWorkflow) -> Result<McpWo
         mcp_steps.len()
     );
 
+    let groups = marker_groups.finish(mcp_steps.len());
     Ok(McpWorkflow {
         steps: mcp_steps,
+        groups,
+        annotations,
         metadata: Some(WorkflowMetadata {
             name: Some(workflow.name.clone()),
             description: None,
@@ -158,58 +324,53 @@ pub async fn convert_workflow_to_mcp(workflow: RecordedWorkflow) -> Result<McpWo
     })
 }
 
-/// Load a workflow from a JSON file and convert it to MCP
+/// Load a recording (JSON or `.jsonl.gz`) and convert it to MCP
 pub async fn load_and_convert_workflow(file_path: &str) -> Result<McpWorkflow> {
-    info!("Loading workflow from: {}", file_path);
-
-    // Read the JSON file
-    let content = std::fs::read_to_string(file_path)?;
-
-    // Parse as RecordedWorkflow (from recorder crate)
-    let workflow: RecordedWorkflow = serde_json::from_str(&content)?;
-
-    // Convert to MCP sequences
-    convert_workflow_to_mcp(workflow).await
+    load_and_convert_workflow_with_config(file_path, ConversionConfig::default()).await
 }
 
-/// Convert a workflow with custom configuration
-pub async fn convert_workflow_with_config(
-    workflow: RecordedWorkflow,
+/// Load a recording (JSON or `.jsonl.gz`) and convert it to MCP with custom configuration
+pub async fn load_and_convert_workflow_with_config(
+    file_path: &str,
     config: ConversionConfig,
 ) -> Result<McpWorkflow> {
-    info!("Converting workflow with custom config");
-
-    let converter = McpConverter::with_config(config);
-    let mut mcp_steps = Vec::new();
-    let mut conversion_notes = Vec::new();
+    info!("Loading workflow from: {}", file_path);
 
-    for (index, event) in workflow.events.iter().enumerate() {
-        match converter.convert_event(&event.event, None).await {
-            Ok(conversion_result) => {
-                mcp_steps.extend(conversion_result.primary_sequence);
+    // Any encoding and format version written by the recorder
+    let workflow = terminator_workflow_recorder::read_recording(Path::new(file_path))?;
 
-                if !conversion_result.conversion_notes.is_empty() {
-                    conversion_notes.extend(conversion_result.conversion_notes);
-                }
-            }
-            Err(e) => {
-                warn!("Failed to convert event {}: {}", index + 1, e);
-                conversion_notes.push(format!("Event {} conversion failed: {}", index + 1, e));
-                continue;
+    // Convert to MCP sequences
+    let mut mcp_workflow = convert_workflow_with_config(workflow, config).await?;
+
+    // Screenshot paths are relative to the recording
+    let base_dir = Path::new(file_path).parent().unwrap_or(Path::new(""));
+    for annotation in &mut mcp_workflow.annotations {
+        let screenshot = &mut annotation.screenshot;
+        for image in [&mut screenshot.screen, &mut screenshot.element]
+            .into_iter()
+            .flatten()
+        {
+            if let Some(path) = &mut image.path {
+                *path = base_dir.join(&*path).to_string_lossy().into_owned();
             }
         }
     }
+    Ok(mcp_workflow)
+}
 
-    Ok(McpWorkflow {
-        steps: mcp_steps,
-        metadata: Some(WorkflowMetadata {
-            name: Some(workflow.name.clone()),
-            description: None,
-            created_at: Some(format!("{}", workflow.start_time)),
-            event_count: workflow.events.len(),
-            conversion_notes,
-        }),
-    })
+/// Render a recording saved by `record_workflow` as an HTML storyboard of its actions
+/// and screenshots. The page is written to `output`, or next to the recording with an
+/// `.html` extension, so that screenshot files resolve.
+pub fn write_storyboard(recording: &Path, output: Option<&Path>) -> Result<PathBuf> {
+    let workflow = terminator_workflow_recorder::read_recording(recording)?;
+    let output = output
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| recording.with_extension("html"));
+    std::fs::write(
+        &output,
+        terminator_workflow_recorder::render_storyboard(&workflow),
+    )?;
+    Ok(output)
 }
 
 /// Validate that a workflow can be converted successfully
@@ -237,7 +398,7 @@ pub async fn validate_workflow_conversion(workflow: &RecordedWorkflow) -> Result
     Ok(validation_notes)
 }
 
-fn event_type_name(event: &WorkflowEvent) -> &'static str {
+pub(crate) fn event_type_name(event: &WorkflowEvent) -> &'static str {
     match event {
         WorkflowEvent::Click(_) => "Click",
         WorkflowEvent::Keyboard(_) => "Keyboard",
@@ -258,7 +419,8 @@ fn event_type_name(event: &WorkflowEvent) -> &'static str {
 mod tests {
     use super::*;
     use crate::workflow_events::{
-        ButtonInteractionType, ClickEvent, EventMetadata, Position, RecordedEvent,
+        ButtonInteractionType, ClickEvent, EventMetadata, Position, RecordedEvent, ScreenshotImage,
+        WorkflowMarker,
     };
 
     #[tokio::test]
@@ -285,6 +447,7 @@ mod tests {
                 event: click_event,
                 metadata: None,
             }],
+            markers: vec![],
         };
 
         let result = convert_workflow_to_mcp(workflow).await.unwrap();
@@ -292,4 +455,81 @@ mod tests {
         assert!(!result.steps.is_empty());
         assert_eq!(result.metadata.as_ref().unwrap().event_count, 1);
     }
+
+    #[tokio::test]
+    async fn test_markers_become_groups_and_screenshots_annotations() {
+        let click = |text: &str| {
+            WorkflowEvent::Click(ClickEvent {
+                element_text: text.to_string(),
+                interaction_type: ButtonInteractionType::Click,
+                element_role: "Button".to_string(),
+                was_enabled: true,
+                click_position: None,
+                element_description: None,
+                child_text_content: vec![],
+                relative_position: None,
+                metadata: EventMetadata::empty(),
+            })
+        };
+        let screenshot = EventScreenshot {
+            screen: Some(ScreenshotImage {
+                width: 640,
+                height: 360,
+                path: Some("grouped_screenshots/event_0001_screen.png".to_string()),
+                data: None,
+            }),
+            element: None,
+        };
+        let marker = |label: &str, timestamp| WorkflowMarker {
+            label: label.to_string(),
+            timestamp,
+        };
+        let workflow = RecordedWorkflow {
+            name: "Grouped".to_string(),
+            start_time: 1000,
+            end_time: Some(5000),
+            events: [(1000, "Open"), (2000, "Next"), (3000, "Save")]
+                .into_iter()
+                .map(|(timestamp, text)| {
+                    let mut event = click(text);
+                    if text == "Next" {
+                        event.metadata_mut().screenshot = Some(screenshot.clone());
+                    }
+                    RecordedEvent {
+                        timestamp,
+                        event,
+                        metadata: None,
+                    }
+                })
+                .collect(),
+            markers: vec![
+                marker("Empty", 1500),
+                marker("Details", 1600),
+                marker("Trailing", 4000),
+            ],
+        };
+
+        let result = convert_workflow_to_mcp(workflow).await.unwrap();
+        assert_eq!(result.steps.len(), 3);
+        assert_eq!(
+            result.groups,
+            vec![StepGroup {
+                name: "Details".to_string(),
+                start: 1,
+                len: 2,
+            }]
+        );
+        assert_eq!(
+            result.annotations,
+            vec![StepAnnotation {
+                step: 1,
+                screenshot,
+            }]
+        );
+        let items = result.sequence_items();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0]["tool_name"], "click_element");
+        assert_eq!(items[1]["group_name"], "Details");
+        assert_eq!(items[1]["steps"].as_array().unwrap().len(), 2);
+    }
 }
@@ -19,6 +19,7 @@ pub use terminator_workflow_recorder::{
     EnhancedUIElement,
     // Metadata
     EventMetadata,
+    EventScreenshot,
     FieldFocusMethod,
     HotkeyEvent,
     // Context types
@@ -34,6 +35,7 @@ pub use terminator_workflow_recorder::{
     // Workflow types
     RecordedWorkflow,
     Rect,
+    ScreenshotImage,
     SelectionMethod,
     SelectorCandidate,
     TabAction,
@@ -42,7 +44,9 @@ pub use terminator_workflow_recorder::{
     TextInputMethod,
     TextSelectionEvent,
     UIElementInfo,
+    WindowContext,
     WorkflowEvent,
+    WorkflowMarker,
 };
 
 // Basic types are now re-exported from terminator_workflow_recorder above
@@ -0,0 +1,1190 @@
+//! Static checks for workflow files run by `execute_sequence`.
+//!
+//! Everything here works on the YAML text alone: nothing is executed and no desktop is
+//! needed, so the checks can run in CI before a workflow ever reaches a runner.
+
+use crate::expression_eval;
+use crate::workflow_schema::{self, METADATA_KEYS};
+use regex::Regex;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::sync::OnceLock;
+use yaml_rust2::parser::{Event, MarkedEventReceiver, Parser};
+use yaml_rust2::scanner::Marker;
+
+/// Tools whose output can add arbitrary variables to the env at runtime.
+const SCRIPT_TOOLS: &[&str] = &["run_command", "execute_browser_script"];
+
+/// Argument keys holding a single selector.
+const SELECTOR_KEYS: &[&str] = &["selector", "tree_from_selector"];
+
+/// Argument keys holding comma-separated selectors.
+const SELECTOR_LIST_KEYS: &[&str] = &["alternative_selectors", "fallback_selectors"];
+
+/// Variables `execute_sequence` sets while running cleanup steps.
+const FAILURE_VARIABLES: &[&str] = &["failed_step_id", "failed_step_error"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// 1-based line of the offending node in the YAML source.
+    pub line: usize,
+    /// 1-based column of the offending node in the YAML source.
+    pub column: usize,
+    /// JSON pointer to the offending node, e.g. `/steps/2/arguments/selector`.
+    pub path: String,
+    pub message: String,
+}
+
+/// Lints a workflow file, returning its diagnostics ordered by position.
+pub fn lint_workflow(source: &str) -> Vec<Diagnostic> {
+    let document: Value = match serde_yaml::from_str(source) {
+        Ok(document) => document,
+        Err(e) => {
+            let (line, column) = e
+                .location()
+                .map(|l| (l.line(), l.column()))
+                .unwrap_or((1, 1));
+            return vec![Diagnostic {
+                severity: Severity::Error,
+                line,
+                column,
+                path: String::new(),
+                message: format!("invalid YAML: {e}"),
+            }];
+        }
+    };
+
+    let mut positions = Positions::default();
+    // serde_yaml accepted the document, so this parse only fails on exotic input;
+    // diagnostics then fall back to the start of the file.
+    let _ = Parser::new_from_str(source).load(&mut positions, false);
+
+    let schema = workflow_schema::workflow_schema();
+    let mut linter = Linter {
+        validator: Validator { root: &schema },
+        tools: schema
+            .pointer("/definitions/ToolCall/properties/tool_name/enum")
+            .and_then(Value::as_array)
+            .map(|names| {
+                names
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default(),
+        findings: Vec::new(),
+    };
+    linter.lint_document(&document);
+
+    let mut diagnostics: Vec<Diagnostic> = linter
+        .findings
+        .into_iter()
+        .map(|finding| {
+            let (line, column) = positions.locate(&finding.path, finding.at_key);
+            Diagnostic {
+                severity: finding.severity,
+                line,
+                column,
+                path: finding.path,
+                message: finding.message,
+            }
+        })
+        .collect();
+    diagnostics.sort_by_key(|d| (d.line, d.column, d.severity));
+    let mut seen = HashSet::new();
+    diagnostics.retain(|d| seen.insert((d.line, d.column, d.message.clone())));
+    diagnostics
+}
+
+struct Finding {
+    severity: Severity,
+    path: String,
+    /// Point at the mapping key rather than its value (for unknown fields).
+    at_key: bool,
+    message: String,
+}
+
+impl Finding {
+    fn error(path: &str, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            path: path.to_string(),
+            at_key: false,
+            message: message.into(),
+        }
+    }
+
+    fn warning(path: &str, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            path: path.to_string(),
+            at_key: false,
+            message: message.into(),
+        }
+    }
+}
+
+fn child(path: &str, segment: impl fmt::Display) -> String {
+    let segment = segment.to_string().replace('~', "~0").replace('/', "~1");
+    format!("{path}/{segment}")
+}
+
+fn placeholder_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    // Same pattern `substitute_variables` uses at runtime.
+    RE.get_or_init(|| Regex::new(r"\$?\{\{(.*?)\}\}").unwrap())
+}
+
+/// Whether `value` is a single `{{...}}` placeholder, which may expand to any type.
+fn is_placeholder(value: &Value) -> bool {
+    value.as_str().is_some_and(|s| {
+        placeholder_regex()
+            .find(s)
+            .is_some_and(|m| m.start() == 0 && m.end() == s.len())
+    })
+}
+
+/// Source positions of every node, keyed by JSON pointer.
+#[derive(Default)]
+struct Positions {
+    values: HashMap<String, Marker>,
+    keys: HashMap<String, Marker>,
+    stack: Vec<Frame>,
+    documents: usize,
+}
+
+enum Frame {
+    Mapping { path: String, key: Option<String> },
+    Sequence { path: String, index: usize },
+}
+
+impl Positions {
+    fn locate(&self, path: &str, at_key: bool) -> (usize, usize) {
+        if at_key {
+            if let Some(mark) = self.keys.get(path) {
+                return (mark.line(), mark.col() + 1);
+            }
+        }
+        let mut path = path;
+        loop {
+            if let Some(mark) = self.values.get(path) {
+                return (mark.line(), mark.col() + 1);
+            }
+            match path.rfind('/') {
+                Some(pos) => path = &path[..pos],
+                None => return (1, 1),
+            }
+        }
+    }
+}
+
+impl MarkedEventReceiver for Positions {
+    fn on_event(&mut self, event: Event, mark: Marker) {
+        match event {
+            Event::DocumentStart => self.documents += 1,
+            Event::Scalar(..)
+            | Event::SequenceStart(..)
+            | Event::MappingStart(..)
+            | Event::Alias(..)
+                if self.documents == 1 =>
+            {
+                let path = match self.stack.last_mut() {
+                    None => String::new(),
+                    Some(Frame::Sequence { path, index }) => {
+                        *index += 1;
+                        child(path, *index - 1)
+                    }
+                    Some(Frame::Mapping { path, key }) => match key.take() {
+                        Some(key) => child(path, key),
+                        None => {
+                            let key_name = match &event {
+                                Event::Scalar(value, ..) => value.clone(),
+                                _ => "?".to_string(),
+                            };
+                            let key_path = child(path, &key_name);
+                            // The parser marks block mappings after their first key,
+                            // so locate a mapping by that key instead.
+                            self.values.entry(path.clone()).or_insert(mark);
+                            *key = Some(key_name);
+                            self.keys.entry(key_path.clone()).or_insert(mark);
+                            match event {
+                                // Complex keys are not used by workflows; track them
+                                // under a placeholder path so nesting stays balanced.
+                                Event::SequenceStart(..) => self.stack.push(Frame::Sequence {
+                                    path: key_path,
+                                    index: 0,
+                                }),
+                                Event::MappingStart(..) => self.stack.push(Frame::Mapping {
+                                    path: key_path,
+                                    key: None,
+                                }),
+                                _ => {}
+                            }
+                            return;
+                        }
+                    },
+                };
+                if !matches!(event, Event::MappingStart(..)) {
+                    self.values.entry(path.clone()).or_insert(mark);
+                }
+                match event {
+                    Event::SequenceStart(..) => self.stack.push(Frame::Sequence { path, index: 0 }),
+                    Event::MappingStart(..) => self.stack.push(Frame::Mapping { path, key: None }),
+                    _ => {}
+                }
+            }
+            Event::SequenceEnd | Event::MappingEnd if self.documents == 1 => {
+                self.stack.pop();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// A small JSON Schema (draft-07 subset) validator for the schema built by
+/// [`workflow_schema::workflow_schema`], with messages aimed at workflow authors.
+struct Validator<'a> {
+    root: &'a Value,
+}
+
+impl Validator<'_> {
+    fn validate(&self, schema: &Value, instance: &Value, path: &str, out: &mut Vec<Finding>) {
+        let schema = match schema {
+            Value::Bool(true) => return,
+            Value::Bool(false) => {
+                out.push(Finding::error(path, "no value is allowed here"));
+                return;
+            }
+            Value::Object(schema) => schema,
+            _ => return,
+        };
+        if is_placeholder(instance) {
+            return;
+        }
+
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            if let Some(target) = self.resolve(reference) {
+                self.validate(target, instance, path, out);
+            }
+            return;
+        }
+
+        if let Some(types) = schema.get("type") {
+            if !type_names(types).iter().any(|t| has_type(instance, t)) {
+                out.push(Finding::error(
+                    path,
+                    format!(
+                        "expected {}, found {}",
+                        type_names(types).join(" or "),
+                        kind(instance)
+                    ),
+                ));
+                return;
+            }
+        }
+
+        if let Some(Value::Array(allowed)) = schema.get("enum") {
+            // Unknown tool names get a dedicated message from the linter.
+            if !allowed.contains(instance) && !path.ends_with("/tool_name") {
+                out.push(Finding::error(
+                    path,
+                    format!("expected one of {}, found {instance}", list_values(allowed)),
+                ));
+            }
+        }
+        if let Some(expected) = schema.get("const") {
+            if expected != instance {
+                out.push(Finding::error(
+                    path,
+                    format!("expected {expected}, found {instance}"),
+                ));
+            }
+        }
+        if let Some(number) = instance.as_f64() {
+            if let Some(minimum) = schema.get("minimum").and_then(Value::as_f64) {
+                if number < minimum {
+                    out.push(Finding::error(path, format!("must be at least {minimum}")));
+                }
+            }
+            if let Some(maximum) = schema.get("maximum").and_then(Value::as_f64) {
+                if number > maximum {
+                    out.push(Finding::error(path, format!("must be at most {maximum}")));
+                }
+            }
+        }
+
+        if let Value::Object(object) = instance {
+            self.validate_object(schema, object, path, out);
+        }
+        if let (Value::Array(items), Some(item_schema)) = (instance, schema.get("items")) {
+            for (index, item) in items.iter().enumerate() {
+                self.validate(item_schema, item, &child(path, index), out);
+            }
+        }
+
+        if let Some(Value::Array(all)) = schema.get("allOf") {
+            for sub in all {
+                self.validate(sub, instance, path, out);
+            }
+        }
+        for keyword in ["anyOf", "oneOf"] {
+            if let Some(Value::Array(branches)) = schema.get(keyword) {
+                self.validate_branches(branches, instance, path, out);
+            }
+        }
+        if let Some(condition) = schema.get("if") {
+            let mut probe = Vec::new();
+            self.validate(condition, instance, path, &mut probe);
+            let branch = if probe.iter().any(|f| f.severity == Severity::Error) {
+                schema.get("else")
+            } else {
+                schema.get("then")
+            };
+            if let Some(branch) = branch {
+                self.validate(branch, instance, path, out);
+            }
+        }
+    }
+
+    fn validate_object(
+        &self,
+        schema: &Map<String, Value>,
+        object: &Map<String, Value>,
+        path: &str,
+        out: &mut Vec<Finding>,
+    ) {
+        if let Some(Value::Array(required)) = schema.get("required") {
+            for name in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(name) {
+                    out.push(Finding::error(
+                        path,
+                        format!("missing required field '{name}'"),
+                    ));
+                }
+            }
+        }
+
+        let properties = schema.get("properties").and_then(Value::as_object);
+        // Only complete struct schemas know all their fields; partial ones such as the
+        // `then` of a tool condition describe a single property.
+        let is_struct = properties.is_some()
+            && schema
+                .get("type")
+                .is_some_and(|t| type_names(t).contains(&"object"));
+        for (name, value) in object {
+            let value_path = child(path, name);
+            if let Some(property) = properties.and_then(|p| p.get(name)) {
+                self.validate(property, value, &value_path, out);
+                continue;
+            }
+            match schema.get("additionalProperties") {
+                Some(Value::Bool(false)) => out.push(Finding {
+                    at_key: true,
+                    ..Finding::error(&value_path, format!("unknown field '{name}'"))
+                }),
+                Some(additional @ Value::Object(_)) => {
+                    self.validate(additional, value, &value_path, out)
+                }
+                _ if is_struct => out.push(Finding {
+                    at_key: true,
+                    ..Finding::warning(&value_path, format!("unknown field '{name}' is ignored"))
+                }),
+                _ => {}
+            }
+        }
+    }
+
+    fn validate_branches(
+        &self,
+        branches: &[Value],
+        instance: &Value,
+        path: &str,
+        out: &mut Vec<Finding>,
+    ) {
+        let mut attempts = Vec::with_capacity(branches.len());
+        for branch in branches {
+            let mut findings = Vec::new();
+            self.validate(branch, instance, path, &mut findings);
+            if !findings.iter().any(|f| f.severity == Severity::Error) {
+                out.extend(findings);
+                return;
+            }
+            attempts.push(findings);
+        }
+
+        // Prefer the branch that accepted the value's shape and only failed deeper down,
+        // e.g. the struct of an `Option<Struct>` rather than its `null`.
+        let nested = format!("{path}/");
+        let best = attempts
+            .into_iter()
+            .filter(|findings| {
+                findings
+                    .iter()
+                    .filter(|f| f.severity == Severity::Error)
+                    .all(|f| f.path.starts_with(&nested))
+            })
+            .min_by_key(|findings| findings.len());
+        match best {
+            Some(findings) => out.extend(findings),
+            None => {
+                let expected: Vec<String> = branches.iter().map(|b| self.describe(b)).collect();
+                out.push(Finding::error(
+                    path,
+                    format!(
+                        "expected {}, found {}",
+                        expected.join(" or "),
+                        kind(instance)
+                    ),
+                ));
+            }
+        }
+    }
+
+    fn resolve(&self, reference: &str) -> Option<&Value> {
+        self.root.pointer(reference.strip_prefix('#')?)
+    }
+
+    fn describe(&self, schema: &Value) -> String {
+        let Some(schema) = schema.as_object() else {
+            return "any value".to_string();
+        };
+        if let Some(target) = schema
+            .get("$ref")
+            .and_then(Value::as_str)
+            .and_then(|r| self.resolve(r))
+        {
+            return self.describe(target);
+        }
+        if let Some(Value::Array(allowed)) = schema.get("enum") {
+            return format!("one of {}", list_values(allowed));
+        }
+        if let Some(expected) = schema.get("const") {
+            return expected.to_string();
+        }
+        match schema.get("type") {
+            Some(types) => type_names(types).join(" or "),
+            None => "a matching value".to_string(),
+        }
+    }
+}
+
+fn type_names(types: &Value) -> Vec<&str> {
+    match types {
+        Value::String(t) => vec![t.as_str()],
+        Value::Array(types) => types.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn has_type(instance: &Value, name: &str) -> bool {
+    match name {
+        "null" => instance.is_null(),
+        "boolean" => instance.is_boolean(),
+        "string" => instance.is_string(),
+        "array" => instance.is_array(),
+        "object" => instance.is_object(),
+        "number" => instance.is_number(),
+        "integer" => {
+            instance.is_i64()
+                || instance.is_u64()
+                || instance.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        _ => true,
+    }
+}
+
+fn kind(instance: &Value) -> &'static str {
+    match instance {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn list_values(values: &[Value]) -> String {
+    values
+        .iter()
+        .map(Value::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// A tool invocation anywhere in the workflow: a step, a group member or a hook.
+struct ToolUse<'a> {
+    path: String,
+    call: &'a Map<String, Value>,
+}
+
+struct Linter<'a> {
+    validator: Validator<'a>,
+    tools: BTreeSet<String>,
+    findings: Vec<Finding>,
+}
+
+impl Linter<'_> {
+    fn lint_document(&mut self, document: &Value) {
+        let Some(root) = document.as_object() else {
+            self.findings.push(Finding::error(
+                "",
+                format!("a workflow must be a mapping, found {}", kind(document)),
+            ));
+            return;
+        };
+
+        if let Some(tool_name) = root.get("tool_name") {
+            if tool_name.as_str() != Some("execute_sequence") {
+                self.findings.push(Finding::error(
+                    "/tool_name",
+                    format!("workflow files must call execute_sequence, found {tool_name}"),
+                ));
+                return;
+            }
+            for name in root.keys() {
+                if name != "tool_name"
+                    && name != "arguments"
+                    && !METADATA_KEYS.contains(&name.as_str())
+                {
+                    self.findings.push(Finding {
+                        at_key: true,
+                        ..Finding::warning(
+                            &child("", name),
+                            format!("unknown field '{name}' is ignored"),
+                        )
+                    });
+                }
+            }
+            match root.get("arguments") {
+                Some(Value::Object(arguments)) => self.lint_workflow(arguments, "/arguments"),
+                Some(other) => self.findings.push(Finding::error(
+                    "/arguments",
+                    format!("expected object, found {}", kind(other)),
+                )),
+                None => self
+                    .findings
+                    .push(Finding::error("", "missing required field 'arguments'")),
+            }
+        } else {
+            self.lint_workflow(root, "");
+        }
+    }
+
+    fn lint_workflow(&mut self, workflow: &Map<String, Value>, base: &str) {
+        let schema = serde_json::json!({ "$ref": "#/definitions/ExecuteSequenceArgs" });
+        let instance = Value::Object(workflow.clone());
+        self.validator
+            .validate(&schema, &instance, base, &mut self.findings);
+
+        if !workflow.contains_key("steps") && !workflow.contains_key("url") {
+            self.findings.push(Finding::error(
+                base,
+                "a workflow needs 'steps' (or a 'url' to load them from)",
+            ));
+        }
+
+        let main = steps_of(workflow, "steps");
+        let troubleshooting = steps_of(workflow, "troubleshooting");
+        let step_path = |index: usize| {
+            if index < main.len() {
+                child(&child(base, "steps"), index)
+            } else {
+                child(&child(base, "troubleshooting"), index - main.len())
+            }
+        };
+        let all_steps: Vec<&Value> = main.iter().chain(troubleshooting.iter()).copied().collect();
+
+        // Tool invocations, for tool names, arguments, placeholders and selectors.
+        let mut uses = Vec::new();
+        for (index, step) in all_steps.iter().enumerate() {
+            let Some(step) = step.as_object() else {
+                continue;
+            };
+            let path = step_path(index);
+            match (step.get("tool_name"), step.get("group_name")) {
+                (Some(_), Some(_)) => self.findings.push(Finding::error(
+                    &path,
+                    "a step cannot have both 'tool_name' and 'group_name'",
+                )),
+                (None, None) => self.findings.push(Finding::error(
+                    &path,
+                    "a step needs either 'tool_name' or 'group_name'",
+                )),
+                _ => {}
+            }
+            if step.contains_key("tool_name") {
+                uses.push(ToolUse {
+                    path: path.clone(),
+                    call: step,
+                });
+            }
+            for list in ["steps", "on_failure", "finally"] {
+                collect_calls(step, list, &path, &mut uses);
+            }
+            if let Some(retry) = step.get("retry").and_then(Value::as_object) {
+                collect_calls(retry, "before_retry", &child(&path, "retry"), &mut uses);
+            }
+        }
+        for list in ["on_failure", "finally"] {
+            collect_calls(workflow, list, base, &mut uses);
+        }
+
+        let has_scripts = uses.iter().any(|u| {
+            u.call
+                .get("tool_name")
+                .and_then(Value::as_str)
+                .is_some_and(|name| SCRIPT_TOOLS.contains(&name))
+        });
+
+        // Step ids; later duplicates win at runtime, like here.
+        let mut ids: HashMap<String, usize> = HashMap::new();
+        for (index, step) in all_steps.iter().enumerate() {
+            if let Some(id) = step.get("id").and_then(Value::as_str) {
+                if ids.insert(id.to_string(), index).is_some() {
+                    self.findings.push(Finding::warning(
+                        &child(&step_path(index), "id"),
+                        format!("duplicate step id '{id}'; jumps to it go to this step"),
+                    ));
+                }
+            }
+        }
+
+        let scope = Scope::new(workflow, &uses, has_scripts);
+
+        for use_ in &uses {
+            self.lint_tool_use(use_, &scope);
+        }
+
+        for key in ["start_from_step", "end_at_step"] {
+            if let Some(id) = workflow.get(key).and_then(Value::as_str) {
+                if !ids.contains_key(id) {
+                    self.findings.push(Finding::error(
+                        &child(base, key),
+                        format!("{key} refers to unknown step id '{id}'"),
+                    ));
+                }
+            }
+        }
+
+        // Control flow: conditions, jump targets and reachability.
+        let stops_on_failure = workflow.get("stop_on_error").and_then(Value::as_bool)
+            != Some(false)
+            && workflow.get("continue").and_then(Value::as_bool) != Some(true);
+        let mut edges: Vec<Vec<usize>> = vec![Vec::new(); all_steps.len()];
+        for (index, step) in all_steps.iter().enumerate() {
+            let Some(step) = step.as_object() else {
+                continue;
+            };
+            let path = step_path(index);
+            let section_end = if index < main.len() {
+                main.len()
+            } else {
+                all_steps.len()
+            };
+
+            let mut never_runs = false;
+            if let Some(condition) = step.get("if").and_then(Value::as_str) {
+                let condition_path = child(&path, "if");
+                self.lint_expression(condition, &condition_path, &scope);
+                if expression_eval::check(condition).is_ok() && is_literal(condition, false) {
+                    never_runs = true;
+                    self.findings.push(Finding::warning(
+                        &condition_path,
+                        "condition is always false; this step never runs",
+                    ));
+                }
+            }
+
+            let mut falls_through = true;
+            if let Some(target) = step.get("fallback_id").and_then(Value::as_str) {
+                match ids.get(target) {
+                    Some(&target) if !never_runs => edges[index].push(target),
+                    Some(_) => {}
+                    None => self.findings.push(Finding::error(
+                        &child(&path, "fallback_id"),
+                        format!("fallback_id refers to unknown step id '{target}'"),
+                    )),
+                }
+            }
+            if let Some(jumps) = step.get("jumps").and_then(Value::as_array) {
+                for (jump_index, jump) in jumps.iter().enumerate() {
+                    let jump_path = child(&child(&path, "jumps"), jump_index);
+                    let condition = jump.get("if").and_then(Value::as_str);
+                    if let Some(condition) = condition {
+                        self.lint_expression(condition, &child(&jump_path, "if"), &scope);
+                    }
+                    let Some(target) = jump.get("to_id").and_then(Value::as_str) else {
+                        continue;
+                    };
+                    match ids.get(target) {
+                        Some(&target) if !never_runs => edges[index].push(target),
+                        Some(_) => {}
+                        None => self.findings.push(Finding::error(
+                            &child(&jump_path, "to_id"),
+                            format!("jump refers to unknown step id '{target}'"),
+                        )),
+                    }
+                    let unconditional = condition.is_some_and(|c| is_literal(c, true));
+                    let survives_failure = step.get("continue_on_error").and_then(Value::as_bool)
+                        == Some(true)
+                        || !stops_on_failure;
+                    if unconditional && !never_runs && !survives_failure {
+                        falls_through = false;
+                    }
+                }
+            }
+            if falls_through && index + 1 < section_end {
+                edges[index].push(index + 1);
+            }
+        }
+
+        let entry = workflow
+            .get("start_from_step")
+            .and_then(Value::as_str)
+            .and_then(|id| ids.get(id).copied())
+            .or(if main.is_empty() { None } else { Some(0) });
+        let mut reachable = vec![false; all_steps.len()];
+        let mut queue: VecDeque<usize> = entry.into_iter().collect();
+        while let Some(index) = queue.pop_front() {
+            if std::mem::replace(&mut reachable[index], true) {
+                continue;
+            }
+            queue.extend(edges[index].iter().copied());
+        }
+        for (index, reached) in reachable.iter().enumerate() {
+            if *reached {
+                continue;
+            }
+            let message = if index < main.len() {
+                "step is unreachable: an earlier step always jumps past it"
+            } else {
+                "troubleshooting step is unreachable: no fallback_id or jump leads to it"
+            };
+            self.findings
+                .push(Finding::warning(&step_path(index), message));
+        }
+
+        if let Some(Value::Object(selectors)) = workflow.get("selectors") {
+            for (name, selector) in selectors {
+                if let Some(selector) = selector.as_str() {
+                    self.lint_selector(selector, &child(&child(base, "selectors"), name));
+                }
+            }
+        }
+    }
+
+    fn lint_tool_use(&mut self, use_: &ToolUse, scope: &Scope) {
+        if let Some(name) = use_.call.get("tool_name").and_then(Value::as_str) {
+            if !self.tools.contains(name) {
+                let mut message = format!("unknown tool '{name}'");
+                if let Some(suggestion) = closest(name, &self.tools) {
+                    message.push_str(&format!(" (did you mean '{suggestion}'?)"));
+                }
+                self.findings
+                    .push(Finding::error(&child(&use_.path, "tool_name"), message));
+            }
+        }
+
+        let Some(arguments) = use_.call.get("arguments") else {
+            return;
+        };
+        let arguments_path = child(&use_.path, "arguments");
+        self.lint_placeholders(arguments, &arguments_path, scope);
+        if let Value::Object(arguments) = arguments {
+            for (key, value) in arguments {
+                let Some(value) = value.as_str() else {
+                    continue;
+                };
+                let value_path = child(&arguments_path, key);
+                if SELECTOR_KEYS.contains(&key.as_str()) {
+                    self.lint_selector(value, &value_path);
+                } else if SELECTOR_LIST_KEYS.contains(&key.as_str()) {
+                    for selector in value.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+                        self.lint_selector(selector, &value_path);
+                    }
+                }
+            }
+        }
+    }
+
+    fn lint_placeholders(&mut self, value: &Value, path: &str, scope: &Scope) {
+        match value {
+            Value::Object(object) => {
+                for (key, value) in object {
+                    self.lint_placeholders(value, &child(path, key), scope);
+                }
+            }
+            Value::Array(items) => {
+                for (index, value) in items.iter().enumerate() {
+                    self.lint_placeholders(value, &child(path, index), scope);
+                }
+            }
+            Value::String(text) => {
+                for captures in placeholder_regex().captures_iter(text) {
+                    let inner = captures[1].trim();
+                    if is_variable_path(inner) {
+                        self.check_reference(inner, path, scope);
+                    } else if looks_like_expression(inner) {
+                        self.lint_expression(inner, path, scope);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn lint_expression(&mut self, expression: &str, path: &str, scope: &Scope) {
+        match expression_eval::check(expression) {
+            Ok(references) => {
+                for reference in references {
+                    self.check_reference(&reference, path, scope);
+                }
+            }
+            Err(reason) => self.findings.push(Finding::error(
+                path,
+                format!("invalid expression '{expression}' (always false): {reason}"),
+            )),
+        }
+    }
+
+    fn check_reference(&mut self, reference: &str, path: &str, scope: &Scope) {
+        if let Some(message) = scope.undefined(reference) {
+            if scope.has_scripts {
+                self.findings.push(Finding::warning(
+                    path,
+                    format!("{message}, unless a script step sets it at runtime"),
+                ));
+            } else {
+                self.findings.push(Finding::error(path, message));
+            }
+        }
+    }
+
+    fn lint_selector(&mut self, selector: &str, path: &str) {
+        for part in selector.split(">>").map(str::trim) {
+            // Placeholders are only known at runtime.
+            if placeholder_regex().is_match(part) {
+                continue;
+            }
+            if let Some(reason) = invalid_reason(&terminator::Selector::from(part)) {
+                self.findings.push(Finding::error(
+                    path,
+                    format!("malformed selector '{selector}': {reason}"),
+                ));
+            }
+        }
+    }
+}
+
+/// Names a workflow can reference in `{{...}}` placeholders and `if` conditions.
+struct Scope {
+    roots: HashSet<String>,
+    env: HashSet<String>,
+    selectors: Option<HashSet<String>>,
+    has_scripts: bool,
+}
+
+impl Scope {
+    fn new(workflow: &Map<String, Value>, uses: &[ToolUse], has_scripts: bool) -> Self {
+        let mut roots = HashSet::new();
+        let mut env = HashSet::new();
+        if let Some(Value::Object(variables)) = workflow.get("variables") {
+            roots.extend(variables.keys().cloned());
+        }
+        if let Some(Value::Object(inputs)) = workflow.get("inputs") {
+            roots.extend(inputs.keys().cloned());
+            env.extend(inputs.keys().cloned());
+        }
+        // Inputs can also be passed when the workflow is run.
+        env.insert("inputs".to_string());
+        let mut ids: Vec<&str> = Vec::new();
+        for list in ["steps", "troubleshooting"] {
+            ids.extend(
+                steps_of(workflow, list)
+                    .into_iter()
+                    .filter_map(|s| s.get("id").and_then(Value::as_str)),
+            );
+        }
+        ids.extend(
+            uses.iter()
+                .filter_map(|u| u.call.get("id").and_then(Value::as_str)),
+        );
+        for id in ids {
+            env.insert(format!("{id}_result"));
+            env.insert(format!("{id}_status"));
+        }
+        env.extend(FAILURE_VARIABLES.iter().map(|v| v.to_string()));
+        // The env is also flattened into the root of the context.
+        roots.extend(env.iter().cloned());
+        roots.insert("env".to_string());
+
+        let selectors = match workflow.get("selectors") {
+            Some(Value::Object(selectors)) => Some(selectors.keys().cloned().collect()),
+            _ => None,
+        };
+        if selectors.is_some() {
+            roots.insert("selectors".to_string());
+        }
+        Self {
+            roots,
+            env,
+            selectors,
+            has_scripts,
+        }
+    }
+
+    /// Describes why `reference` (a dotted variable path) is undefined, if it is.
+    fn undefined(&self, reference: &str) -> Option<String> {
+        let mut parts = reference.split('.');
+        let root = parts.next().unwrap_or_default();
+        let next = parts.next();
+        match root {
+            // Scripts return arbitrary env values, so only script-free workflows are checked.
+            "env" if self.has_scripts => None,
+            "env" => match next {
+                Some(name) if !self.env.contains(name) => Some(format!(
+                    "'{reference}' is not defined: no input or step id sets env.{name}"
+                )),
+                _ => None,
+            },
+            "selectors" if self.selectors.is_some() => match next {
+                Some(name) if !self.selectors.as_ref().is_some_and(|s| s.contains(name)) => Some(
+                    format!("unknown selector '{name}' (not in the workflow's selectors)"),
+                ),
+                _ => None,
+            },
+            _ if self.roots.contains(root) => None,
+            _ => Some(format!(
+                "undefined variable '{root}' (not a variable, input, selectors map or step result)"
+            )),
+        }
+    }
+}
+
+fn steps_of<'a>(workflow: &'a Map<String, Value>, key: &str) -> Vec<&'a Value> {
+    workflow
+        .get(key)
+        .and_then(Value::as_array)
+        .map(|steps| steps.iter().collect())
+        .unwrap_or_default()
+}
+
+fn collect_calls<'a>(
+    owner: &'a Map<String, Value>,
+    key: &str,
+    owner_path: &str,
+    uses: &mut Vec<ToolUse<'a>>,
+) {
+    let Some(calls) = owner.get(key).and_then(Value::as_array) else {
+        return;
+    };
+    for (index, call) in calls.iter().enumerate() {
+        if let Some(call) = call.as_object() {
+            uses.push(ToolUse {
+                path: child(&child(owner_path, key), index),
+                call,
+            });
+        }
+    }
+}
+
+fn is_literal(condition: &str, value: bool) -> bool {
+    let condition = condition.trim();
+    if value {
+        condition == "true" || condition.replace(' ', "") == "always()"
+    } else {
+        condition == "false"
+    }
+}
+
+fn is_variable_path(s: &str) -> bool {
+    !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+}
+
+/// Mirrors the heuristic `substitute_variables` uses to evaluate a placeholder.
+fn looks_like_expression(inner: &str) -> bool {
+    inner.contains('(')
+        || inner.contains("==")
+        || inner.contains("!=")
+        || inner.contains("contains")
+        || inner.contains("startsWith")
+        || inner.contains("endsWith")
+}
+
+fn invalid_reason(selector: &terminator::Selector) -> Option<String> {
+    use terminator::Selector;
+    match selector {
+        Selector::Invalid(reason) => Some(reason.clone()),
+        Selector::Chain(parts) => parts.iter().find_map(invalid_reason),
+        Selector::RightOf(inner)
+        | Selector::LeftOf(inner)
+        | Selector::Above(inner)
+        | Selector::Below(inner)
+        | Selector::Near(inner)
+        | Selector::Has(inner) => invalid_reason(inner),
+        _ => None,
+    }
+}
+
+/// The known name closest to `name`, if it is a plausible typo.
+fn closest<'a>(name: &str, candidates: &'a BTreeSet<String>) -> Option<&'a str> {
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, candidate)| *distance <= (candidate.len() / 3).max(2))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.as_str())
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(source: &str) -> Vec<String> {
+        lint_workflow(source)
+            .into_iter()
+            .map(|d| format!("{}:{} {}: {}", d.line, d.column, d.severity, d.message))
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_workflow_has_no_diagnostics() {
+        let source = r#"
+variables:
+  name:
+    type: string
+    label: Name
+selectors:
+  field: "role:Edit|name:Name"
+steps:
+  - tool_name: open_application
+    arguments:
+      app_name: notepad
+    id: open
+  - tool_name: type_into_element
+    arguments:
+      selector: "{{selectors.field}}"
+      text_to_type: "Hello {{name}}"
+    if: "open_status == 'success'"
+    fallback_id: recover
+troubleshooting:
+  - tool_name: press_key_global
+    arguments:
+      key: "{Escape}"
+    id: recover
+"#;
+        assert_eq!(messages(source), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_reports_positions_and_problems() {
+        let source = r#"tool_name: execute_sequence
+arguments:
+  steps:
+    - tool_name: clik_element
+      arguments:
+        selector: "role:Button|name:OK"
+    - tool_name: open_application
+      arguments:
+        app_name: 42
+        window: main
+      jumps:
+        - if: "count > 3"
+          to_id: missing
+    - tool_name: click_element
+      arguments:
+        selector: "bogus"
+        text: "{{undefined_var}}"
+"#;
+        let found = messages(source);
+        let expected = [
+            "4:18 error: unknown tool 'clik_element' (did you mean 'click_element'?)",
+            "9:19 error: expected string, found integer",
+            "10:9 warning: unknown field 'window' is ignored",
+            "12:15 error: invalid expression 'count > 3' (always false)",
+            "13:18 error: jump refers to unknown step id 'missing'",
+            "16:19 error: malformed selector 'bogus'",
+            "17:15 error: undefined variable 'undefined_var'",
+        ];
+        for expected in expected {
+            assert!(
+                found.iter().any(|f| f.starts_with(expected)),
+                "missing '{expected}' in {found:#?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_reachability() {
+        let source = r#"
+steps:
+  - tool_name: press_key_global
+    arguments: { key: "{Enter}" }
+    jumps:
+      - if: "true"
+        to_id: last
+  - tool_name: press_key_global
+    arguments: { key: "{Tab}" }
+  - tool_name: press_key_global
+    arguments: { key: "{Tab}" }
+    id: last
+troubleshooting:
+  - tool_name: press_key_global
+    arguments: { key: "{Escape}" }
+"#;
+        let found = messages(source);
+        assert!(found
+            .iter()
+            .any(|f| f.starts_with("8:5 warning: step is unreachable")));
+        assert!(found
+            .iter()
+            .any(|f| f.starts_with("14:5 warning: troubleshooting step is unreachable")));
+        assert_eq!(found.len(), 2, "{found:#?}");
+    }
+
+    #[test]
+    fn test_invalid_yaml_reports_location() {
+        let diagnostics = lint_workflow("steps:\n  - tool_name: [unclosed\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert!(diagnostics[0].line >= 2);
+    }
+}
@@ -0,0 +1,545 @@
+//! Turns the literal values of a recording into workflow variables.
+//!
+//! A recording of "create customer Mario Rossi" hard-codes the typed name. This pass finds
+//! the values a human entered (typed or pasted text, dropdown and suggestion choices),
+//! proposes a `variables:` definition for each, and rewrites the converted steps to use
+//! `{{inputs.<name>}}` with the recorded value as the default.
+
+use crate::utils::{VariableDefinition, VariableType};
+use crate::workflow_converter::{convert_workflow_to_mcp, McpWorkflow};
+use crate::workflow_events::{
+    ClipboardAction, McpToolStep, RecordedWorkflow, TextInputMethod, WorkflowEvent,
+};
+use anyhow::Result;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::sync::OnceLock;
+
+/// Roles of list entries clicked to choose a value.
+const CHOICE_ROLES: &[&str] = &["listitem", "menuitem", "comboboxitem", "option", "dataitem"];
+
+/// How the recorded value was entered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValueSource {
+    Typed,
+    Pasted,
+    Choice,
+}
+
+/// A recorded literal proposed as a workflow variable.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProposedVariable {
+    pub name: String,
+    /// The value as it was recorded.
+    pub value: String,
+    pub source: ValueSource,
+    pub definition: VariableDefinition,
+}
+
+/// Propose a variable for every value entered in `workflow`, in recording order.
+pub fn propose_variables(workflow: &RecordedWorkflow) -> Vec<ProposedVariable> {
+    let mut proposals: Vec<ProposedVariable> = Vec::new();
+    let mut last_paste: Option<&str> = None;
+    let mut last_combo_box: Option<&str> = None;
+
+    for recorded in &workflow.events {
+        match &recorded.event {
+            WorkflowEvent::Clipboard(event) if event.action == ClipboardAction::Paste => {
+                last_paste = event.content.as_deref().filter(|_| !event.truncated);
+            }
+            WorkflowEvent::TextInputCompleted(event) => {
+                let value = event.text_value.trim();
+                if value.is_empty() {
+                    continue;
+                }
+                let label = event
+                    .field_name
+                    .as_deref()
+                    .map(str::trim)
+                    .filter(|n| !n.is_empty())
+                    .unwrap_or(&event.field_type);
+                let source = if event.input_method == TextInputMethod::Suggestion {
+                    ValueSource::Choice
+                } else if event.input_method == TextInputMethod::Pasted
+                    || last_paste.is_some_and(|p| !p.is_empty() && value.contains(p.trim()))
+                {
+                    ValueSource::Pasted
+                } else {
+                    ValueSource::Typed
+                };
+                last_paste = None;
+                propose(&mut proposals, label, &event.text_value, source);
+            }
+            WorkflowEvent::Click(event) => {
+                let role = event.element_role.to_lowercase().replace([' ', '_'], "");
+                if role == "combobox" {
+                    last_combo_box = Some(event.element_text.trim()).filter(|t| !t.is_empty());
+                } else if CHOICE_ROLES.contains(&role.as_str())
+                    && !event.element_text.trim().is_empty()
+                {
+                    let label = last_combo_box.take().unwrap_or("Option");
+                    propose(
+                        &mut proposals,
+                        label,
+                        &event.element_text,
+                        ValueSource::Choice,
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+    proposals
+}
+
+fn propose(proposals: &mut Vec<ProposedVariable>, label: &str, value: &str, source: ValueSource) {
+    let base = variable_name(label);
+    let mut name = base.clone();
+    let mut n = 1;
+    while proposals.iter().any(|p| p.name == name) {
+        n += 1;
+        name = format!("{base}_{n}");
+    }
+
+    // Choices end up inside selectors, where only text can be substituted.
+    let (r#type, default, regex) = match source {
+        ValueSource::Choice => (VariableType::String, json!(value), None),
+        _ => infer_type(value),
+    };
+    // A value masked by the recorder has to be supplied when the workflow runs.
+    let masked = is_mask_token(value);
+    let how = match source {
+        ValueSource::Typed => "typed into",
+        ValueSource::Pasted => "pasted into",
+        ValueSource::Choice => "chosen for",
+    };
+    proposals.push(ProposedVariable {
+        name,
+        value: value.to_string(),
+        source,
+        definition: VariableDefinition {
+            r#type,
+            label: label.to_string(),
+            description: Some(if masked {
+                format!("Value {how} '{label}'; masked when the workflow was recorded.")
+            } else {
+                format!("Value {how} '{label}' when the workflow was recorded.")
+            }),
+            default: (!masked).then_some(default),
+            regex: regex.filter(|_| !masked),
+            options: None,
+            required: Some(masked),
+        },
+    });
+}
+
+/// Whether `value` is a token such as `[PASSWORD_1]` that the recorder put in place of a
+/// password or personal data.
+pub(crate) fn is_mask_token(value: &str) -> bool {
+    value
+        .strip_prefix('[')
+        .and_then(|v| v.strip_suffix(']'))
+        .and_then(|v| v.rsplit_once('_'))
+        .is_some_and(|(kind, n)| {
+            !kind.is_empty()
+                && kind.chars().all(|c| c.is_ascii_uppercase() || c == '_')
+                && !n.is_empty()
+                && n.chars().all(|c| c.is_ascii_digit())
+        })
+}
+
+/// `snake_case` identifier for a field label, e.g. "First (Given) Name" -> `first_given_name`.
+fn variable_name(label: &str) -> String {
+    let mut name = String::new();
+    for c in label.chars() {
+        if c.is_ascii_alphanumeric() {
+            name.push(c.to_ascii_lowercase());
+        } else if !name.is_empty() && !name.ends_with('_') {
+            name.push('_');
+        }
+        if name.len() >= 40 {
+            break;
+        }
+    }
+    let name = name.trim_end_matches('_');
+    match name.chars().next() {
+        None => "value".to_string(),
+        Some(c) if c.is_ascii_digit() => format!("field_{name}"),
+        Some(_) => name.to_string(),
+    }
+}
+
+/// Type, default and validation pattern for a typed value.
+fn infer_type(value: &str) -> (VariableType, Value, Option<String>) {
+    static NUMBER: OnceLock<Regex> = OnceLock::new();
+    let number = NUMBER.get_or_init(|| Regex::new(r"^-?(0|[1-9]\d{0,8})(\.\d+)?$").unwrap());
+    if number.is_match(value) {
+        if let Ok(n) = value.parse::<i64>() {
+            return (VariableType::Number, json!(n), None);
+        }
+        // Only when the number prints back as typed: "19.90" or "3.10" stay strings.
+        if let Some(n) = value
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .filter(|n| n.to_string() == value)
+        {
+            return (VariableType::Number, Value::Number(n), None);
+        }
+    }
+    if value == "true" || value == "false" {
+        return (VariableType::Boolean, json!(value == "true"), None);
+    }
+
+    static PATTERNS: OnceLock<Vec<(Regex, &'static str)>> = OnceLock::new();
+    let patterns = PATTERNS.get_or_init(|| {
+        [
+            r"^[^@\s]+@[^@\s]+\.[^@\s]+$",
+            r"^\d{4}-\d{2}-\d{2}$",
+            r"^\d{2}/\d{2}/\d{4}$",
+            r"^\d{2}\.\d{2}\.\d{4}$",
+            r"^\d+$",
+            r"^\+?[\d\s().-]{7,}$",
+        ]
+        .into_iter()
+        .map(|p| (Regex::new(p).unwrap(), p))
+        .collect()
+    });
+    let regex = patterns
+        .iter()
+        .find(|(re, _)| re.is_match(value))
+        .map(|(_, p)| p.to_string());
+    (VariableType::String, json!(value), regex)
+}
+
+/// Replace recorded values in `steps` with `{{inputs.<name>}}` references, matching steps
+/// and variables in recording order. Returns the variables that were used.
+pub fn apply_variables(
+    steps: &mut [McpToolStep],
+    variables: Vec<ProposedVariable>,
+) -> Vec<ProposedVariable> {
+    let mut pending: Vec<Option<ProposedVariable>> = variables.into_iter().map(Some).collect();
+    let mut used = Vec::new();
+    // The field of the last substituted value, whose checks expect the variable
+    let mut checked_field: Option<(String, String, String)> = None;
+    for step in steps.iter_mut() {
+        if step.tool_name == "wait_for_element" {
+            if let Some((selector, value, reference)) = &checked_field {
+                if step.arguments["selector"].as_str() == Some(selector.as_str())
+                    && step.arguments["expected_value"].as_str() == Some(value.as_str())
+                {
+                    step.arguments["expected_value"] = json!(reference);
+                    if let Some(description) = &mut step.description {
+                        *description = description.replace(value.as_str(), reference);
+                    }
+                }
+            }
+            continue;
+        }
+        checked_field = None;
+        let Some(slot) = pending
+            .iter_mut()
+            .find(|v| v.as_ref().is_some_and(|v| matches_step(step, v)))
+        else {
+            continue;
+        };
+        let variable = slot.take().unwrap();
+        let reference = format!("{{{{inputs.{}}}}}", variable.name);
+        if step.tool_name == "type_into_element" || step.tool_name == "select_option" {
+            let key = if step.tool_name == "type_into_element" {
+                "text_to_type"
+            } else {
+                "option_name"
+            };
+            step.arguments[key] = json!(reference);
+            if let Some(selector) = step.arguments["selector"].as_str() {
+                checked_field = Some((
+                    selector.to_string(),
+                    variable.value.clone(),
+                    reference.clone(),
+                ));
+            }
+        } else if let Some(selector) = step.arguments["selector"].as_str() {
+            step.arguments["selector"] =
+                json!(replace_last_name(selector, &variable.value, &reference));
+        }
+        if let Some(description) = &mut step.description {
+            *description = description.replace(&variable.value, &reference);
+        }
+        used.push(variable);
+    }
+    used
+}
+
+fn matches_step(step: &McpToolStep, variable: &ProposedVariable) -> bool {
+    match step.tool_name.as_str() {
+        "type_into_element" => {
+            variable.source != ValueSource::Choice
+                && step.arguments["text_to_type"].as_str() == Some(variable.value.as_str())
+        }
+        "select_option" => {
+            variable.source == ValueSource::Choice
+                && step.arguments["option_name"].as_str() == Some(variable.value.as_str())
+        }
+        "click_element" => {
+            variable.source == ValueSource::Choice
+                && step.arguments["selector"].as_str().is_some_and(|selector| {
+                    replace_last_name(selector, &variable.value, "") != selector
+                })
+        }
+        _ => false,
+    }
+}
+
+/// Replaces `name:<value>` in the innermost part of a `>>` chained selector.
+fn replace_last_name(selector: &str, value: &str, replacement: &str) -> String {
+    let (scope, last) = match selector.rsplit_once(" >> ") {
+        Some((scope, last)) => (Some(scope), last),
+        None => (None, selector),
+    };
+    let parts: Vec<String> = last
+        .split('|')
+        .map(|part| {
+            if part.trim() == format!("name:{value}") {
+                format!("name:{replacement}")
+            } else {
+                part.to_string()
+            }
+        })
+        .collect();
+    let last = parts.join("|");
+    match scope {
+        Some(scope) => format!("{scope} >> {last}"),
+        None => last,
+    }
+}
+
+/// Convert `workflow` to MCP steps that take the recorded values as inputs.
+pub async fn convert_workflow_with_variables(
+    workflow: RecordedWorkflow,
+) -> Result<(McpWorkflow, Vec<ProposedVariable>)> {
+    let proposals = propose_variables(&workflow);
+    let mut mcp_workflow = convert_workflow_to_mcp(workflow).await?;
+    let variables = apply_variables(&mut mcp_workflow.steps, proposals);
+    Ok((mcp_workflow, variables))
+}
+
+/// The `variables` and `inputs` maps of an `execute_sequence` call for `variables`.
+/// Inputs repeat the defaults so `{{inputs.<name>}}` resolves when none are passed.
+pub fn variables_and_inputs(variables: &[ProposedVariable]) -> (Value, Value) {
+    let mut definitions = Map::new();
+    let mut inputs = Map::new();
+    for variable in variables {
+        definitions.insert(
+            variable.name.clone(),
+            serde_json::to_value(&variable.definition).unwrap_or_default(),
+        );
+        if let Some(default) = &variable.definition.default {
+            inputs.insert(variable.name.clone(), default.clone());
+        }
+    }
+    (Value::Object(definitions), Value::Object(inputs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workflow_events::{
+        ButtonInteractionType, ClickEvent, EventMetadata, FieldFocusMethod, RecordedEvent,
+        TextInputCompletedEvent,
+    };
+
+    fn typed(field: &str, value: &str, method: TextInputMethod) -> WorkflowEvent {
+        WorkflowEvent::TextInputCompleted(TextInputCompletedEvent {
+            text_value: value.to_string(),
+            field_name: Some(field.to_string()),
+            field_type: "Edit".to_string(),
+            input_method: method,
+            focus_method: FieldFocusMethod::MouseClick,
+            typing_duration_ms: 500,
+            keystroke_count: value.len() as u32,
+            metadata: EventMetadata::empty(),
+        })
+    }
+
+    fn click(role: &str, text: &str) -> WorkflowEvent {
+        WorkflowEvent::Click(ClickEvent {
+            element_text: text.to_string(),
+            interaction_type: ButtonInteractionType::Click,
+            element_role: role.to_string(),
+            was_enabled: true,
+            click_position: None,
+            element_description: None,
+            child_text_content: vec![],
+            relative_position: None,
+            metadata: EventMetadata::empty(),
+        })
+    }
+
+    fn recording(events: Vec<WorkflowEvent>) -> RecordedWorkflow {
+        RecordedWorkflow {
+            name: "Create customer".to_string(),
+            start_time: 0,
+            end_time: None,
+            events: events
+                .into_iter()
+                .map(|event| RecordedEvent {
+                    timestamp: 0,
+                    event,
+                    metadata: None,
+                })
+                .collect(),
+            markers: vec![],
+        }
+    }
+
+    #[test]
+    fn test_propose_variables() {
+        let proposals = propose_variables(&recording(vec![
+            typed("First (Given) Name", "Mario", TextInputMethod::Typed),
+            typed("Last name", "Rossi", TextInputMethod::Pasted),
+            typed("Age", "42", TextInputMethod::Typed),
+            typed("E-mail", "mario@example.com", TextInputMethod::Typed),
+            typed("Last name", "Bianchi", TextInputMethod::Typed),
+            click("ComboBox", "Country"),
+            click("ListItem", "Italy"),
+        ]));
+
+        let names: Vec<_> = proposals.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "first_given_name",
+                "last_name",
+                "age",
+                "e_mail",
+                "last_name_2",
+                "country"
+            ]
+        );
+        assert_eq!(proposals[1].source, ValueSource::Pasted);
+        assert!(matches!(
+            proposals[2].definition.r#type,
+            VariableType::Number
+        ));
+        assert_eq!(proposals[2].definition.default, Some(json!(42)));
+        assert!(proposals[3]
+            .definition
+            .regex
+            .as_deref()
+            .unwrap()
+            .contains('@'));
+        assert_eq!(proposals[5].source, ValueSource::Choice);
+        assert_eq!(proposals[5].definition.label, "Country");
+        assert_eq!(proposals[5].value, "Italy");
+
+        // Values masked by the recorder become required inputs without a default.
+        let masked = propose_variables(&recording(vec![typed(
+            "Password",
+            "[PASSWORD_1]",
+            TextInputMethod::Typed,
+        )]));
+        assert_eq!(masked[0].definition.default, None);
+        assert_eq!(masked[0].definition.required, Some(true));
+    }
+
+    #[test]
+    fn test_decimals_keep_their_text() {
+        assert_eq!(infer_type("2.5").1, json!(2.5));
+        for value in ["19.90", "3.10", "0.50"] {
+            let (r#type, default, _) = infer_type(value);
+            assert!(matches!(r#type, VariableType::String), "{value}");
+            assert_eq!(default, json!(value));
+        }
+    }
+
+    #[test]
+    fn test_apply_variables_in_order() {
+        let proposals = propose_variables(&recording(vec![
+            typed("Name", "Rossi", TextInputMethod::Typed),
+            typed("Surname", "Rossi", TextInputMethod::Typed),
+            click("ListItem", "Italy"),
+        ]));
+        let step = |tool: &str, arguments: Value| McpToolStep {
+            tool_name: tool.to_string(),
+            arguments,
+            description: None,
+            timeout_ms: None,
+            continue_on_error: None,
+            delay_ms: None,
+        };
+        let mut steps = vec![
+            step(
+                "type_into_element",
+                json!({"selector": "role:Edit|name:Name", "text_to_type": "Rossi"}),
+            ),
+            step(
+                "type_into_element",
+                json!({"selector": "role:Edit|name:Surname", "text_to_type": "Rossi"}),
+            ),
+            step(
+                "wait_for_element",
+                json!({"selector": "role:Edit|name:Surname", "expected_value": "Rossi"}),
+            ),
+            step(
+                "click_element",
+                json!({"selector": "role:Window|name:Italy >> role:ListItem|name:Italy"}),
+            ),
+        ];
+
+        let used = apply_variables(&mut steps, proposals);
+
+        assert_eq!(used.len(), 3);
+        assert_eq!(steps[0].arguments["text_to_type"], "{{inputs.name}}");
+        assert_eq!(steps[1].arguments["text_to_type"], "{{inputs.surname}}");
+        assert_eq!(steps[2].arguments["expected_value"], "{{inputs.surname}}");
+        assert_eq!(
+            steps[3].arguments["selector"],
+            "role:Window|name:Italy >> role:ListItem|name:{{inputs.option}}"
+        );
+        let (variables, inputs) = variables_and_inputs(&used);
+        assert_eq!(variables["surname"]["type"], "string");
+        assert_eq!(inputs["option"], "Italy");
+    }
+
+    #[tokio::test]
+    async fn test_convert_workflow_with_variables() {
+        let (mcp_workflow, variables) = convert_workflow_with_variables(recording(vec![
+            click("Edit", "Customer name"),
+            typed("Customer name", "Mario Rossi", TextInputMethod::Typed),
+            click("ComboBox", "Currency"),
+            click("ListItem", "EUR"),
+            click("ListItem", "Italy"),
+        ]))
+        .await
+        .unwrap();
+
+        let names: Vec<_> = variables.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(names, ["customer_name", "currency", "option"]);
+        // The focus click is dropped and the combo box choice becomes select_option.
+        let tools: Vec<_> = mcp_workflow
+            .steps
+            .iter()
+            .map(|s| s.tool_name.as_str())
+            .collect();
+        assert_eq!(
+            tools,
+            ["type_into_element", "select_option", "click_element"]
+        );
+        assert_eq!(
+            mcp_workflow.steps[1].arguments["option_name"],
+            "{{inputs.currency}}"
+        );
+        let text = mcp_workflow
+            .steps
+            .iter()
+            .find(|s| s.tool_name == "type_into_element")
+            .unwrap();
+        assert_eq!(text.arguments["text_to_type"], "{{inputs.customer_name}}");
+        let steps = serde_json::to_string(&mcp_workflow.steps).unwrap();
+        assert!(!steps.contains("Mario Rossi"));
+        assert!(steps.contains("name:{{inputs.option}}"));
+    }
+}
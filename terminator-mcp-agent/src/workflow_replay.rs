@@ -0,0 +1,467 @@
+//! Offline replay of workflows against recorded UI-tree fixtures.
+//!
+//! A run with `capture_fixtures` writes one JSON fixture per tool call: the focused
+//! window's UI tree just before the call (without password field contents), the call's
+//! arguments (with secrets masked as in the audit log) and its result. Only the results
+//! replay returns are kept in full, with secret-looking keys masked; other calls keep
+//! just their status.
+//! [`run_workflow_test`] then runs the same workflow through `execute_sequence` on a
+//! [`FixtureEngine`] desktop: each call loads its fixture, selectors resolve against the
+//! recorded tree, and UI actions are collected instead of performed. Tools whose effect
+//! can't be reproduced from a tree (scripts, screenshots) return their recorded result.
+//! The resulting [report](run_workflow_test) is compared with a golden file.
+
+use crate::utils::DesktopWrapper;
+use rmcp::model::{CallToolRequestParam, CallToolResult, RawContent};
+use rmcp::{ErrorData as McpError, ServiceExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use terminator::platforms::fixture::{FixtureAction, FixtureEngine, FixtureWindow};
+use terminator::Desktop;
+use tokio::sync::Mutex;
+use tracing::warn;
+
+/// Name of the expected report inside a fixtures directory.
+pub const GOLDEN_FILE: &str = "golden.json";
+
+/// Tools that replay returns the recorded result for instead of running.
+pub const RECORDED_TOOLS: &[&str] = &[
+    "run_command",
+    "execute_browser_script",
+    "capture_element_screenshot",
+    "record_workflow",
+];
+
+/// One recorded tool call.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fixture {
+    pub step_index: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step_id: Option<String>,
+    pub tool_name: String,
+    pub arguments: Value,
+    /// The focused window before the call; absent when it could not be captured.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window: Option<FixtureWindow>,
+    pub result: RecordedResult,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordedResult {
+    Success(CallToolResult),
+    Error(McpError),
+}
+
+impl RecordedResult {
+    /// What a fixture keeps of `tool`'s result. Replay returns the results of
+    /// [`RECORDED_TOOLS`], so those are kept with secret-looking keys masked (their
+    /// `env` output feeds later steps and can't be masked wholesale like the
+    /// arguments); other tools keep only whether they succeeded.
+    fn capture(tool: &str, result: &Result<CallToolResult, McpError>) -> Self {
+        let keep = RECORDED_TOOLS.contains(&tool);
+        match result {
+            Ok(result) if keep => {
+                let mut result = result.clone();
+                for content in &mut result.content {
+                    if let RawContent::Text(text) = &mut content.raw {
+                        if let Ok(parsed) = serde_json::from_str::<Value>(&text.text) {
+                            text.text = crate::audit::redact(&parsed).to_string();
+                        }
+                    }
+                }
+                result.structured_content =
+                    result.structured_content.as_ref().map(crate::audit::redact);
+                RecordedResult::Success(result)
+            }
+            Ok(result) => RecordedResult::Success(CallToolResult {
+                content: Vec::new(),
+                structured_content: None,
+                is_error: result.is_error,
+                meta: None,
+            }),
+            Err(e) => {
+                let mut e = e.clone();
+                e.data = e.data.as_ref().filter(|_| keep).map(crate::audit::redact);
+                RecordedResult::Error(e)
+            }
+        }
+    }
+}
+
+/// A replayed tool call, as it appears in the report.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayedCall {
+    pub step_index: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step_id: Option<String>,
+    pub tool_name: String,
+    /// Arguments after variable substitution; omitted for recorded tools.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<Value>,
+    pub status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub actions: Vec<FixtureAction>,
+    /// No fixture matched this call, so it ran against the previous call's tree.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub missing_fixture: bool,
+}
+
+/// What a server does with the tool calls of `execute_sequence`.
+pub enum FixtureSession {
+    Capture {
+        dir: PathBuf,
+        calls: AtomicUsize,
+    },
+    Replay {
+        engine: Arc<FixtureEngine>,
+        fixtures: Vec<Fixture>,
+        state: Mutex<ReplayState>,
+    },
+}
+
+#[derive(Default)]
+pub struct ReplayState {
+    cursor: usize,
+    calls: Vec<ReplayedCall>,
+}
+
+/// A tool call in progress; see [`FixtureSession::begin`].
+pub struct FixtureCall {
+    number: usize,
+    step_index: usize,
+    step_id: Option<String>,
+    tool_name: String,
+    arguments: Value,
+    window: Option<FixtureWindow>,
+    fixture: Option<usize>,
+}
+
+impl FixtureSession {
+    /// Record every call into `dir`, creating it if needed.
+    pub fn capture(dir: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        Ok(FixtureSession::Capture {
+            dir,
+            calls: AtomicUsize::new(0),
+        })
+    }
+
+    /// Replay `fixtures` (in recording order) on `engine`.
+    pub fn replay(engine: Arc<FixtureEngine>, fixtures: Vec<Fixture>) -> Self {
+        FixtureSession::Replay {
+            engine,
+            fixtures,
+            state: Mutex::new(ReplayState::default()),
+        }
+    }
+
+    /// Called before a tool runs: snapshots the focused window when capturing, or loads
+    /// the matching fixture into the engine when replaying.
+    pub async fn begin(
+        &self,
+        desktop: &Arc<Desktop>,
+        step_index: usize,
+        step_id: Option<&str>,
+        tool_name: &str,
+        arguments: &Value,
+    ) -> FixtureCall {
+        let mut call = FixtureCall {
+            number: 0,
+            step_index,
+            step_id: step_id.map(str::to_string),
+            tool_name: tool_name.to_string(),
+            arguments: arguments.clone(),
+            window: None,
+            fixture: None,
+        };
+        match self {
+            FixtureSession::Capture { calls, .. } => {
+                call.number = calls.fetch_add(1, Ordering::SeqCst);
+                let desktop = desktop.clone();
+                call.window =
+                    match tokio::task::spawn_blocking(move || FixtureWindow::capture(&desktop))
+                        .await
+                    {
+                        Ok(Ok(window)) => Some(window),
+                        Ok(Err(e)) => {
+                            warn!("Could not capture a fixture before '{tool_name}': {e}");
+                            None
+                        }
+                        Err(e) => {
+                            warn!("Fixture capture before '{tool_name}' panicked: {e}");
+                            None
+                        }
+                    };
+            }
+            FixtureSession::Replay {
+                engine,
+                fixtures,
+                state,
+            } => {
+                let mut state = state.lock().await;
+                let matches = |f: &Fixture| {
+                    f.tool_name == tool_name
+                        && match (&f.step_id, step_id) {
+                            (Some(recorded), Some(id)) => recorded == id,
+                            _ => f.step_index == step_index,
+                        }
+                };
+                // Prefer the next recording; a step run again after a jump back reuses
+                // an earlier one.
+                call.fixture = fixtures[state.cursor..]
+                    .iter()
+                    .position(matches)
+                    .map(|i| i + state.cursor)
+                    .or_else(|| fixtures.iter().position(matches));
+                if let Some(index) = call.fixture {
+                    state.cursor = state.cursor.max(index + 1);
+                    engine.load(fixtures[index].window.clone());
+                }
+                engine.take_actions();
+            }
+        }
+        call
+    }
+
+    /// The result to use instead of running the tool, for recorded tools during replay.
+    pub fn recorded_result(&self, call: &FixtureCall) -> Option<Result<CallToolResult, McpError>> {
+        let FixtureSession::Replay { fixtures, .. } = self else {
+            return None;
+        };
+        if !RECORDED_TOOLS.contains(&call.tool_name.as_str()) {
+            return None;
+        }
+        Some(match call.fixture.map(|i| &fixtures[i].result) {
+            Some(RecordedResult::Success(result)) => Ok(result.clone()),
+            Some(RecordedResult::Error(e)) => Err(e.clone()),
+            None => Err(McpError::internal_error(
+                format!(
+                    "No recorded result for '{}' in the fixtures",
+                    call.tool_name
+                ),
+                Some(json!({"step_index": call.step_index, "step_id": call.step_id})),
+            )),
+        })
+    }
+
+    /// Called after a tool ran: writes the fixture when capturing, or adds the call and
+    /// its actions to the report when replaying.
+    pub async fn finish(&self, call: FixtureCall, result: &Result<CallToolResult, McpError>) {
+        match self {
+            FixtureSession::Capture { dir, .. } => {
+                let path = dir.join(format!("{:04}-{}.json", call.number, call.tool_name));
+                let fixture = Fixture {
+                    step_index: call.step_index,
+                    step_id: call.step_id,
+                    arguments: crate::audit::redact_arguments(&call.tool_name, &call.arguments),
+                    result: RecordedResult::capture(&call.tool_name, result),
+                    tool_name: call.tool_name,
+                    window: call.window,
+                };
+                let written = serde_json::to_vec_pretty(&fixture)
+                    .map_err(std::io::Error::from)
+                    .and_then(|json| std::fs::write(&path, json));
+                if let Err(e) = written {
+                    warn!("Failed to write fixture {}: {e}", path.display());
+                }
+            }
+            FixtureSession::Replay { engine, state, .. } => {
+                let recorded = RECORDED_TOOLS.contains(&call.tool_name.as_str());
+                let replayed = ReplayedCall {
+                    step_index: call.step_index,
+                    step_id: call.step_id,
+                    arguments: (!recorded).then_some(call.arguments),
+                    tool_name: call.tool_name,
+                    status: if result.is_ok() { "success" } else { "error" }.to_string(),
+                    error: result.as_ref().err().map(|e| e.message.to_string()),
+                    actions: engine.take_actions(),
+                    missing_fixture: call.fixture.is_none(),
+                };
+                state.lock().await.calls.push(replayed);
+            }
+        }
+    }
+}
+
+/// The capture session of one `execute_sequence` request, kept in the request's
+/// extensions so concurrent runs record into their own directories.
+#[derive(Clone)]
+pub struct FixtureCapture(pub Arc<FixtureSession>);
+
+/// Load the fixtures in `dir`, ordered by file name (the recording order).
+pub fn load_fixtures(dir: &Path) -> anyhow::Result<Vec<Fixture>> {
+    let mut paths = Vec::new();
+    for entry in std::fs::read_dir(dir)
+        .map_err(|e| anyhow::anyhow!("failed to read {}: {e}", dir.display()))?
+    {
+        let path = entry?.path();
+        let is_fixture = path.extension().is_some_and(|ext| ext == "json")
+            && path.file_name().is_some_and(|name| name != GOLDEN_FILE);
+        if is_fixture {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    paths
+        .iter()
+        .map(|path| {
+            let text = std::fs::read_to_string(path)?;
+            serde_json::from_str(&text)
+                .map_err(|e| anyhow::anyhow!("invalid fixture {}: {e}", path.display()))
+        })
+        .collect()
+}
+
+/// Run `workflow` through `execute_sequence` against the fixtures in `fixtures_dir` and
+/// return the report: final status, every tool call with its substituted arguments,
+/// outcome and UI actions, and the parsed output.
+pub async fn run_workflow_test(
+    workflow: &Path,
+    fixtures_dir: &Path,
+    inputs: Option<Value>,
+) -> anyhow::Result<Value> {
+    let fixtures = load_fixtures(fixtures_dir)?;
+    if fixtures.is_empty() {
+        anyhow::bail!(
+            "no fixtures in {}; record some with capture_fixtures first",
+            fixtures_dir.display()
+        );
+    }
+    let engine = Arc::new(FixtureEngine::new());
+    let session = Arc::new(FixtureSession::replay(engine.clone(), fixtures));
+    let server = DesktopWrapper::with_desktop(Desktop::with_engine(engine), None)
+        .with_fixture_replay(session.clone());
+
+    let workflow = std::fs::canonicalize(workflow)
+        .map_err(|e| anyhow::anyhow!("failed to resolve {}: {e}", workflow.display()))?;
+    let mut arguments = serde_json::Map::new();
+    arguments.insert(
+        "url".to_string(),
+        json!(format!("file://{}", workflow.display())),
+    );
+    if let Some(inputs) = inputs {
+        arguments.insert("inputs".to_string(), inputs);
+    }
+
+    let (server_io, client_io) = tokio::io::duplex(1 << 20);
+    let server_task = tokio::spawn(async move {
+        match server.serve(server_io).await {
+            Ok(running) => {
+                let _ = running.waiting().await;
+            }
+            Err(e) => warn!("Replay server failed to start: {e}"),
+        }
+    });
+    let client = ().serve(client_io).await?;
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "execute_sequence".into(),
+            arguments: Some(arguments),
+        })
+        .await;
+    let _ = client.cancel().await;
+    let _ = server_task.await;
+
+    let summary = result
+        .map_err(|e| anyhow::anyhow!("execute_sequence failed: {e}"))?
+        .content
+        .first()
+        .and_then(|content| crate::server::extract_content_json(content).ok())
+        .unwrap_or(Value::Null);
+    let calls = match &*session {
+        FixtureSession::Replay { state, .. } => std::mem::take(&mut state.lock().await.calls),
+        FixtureSession::Capture { .. } => Vec::new(),
+    };
+
+    let mut report = serde_json::Map::new();
+    report.insert("status".to_string(), summary["status"].clone());
+    for key in ["used_fallback", "parsed_output", "parser_error"] {
+        if let Some(value) = summary.get(key) {
+            report.insert(key.to_string(), value.clone());
+        }
+    }
+    report.insert("calls".to_string(), serde_json::to_value(calls)?);
+    Ok(Value::Object(report))
+}
+
+/// Differences between a golden report and an actual one, as `path: expected ..., got ...`.
+pub fn compare_reports(expected: &Value, actual: &Value) -> Vec<String> {
+    fn walk(path: &str, expected: &Value, actual: &Value, out: &mut Vec<String>) {
+        match (expected, actual) {
+            (Value::Object(e), Value::Object(a)) => {
+                for (key, value) in e {
+                    let child = format!("{path}/{key}");
+                    match a.get(key) {
+                        Some(actual) => walk(&child, value, actual, out),
+                        None => out.push(format!("{child}: expected {value}, got nothing")),
+                    }
+                }
+                for (key, value) in a.iter().filter(|(key, _)| !e.contains_key(*key)) {
+                    out.push(format!("{path}/{key}: unexpected {value}"));
+                }
+            }
+            (Value::Array(e), Value::Array(a)) => {
+                for (index, (expected, actual)) in e.iter().zip(a).enumerate() {
+                    walk(&format!("{path}/{index}"), expected, actual, out);
+                }
+                if e.len() != a.len() {
+                    out.push(format!(
+                        "{}: expected {} entries, got {}",
+                        if path.is_empty() { "/" } else { path },
+                        e.len(),
+                        a.len()
+                    ));
+                }
+            }
+            _ if expected != actual => {
+                let path = if path.is_empty() { "/" } else { path };
+                out.push(format!("{path}: expected {expected}, got {actual}"));
+            }
+            _ => {}
+        }
+    }
+    let mut out = Vec::new();
+    walk("", expected, actual, &mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_reports_lists_paths() {
+        let golden = json!({
+            "status": "success",
+            "calls": [
+                {"tool_name": "click_element", "actions": [{"action": "click", "target": "Button|OK"}]},
+                {"tool_name": "press_key"}
+            ]
+        });
+        assert!(compare_reports(&golden, &golden).is_empty());
+
+        let actual = json!({
+            "status": "failed",
+            "calls": [
+                {"tool_name": "click_element", "actions": [{"action": "click", "target": "Button|Cancel"}]}
+            ],
+            "parser_error": "boom"
+        });
+        assert_eq!(
+            compare_reports(&golden, &actual),
+            vec![
+                "/status: expected \"success\", got \"failed\"",
+                "/calls/0/actions/0/target: expected \"Button|OK\", got \"Button|Cancel\"",
+                "/calls: expected 2 entries, got 1",
+                "/parser_error: unexpected \"boom\"",
+            ]
+        );
+    }
+}
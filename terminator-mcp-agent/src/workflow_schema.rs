@@ -0,0 +1,200 @@
+//! JSON Schema for workflow files run by `execute_sequence`.
+//!
+//! The schema is generated from the same `schemars` types the server uses to parse
+//! workflows, with each step's `arguments` constrained by the input schema of the tool
+//! named in `tool_name`.
+
+use crate::utils::{DesktopWrapper, ExecuteSequenceArgs};
+use rmcp::handler::server::common::schema_for_type;
+use serde_json::{json, Map, Value};
+
+/// Top-level keys found in workflow files that `execute_sequence` itself ignores.
+pub const METADATA_KEYS: &[&str] = &["name", "description", "version", "cron"];
+
+/// Name of the definition holding the argument schema of `tool_name`.
+pub fn arguments_definition(tool_name: &str) -> String {
+    format!("{tool_name}_arguments")
+}
+
+/// Builds the JSON Schema (draft-07) for a workflow file: either the arguments of
+/// `execute_sequence` directly or a `{tool_name: execute_sequence, arguments: ...}` wrapper.
+pub fn workflow_schema() -> Value {
+    // Generated like the tool input schemas so shared types produce identical definitions.
+    let mut root = Value::Object(schema_for_type::<ExecuteSequenceArgs>());
+    restore_null_types(&mut root);
+    let Value::Object(mut root) = root else {
+        unreachable!("root schema is an object");
+    };
+
+    let mut definitions = match root.remove("definitions") {
+        Some(Value::Object(definitions)) => definitions,
+        _ => Map::new(),
+    };
+    root.remove("$schema");
+    root.remove("title");
+    if let Some(Value::Object(properties)) = root.get_mut("properties") {
+        add_metadata_properties(properties);
+    }
+    definitions.insert("ExecuteSequenceArgs".to_string(), Value::Object(root));
+
+    let mut tools = DesktopWrapper::tool_definitions();
+    tools.sort_by(|a, b| a.name.cmp(&b.name));
+    let tool_names: Vec<Value> = tools.iter().map(|t| json!(t.name)).collect();
+    let mut conditions = Vec::new();
+    for tool in &tools {
+        let mut schema = Value::Object((*tool.input_schema).clone());
+        restore_null_types(&mut schema);
+        let local = match schema.as_object_mut().and_then(|s| {
+            s.remove("$schema");
+            s.remove("title");
+            s.remove("definitions")
+        }) {
+            Some(Value::Object(local)) => local,
+            _ => Map::new(),
+        };
+        // Tools share most argument types; keep one copy and rename real conflicts.
+        let renames: Vec<(String, String)> = local
+            .iter()
+            .filter(|(name, definition)| definitions.get(*name).is_some_and(|d| d != *definition))
+            .map(|(name, _)| (name.clone(), format!("{name}_{}", tool.name)))
+            .collect();
+        for (name, mut definition) in local {
+            for (from, to) in &renames {
+                rewrite_refs(&mut definition, from, to);
+            }
+            let target = renames
+                .iter()
+                .find(|(from, _)| *from == name)
+                .map_or(name, |(_, to)| to.clone());
+            definitions.entry(target).or_insert(definition);
+        }
+        for (from, to) in &renames {
+            rewrite_refs(&mut schema, from, to);
+        }
+        definitions.insert(arguments_definition(&tool.name), schema);
+        conditions.push(json!({
+            "if": {
+                "properties": { "tool_name": { "const": tool.name } },
+                "required": ["tool_name"]
+            },
+            "then": {
+                "properties": {
+                    "arguments": { "$ref": definition_ref(&arguments_definition(&tool.name)) }
+                }
+            }
+        }));
+    }
+
+    for (step_type, nullable) in [("SequenceStep", true), ("ToolCall", false)] {
+        let Some(Value::Object(step)) = definitions.get_mut(step_type) else {
+            continue;
+        };
+        if let Some(Value::Object(tool_name)) = step
+            .get_mut("properties")
+            .and_then(|p| p.get_mut("tool_name"))
+        {
+            let mut allowed = tool_names.clone();
+            if nullable {
+                allowed.push(Value::Null);
+            }
+            tool_name.insert("enum".to_string(), Value::Array(allowed));
+        }
+        step.insert("allOf".to_string(), Value::Array(conditions.clone()));
+    }
+
+    let mut wrapper_properties = Map::new();
+    wrapper_properties.insert(
+        "tool_name".to_string(),
+        json!({ "const": "execute_sequence" }),
+    );
+    wrapper_properties.insert(
+        "arguments".to_string(),
+        json!({ "$ref": "#/definitions/ExecuteSequenceArgs" }),
+    );
+    add_metadata_properties(&mut wrapper_properties);
+
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "Terminator workflow",
+        "description": "Arguments of execute_sequence, or an execute_sequence tool call.",
+        "anyOf": [
+            {
+                "allOf": [{ "$ref": "#/definitions/ExecuteSequenceArgs" }],
+                "not": { "required": ["tool_name"] }
+            },
+            {
+                "type": "object",
+                "required": ["tool_name", "arguments"],
+                "properties": wrapper_properties
+            }
+        ],
+        "definitions": definitions
+    })
+}
+
+fn add_metadata_properties(properties: &mut Map<String, Value>) {
+    for (name, types, description) in [
+        ("name", json!("string"), "Workflow name."),
+        ("description", json!("string"), "Workflow description."),
+        ("version", json!(["string", "number"]), "Workflow version."),
+        (
+            "cron",
+            json!("string"),
+            "Cron schedule used by 'terminator mcp run'.",
+        ),
+    ] {
+        properties.insert(
+            name.to_string(),
+            json!({
+                "type": types,
+                "description": format!("{description} Ignored by execute_sequence."),
+            }),
+        );
+    }
+}
+
+/// Tool schemas use the OpenAPI `nullable` keyword; turn it back into standard JSON Schema.
+fn restore_null_types(schema: &mut Value) {
+    match schema {
+        Value::Object(object) => {
+            if object.remove("nullable") == Some(Value::Bool(true)) {
+                let restored = match object.get("type") {
+                    Some(Value::String(t)) => Some(json!([t, "null"])),
+                    Some(Value::Array(types)) => Some(Value::Array(
+                        types.iter().cloned().chain([json!("null")]).collect(),
+                    )),
+                    _ if object.get("const") == Some(&Value::Null) => Some(json!("null")),
+                    _ => None,
+                };
+                if object.get("const") == Some(&Value::Null) {
+                    object.remove("const");
+                }
+                if let Some(restored) = restored {
+                    object.insert("type".to_string(), restored);
+                }
+            }
+            object.values_mut().for_each(restore_null_types);
+        }
+        Value::Array(items) => items.iter_mut().for_each(restore_null_types),
+        _ => {}
+    }
+}
+
+fn definition_ref(name: &str) -> String {
+    format!("#/definitions/{name}")
+}
+
+fn rewrite_refs(schema: &mut Value, from: &str, to: &str) {
+    match schema {
+        Value::Object(object) => {
+            if let Some(Value::String(reference)) = object.get_mut("$ref") {
+                if *reference == definition_ref(from) {
+                    *reference = definition_ref(to);
+                }
+            }
+            object.values_mut().for_each(|v| rewrite_refs(v, from, to));
+        }
+        Value::Array(items) => items.iter_mut().for_each(|v| rewrite_refs(v, from, to)),
+        _ => {}
+    }
+}
@@ -1,5 +1,5 @@
 use serde_json::json;
-use terminator_mcp_agent::expression_eval::evaluate;
+use terminator_mcp_agent::expression_eval::{check, evaluate};
 
 #[test]
 fn test_evaluate_binary_expressions() {
@@ -387,3 +387,26 @@ fn test_unicode_spaces_normalized() {
     // Test with thin space
     assert!(evaluate("env.status\u{2009}==\u{2009}'active'", &vars));
 }
+
+#[test]
+fn test_check_collects_references() {
+    assert_eq!(
+        check("env.ready == true && contains(policy.types, 'FEX')").unwrap(),
+        vec!["env.ready", "policy.types"]
+    );
+    assert_eq!(
+        check("!login_status || always()").unwrap(),
+        vec!["login_status"]
+    );
+    assert!(check("true").unwrap().is_empty());
+}
+
+#[test]
+fn test_check_rejects_unsupported_syntax() {
+    assert!(check("count == 5").is_err());
+    assert!(check("count > 5").is_err());
+    assert!(check("matches(name, 'x')").is_err());
+    assert!(check("contains(name)").is_err());
+    assert!(check("always(x)").is_err());
+    assert!(check("").is_err());
+}
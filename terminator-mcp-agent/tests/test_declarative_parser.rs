@@ -0,0 +1,210 @@
+use serde_json::{json, Value};
+use terminator_mcp_agent::output_parser::run_output_parser;
+
+/// Wrap a `get_window_tree` fixture the way it appears in an `execute_sequence` summary.
+fn sequence_result(fixture: &str) -> Value {
+    let tree_output: Value = serde_json::from_str(fixture).unwrap();
+    json!({
+        "action": "execute_sequence",
+        "status": "success",
+        "results": [
+            {
+                "step_id": "open_app",
+                "tool_name": "open_application",
+                "status": "success",
+                "result": {"content": [{"status": "success"}]}
+            },
+            {
+                "step_id": "capture_tree",
+                "tool_name": "get_window_tree",
+                "status": "success",
+                "result": {"content": [tree_output]}
+            }
+        ]
+    })
+}
+
+async fn parse(parser: Value, sequence_result: &Value) -> Value {
+    let parsed = run_output_parser(&parser, sequence_result)
+        .await
+        .unwrap()
+        .unwrap();
+    parsed["result"].clone()
+}
+
+#[tokio::test]
+async fn test_declarative_rows_with_regex_and_types() {
+    let summary = sequence_result(include_str!("fixtures/get_window_tree_quotes.json"));
+    let parser = json!({
+        "ui_tree_source_step_id": "capture_tree",
+        "declarative": {
+            "root": "role:List|Quotes",
+            "rows": "role:Group >> has:role:Image",
+            "fields": {
+                "carrier": {"selector": "role:Text", "regex": "^([^:]+):"},
+                "product": {"selector": "role:Text", "regex": ":\\s*(.+)$"},
+                "monthly_price": {"selector": "text:/mo", "type": "number"},
+                "compare": {
+                    "selector": "role:CheckBox",
+                    "attribute": "is_toggled",
+                    "type": "boolean",
+                    "default": false
+                }
+            }
+        }
+    });
+
+    assert_eq!(
+        parse(parser, &summary).await,
+        json!([
+            {"carrier": "Acme", "product": "Gold Plan", "monthly_price": 1204.5, "compare": true},
+            {"carrier": "Globex", "product": "Silver Plan", "monthly_price": 89.99, "compare": false},
+            {"carrier": "Acme", "product": "Bronze Plan", "monthly_price": null, "compare": false}
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_declarative_required_and_grouping() {
+    let summary = sequence_result(include_str!("fixtures/get_window_tree_quotes.json"));
+    let parser = json!({
+        "declarative": {
+            "rows": "role:Group",
+            "fields": {
+                "carrier": {"selector": "role:Text", "regex": "^([^:]+):"},
+                "price": {"selector": "text:/mo", "type": "number"}
+            },
+            "required": ["carrier"],
+            "group_by": "carrier"
+        }
+    });
+
+    assert_eq!(
+        parse(parser, &summary).await,
+        json!([
+            {"carrier": "Acme", "items": [
+                {"carrier": "Acme", "price": 1204.5},
+                {"carrier": "Acme", "price": null}
+            ]},
+            {"carrier": "Globex", "items": [
+                {"carrier": "Globex", "price": 89.99}
+            ]}
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_declarative_table_by_native_id() {
+    let summary = sequence_result(include_str!("fixtures/get_window_tree_orders.json"));
+    let parser = json!({
+        "declarative": {
+            "root": "nativeid:ordersGrid",
+            "rows": "role:DataItem",
+            "fields": {
+                "order": {"selector": "role:Edit|Order", "attribute": "value"},
+                "status": {"selector": "role:Edit|Status", "attribute": "value"},
+                "qty": {"selector": "role:Edit|Qty", "attribute": "value", "type": "integer"},
+                "row_id": "id"
+            },
+            "group_by": "status"
+        }
+    });
+
+    assert_eq!(
+        parse(parser, &summary).await,
+        json!([
+            {"status": "Shipped", "items": [
+                {"order": "SO-1001", "status": "Shipped", "qty": 12, "row_id": "3"},
+                {"order": "SO-1003", "status": "Shipped", "qty": 1500, "row_id": "11"}
+            ]},
+            {"status": "Pending", "items": [
+                {"order": "SO-1002", "status": "Pending", "qty": 3, "row_id": "7"}
+            ]}
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_declarative_single_object_with_json_paths() {
+    let summary = sequence_result(include_str!("fixtures/get_window_tree_orders.json"));
+    let parser = json!({
+        "declarative": {
+            "fields": {
+                "loaded": {"selector": "role:StatusBar", "regex": "(\\d+) orders", "type": "integer"},
+                "window": "$.results[-1].result.content[0].ui_tree.attributes.name",
+                "steps": "$.results[*].step_id",
+                "pid": {"path": "$.results[1].result.content[0].pid", "type": "string"}
+            }
+        }
+    });
+
+    assert_eq!(
+        parse(parser, &summary).await,
+        json!({
+            "loaded": 3,
+            "window": "Orders - Northwind",
+            "steps": ["open_app", "capture_tree"],
+            "pid": "7788"
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_declarative_rows_from_step_results() {
+    // API-style workflow: no UI tree, rows come straight from a step's result.
+    let summary = json!({
+        "results": [{
+            "step_id": "fetch_invoices",
+            "tool_name": "run_command",
+            "status": "success",
+            "result": {"invoices": [
+                {"number": "INV-7", "amount": "$120.00", "paid": "yes"},
+                {"number": "INV-8", "amount": "$75.50", "paid": "no"}
+            ]}
+        }]
+    });
+    let parser = json!({
+        "declarative": {
+            "rows": "$.results[0].result.invoices[*]",
+            "fields": {
+                "number": "@.number",
+                "amount": {"path": "@.amount", "type": "number"},
+                "paid": {"path": "@.paid", "type": "boolean"},
+                "source": "$.results[0].step_id"
+            }
+        }
+    });
+
+    assert_eq!(
+        parse(parser, &summary).await,
+        json!([
+            {"number": "INV-7", "amount": 120.0, "paid": true, "source": "fetch_invoices"},
+            {"number": "INV-8", "amount": 75.5, "paid": false, "source": "fetch_invoices"}
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_declarative_errors() {
+    let summary = sequence_result(include_str!("fixtures/get_window_tree_orders.json"));
+
+    let mixed = json!({"run": "return [];", "declarative": {"fields": {"a": "name"}}});
+    let err = run_output_parser(&mixed, &summary).await.unwrap_err();
+    assert!(err.to_string().contains("Cannot combine 'declarative'"));
+
+    let missing_root = json!({"declarative": {"root": "role:Dialog", "fields": {}}});
+    let err = run_output_parser(&missing_root, &summary)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("matched nothing"));
+
+    let bad_selector = json!({"declarative": {"rows": "bogus", "fields": {}}});
+    assert!(run_output_parser(&bad_selector, &summary).await.is_err());
+
+    let compact =
+        json!({"results": [{"step_id": "t", "result": {"ui_tree": "- [Window] Orders"}}]});
+    let err = run_output_parser(&json!({"declarative": {"rows": "role:Edit"}}), &compact)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("verbose_json"));
+}
@@ -0,0 +1,80 @@
+//! Reads MCP resources through a client on a fixture desktop, to check that they are
+//! subject to the tool policy and confined to the workflows root.
+
+use rmcp::model::{ReadResourceRequestParam, ResourceContents};
+use rmcp::service::RunningService;
+use rmcp::{RoleClient, ServiceExt};
+use std::sync::Arc;
+use terminator::platforms::fixture::FixtureEngine;
+use terminator::Desktop;
+use terminator_mcp_agent::policy::ToolPolicy;
+use terminator_mcp_agent::utils::DesktopWrapper;
+
+type Client = RunningService<RoleClient, ()>;
+
+async fn connect(server: DesktopWrapper) -> Client {
+    let (server_io, client_io) = tokio::io::duplex(1 << 20);
+    tokio::spawn(async move {
+        let running = server.serve(server_io).await.unwrap();
+        let _ = running.waiting().await;
+    });
+    ().serve(client_io).await.unwrap()
+}
+
+fn server() -> DesktopWrapper {
+    DesktopWrapper::with_desktop(Desktop::with_engine(Arc::new(FixtureEngine::new())), None)
+}
+
+async fn read(client: &Client, uri: &str) -> Result<String, rmcp::ServiceError> {
+    let result = client
+        .read_resource(ReadResourceRequestParam {
+            uri: uri.to_string(),
+        })
+        .await?;
+    match &result.contents[0] {
+        ResourceContents::TextResourceContents { text, .. } => Ok(text.clone()),
+        ResourceContents::BlobResourceContents { blob, .. } => Ok(blob.clone()),
+    }
+}
+
+#[tokio::test]
+async fn test_resource_reads_follow_the_tool_policy() {
+    let policy =
+        ToolPolicy::compile(serde_yaml::from_str("deny: ['get_applications']").unwrap()).unwrap();
+    let client = connect(server().with_policy(policy)).await;
+
+    let denied = read(&client, "terminator://applications").await;
+    assert!(
+        format!("{denied:?}").contains("denied by policy"),
+        "{denied:?}"
+    );
+    let _ = client.cancel().await;
+}
+
+#[tokio::test]
+async fn test_workflow_resources_stay_in_the_root() {
+    let root = tempfile::tempdir().unwrap();
+    let outside = tempfile::tempdir().unwrap();
+    std::fs::write(root.path().join("login.yml"), "steps: []").unwrap();
+    std::fs::write(outside.path().join("secret.yml"), "token: 123").unwrap();
+
+    let client = connect(server().with_workflows_root(Some(root.path().to_path_buf()))).await;
+    assert_eq!(
+        read(&client, "terminator://workflows/login.yml")
+            .await
+            .unwrap(),
+        "steps: []"
+    );
+    let escape = format!(
+        "terminator://workflows/..%2F{}%2Fsecret.yml",
+        outside.path().file_name().unwrap().to_string_lossy()
+    );
+    assert!(read(&client, &escape).await.is_err());
+    let _ = client.cancel().await;
+
+    let client = connect(server()).await;
+    assert!(read(&client, "terminator://workflows/login.yml")
+        .await
+        .is_err());
+    let _ = client.cancel().await;
+}
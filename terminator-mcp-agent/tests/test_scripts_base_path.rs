@@ -106,6 +106,11 @@ steps:
             follow_fallback: Some(false),
             scripts_base_path: Some("/custom/path".to_string()),
             execute_jumps_at_end: Some(false),
+            timeout: None,
+            on_failure: None,
+            finally: None,
+            capture_fixtures: None,
+            cleanup_timeout: None,
         };
 
         let serialized = serde_json::to_string(&args).unwrap();
@@ -0,0 +1,366 @@
+//! Runs `execute_sequence` and debug sessions end to end on a fixture desktop, so that
+//! control flow such as timeouts and cleanup blocks can be checked without a real UI.
+
+use rmcp::model::{CallToolRequest, CallToolRequestParam, ClientRequest};
+use rmcp::service::{PeerRequestOptions, RunningService};
+use rmcp::{RoleClient, ServiceExt};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use terminator::platforms::fixture::{FixtureEngine, FixtureWindow};
+use terminator::Desktop;
+use terminator_mcp_agent::server::extract_content_json;
+use terminator_mcp_agent::utils::DesktopWrapper;
+
+type Client = RunningService<RoleClient, ()>;
+
+/// Serve a fresh server on `engine` and connect a client to it.
+async fn connect(engine: &Arc<FixtureEngine>) -> Client {
+    let server = DesktopWrapper::with_desktop(Desktop::with_engine(engine.clone()), None);
+    let (server_io, client_io) = tokio::io::duplex(1 << 20);
+    tokio::spawn(async move {
+        let running = server.serve(server_io).await.unwrap();
+        let _ = running.waiting().await;
+    });
+    ().serve(client_io).await.unwrap()
+}
+
+fn request(tool: &str, arguments: Value) -> CallToolRequestParam {
+    CallToolRequestParam {
+        name: tool.to_string().into(),
+        arguments: arguments.as_object().cloned(),
+    }
+}
+
+/// Call `tool` and return the JSON it answered with.
+async fn call(client: &Client, tool: &str, arguments: Value) -> Value {
+    let result = client.call_tool(request(tool, arguments)).await.unwrap();
+    extract_content_json(&result.content[0]).unwrap()
+}
+
+/// Call `tool` on a fresh server.
+async fn call_once(tool: &str, arguments: Value) -> Value {
+    let client = connect(&Arc::new(FixtureEngine::new())).await;
+    let result = call(&client, tool, arguments).await;
+    let _ = client.cancel().await;
+    result
+}
+
+/// The shell commands `run_command` ran on `engine` since the last call.
+fn commands(engine: &FixtureEngine) -> Vec<String> {
+    engine
+        .take_actions()
+        .into_iter()
+        .filter(|action| action.action == "run_command")
+        .filter_map(|action| action.value)
+        .collect()
+}
+
+fn shell(command: &str) -> Value {
+    json!({"tool_name": "run_command", "arguments": {"run": command}})
+}
+
+/// Wait until `engine` ran at least one command and return the commands.
+async fn wait_for_commands(engine: &FixtureEngine) -> Vec<String> {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let mut ran = Vec::new();
+    while ran.is_empty() && Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        ran.extend(commands(engine));
+    }
+    ran
+}
+
+#[tokio::test]
+async fn test_step_timeout_cancels_the_step() {
+    let started = Instant::now();
+    let summary = call_once(
+        "execute_sequence",
+        json!({
+            "steps": [
+                {"tool_name": "delay", "id": "slow", "arguments": {"delay_ms": 30000}, "timeout": "200ms"},
+                {"tool_name": "delay", "id": "after", "arguments": {"delay_ms": 1}}
+            ],
+            "stop_on_error": true
+        }),
+    )
+    .await;
+
+    assert!(started.elapsed() < Duration::from_secs(10), "{summary}");
+    assert_eq!(summary["status"], "failed");
+    let results = summary["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1, "the sequence stops at the timed out step");
+    assert_eq!(results[0]["timed_out"], true);
+    assert_eq!(results[0]["error"], "Step timed out after 200ms");
+}
+
+#[tokio::test]
+async fn test_timed_out_locator_step_does_not_act_later() {
+    let engine = Arc::new(FixtureEngine::new());
+    let client = connect(&engine).await;
+    let summary = call(
+        &client,
+        "execute_sequence",
+        json!({
+            "steps": [{
+                "tool_name": "click_element",
+                "arguments": {"selector": "role:Button|name:Later"},
+                "timeout": "200ms"
+            }],
+            "stop_on_error": true
+        }),
+    )
+    .await;
+    assert_eq!(summary["status"], "failed", "{summary}");
+
+    // Without a `timeout_ms` of its own, the locator would poll for its default 3s and
+    // click the button once it appears.
+    let window: FixtureWindow = serde_json::from_value(json!({
+        "pid": 7,
+        "window_title": "Demo",
+        "tree": {
+            "attributes": {"role": "Window", "name": "Demo"},
+            "children": [{"attributes": {"role": "Button", "name": "Later"}}]
+        }
+    }))
+    .unwrap();
+    engine.load(Some(window));
+    tokio::time::sleep(Duration::from_millis(3500)).await;
+    let _ = client.cancel().await;
+
+    let clicks: Vec<_> = engine
+        .take_actions()
+        .into_iter()
+        .filter(|action| action.action == "click")
+        .collect();
+    assert!(clicks.is_empty(), "{clicks:?}");
+}
+
+#[tokio::test]
+async fn test_workflow_timeout_stops_before_the_next_step() {
+    let summary = call_once(
+        "execute_sequence",
+        json!({
+            "steps": [
+                {"tool_name": "delay", "arguments": {"delay_ms": 30000}},
+                {"tool_name": "delay", "arguments": {"delay_ms": 1}}
+            ],
+            "timeout": "300ms",
+            "stop_on_error": false
+        }),
+    )
+    .await;
+
+    assert_eq!(summary["status"], "failed");
+    assert_eq!(summary["timed_out"], true);
+    assert_eq!(summary["results"].as_array().unwrap().len(), 1);
+    assert_eq!(summary["results"][0]["timed_out"], true);
+}
+
+#[tokio::test]
+async fn test_group_cleanup_runs_once_after_retries() {
+    let engine = Arc::new(FixtureEngine::new());
+    let client = connect(&engine).await;
+    let summary = call(
+        &client,
+        "execute_sequence",
+        json!({
+            "steps": [{
+                "group_name": "fill_form",
+                "retries": 2,
+                "steps": [
+                    {"tool_name": "delay", "id": "submit", "arguments": {"delay_ms": 5000}, "timeout": "50ms"}
+                ],
+                "on_failure": [shell("echo group on_failure")],
+                "finally": [shell("echo group finally")]
+            }],
+            "on_failure": [shell("echo workflow on_failure")],
+            "finally": [shell("echo workflow finally")]
+        }),
+    )
+    .await;
+    let _ = client.cancel().await;
+
+    assert_eq!(summary["status"], "failed");
+    assert_eq!(
+        commands(&engine),
+        [
+            "echo group on_failure",
+            "echo group finally",
+            "echo workflow on_failure",
+            "echo workflow finally"
+        ]
+    );
+    let group = &summary["results"][0];
+    assert_eq!(group["on_failure_results"].as_array().unwrap().len(), 1);
+    assert_eq!(group["finally_results"].as_array().unwrap().len(), 1);
+    assert_eq!(summary["finally_results"][0]["status"], "success");
+}
+
+#[tokio::test]
+async fn test_cleanup_skips_on_failure_after_success() {
+    let engine = Arc::new(FixtureEngine::new());
+    let client = connect(&engine).await;
+    let summary = call(
+        &client,
+        "execute_sequence",
+        json!({
+            "steps": [shell("echo step")],
+            "on_failure": [shell("echo on_failure")],
+            "finally": [shell("echo finally")]
+        }),
+    )
+    .await;
+    let _ = client.cancel().await;
+
+    assert_eq!(summary["status"], "success");
+    assert_eq!(commands(&engine), ["echo step", "echo finally"]);
+    assert!(summary.get("on_failure_results").is_none());
+}
+
+#[tokio::test]
+async fn test_finally_runs_when_the_request_is_cancelled() {
+    let engine = Arc::new(FixtureEngine::new());
+    let client = connect(&engine).await;
+    let steps: Vec<Value> = (0..20)
+        .map(|_| json!({"tool_name": "delay", "arguments": {"delay_ms": 100}}))
+        .collect();
+    let handle = client
+        .send_cancellable_request(
+            ClientRequest::CallToolRequest(CallToolRequest::new(request(
+                "execute_sequence",
+                json!({"steps": steps, "finally": [shell("echo finally")]}),
+            ))),
+            PeerRequestOptions::no_options(),
+        )
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    handle.cancel(Some("test".to_string())).await.unwrap();
+
+    // The sequence notices the cancellation after the running step and then cleans up.
+    let ran = wait_for_commands(&engine).await;
+    let _ = client.cancel().await;
+    assert_eq!(ran, ["echo finally"]);
+}
+
+#[tokio::test]
+async fn test_cancellation_ends_the_retry_backoff() {
+    let engine = Arc::new(FixtureEngine::new());
+    let client = connect(&engine).await;
+    let step = json!({
+        "tool_name": "click_element",
+        "arguments": {"selector": "role:Button|name:Missing", "timeout_ms": 10},
+        "retry": {"max_attempts": 3, "initial_delay": "30s", "before_retry": [shell("echo before_retry")]}
+    });
+    let handle = client
+        .send_cancellable_request(
+            ClientRequest::CallToolRequest(CallToolRequest::new(request(
+                "execute_sequence",
+                json!({"steps": [step], "finally": [shell("echo finally")]}),
+            ))),
+            PeerRequestOptions::no_options(),
+        )
+        .await
+        .unwrap();
+    let mut ran = wait_for_commands(&engine).await;
+    assert_eq!(ran, ["echo before_retry"]);
+    handle.cancel(Some("test".to_string())).await.unwrap();
+
+    // The 30s backoff is cut short, so the cleanup runs right away.
+    ran.extend(wait_for_commands(&engine).await);
+    let _ = client.cancel().await;
+    assert_eq!(ran, ["echo before_retry", "echo finally"]);
+}
+
+#[tokio::test]
+async fn test_cleanup_steps_without_timeout_are_bounded() {
+    let started = Instant::now();
+    let summary = call_once(
+        "execute_sequence",
+        json!({
+            "steps": [shell("echo step")],
+            "finally": [
+                {"tool_name": "delay", "arguments": {"delay_ms": 30000}},
+                shell("echo finally")
+            ],
+            "cleanup_timeout": "200ms"
+        }),
+    )
+    .await;
+
+    assert!(started.elapsed() < Duration::from_secs(10), "{summary}");
+    assert_eq!(summary["finally_results"][0]["timed_out"], true);
+    assert_eq!(summary["finally_results"][1]["status"], "success");
+}
+
+#[tokio::test]
+async fn test_debugger_step_continue_and_stop() {
+    let engine = Arc::new(FixtureEngine::new());
+    let client = connect(&engine).await;
+    let step = |id: &str| json!({"tool_name": "run_command", "id": id, "arguments": {"run": format!("echo {id}")}});
+
+    let paused = call(
+        &client,
+        "debug_start",
+        json!({
+            "steps": [step("a"), step("b"), step("c")],
+            "finally": [shell("echo finally")],
+            "breakpoints": ["c"]
+        }),
+    )
+    .await;
+    assert_eq!(paused["status"], "paused");
+    assert_eq!(paused["step_index"], 0);
+    assert_eq!(paused["pause_reason"], "step");
+    assert!(commands(&engine).is_empty());
+    let session = json!({"session_id": paused["session_id"]});
+
+    let paused = call(&client, "debug_step", session.clone()).await;
+    assert_eq!(paused["step_index"], 1);
+    assert_eq!(commands(&engine), ["echo a"]);
+
+    let paused = call(&client, "debug_continue", session.clone()).await;
+    assert_eq!(paused["step_id"], "c");
+    assert_eq!(paused["pause_reason"], "breakpoint");
+    assert_eq!(commands(&engine), ["echo b"]);
+
+    // Stopping skips the remaining steps but still runs the cleanup blocks.
+    let stopped = call(&client, "debug_stop", session.clone()).await;
+    assert_eq!(stopped["status"], "finished");
+    assert_eq!(commands(&engine), ["echo finally"]);
+    assert_eq!(
+        stopped["result"]["data"]["finally_results"][0]["status"],
+        "success"
+    );
+
+    let after_stop = client.call_tool(request("debug_step", session)).await;
+    assert!(after_stop.is_err(), "the session is gone: {after_stop:?}");
+    let _ = client.cancel().await;
+}
+
+#[tokio::test]
+async fn test_idle_debug_session_stops_itself() {
+    let engine = Arc::new(FixtureEngine::new());
+    let client = connect(&engine).await;
+    let paused = call(
+        &client,
+        "debug_start",
+        json!({
+            "steps": [shell("echo step")],
+            "finally": [shell("echo finally")],
+            "idle_timeout": "200ms"
+        }),
+    )
+    .await;
+    assert_eq!(paused["status"], "paused");
+
+    assert_eq!(wait_for_commands(&engine).await, ["echo finally"]);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let session = json!({"session_id": paused["session_id"]});
+    assert!(client
+        .call_tool(request("debug_step", session))
+        .await
+        .is_err());
+    let _ = client.cancel().await;
+}
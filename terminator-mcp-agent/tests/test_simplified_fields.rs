@@ -204,3 +204,109 @@ fn test_mixed_old_and_new_syntax() {
         Some("2s".to_string())
     );
 }
+
+#[test]
+fn test_timeout_fields() {
+    // Timeouts can be set on the workflow root, on steps, on groups and on steps inside groups
+    let workflow = json!({
+        "timeout": "10m",
+        "steps": [
+            {
+                "tool_name": "run_command",
+                "arguments": { "engine": "javascript", "run": "return {};" },
+                "timeout": "30s"
+            },
+            {
+                "group_name": "fill_form",
+                "timeout": "2m",
+                "steps": [
+                    {
+                        "tool_name": "click_element",
+                        "arguments": { "selector": "role:Button|name:Save" },
+                        "timeout": "5s"
+                    }
+                ]
+            }
+        ]
+    });
+
+    let args: ExecuteSequenceArgs = serde_json::from_value(workflow).unwrap();
+    assert_eq!(args.timeout, Some("10m".to_string()));
+    let steps = args.steps.unwrap();
+    assert_eq!(steps[0].timeout, Some("30s".to_string()));
+    assert_eq!(steps[1].timeout, Some("2m".to_string()));
+    assert_eq!(
+        steps[1].steps.as_ref().unwrap()[0].timeout,
+        Some("5s".to_string())
+    );
+    assert_eq!(parse_duration("30s").unwrap(), 30_000);
+}
+
+#[test]
+fn test_retry_policy_fields() {
+    use terminator_mcp_agent::utils::{BackoffStrategy, RetryErrorClass};
+
+    let yaml = r#"
+steps:
+  - tool_name: click_element
+    arguments:
+      selector: "role:Button|name:Submit"
+    retry:
+      max_attempts: 4
+      backoff: exponential
+      initial_delay: 250ms
+      max_delay: 5s
+      on: [element_not_found, obscured]
+      before_retry:
+        - tool_name: press_key_global
+          arguments:
+            key: "{Escape}"
+"#;
+
+    let args: ExecuteSequenceArgs = serde_yaml::from_str(yaml).unwrap();
+    let retry = args.steps.unwrap()[0].retry.clone().unwrap();
+    assert_eq!(retry.max_attempts, Some(4));
+    assert_eq!(retry.backoff, Some(BackoffStrategy::Exponential));
+    assert_eq!(retry.initial_delay, Some("250ms".to_string()));
+    assert_eq!(retry.max_delay, Some("5s".to_string()));
+    assert_eq!(
+        retry.on,
+        Some(vec![
+            RetryErrorClass::ElementNotFound,
+            RetryErrorClass::Obscured
+        ])
+    );
+    assert_eq!(retry.before_retry.unwrap()[0].tool_name, "press_key_global");
+
+    let invalid = serde_yaml::from_str::<ExecuteSequenceArgs>(
+        "steps:\n  - tool_name: click_element\n    retry:\n      on: [flaky]\n",
+    );
+    assert!(invalid.is_err());
+}
+
+#[test]
+fn test_debug_start_args() {
+    use terminator_mcp_agent::utils::DebugStartArgs;
+
+    // debug_start takes the regular workflow fields alongside the breakpoint settings
+    let args: DebugStartArgs = serde_json::from_value(json!({
+        "steps": [
+            { "tool_name": "delay", "id": "wait", "arguments": { "delay_ms": 10 } }
+        ],
+        "inputs": { "invoice_id": "INV-1" },
+        "breakpoints": ["wait"],
+        "breakpoint_conditions": ["invoice_id == 'INV-1'"],
+        "stop_on_entry": false
+    }))
+    .unwrap();
+
+    let steps = args.workflow.steps.unwrap();
+    assert_eq!(steps[0].id, Some("wait".to_string()));
+    assert_eq!(args.workflow.inputs.unwrap()["invoice_id"], "INV-1");
+    assert_eq!(args.breakpoints, Some(vec!["wait".to_string()]));
+    assert_eq!(
+        args.breakpoint_conditions,
+        Some(vec!["invoice_id == 'INV-1'".to_string()])
+    );
+    assert_eq!(args.stop_on_entry, Some(false));
+}
@@ -0,0 +1,214 @@
+use rmcp::model::CallToolRequestParam;
+use rmcp::ServiceExt;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use terminator::platforms::fixture::{FixtureEngine, FixtureWindow};
+use terminator::Desktop;
+use terminator_mcp_agent::utils::DesktopWrapper;
+use terminator_mcp_agent::workflow_replay::{compare_reports, load_fixtures, run_workflow_test};
+
+const WORKFLOW: &str = r#"
+steps:
+  - tool_name: click_element
+    id: open_form
+    arguments:
+      selector: "role:Button|name:New"
+  - tool_name: type_into_element
+    id: type_name
+    arguments:
+      selector: "role:Edit|name:User name"
+      text_to_type: "{{user}}"
+  - tool_name: run_command
+    id: lookup
+    arguments:
+      run: "exit 1"
+"#;
+
+fn fixture(step_index: usize, step_id: &str, tool_name: &str) -> Value {
+    json!({
+        "step_index": step_index,
+        "step_id": step_id,
+        "tool_name": tool_name,
+        "arguments": {},
+        "window": {
+            "pid": 7,
+            "application_name": "Demo",
+            "window_title": "Demo",
+            "tree": {
+                "attributes": {"role": "Window", "name": "Demo"},
+                "children": [
+                    {"attributes": {"role": "Button", "name": "New"}},
+                    {"attributes": {"role": "Edit", "name": "User name"}}
+                ]
+            }
+        },
+        "result": {"success": {"content": [{"type": "text", "text": "{\"status\": \"success\"}"}]}}
+    })
+}
+
+fn setup() -> (PathBuf, PathBuf) {
+    let dir = std::env::temp_dir().join(format!("workflow_replay_{}", rand::random::<u64>()));
+    let fixtures = dir.join("fixtures");
+    std::fs::create_dir_all(&fixtures).unwrap();
+    for (i, (id, tool)) in [
+        ("open_form", "click_element"),
+        ("type_name", "type_into_element"),
+        ("lookup", "run_command"),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        std::fs::write(
+            fixtures.join(format!("{i:04}-{tool}.json")),
+            fixture(i, id, tool).to_string(),
+        )
+        .unwrap();
+    }
+    let workflow = dir.join("workflow.yml");
+    std::fs::write(&workflow, WORKFLOW).unwrap();
+    (workflow, fixtures)
+}
+
+#[tokio::test]
+async fn test_replay_records_actions_without_a_desktop() {
+    let (workflow, fixtures) = setup();
+
+    let report = run_workflow_test(&workflow, &fixtures, Some(json!({"user": "alice"})))
+        .await
+        .unwrap();
+
+    assert_eq!(report["status"], "success");
+    let calls = report["calls"].as_array().unwrap();
+    assert_eq!(calls.len(), 3);
+    assert_eq!(
+        calls[0]["actions"],
+        json!([{"action": "click", "target": "Button|New"}])
+    );
+    assert_eq!(calls[1]["arguments"]["text_to_type"], "alice");
+    assert_eq!(
+        calls[1]["actions"][1],
+        json!({"action": "type_text", "target": "Edit|User name", "value": "alice"})
+    );
+    // The script is not run again; its recorded success is returned.
+    assert_eq!(calls[2]["status"], "success");
+    assert!(calls[2].get("arguments").is_none());
+
+    std::fs::remove_dir_all(workflow.parent().unwrap()).ok();
+}
+
+#[tokio::test]
+async fn test_replay_detects_selector_changes() {
+    let (workflow, fixtures) = setup();
+    let golden = run_workflow_test(&workflow, &fixtures, Some(json!({"user": "alice"})))
+        .await
+        .unwrap();
+
+    std::fs::write(&workflow, WORKFLOW.replace("name:New", "name:Open")).unwrap();
+    let report = run_workflow_test(&workflow, &fixtures, Some(json!({"user": "alice"})))
+        .await
+        .unwrap();
+
+    assert_eq!(report["calls"][0]["status"], "error");
+    let diffs = compare_reports(&golden, &report);
+    assert!(diffs.iter().any(|d| d.starts_with("/calls/0/status:")));
+
+    std::fs::remove_dir_all(workflow.parent().unwrap()).ok();
+}
+
+#[tokio::test]
+async fn test_concurrent_captures_record_their_own_calls() {
+    let engine = Arc::new(FixtureEngine::new());
+    let window: FixtureWindow =
+        serde_json::from_value(fixture(0, "", "")["window"].clone()).unwrap();
+    engine.load(Some(window));
+    let server = DesktopWrapper::with_desktop(Desktop::with_engine(engine), None);
+    let (server_io, client_io) = tokio::io::duplex(1 << 20);
+    tokio::spawn(async move {
+        let running = server.serve(server_io).await.unwrap();
+        let _ = running.waiting().await;
+    });
+    let client = ().serve(client_io).await.unwrap();
+
+    let capture = |dir: &Path, steps: Value| {
+        let arguments = json!({"steps": steps, "capture_fixtures": dir});
+        client.call_tool(CallToolRequestParam {
+            name: "execute_sequence".into(),
+            arguments: arguments.as_object().cloned(),
+        })
+    };
+    let typing = tempfile::tempdir().unwrap();
+    let clicking = tempfile::tempdir().unwrap();
+    let (typed, clicked) = tokio::join!(
+        capture(
+            typing.path(),
+            json!([{"tool_name": "type_into_element", "arguments": {
+                "selector": "role:Edit|name:User name", "text_to_type": "hunter2"}}]),
+        ),
+        capture(
+            clicking.path(),
+            json!([{"tool_name": "click_element", "arguments": {"selector": "role:Button|name:New"}},
+                   {"tool_name": "click_element", "arguments": {"selector": "role:Button|name:New"}}]),
+        ),
+    );
+    typed.unwrap();
+    clicked.unwrap();
+    let _ = client.cancel().await;
+
+    let typed = load_fixtures(typing.path()).unwrap();
+    assert_eq!(typed.len(), 1);
+    assert_eq!(typed[0].tool_name, "type_into_element");
+    assert_eq!(typed[0].arguments["text_to_type"], "[REDACTED]");
+    let clicked = load_fixtures(clicking.path()).unwrap();
+    assert_eq!(clicked.len(), 2);
+    assert!(clicked.iter().all(|f| f.tool_name == "click_element"));
+}
+
+#[tokio::test]
+async fn test_capture_leaves_out_passwords_and_unused_results() {
+    let engine = Arc::new(FixtureEngine::new());
+    let window: FixtureWindow = serde_json::from_value(json!({
+        "pid": 7,
+        "window_title": "Login",
+        "tree": {
+            "attributes": {"role": "Window", "name": "Login"},
+            "children": [
+                {"attributes": {"role": "Edit", "name": "User name", "value": "alice"}},
+                {"attributes": {"role": "Edit", "name": "Password", "value": "hunter2",
+                                "properties": {"IsPassword": true}}},
+                {"attributes": {"role": "Button", "name": "Sign in"}}
+            ]
+        }
+    }))
+    .unwrap();
+    engine.load(Some(window));
+    let server = DesktopWrapper::with_desktop(Desktop::with_engine(engine), None);
+    let (server_io, client_io) = tokio::io::duplex(1 << 20);
+    tokio::spawn(async move {
+        let running = server.serve(server_io).await.unwrap();
+        let _ = running.waiting().await;
+    });
+    let client = ().serve(client_io).await.unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let arguments = json!({
+        "steps": [{"tool_name": "click_element", "arguments": {"selector": "role:Button|name:Sign in"}}],
+        "capture_fixtures": dir.path()
+    });
+    client
+        .call_tool(CallToolRequestParam {
+            name: "execute_sequence".into(),
+            arguments: arguments.as_object().cloned(),
+        })
+        .await
+        .unwrap();
+    let _ = client.cancel().await;
+
+    let fixtures = load_fixtures(dir.path()).unwrap();
+    let saved = serde_json::to_value(&fixtures[0]).unwrap();
+    let fields = &saved["window"]["tree"]["children"];
+    assert_eq!(fields[0]["attributes"]["value"], "alice");
+    assert!(fields[1]["attributes"].get("value").is_none(), "{fields}");
+    // Replay never returns a click's result, so only its status is kept.
+    assert_eq!(saved["result"]["success"]["content"], json!([]));
+}
@@ -36,6 +36,11 @@ fn test_execute_sequence_args_serialization() {
         end_at_step: None,
         troubleshooting: None,
         execute_jumps_at_end: None,
+        timeout: None,
+        on_failure: None,
+        finally: None,
+        capture_fixtures: None,
+        cleanup_timeout: None,
     };
 
     let json = serde_json::to_string(&args).unwrap();
@@ -144,6 +149,7 @@ fn test_complex_arguments_preservation() {
         continue_on_error: None,
         delay_ms: None,
         id: None,
+        timeout: None,
     };
 
     let serialized = serde_json::to_value(&tool_call).unwrap();
@@ -25,6 +25,10 @@ pub enum WorkflowRecorderError {
     #[error("Windows API error: {0}")]
     WindowsError(String),
 
+    /// Error when a recording file is malformed or from a newer format version
+    #[error("Invalid recording file: {0}")]
+    FormatError(String),
+
     /// Error from notify file watcher
     #[error("File watcher error: {0}")]
     NotifyError(#[from] notify::Error),
@@ -366,6 +366,9 @@ pub struct DomElementInfo {
     pub xpath: String,
     pub inner_text: Option<String>,
     pub input_value: Option<String>,
+    /// The `type` attribute of input elements (e.g. "password")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_type: Option<String>,
     pub is_visible: bool,
     pub is_interactive: bool,
     pub aria_label: Option<String>,
@@ -493,6 +496,12 @@ impl WorkflowEvent {
     pub fn ui_element(&self) -> Option<&UIElement> {
         self.metadata().ui_element.as_ref()
     }
+
+    /// Whether this is a raw keyboard or mouse event rather than a user action
+    /// such as a click, a hotkey or a completed text input.
+    pub fn is_raw_input(&self) -> bool {
+        matches!(self, WorkflowEvent::Mouse(_) | WorkflowEvent::Keyboard(_))
+    }
 }
 
 /// Represents an MCP tool step for execution
@@ -573,6 +582,17 @@ pub struct RecordedEvent {
     pub metadata: Option<EventMetadata>,
 }
 
+/// A named checkpoint added while recording
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WorkflowMarker {
+    /// The label of the checkpoint, e.g. "Fill customer details"
+    pub label: String,
+
+    /// When the marker was added (milliseconds since epoch); events from this
+    /// moment on belong to the marker until the next one
+    pub timestamp: u64,
+}
+
 /// Represents a recorded workflow
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RecordedWorkflow {
@@ -588,6 +608,10 @@ pub struct RecordedWorkflow {
 
     /// The recorded events
     pub events: Vec<RecordedEvent>,
+
+    /// Checkpoints added while recording, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub markers: Vec<WorkflowMarker>,
 }
 
 impl RecordedWorkflow {
@@ -603,6 +627,7 @@ impl RecordedWorkflow {
             start_time: now,
             end_time: None,
             events: Vec::new(),
+            markers: Vec::new(),
         }
     }
 
@@ -629,6 +654,38 @@ impl RecordedWorkflow {
         self.events.push(recorded_event);
     }
 
+    /// Add a named checkpoint at the current time
+    pub fn add_marker(&mut self, label: String) {
+        let timestamp = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+
+        self.markers.push(WorkflowMarker { label, timestamp });
+    }
+
+    /// Remove the last `count` user actions, together with the raw keyboard and mouse
+    /// events that belong to them. Returns the number of actions removed.
+    pub fn undo_actions(&mut self, count: usize) -> usize {
+        let mut removed = 0;
+        let mut keep = self.events.len();
+        for (index, event) in self.events.iter().enumerate().rev() {
+            if event.event.is_raw_input() {
+                continue;
+            }
+            if removed == count {
+                keep = index + 1;
+                break;
+            }
+            removed += 1;
+            keep = 0;
+        }
+        if removed > 0 {
+            self.events.truncate(keep);
+        }
+        removed
+    }
+
     /// Finish the recording
     pub fn finish(&mut self) {
         let now = SystemTime::now()
@@ -669,25 +726,24 @@ impl RecordedWorkflow {
         serde_json::from_slice(bytes)
     }
 
-    /// Save the workflow to a JSON file
+    /// Save the workflow to a JSON file, or to a gzip-compressed JSON Lines file when
+    /// the path ends in `.gz` (see `write_recording`)
     pub fn save_to_file<P: AsRef<std::path::Path>>(
         &self,
         path: P,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        let json = self.to_json()?;
-        std::fs::write(path, json)?;
+        crate::write_recording(self, path.as_ref())?;
         Ok(())
     }
 
-    /// Load a workflow from a JSON file
+    /// Load a workflow saved in any encoding and format version (see `RecordingReader`)
     /// Note: This creates a workflow with serializable UI elements,
     /// not the original UIElement instances
     pub fn load_from_file<P: AsRef<std::path::Path>>(
         path: P,
     ) -> Result<SerializableRecordedWorkflow, Box<dyn std::error::Error>> {
-        let json = std::fs::read_to_string(path)?;
-        let workflow = Self::from_json(&json)?;
-        Ok(workflow)
+        let reader = crate::RecordingReader::open(path.as_ref())?;
+        Ok(reader.into_serializable_workflow()?)
     }
 }
 
@@ -919,6 +975,70 @@ pub struct EventMetadata {
     /// If None, the timestamp will be generated when the event is recorded
     #[serde(skip_serializing_if = "Option::is_none")]
     pub timestamp: Option<u64>,
+
+    /// Screenshots taken for this event (see `EventScreenshotConfig`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub screenshot: Option<EventScreenshot>,
+
+    /// Window the event happened in. Unlike `ui_element`, it survives loading the
+    /// recording when the window no longer exists.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window: Option<WindowContext>,
+
+    /// Snapshot of `ui_element` taken when the event was masked, without its value and
+    /// with its name masked. Exported instead of the live element, whose value would be
+    /// read again (and could hold what the user typed) when the event is saved or streamed.
+    #[serde(skip)]
+    pub masked_element: Option<SerializableUIElement>,
+}
+
+/// Window of a recorded user action
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct WindowContext {
+    /// Title of the window
+    pub title: String,
+
+    /// Role of the window element, e.g. "Window" or "Dialog"
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub role: String,
+}
+
+/// Screenshots taken when a high-level event was recorded
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct EventScreenshot {
+    /// Downscaled screenshot of the monitor the event happened on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub screen: Option<ScreenshotImage>,
+
+    /// Crop around the event's UI element, at full resolution
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub element: Option<ScreenshotImage>,
+}
+
+/// A PNG image, either embedded or stored next to the workflow JSON file
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScreenshotImage {
+    pub width: u32,
+    pub height: u32,
+
+    /// Path of the PNG file, relative to the workflow JSON file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+
+    /// Base64-encoded PNG data
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+}
+
+impl ScreenshotImage {
+    /// The image as an `<img src>`/URL: the embedded data as a `data:` URL, otherwise the path
+    pub fn src(&self) -> Option<String> {
+        match (&self.data, &self.path) {
+            (Some(data), _) => Some(format!("data:image/png;base64,{data}")),
+            (None, Some(path)) => Some(path.clone()),
+            (None, None) => None,
+        }
+    }
 }
 
 // implement empty() constructor
@@ -927,6 +1047,9 @@ impl EventMetadata {
         Self {
             ui_element: None,
             timestamp: None,
+            screenshot: None,
+            window: None,
+            masked_element: None,
         }
     }
 
@@ -940,6 +1063,9 @@ impl EventMetadata {
         Self {
             ui_element: None,
             timestamp: Some(now),
+            screenshot: None,
+            window: None,
+            masked_element: None,
         }
     }
 
@@ -953,6 +1079,9 @@ impl EventMetadata {
         Self {
             ui_element,
             timestamp: Some(now),
+            screenshot: None,
+            window: None,
+            masked_element: None,
         }
     }
 }
@@ -1016,13 +1145,26 @@ pub struct SerializableEventMetadata {
     /// The exact timestamp when this event occurred (milliseconds since epoch)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub timestamp: Option<u64>,
+
+    /// Screenshots taken for this event
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub screenshot: Option<EventScreenshot>,
+
+    /// Window the event happened in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window: Option<WindowContext>,
 }
 
 impl From<&EventMetadata> for SerializableEventMetadata {
     fn from(metadata: &EventMetadata) -> Self {
         Self {
-            ui_element: metadata.ui_element.as_ref().map(|elem| elem.into()),
+            ui_element: metadata
+                .masked_element
+                .clone()
+                .or_else(|| metadata.ui_element.as_ref().map(|elem| elem.into())),
             timestamp: metadata.timestamp,
+            screenshot: metadata.screenshot.clone(),
+            window: metadata.window.clone(),
         }
     }
 }
@@ -1387,24 +1529,35 @@ impl From<&RecordedEvent> for SerializableRecordedEvent {
 /// Serializable version of RecordedWorkflow for JSON export
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SerializableRecordedWorkflow {
+    /// Missing in files written before versioning (version 1)
+    #[serde(default = "legacy_format_version")]
+    pub format_version: u32,
     pub name: String,
     pub start_time: u64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub end_time: Option<u64>,
     pub events: Vec<SerializableRecordedEvent>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub markers: Vec<WorkflowMarker>,
 }
 
 impl From<&RecordedWorkflow> for SerializableRecordedWorkflow {
     fn from(workflow: &RecordedWorkflow) -> Self {
         Self {
+            format_version: crate::RECORDING_FORMAT_VERSION,
             name: workflow.name.clone(),
             start_time: workflow.start_time,
             end_time: workflow.end_time,
             events: workflow.events.iter().map(|e| e.into()).collect(),
+            markers: workflow.markers.clone(),
         }
     }
 }
 
+fn legacy_format_version() -> u32 {
+    1
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1475,4 +1628,48 @@ mod tests {
         assert!(!is_empty_string(&Some("something empty".to_string())));
         assert!(!is_empty_string(&Some("none selected".to_string())));
     }
+
+    #[test]
+    fn test_undo_actions() {
+        let key = |key_code| {
+            WorkflowEvent::Keyboard(KeyboardEvent {
+                key_code,
+                is_key_down: true,
+                ctrl_pressed: false,
+                alt_pressed: false,
+                shift_pressed: false,
+                win_pressed: false,
+                character: None,
+                scan_code: None,
+                metadata: EventMetadata::empty(),
+            })
+        };
+        let hotkey = |combination: &str| {
+            WorkflowEvent::Hotkey(HotkeyEvent {
+                combination: combination.to_string(),
+                action: None,
+                is_global: true,
+                metadata: EventMetadata::empty(),
+            })
+        };
+        let mut workflow = RecordedWorkflow::new("Undo".to_string());
+        for event in [
+            hotkey("Ctrl+C"),
+            key(0x11),
+            key(0x56),
+            hotkey("Ctrl+V"),
+            key(0x11),
+            key(0x53),
+            hotkey("Ctrl+S"),
+        ] {
+            workflow.add_event(event);
+        }
+
+        // The keystrokes leading up to an undone action go with it.
+        assert_eq!(workflow.undo_actions(2), 2);
+        assert_eq!(workflow.events.len(), 1);
+        assert_eq!(workflow.undo_actions(5), 1);
+        assert!(workflow.events.is_empty());
+        assert_eq!(workflow.undo_actions(1), 0);
+    }
 }
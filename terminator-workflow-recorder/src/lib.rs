@@ -1,26 +1,33 @@
-//! Workflow Recorder crate for Windows
+//! Workflow Recorder crate for Windows and Linux
 //!
-//! This crate provides functionality to record user interactions with the Windows UI,
+//! This crate provides functionality to record user interactions with the desktop UI,
 //! including mouse clicks, keyboard input, and window focus changes.
 //! The recorded workflow can be saved as a JSON file for later playback or analysis.
 
-#![cfg_attr(not(target_os = "windows"), allow(unused))]
+#![cfg_attr(not(any(target_os = "windows", target_os = "linux")), allow(unused))]
 
 mod error;
 mod events;
+mod postprocess;
 mod recorder;
+mod recording_file;
+mod storyboard;
 
 pub use error::*;
 pub use events::{
     ApplicationSwitchEvent, ApplicationSwitchMethod, BrowserClickEvent, BrowserTabNavigationEvent,
     BrowserTextInputEvent, ButtonInteractionType, ClickEvent, ClipboardAction, ClipboardEvent,
-    DomElementInfo, DragDropEvent, EnhancedUIElement, EventMetadata, FieldFocusMethod, HotkeyEvent,
-    InteractionContext, KeyboardEvent, McpToolStep, MouseButton, MouseEvent, MouseEventType,
-    Position, RecordedEvent, RecordedWorkflow, Rect, SelectionMethod, SelectorCandidate, TabAction,
-    TabNavigationMethod, TextInputCompletedEvent, TextInputMethod, TextSelectionEvent,
-    UIElementInfo, WorkflowEvent,
+    DomElementInfo, DragDropEvent, EnhancedUIElement, EventMetadata, EventScreenshot,
+    FieldFocusMethod, HotkeyEvent, InteractionContext, KeyboardEvent, McpToolStep, MouseButton,
+    MouseEvent, MouseEventType, Position, RecordedEvent, RecordedWorkflow, Rect, ScreenshotImage,
+    SelectionMethod, SelectorCandidate, SerializableWorkflowEvent, TabAction, TabNavigationMethod,
+    TextInputCompletedEvent, TextInputMethod, TextSelectionEvent, UIElementInfo, WindowContext,
+    WorkflowEvent, WorkflowMarker,
 };
+pub use postprocess::*;
 pub use recorder::*;
+pub use recording_file::*;
+pub use storyboard::render_storyboard;
 
 #[cfg(target_os = "windows")]
 pub mod structs {
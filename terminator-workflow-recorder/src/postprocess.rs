@@ -0,0 +1,574 @@
+//! Post-processing of recorded events.
+//!
+//! Raw recordings contain noise that a person performing the task would never write down:
+//! focus clicks right before typing, Alt-Tabs that end in the application they started from,
+//! a field edited twice in a row, a combo box opened only to pick one of its items. A
+//! [`PostProcessPipeline`] runs a list of [`PostProcessPass`]es over the events of a
+//! recording and reports what each of them changed. Raw keyboard and mouse events are left
+//! in place; passes only look at user actions.
+
+use crate::events::SerializableUIElement;
+use crate::{
+    ApplicationSwitchEvent, ButtonInteractionType, ClickEvent, EventMetadata, FieldFocusMethod,
+    MouseEventType, RecordedEvent, RecordedWorkflow, TextInputCompletedEvent, TextInputMethod,
+    WorkflowEvent,
+};
+use serde::{Deserialize, Serialize};
+
+/// A rewrite of the events of a recording
+pub trait PostProcessPass: Send + Sync {
+    /// Name of the pass in the report
+    fn name(&self) -> &str;
+
+    /// Rewrite `events` in place and describe each change
+    fn apply(&self, events: &mut Vec<RecordedEvent>) -> Vec<String>;
+}
+
+/// Which of the built-in passes `PostProcessPipeline::from_config` runs
+#[derive(Debug, Clone)]
+pub struct PostProcessConfig {
+    /// Drop a click on the element that was clicked just before
+    pub drop_repeated_clicks: bool,
+
+    /// Clicks on the same element closer than this are repeated clicks
+    pub repeated_click_window_ms: u64,
+
+    /// Drop application switches that end in the application they started from
+    pub drop_noop_app_switches: bool,
+
+    /// Turn a click on a combo box followed by a click on one of its items into one selection
+    pub collapse_dropdown_selection: bool,
+
+    /// Drop the click that focused a field right before text was entered into it
+    pub collapse_click_then_type: bool,
+
+    /// Keep only the final value of consecutive edits to the same field
+    pub merge_field_edits: bool,
+}
+
+impl Default for PostProcessConfig {
+    fn default() -> Self {
+        Self {
+            drop_repeated_clicks: true,
+            repeated_click_window_ms: 1000,
+            drop_noop_app_switches: true,
+            collapse_dropdown_selection: true,
+            collapse_click_then_type: true,
+            merge_field_edits: true,
+        }
+    }
+}
+
+impl PostProcessConfig {
+    /// A configuration that runs no passes
+    pub fn disabled() -> Self {
+        Self {
+            drop_repeated_clicks: false,
+            drop_noop_app_switches: false,
+            collapse_dropdown_selection: false,
+            collapse_click_then_type: false,
+            merge_field_edits: false,
+            ..Self::default()
+        }
+    }
+}
+
+/// What one pass changed
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PassReport {
+    pub pass: String,
+    pub events_before: usize,
+    pub events_after: usize,
+    /// One line per change, e.g. "dropped focus click on 'Name' (Edit) before typing"
+    pub changes: Vec<String>,
+}
+
+/// What a pipeline run changed, pass by pass
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PostProcessReport {
+    pub passes: Vec<PassReport>,
+}
+
+impl PostProcessReport {
+    /// Whether any pass changed the events
+    pub fn is_empty(&self) -> bool {
+        self.passes.iter().all(|p| p.changes.is_empty())
+    }
+
+    /// All changes as "pass: change" lines, in the order they were made
+    pub fn notes(&self) -> Vec<String> {
+        self.passes
+            .iter()
+            .flat_map(|p| p.changes.iter().map(move |c| format!("{}: {}", p.pass, c)))
+            .collect()
+    }
+}
+
+/// An ordered list of passes over the events of a recording
+#[derive(Default)]
+pub struct PostProcessPipeline {
+    passes: Vec<Box<dyn PostProcessPass>>,
+}
+
+impl PostProcessPipeline {
+    /// A pipeline without passes
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The built-in passes enabled in `config`, in the order they build on each other
+    pub fn from_config(config: &PostProcessConfig) -> Self {
+        let mut pipeline = Self::new();
+        if config.drop_repeated_clicks {
+            pipeline = pipeline.with_pass(DropRepeatedClicks {
+                window_ms: config.repeated_click_window_ms,
+            });
+        }
+        if config.drop_noop_app_switches {
+            pipeline = pipeline.with_pass(DropNoopAppSwitches);
+        }
+        if config.collapse_dropdown_selection {
+            pipeline = pipeline.with_pass(CollapseDropdownSelection);
+        }
+        if config.collapse_click_then_type {
+            pipeline = pipeline.with_pass(CollapseClickThenType);
+        }
+        if config.merge_field_edits {
+            pipeline = pipeline.with_pass(MergeFieldEdits);
+        }
+        pipeline
+    }
+
+    /// Append a pass
+    pub fn with_pass(mut self, pass: impl PostProcessPass + 'static) -> Self {
+        self.passes.push(Box::new(pass));
+        self
+    }
+
+    /// Run the passes over `events` in order
+    pub fn run(&self, events: &mut Vec<RecordedEvent>) -> PostProcessReport {
+        let passes = self
+            .passes
+            .iter()
+            .map(|pass| {
+                let events_before = events.len();
+                let changes = pass.apply(events);
+                PassReport {
+                    pass: pass.name().to_string(),
+                    events_before,
+                    events_after: events.len(),
+                    changes,
+                }
+            })
+            .collect();
+        PostProcessReport { passes }
+    }
+
+    /// Run the passes over the events of `workflow`
+    pub fn process(&self, workflow: &mut RecordedWorkflow) -> PostProcessReport {
+        self.run(&mut workflow.events)
+    }
+}
+
+/// Drops a click on the element clicked by the previous user action within a time window.
+/// Clicks that change state (toggles, dropdowns), the clicks of a double-click and clicks
+/// whose element can't be told apart from others by automation id, bounds or window
+/// are kept.
+pub struct DropRepeatedClicks {
+    pub window_ms: u64,
+}
+
+impl PostProcessPass for DropRepeatedClicks {
+    fn name(&self) -> &str {
+        "drop_repeated_clicks"
+    }
+
+    fn apply(&self, events: &mut Vec<RecordedEvent>) -> Vec<String> {
+        let mut changes = Vec::new();
+        let mut previous: Option<(u64, &ClickEvent)> = None;
+        let mut double_click = false;
+        let mut dropped = vec![false; events.len()];
+        for (index, recorded) in events.iter().enumerate() {
+            if let WorkflowEvent::Mouse(mouse) = &recorded.event {
+                double_click |= mouse.event_type == MouseEventType::DoubleClick;
+            }
+            if recorded.event.is_raw_input() {
+                continue;
+            }
+            let after_double_click = std::mem::take(&mut double_click);
+            let WorkflowEvent::Click(click) = &recorded.event else {
+                previous = None;
+                continue;
+            };
+            // The raw double-click may also follow the second click
+            let is_double_click = after_double_click
+                || events[index + 1..]
+                    .iter()
+                    .take_while(|later| later.event.is_raw_input())
+                    .any(|later| {
+                        matches!(&later.event, WorkflowEvent::Mouse(mouse)
+                            if mouse.event_type == MouseEventType::DoubleClick)
+                    });
+            if let Some((timestamp, last)) = previous {
+                if !is_double_click
+                    && !changes_state(click)
+                    && same_click_target(last, click)
+                    && recorded.timestamp.saturating_sub(timestamp) <= self.window_ms
+                {
+                    dropped[index] = true;
+                    changes.push(format!(
+                        "dropped repeated click on {}",
+                        describe_click(click)
+                    ));
+                    continue;
+                }
+            }
+            previous = Some((recorded.timestamp, click));
+        }
+        remove_marked(events, &dropped);
+        changes
+    }
+}
+
+/// Drops application switches that end in the application they started from. A run of
+/// switches with no other user action in between (Alt-Tab cycling) is kept as its last
+/// switch, starting from the first one's application.
+pub struct DropNoopAppSwitches;
+
+impl PostProcessPass for DropNoopAppSwitches {
+    fn name(&self) -> &str {
+        "drop_noop_app_switches"
+    }
+
+    fn apply(&self, events: &mut Vec<RecordedEvent>) -> Vec<String> {
+        let mut changes = Vec::new();
+        let mut dropped = vec![false; events.len()];
+        let mut current_app: Option<String> = None;
+        let mut index = 0;
+        while index < events.len() {
+            let Some(first) = app_switch(&events[index].event) else {
+                index += 1;
+                continue;
+            };
+            let from = current_app
+                .clone()
+                .or_else(|| first.from_application.clone());
+
+            // Collect the run of switches that starts here
+            let mut run = vec![index];
+            let mut next = index + 1;
+            while next < events.len() {
+                if app_switch(&events[next].event).is_some() {
+                    run.push(next);
+                } else if !events[next].event.is_raw_input() {
+                    break;
+                }
+                next += 1;
+            }
+            let last_index = run[run.len() - 1];
+            let target = app_switch(&events[last_index].event)
+                .map(|last| last.to_application.clone())
+                .unwrap_or_default();
+
+            if from.as_deref() == Some(target.as_str()) {
+                for &i in &run {
+                    dropped[i] = true;
+                }
+                changes.push(format!(
+                    "dropped {} switch(es) that ended in {}",
+                    run.len(),
+                    target
+                ));
+            } else if run.len() > 1 {
+                for &i in &run[..run.len() - 1] {
+                    dropped[i] = true;
+                }
+                if let WorkflowEvent::ApplicationSwitch(last) = &mut events[last_index].event {
+                    last.from_application = from;
+                    last.switch_count = Some(run.len() as u32);
+                }
+                changes.push(format!(
+                    "merged {} consecutive switches into one to {}",
+                    run.len(),
+                    target
+                ));
+            }
+            current_app = Some(target);
+            index = next;
+        }
+        remove_marked(events, &dropped);
+        changes
+    }
+}
+
+/// Turns a click on a combo box followed by a click on one of its items into a single
+/// `TextInputCompleted` event on the combo box with `TextInputMethod::Suggestion`, which the
+/// MCP converter emits as `select_option`. A suggestion input the recorder emitted for the
+/// same item is absorbed.
+pub struct CollapseDropdownSelection;
+
+impl PostProcessPass for CollapseDropdownSelection {
+    fn name(&self) -> &str {
+        "collapse_dropdown_selection"
+    }
+
+    fn apply(&self, events: &mut Vec<RecordedEvent>) -> Vec<String> {
+        let mut changes = Vec::new();
+        let mut dropped = vec![false; events.len()];
+        let mut index = 0;
+        while index < events.len() {
+            let WorkflowEvent::Click(opener) = &events[index].event else {
+                index += 1;
+                continue;
+            };
+            if !is_dropdown_opener(opener) {
+                index += 1;
+                continue;
+            }
+            let Some(item_index) = next_action(events, index) else {
+                break;
+            };
+            let WorkflowEvent::Click(item) = &events[item_index].event else {
+                index = item_index;
+                continue;
+            };
+            if !is_dropdown_item(item) || item.element_text.is_empty() {
+                index = item_index;
+                continue;
+            }
+
+            let option = item.element_text.clone();
+            let selection = TextInputCompletedEvent {
+                text_value: option.clone(),
+                field_name: Some(opener.element_text.clone()).filter(|name| !name.is_empty()),
+                field_type: opener.element_role.clone(),
+                input_method: TextInputMethod::Suggestion,
+                focus_method: FieldFocusMethod::MouseClick,
+                typing_duration_ms: events[item_index]
+                    .timestamp
+                    .saturating_sub(events[index].timestamp),
+                keystroke_count: 0,
+                metadata: opener.metadata.clone(),
+            };
+            changes.push(format!(
+                "collapsed opening {} and clicking '{}' into a selection",
+                describe_click(opener),
+                option
+            ));
+            events[index].event = WorkflowEvent::TextInputCompleted(selection);
+            dropped[item_index] = true;
+
+            let mut after = item_index;
+            if let Some(echo_index) = next_action(events, item_index) {
+                if let WorkflowEvent::TextInputCompleted(echo) = &events[echo_index].event {
+                    if echo.input_method == TextInputMethod::Suggestion && echo.text_value == option
+                    {
+                        dropped[echo_index] = true;
+                        after = echo_index;
+                    }
+                }
+            }
+            index = after + 1;
+        }
+        remove_marked(events, &dropped);
+        changes
+    }
+}
+
+/// Drops the click that focused a field when the next user action enters text into that
+/// field; `type_into_element` focuses the field itself.
+pub struct CollapseClickThenType;
+
+impl PostProcessPass for CollapseClickThenType {
+    fn name(&self) -> &str {
+        "collapse_click_then_type"
+    }
+
+    fn apply(&self, events: &mut Vec<RecordedEvent>) -> Vec<String> {
+        let mut changes = Vec::new();
+        let mut dropped = vec![false; events.len()];
+        for index in 0..events.len() {
+            let WorkflowEvent::Click(click) = &events[index].event else {
+                continue;
+            };
+            let Some(next) = next_action(events, index) else {
+                continue;
+            };
+            let WorkflowEvent::TextInputCompleted(input) = &events[next].event else {
+                continue;
+            };
+            if input.input_method != TextInputMethod::Suggestion
+                && click_targets_field(click, input)
+            {
+                dropped[index] = true;
+                changes.push(format!(
+                    "dropped focus click on {} before typing",
+                    describe_click(click)
+                ));
+            }
+        }
+        remove_marked(events, &dropped);
+        changes
+    }
+}
+
+/// Keeps only the last of consecutive text inputs into the same field, since it holds the
+/// field's final value (e.g. after a correction with Backspace). Inputs are only merged
+/// when they are known to be into the same element, by automation id, bounds or window.
+pub struct MergeFieldEdits;
+
+impl PostProcessPass for MergeFieldEdits {
+    fn name(&self) -> &str {
+        "merge_field_edits"
+    }
+
+    fn apply(&self, events: &mut Vec<RecordedEvent>) -> Vec<String> {
+        let mut changes = Vec::new();
+        let mut dropped = vec![false; events.len()];
+        for index in 0..events.len() {
+            let WorkflowEvent::TextInputCompleted(edit) = &events[index].event else {
+                continue;
+            };
+            let Some(next) = next_action(events, index) else {
+                continue;
+            };
+            let WorkflowEvent::TextInputCompleted(later) = &events[next].event else {
+                continue;
+            };
+            if edit.input_method == TextInputMethod::Suggestion
+                || later.input_method == TextInputMethod::Suggestion
+                || edit.field_type != later.field_type
+                || edit.field_name != later.field_name
+                || !same_element(&edit.metadata, &later.metadata)
+            {
+                continue;
+            }
+
+            let (earlier_value, duration, keystrokes, method) = (
+                edit.text_value.clone(),
+                edit.typing_duration_ms,
+                edit.keystroke_count,
+                edit.input_method.clone(),
+            );
+            if let WorkflowEvent::TextInputCompleted(later) = &mut events[next].event {
+                later.typing_duration_ms += duration;
+                later.keystroke_count += keystrokes;
+                if later.input_method != method {
+                    later.input_method = TextInputMethod::Mixed;
+                }
+                changes.push(format!(
+                    "merged edit '{}' into final value '{}' of {}",
+                    earlier_value,
+                    later.text_value,
+                    later.field_name.as_deref().unwrap_or(&later.field_type)
+                ));
+            }
+            dropped[index] = true;
+        }
+        remove_marked(events, &dropped);
+        changes
+    }
+}
+
+/// Index of the next user action after `index`, skipping raw keyboard and mouse events
+fn next_action(events: &[RecordedEvent], index: usize) -> Option<usize> {
+    (index + 1..events.len()).find(|&i| !events[i].event.is_raw_input())
+}
+
+fn remove_marked(events: &mut Vec<RecordedEvent>, dropped: &[bool]) {
+    let mut flags = dropped.iter();
+    events.retain(|_| !flags.next().copied().unwrap_or(false));
+}
+
+fn app_switch(event: &WorkflowEvent) -> Option<&ApplicationSwitchEvent> {
+    match event {
+        WorkflowEvent::ApplicationSwitch(switch) => Some(switch),
+        _ => None,
+    }
+}
+
+fn same_click_target(a: &ClickEvent, b: &ClickEvent) -> bool {
+    a.element_role == b.element_role
+        && a.element_text == b.element_text
+        && same_element(&a.metadata, &b.metadata)
+}
+
+/// A second click on a toggle or dropdown undoes the first, so it is not noise.
+fn changes_state(click: &ClickEvent) -> bool {
+    matches!(
+        click.interaction_type,
+        ButtonInteractionType::Toggle | ButtonInteractionType::DropdownToggle
+    )
+}
+
+/// Whether two events are known to be on the same element: every identity both events
+/// recorded (automation id, bounds, window title) matches, and there is at least one.
+/// Role and name alone can't tell apart e.g. two unnamed edit fields.
+fn same_element(a: &EventMetadata, b: &EventMetadata) -> bool {
+    let (a, b) = (ElementIdentity::of(a), ElementIdentity::of(b));
+    let mut compared = false;
+    let mut check = |matches: Option<bool>| {
+        compared |= matches.is_some();
+        matches != Some(false)
+    };
+    let id = check(a.id.as_ref().zip(b.id.as_ref()).map(|(a, b)| a == b));
+    let bounds = check(a.bounds.zip(b.bounds).map(|(a, b)| a == b));
+    let window = check(
+        a.window
+            .as_ref()
+            .zip(b.window.as_ref())
+            .map(|(a, b)| a == b),
+    );
+    id && bounds && window && compared
+}
+
+struct ElementIdentity {
+    id: Option<String>,
+    bounds: Option<(f64, f64, f64, f64)>,
+    window: Option<String>,
+}
+
+impl ElementIdentity {
+    fn of(metadata: &EventMetadata) -> Self {
+        let element = metadata.masked_element.clone().or_else(|| {
+            metadata
+                .ui_element
+                .as_ref()
+                .map(SerializableUIElement::from)
+        });
+        let (id, bounds, element_window) = match element {
+            Some(element) => (element.id, element.bounds, element.window_title),
+            None => (None, None, None),
+        };
+        Self {
+            id,
+            bounds,
+            window: metadata
+                .window
+                .as_ref()
+                .map(|window| window.title.clone())
+                .or(element_window),
+        }
+    }
+}
+
+fn click_targets_field(click: &ClickEvent, input: &TextInputCompletedEvent) -> bool {
+    click.element_role.eq_ignore_ascii_case(&input.field_type)
+        && click.element_text == input.field_name.clone().unwrap_or_default()
+}
+
+fn is_dropdown_opener(click: &ClickEvent) -> bool {
+    let role = click.element_role.to_lowercase();
+    role.contains("combobox") && !role.contains("comboboxitem")
+}
+
+fn is_dropdown_item(click: &ClickEvent) -> bool {
+    let role = click.element_role.to_lowercase();
+    ["listitem", "menuitem", "option", "comboboxitem"]
+        .iter()
+        .any(|item| role.contains(item))
+}
+
+fn describe_click(click: &ClickEvent) -> String {
+    format!("'{}' ({})", click.element_text, click.element_role)
+}
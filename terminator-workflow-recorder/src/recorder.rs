@@ -1,8 +1,16 @@
-use crate::{RecordedWorkflow, Result, WorkflowEvent, WorkflowRecorderError};
+use crate::{
+    RecordedWorkflow, RecordingEncoding, RecordingWriter, Result, WorkflowEvent,
+    WorkflowRecorderError,
+};
 use std::{
     collections::HashSet,
-    path::Path,
-    sync::{Arc, Mutex},
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
 };
 use tokio::sync::broadcast;
 use tokio_stream::Stream;
@@ -11,11 +19,23 @@ use tracing::info;
 #[cfg(target_os = "windows")]
 pub mod windows;
 
+#[cfg(target_os = "linux")]
+pub mod linux;
+
 pub mod browser_context;
+mod keys;
+mod masking;
+mod screenshots;
+
+pub use self::masking::*;
+pub use self::screenshots::*;
 
 #[cfg(target_os = "windows")]
 pub use self::windows::*;
 
+#[cfg(target_os = "linux")]
+pub use self::linux::*;
+
 /// Performance mode for the workflow recorder
 #[derive(Debug, Clone, PartialEq, Default)]
 pub enum PerformanceMode {
@@ -169,8 +189,31 @@ pub struct WorkflowRecorderConfig {
 
     /// Reduce expensive UI element capture operations
     pub reduce_ui_element_capture: bool,
+
+    /// Masking of passwords and personal data, applied before events are streamed or saved
+    pub pii_masking: PiiMaskingConfig,
+
+    /// Global hotkey that pauses and resumes recording (e.g. "Ctrl+Shift+F9")
+    /// None disables the hotkey; `pause()`/`resume()` still work
+    pub pause_hotkey: Option<String>,
+
+    /// Screenshots of clicks, text inputs and application switches (off by default)
+    pub event_screenshots: EventScreenshotConfig,
+
+    /// Record the window of each user action in `EventMetadata::window`, so that
+    /// conversions can check which windows and dialogs appeared after it
+    pub record_window_context: bool,
+
+    /// Write the recording to this gzip-compressed JSON Lines file (the path must end
+    /// in `.gz`) while recording, instead of keeping every event in memory. Only the
+    /// events of the last `UNDO_DEPTH` actions are kept until `stop()` writes them.
+    pub output_path: Option<PathBuf>,
 }
 
+/// Actions kept in memory while recording to `output_path`, and so the most
+/// `undo_last_actions` can remove
+pub const UNDO_DEPTH: usize = 50;
+
 impl Default for WorkflowRecorderConfig {
     fn default() -> Self {
         Self {
@@ -409,6 +452,11 @@ impl Default for WorkflowRecorderConfig {
             filter_mouse_noise: false,
             filter_keyboard_noise: false,
             reduce_ui_element_capture: false,
+            pii_masking: PiiMaskingConfig::default(),
+            pause_hotkey: Some("Ctrl+Shift+F9".to_string()),
+            event_screenshots: EventScreenshotConfig::default(),
+            record_window_context: true,
+            output_path: None,
         }
     }
 }
@@ -466,18 +514,28 @@ impl WorkflowRecorderConfig {
 
 /// The workflow recorder
 pub struct WorkflowRecorder {
-    /// The recorded workflow
+    /// The recorded workflow. While recording to `output_path`, only the events not
+    /// written yet.
     pub workflow: Arc<Mutex<RecordedWorkflow>>,
 
+    /// Writer of `output_path`, open while recording
+    output: Arc<Mutex<Option<RecordingWriter<BufWriter<File>>>>>,
+
     /// The event sender
     event_tx: broadcast::Sender<WorkflowEvent>,
 
     /// The configuration
     config: WorkflowRecorderConfig,
 
+    /// Whether recording is paused; events are dropped while it is set
+    paused: Arc<AtomicBool>,
+
     /// The platform-specific recorder
     #[cfg(target_os = "windows")]
     windows_recorder: Option<WindowsRecorder>,
+
+    #[cfg(target_os = "linux")]
+    linux_recorder: Option<LinuxRecorder>,
 }
 
 impl WorkflowRecorder {
@@ -488,10 +546,14 @@ impl WorkflowRecorder {
 
         Self {
             workflow,
+            output: Arc::new(Mutex::new(None)),
             event_tx,
             config,
+            paused: Arc::new(AtomicBool::new(false)),
             #[cfg(target_os = "windows")]
             windows_recorder: None,
+            #[cfg(target_os = "linux")]
+            linux_recorder: None,
         }
     }
 
@@ -505,36 +567,212 @@ impl WorkflowRecorder {
         })
     }
 
+    /// Subscribe to the recorded events. Unlike `event_stream`, a receiver that falls
+    /// more than the channel capacity (100 events) behind gets `RecvError::Lagged` with
+    /// the number of skipped events and can keep receiving.
+    pub fn subscribe(&self) -> broadcast::Receiver<WorkflowEvent> {
+        self.event_tx.subscribe()
+    }
+
     /// Start recording
     pub async fn start(&mut self) -> Result<()> {
         info!("Starting workflow recording");
+        self.open_output()?;
 
         #[cfg(target_os = "windows")]
         {
             let workflow = Arc::clone(&self.workflow);
-            let event_tx = self.event_tx.clone();
+            let raw_event_tx = self.start_event_pipeline()?;
 
             // Start the Windows recorder
-            let windows_recorder = WindowsRecorder::new(self.config.clone(), event_tx).await?;
+            let windows_recorder = WindowsRecorder::new(self.config.clone(), raw_event_tx).await?;
             self.windows_recorder = Some(windows_recorder);
 
             // Start the event processing task
             let event_rx = self.event_tx.subscribe();
+            let output = Arc::clone(&self.output);
+            tokio::spawn(async move {
+                Self::process_events(workflow, output, event_rx).await;
+            });
+
+            Ok(())
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            let workflow = Arc::clone(&self.workflow);
+            let raw_event_tx = self.start_event_pipeline()?;
+
+            // Start the Linux recorder
+            let linux_recorder = LinuxRecorder::new(self.config.clone(), raw_event_tx).await?;
+            self.linux_recorder = Some(linux_recorder);
+
+            // Start the event processing task
+            let event_rx = self.event_tx.subscribe();
+            let output = Arc::clone(&self.output);
             tokio::spawn(async move {
-                Self::process_events(workflow, event_rx).await;
+                Self::process_events(workflow, output, event_rx).await;
             });
 
             Ok(())
         }
 
-        #[cfg(not(target_os = "windows"))]
+        #[cfg(not(any(target_os = "windows", target_os = "linux")))]
         {
             Err(WorkflowRecorderError::InitializationError(
-                "Workflow recording is only supported on Windows".to_string(),
+                "Workflow recording is only supported on Windows and Linux".to_string(),
             ))
         }
     }
 
+    /// Spawn the thread that handles the pause hotkey, drops events while paused, records
+    /// the window of the rest and masks them, and the task that forwards them to
+    /// `event_tx` in order once their screenshots are taken. Returns the sender the
+    /// platform recorder should use.
+    #[cfg(any(target_os = "windows", target_os = "linux"))]
+    fn start_event_pipeline(&self) -> Result<broadcast::Sender<WorkflowEvent>> {
+        let mut masker = PiiMasker::new(&self.config.pii_masking)?;
+        let pause_hotkey = self
+            .config
+            .pause_hotkey
+            .as_deref()
+            .map(|combination| {
+                keys::Hotkey::parse(combination).ok_or_else(|| {
+                    WorkflowRecorderError::InitializationError(format!(
+                        "Invalid pause hotkey '{combination}'"
+                    ))
+                })
+            })
+            .transpose()?;
+        let capturer = if self.config.event_screenshots.enabled {
+            Some(Arc::new(ScreenshotCapturer::new(
+                &self.config.event_screenshots,
+            )?))
+        } else {
+            None
+        };
+        let record_window_context = self.config.record_window_context;
+        let paused = Arc::clone(&self.paused);
+        let (raw_event_tx, mut raw_event_rx) = broadcast::channel(100);
+        // Events waiting for their screenshots, in recording order
+        let (pending_tx, mut pending_rx) = tokio::sync::mpsc::unbounded_channel::<(
+            WorkflowEvent,
+            Option<tokio::task::JoinHandle<Option<crate::EventScreenshot>>>,
+        )>();
+        let event_tx = self.event_tx.clone();
+        tokio::spawn(async move {
+            while let Some((mut event, screenshot)) = pending_rx.recv().await {
+                if let Some(screenshot) = screenshot {
+                    event.metadata_mut().screenshot = screenshot.await.ok().flatten();
+                }
+                let _ = event_tx.send(event);
+            }
+        });
+        // Window context and masking query the accessibility tree, so this stage runs on
+        // its own thread rather than on the runtime's workers.
+        let runtime = tokio::runtime::Handle::current();
+        let pipeline = move || {
+            // `capturer.start` spawns the capture on the runtime
+            let _runtime = runtime.enter();
+            loop {
+                match raw_event_rx.blocking_recv() {
+                    Ok(mut event) => {
+                        if let Some(hotkey) = &pause_hotkey {
+                            match &event {
+                                WorkflowEvent::Keyboard(key) if hotkey.matches(key) => {
+                                    if key.is_key_down {
+                                        let was_paused = paused.fetch_xor(true, Ordering::SeqCst);
+                                        info!(
+                                            "Recording {} by hotkey",
+                                            if was_paused { "resumed" } else { "paused" }
+                                        );
+                                    }
+                                    continue;
+                                }
+                                // The platform recorders also report it as a shortcut
+                                WorkflowEvent::Hotkey(e)
+                                    if hotkey.matches_combination(&e.combination) =>
+                                {
+                                    continue;
+                                }
+                                _ => {}
+                            }
+                        }
+                        if paused.load(Ordering::SeqCst) {
+                            continue;
+                        }
+                        if record_window_context {
+                            attach_window_context(&mut event);
+                        }
+                        // Screenshots would show what masking hid
+                        let masked = masker.mask_event(&mut event);
+                        let screenshot = capturer
+                            .as_ref()
+                            .filter(|_| !masked)
+                            .and_then(|capturer| capturer.start(&event));
+                        if pending_tx.send((event, screenshot)).is_err() {
+                            break;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!("Masking stage lagged, dropped {} events", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        };
+        std::thread::Builder::new()
+            .name("recorder-pipeline".to_string())
+            .spawn(pipeline)
+            .map_err(|e| {
+                WorkflowRecorderError::InitializationError(format!(
+                    "Failed to start the event pipeline: {e}"
+                ))
+            })?;
+        Ok(raw_event_tx)
+    }
+
+    /// Pause recording: events are dropped until `resume()` is called
+    pub fn pause(&self) {
+        info!("Pausing workflow recording");
+        self.paused.store(true, Ordering::SeqCst);
+    }
+
+    /// Resume a paused recording
+    pub fn resume(&self) {
+        info!("Resuming workflow recording");
+        self.paused.store(false, Ordering::SeqCst);
+    }
+
+    /// Whether recording is paused
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    /// Add a named checkpoint; the events that follow become a group of that name
+    /// when the workflow is converted to MCP steps
+    pub fn add_marker(&self, label: impl Into<String>) -> Result<()> {
+        let label = label.into();
+        info!("Adding marker '{}'", label);
+        let mut workflow = self.workflow.lock().map_err(|e| {
+            WorkflowRecorderError::RecordingError(format!("Failed to lock workflow: {e}"))
+        })?;
+        workflow.add_marker(label);
+        Ok(())
+    }
+
+    /// Remove the last `count` recorded actions (clicks, text inputs, hotkeys, ...)
+    /// together with their raw keystrokes and mouse events. Returns how many were removed;
+    /// while recording to `output_path`, at most `UNDO_DEPTH`.
+    pub fn undo_last_actions(&self, count: usize) -> Result<usize> {
+        let mut workflow = self.workflow.lock().map_err(|e| {
+            WorkflowRecorderError::RecordingError(format!("Failed to lock workflow: {e}"))
+        })?;
+        let removed = workflow.undo_actions(count);
+        info!("Undid {} recorded actions", removed);
+        Ok(removed)
+    }
+
     /// Stop recording
     pub async fn stop(&mut self) -> Result<()> {
         info!("Stopping workflow recording");
@@ -546,11 +784,60 @@ impl WorkflowRecorder {
             }
         }
 
+        #[cfg(target_os = "linux")]
+        {
+            if let Some(linux_recorder) = self.linux_recorder.take() {
+                linux_recorder.stop()?;
+            }
+        }
+
         // Mark the workflow as finished
         if let Ok(mut workflow) = self.workflow.lock() {
             workflow.finish();
         }
 
+        self.close_output()
+    }
+
+    /// Create `output_path` if set
+    fn open_output(&self) -> Result<()> {
+        let Some(path) = &self.config.output_path else {
+            return Ok(());
+        };
+        if RecordingEncoding::for_path(path) != RecordingEncoding::JsonLinesGzip {
+            return Err(WorkflowRecorderError::InitializationError(format!(
+                "The output path must end in .gz: {}",
+                path.display()
+            )));
+        }
+        let writer = {
+            let workflow = self.workflow.lock().map_err(|e| {
+                WorkflowRecorderError::InitializationError(format!("Failed to lock workflow: {e}"))
+            })?;
+            RecordingWriter::create(path, &workflow.name, workflow.start_time)?
+        };
+        info!("Writing workflow recording to {:?}", path);
+        if let Ok(mut output) = self.output.lock() {
+            *output = Some(writer);
+        }
+        Ok(())
+    }
+
+    /// Write the remaining events, the markers and the end time to `output_path`
+    fn close_output(&self) -> Result<()> {
+        let Some(mut writer) = self.output.lock().ok().and_then(|mut output| output.take()) else {
+            return Ok(());
+        };
+        let mut workflow = self.workflow.lock().map_err(|e| {
+            WorkflowRecorderError::SaveError(format!("Failed to lock workflow: {e}"))
+        })?;
+        for event in workflow.events.drain(..) {
+            writer.write_event(&event)?;
+        }
+        for marker in &workflow.markers {
+            writer.write_marker(marker)?;
+        }
+        writer.finish(workflow.end_time)?;
         Ok(())
     }
 
@@ -558,10 +845,28 @@ impl WorkflowRecorder {
     pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
         info!("Saving workflow recording to {:?}", path.as_ref());
 
+        // The recording is already in `output_path`; it can only be copied as is.
+        if let Some(output) = &self.config.output_path {
+            if RecordingEncoding::for_path(path.as_ref()) != RecordingEncoding::JsonLinesGzip {
+                return Err(WorkflowRecorderError::SaveError(format!(
+                    "The recording was written to {}, it can only be saved to a .gz path",
+                    output.display()
+                )));
+            }
+            if output != path.as_ref() {
+                std::fs::copy(output, path)?;
+            }
+            return Ok(());
+        }
+
         let workflow = self.workflow.lock().map_err(|e| {
             WorkflowRecorderError::SaveError(format!("Failed to lock workflow: {e}"))
         })?;
 
+        if self.config.event_screenshots.storage == ScreenshotStorage::Files {
+            return save_with_screenshot_files(&workflow, path.as_ref());
+        }
+
         workflow.save_to_file(path).map_err(|e| {
             WorkflowRecorderError::SaveError(format!("Failed to save workflow: {e}"))
         })?;
@@ -572,6 +877,7 @@ impl WorkflowRecorder {
     /// Process events from the event receiver
     async fn process_events(
         workflow: Arc<Mutex<RecordedWorkflow>>,
+        output: Arc<Mutex<Option<RecordingWriter<BufWriter<File>>>>>,
         mut event_rx: broadcast::Receiver<WorkflowEvent>,
     ) {
         while let Ok(event) = event_rx.recv().await {
@@ -592,7 +898,90 @@ impl WorkflowRecorder {
             // Add the event to the workflow (keep lock scope minimal)
             if let Ok(mut workflow_guard) = workflow.lock() {
                 workflow_guard.add_enhanced_event(recorded_event);
+                if let Ok(mut output) = output.lock() {
+                    if let Some(writer) = &mut *output {
+                        if let Err(e) =
+                            write_settled_events(&mut workflow_guard, writer, UNDO_DEPTH)
+                        {
+                            tracing::warn!("Failed to write recorded events: {}", e);
+                        }
+                    }
+                }
             }
         }
     }
 }
+
+/// Write the events before the last `keep_actions` actions, which undo can no longer
+/// reach, and drop them from `workflow`.
+fn write_settled_events<W: Write>(
+    workflow: &mut RecordedWorkflow,
+    writer: &mut RecordingWriter<W>,
+    keep_actions: usize,
+) -> Result<()> {
+    let mut actions = 0;
+    // The raw events after an action belong to the next one, so the last settled event
+    // is the action before the kept ones.
+    let Some(last_settled) = workflow.events.iter().rposition(|event| {
+        if !event.event.is_raw_input() {
+            actions += 1;
+        }
+        actions > keep_actions
+    }) else {
+        return Ok(());
+    };
+    for event in workflow.events.drain(..=last_settled) {
+        writer.write_event(&event)?;
+    }
+    Ok(())
+}
+
+/// Record the window a user action happened in, from its UI element.
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+fn attach_window_context(event: &mut WorkflowEvent) {
+    if event.is_raw_input() || event.metadata().window.is_some() {
+        return;
+    }
+    let Some(element) = event.ui_element() else {
+        return;
+    };
+    let title = element.window_title();
+    if title.is_empty() {
+        return;
+    }
+    let role = element
+        .window()
+        .ok()
+        .flatten()
+        .map(|window| window.role())
+        .unwrap_or_default();
+    event.metadata_mut().window = Some(crate::WindowContext { title, role });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_writes_the_events_undo_cannot_reach() {
+        let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/noisy_form.json");
+        let mut workflow = crate::read_recording(&fixture).unwrap();
+        let recorded = workflow.events.len();
+        let mut writer =
+            RecordingWriter::new(Vec::new(), &workflow.name, workflow.start_time).unwrap();
+
+        write_settled_events(&mut workflow, &mut writer, 10).unwrap();
+        // The mouse event after the second action belongs to the third one.
+        assert_eq!(workflow.events.len(), recorded - 2);
+        assert!(workflow.events[0].event.is_raw_input());
+        let mut undone = workflow.clone();
+        assert_eq!(undone.undo_actions(10), 10);
+        assert!(undone.events.is_empty());
+
+        write_settled_events(&mut workflow, &mut writer, 0).unwrap();
+        assert!(workflow.events.is_empty());
+        let bytes = writer.finish(None).unwrap();
+        let reader = crate::RecordingReader::new(std::io::Cursor::new(bytes)).unwrap();
+        assert_eq!(reader.count(), recorded);
+    }
+}
@@ -0,0 +1,234 @@
+//! Key codes shared by the platform recorders.
+//!
+//! `KeyboardEvent::key_code` always holds a Windows virtual-key code, so recordings look
+//! the same whichever platform they were made on.
+
+use crate::KeyboardEvent;
+use rdev::Key;
+
+/// Convert a Key to a u32
+pub fn key_to_u32(key: &Key) -> u32 {
+    match key {
+        Key::KeyA => 0x41,
+        Key::KeyB => 0x42,
+        Key::KeyC => 0x43,
+        Key::KeyD => 0x44,
+        Key::KeyE => 0x45,
+        Key::KeyF => 0x46,
+        Key::KeyG => 0x47,
+        Key::KeyH => 0x48,
+        Key::KeyI => 0x49,
+        Key::KeyJ => 0x4A,
+        Key::KeyK => 0x4B,
+        Key::KeyL => 0x4C,
+        Key::KeyM => 0x4D,
+        Key::KeyN => 0x4E,
+        Key::KeyO => 0x4F,
+        Key::KeyP => 0x50,
+        Key::KeyQ => 0x51,
+        Key::KeyR => 0x52,
+        Key::KeyS => 0x53,
+        Key::KeyT => 0x54,
+        Key::KeyU => 0x55,
+        Key::KeyV => 0x56,
+        Key::KeyW => 0x57,
+        Key::KeyX => 0x58,
+        Key::KeyY => 0x59,
+        Key::KeyZ => 0x5A,
+        Key::Num0 => 0x30,
+        Key::Num1 => 0x31,
+        Key::Num2 => 0x32,
+        Key::Num3 => 0x33,
+        Key::Num4 => 0x34,
+        Key::Num5 => 0x35,
+        Key::Num6 => 0x36,
+        Key::Num7 => 0x37,
+        Key::Num8 => 0x38,
+        Key::Num9 => 0x39,
+        Key::Escape => 0x1B,
+        Key::Backspace => 0x08,
+        Key::Tab => 0x09,
+        Key::Return => 0x0D,
+        Key::Space => 0x20,
+        Key::LeftArrow => 0x25,
+        Key::UpArrow => 0x26,
+        Key::RightArrow => 0x27,
+        Key::DownArrow => 0x28,
+        Key::Delete => 0x2E,
+        Key::Home => 0x24,
+        Key::End => 0x23,
+        Key::PageUp => 0x21,
+        Key::PageDown => 0x22,
+        Key::F1 => 0x70,
+        Key::F2 => 0x71,
+        Key::F3 => 0x72,
+        Key::F4 => 0x73,
+        Key::F5 => 0x74,
+        Key::F6 => 0x75,
+        Key::F7 => 0x76,
+        Key::F8 => 0x77,
+        Key::F9 => 0x78,
+        Key::F10 => 0x79,
+        Key::F11 => 0x7A,
+        Key::F12 => 0x7B,
+        Key::ShiftLeft => 0xA0,
+        Key::ShiftRight => 0xA1,
+        Key::ControlLeft => 0xA2,
+        Key::ControlRight => 0xA3,
+        Key::Alt => 0xA4,
+        Key::AltGr => 0xA5,
+        Key::MetaLeft => 0x5B,
+        Key::MetaRight => 0x5C,
+        _ => 0,
+    }
+}
+
+/// A key combination such as `Ctrl+Shift+F9`, matched against recorded keystrokes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Hotkey {
+    ctrl: bool,
+    alt: bool,
+    shift: bool,
+    win: bool,
+    key_code: u32,
+}
+
+impl Hotkey {
+    /// Parse `Modifier+...+Key`; modifiers are Ctrl, Alt, Shift and Win (or Super/Meta),
+    /// the key a letter, a digit, F1-F24 or a named key such as Esc, Space or Pause.
+    pub fn parse(combination: &str) -> Option<Self> {
+        let mut hotkey = Hotkey {
+            ctrl: false,
+            alt: false,
+            shift: false,
+            win: false,
+            key_code: 0,
+        };
+        let mut parts = combination.split('+').map(str::trim).peekable();
+        while let Some(part) = parts.next() {
+            if parts.peek().is_none() {
+                hotkey.key_code = key_code(part)?;
+                break;
+            }
+            match part.to_lowercase().as_str() {
+                "ctrl" | "control" => hotkey.ctrl = true,
+                "alt" => hotkey.alt = true,
+                "shift" => hotkey.shift = true,
+                "win" | "super" | "meta" | "cmd" => hotkey.win = true,
+                _ => return None,
+            }
+        }
+        Some(hotkey)
+    }
+
+    /// Whether `event` is this key pressed or released with exactly these modifiers.
+    pub fn matches(&self, event: &KeyboardEvent) -> bool {
+        event.key_code == self.key_code
+            && event.ctrl_pressed == self.ctrl
+            && event.alt_pressed == self.alt
+            && event.shift_pressed == self.shift
+            && event.win_pressed == self.win
+    }
+
+    /// Whether a recorded hotkey combination is this hotkey. Linux records names
+    /// (`Ctrl+Shift+F9`), Windows the virtual key codes held (`[162, 160, 120]`).
+    pub fn matches_combination(&self, combination: &str) -> bool {
+        let recorded = match combination
+            .strip_prefix('[')
+            .and_then(|codes| codes.strip_suffix(']'))
+        {
+            Some(codes) => codes
+                .split(',')
+                .map(|code| code.trim().parse().ok())
+                .collect::<Option<Vec<u32>>>()
+                .and_then(|codes| Self::from_key_codes(&codes)),
+            None => Self::parse(combination),
+        };
+        recorded == Some(*self)
+    }
+
+    fn from_key_codes(codes: &[u32]) -> Option<Self> {
+        let mut hotkey = Hotkey {
+            ctrl: false,
+            alt: false,
+            shift: false,
+            win: false,
+            key_code: 0,
+        };
+        for &code in codes {
+            match code {
+                0x11 | 0xA2 | 0xA3 => hotkey.ctrl = true,
+                0x12 | 0xA4 | 0xA5 => hotkey.alt = true,
+                0x10 | 0xA0 | 0xA1 => hotkey.shift = true,
+                0x5B | 0x5C => hotkey.win = true,
+                _ if hotkey.key_code == 0 => hotkey.key_code = code,
+                _ => return None,
+            }
+        }
+        (hotkey.key_code != 0).then_some(hotkey)
+    }
+}
+
+fn key_code(name: &str) -> Option<u32> {
+    let upper = name.to_uppercase();
+    if let [c] = upper.as_bytes() {
+        return c.is_ascii_alphanumeric().then_some(u32::from(*c));
+    }
+    if let Some(n) = upper.strip_prefix('F').and_then(|n| n.parse::<u32>().ok()) {
+        return (1..=24).contains(&n).then_some(0x6F + n);
+    }
+    Some(match upper.as_str() {
+        "ESC" | "ESCAPE" => 0x1B,
+        "ENTER" | "RETURN" => 0x0D,
+        "SPACE" => 0x20,
+        "TAB" => 0x09,
+        "BACKSPACE" => 0x08,
+        "PAUSE" => 0x13,
+        "INSERT" => 0x2D,
+        "DELETE" => 0x2E,
+        "HOME" => 0x24,
+        "END" => 0x23,
+        "PAGEUP" => 0x21,
+        "PAGEDOWN" => 0x22,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EventMetadata;
+
+    #[test]
+    fn test_hotkey_parse_and_match() {
+        let hotkey = Hotkey::parse("Ctrl+Shift+F9").unwrap();
+        let mut event = KeyboardEvent {
+            key_code: key_to_u32(&Key::F9),
+            is_key_down: true,
+            ctrl_pressed: true,
+            alt_pressed: false,
+            shift_pressed: true,
+            win_pressed: false,
+            character: None,
+            scan_code: None,
+            metadata: EventMetadata::empty(),
+        };
+        assert!(hotkey.matches(&event));
+        event.alt_pressed = true;
+        assert!(!hotkey.matches(&event));
+
+        assert_eq!(Hotkey::parse("alt+p").unwrap().key_code, 0x50);
+        assert!(Hotkey::parse("Ctrl+Hyper+P").is_none());
+        assert!(Hotkey::parse("Ctrl+").is_none());
+    }
+
+    #[test]
+    fn test_hotkey_matches_recorded_combinations() {
+        let hotkey = Hotkey::parse("Ctrl+Shift+F9").unwrap();
+        assert!(hotkey.matches_combination("Ctrl+Shift+F9"));
+        assert!(hotkey.matches_combination("[162, 160, 120]"));
+        assert!(!hotkey.matches_combination("Ctrl+F9"));
+        assert!(!hotkey.matches_combination("[162, 120]"));
+        assert!(!hotkey.matches_combination("[162, 160, 120, 65]"));
+    }
+}
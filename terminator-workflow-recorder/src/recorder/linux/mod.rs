@@ -0,0 +1,1005 @@
+//! Linux recorder.
+//!
+//! Raw mouse and keyboard input comes from the X server's RECORD extension (through
+//! `rdev`), so it needs an X11 session; on Wayland only XWayland clients are seen. The
+//! accessibility side listens to AT-SPI focus, text-changed and window-activate signals.
+//! Both feed a single processor thread that resolves UI elements and turns them into the
+//! same high-level events as the Windows recorder: `Click`, `TextInputCompleted`,
+//! `ApplicationSwitch` and `Hotkey`.
+//!
+//! `rdev` can't stop listening, so one input listener thread serves every recorder of
+//! the process and delivers to the active one. Stopping a recorder ends its processor
+//! and AT-SPI threads.
+
+use crate::events::{
+    ApplicationSwitchEvent, ApplicationSwitchMethod, ButtonInteractionType, ClickEvent,
+    EventMetadata, FieldFocusMethod, HotkeyEvent, KeyboardEvent, MouseButton, MouseEvent,
+    MouseEventType, Position, TextInputCompletedEvent, TextInputMethod,
+};
+use crate::recorder::keys::key_to_u32;
+use crate::{Result, WorkflowEvent, WorkflowRecorderConfig, WorkflowRecorderError};
+use atspi::events::object::{StateChangedEvent, TextChangedEvent};
+use atspi::events::window::ActivateEvent;
+use atspi::{
+    AccessibilityConnection, Event, ObjectEvents, ObjectRef, Operation, State, WindowEvents,
+};
+use rdev::{Button, EventType, Key};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use terminator::{convert_atspi_object_to_terminator, element_at_point, UIElement};
+use tokio::sync::{broadcast, oneshot};
+use tokio_stream::StreamExt;
+use tracing::{debug, error, info, warn};
+
+/// How long after a click or Tab a focus change is attributed to it.
+const FOCUS_ATTRIBUTION_WINDOW: Duration = Duration::from_millis(1000);
+
+/// How long after Alt+Tab a window activation is attributed to it.
+const ALT_TAB_ATTRIBUTION_WINDOW: Duration = Duration::from_millis(2000);
+
+/// AT-SPI roles that take free-form text.
+const TEXT_INPUT_ROLES: &[&str] = &["text", "entry", "password text", "editbar", "terminal"];
+
+/// The recorder the input listener delivers to. `rdev::listen` never returns, so one
+/// listener thread serves every recorder of the process.
+static INPUT_TARGET: Mutex<Option<InputTarget>> = Mutex::new(None);
+
+/// Whether the input listener thread is running
+static INPUT_LISTENER_STARTED: AtomicBool = AtomicBool::new(false);
+
+static NEXT_RECORDER_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Where raw input goes while a recorder is active
+struct InputTarget {
+    recorder_id: u64,
+    sink: Arc<EventSink>,
+    requests: mpsc::Sender<Request>,
+}
+
+/// An AT-SPI object, as named by an event.
+#[derive(Debug, Clone, PartialEq)]
+struct Accessible {
+    destination: String,
+    path: String,
+}
+
+impl From<ObjectRef> for Accessible {
+    fn from(object: ObjectRef) -> Self {
+        Self {
+            destination: object.name.to_string(),
+            path: object.path.to_string(),
+        }
+    }
+}
+
+/// Work for the processor thread, from the input listener and the AT-SPI listener.
+#[derive(Debug)]
+enum Request {
+    ButtonPress {
+        button: MouseButton,
+        position: Position,
+    },
+    /// Enter or Tab, which complete the text input in progress.
+    CompletionKey {
+        key_code: u32,
+    },
+    AltTab,
+    Focus(Accessible),
+    TextChanged {
+        object: Accessible,
+        inserted: Option<usize>,
+    },
+    WindowActivated(Accessible),
+    Stop(mpsc::Sender<()>),
+}
+
+/// Modifier key states
+#[derive(Debug, Clone, Copy, Default)]
+struct Modifiers {
+    ctrl: bool,
+    alt: bool,
+    shift: bool,
+    meta: bool,
+}
+
+impl Modifiers {
+    fn update(&mut self, key: Key, pressed: bool) -> bool {
+        match key {
+            Key::ControlLeft | Key::ControlRight => self.ctrl = pressed,
+            Key::Alt | Key::AltGr => self.alt = pressed,
+            Key::ShiftLeft | Key::ShiftRight => self.shift = pressed,
+            Key::MetaLeft | Key::MetaRight => self.meta = pressed,
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// Sends events through the configured rate limit and noise filters.
+struct EventSink {
+    event_tx: broadcast::Sender<WorkflowEvent>,
+    config: WorkflowRecorderConfig,
+    last_event_time: Mutex<Instant>,
+    events_this_second: Mutex<(u32, Instant)>,
+}
+
+impl EventSink {
+    fn send(&self, event: WorkflowEvent) {
+        if let Some(max_events) = self.config.effective_max_events_per_second() {
+            let mut counter = self.events_this_second.lock().unwrap();
+            let now = Instant::now();
+            if now.duration_since(counter.1).as_secs() >= 1 {
+                *counter = (0, now);
+            }
+            if counter.0 >= max_events {
+                return;
+            }
+            counter.0 += 1;
+        }
+
+        let processing_delay = self.config.effective_processing_delay_ms();
+        if processing_delay > 0 {
+            let mut last_time = self.last_event_time.lock().unwrap();
+            let now = Instant::now();
+            if now.duration_since(*last_time).as_millis() < processing_delay as u128 {
+                return;
+            }
+            *last_time = now;
+        }
+
+        let should_filter = match &event {
+            WorkflowEvent::Mouse(mouse_event) => {
+                self.config.should_filter_mouse_noise()
+                    && matches!(
+                        mouse_event.event_type,
+                        MouseEventType::Move | MouseEventType::Wheel
+                    )
+            }
+            WorkflowEvent::Keyboard(keyboard_event) => {
+                self.config.should_filter_keyboard_noise()
+                    && keyboard_event.is_key_down
+                    && !((32..=126).contains(&keyboard_event.key_code)
+                        || matches!(keyboard_event.key_code, 0x08 | 0x2E | 0x0D | 0x09))
+            }
+            // Never filter high-value events
+            WorkflowEvent::ApplicationSwitch(_)
+            | WorkflowEvent::Click(_)
+            | WorkflowEvent::TextInputCompleted(_) => false,
+            _ => matches!(
+                self.config.performance_mode,
+                crate::PerformanceMode::LowEnergy
+            ),
+        };
+
+        if !should_filter {
+            let _ = self.event_tx.send(event);
+        }
+    }
+}
+
+/// A text field being typed into.
+struct TextInput {
+    element: UIElement,
+    /// The field's AT-SPI object, once known, to match text-changed signals cheaply.
+    object: Option<Accessible>,
+    focus_method: FieldFocusMethod,
+    start_time: Instant,
+    typed: u32,
+    pasted: u32,
+}
+
+impl TextInput {
+    fn new(element: UIElement, focus_method: FieldFocusMethod) -> Self {
+        Self {
+            element,
+            object: None,
+            focus_method,
+            start_time: Instant::now(),
+            typed: 0,
+            pasted: 0,
+        }
+    }
+
+    fn completion_event(&self) -> Option<TextInputCompletedEvent> {
+        if self.typed + self.pasted == 0 {
+            return None;
+        }
+        let text_value = self.element.text(0).unwrap_or_default();
+        if text_value.trim().is_empty() {
+            return None;
+        }
+        let input_method = match (self.typed, self.pasted) {
+            (_, 0) => TextInputMethod::Typed,
+            (0, _) => TextInputMethod::Pasted,
+            _ => TextInputMethod::Mixed,
+        };
+        Some(TextInputCompletedEvent {
+            text_value,
+            field_name: self.element.name(),
+            field_type: self.element.role(),
+            input_method,
+            focus_method: self.focus_method.clone(),
+            typing_duration_ms: self.start_time.elapsed().as_millis() as u64,
+            keystroke_count: self.typed + self.pasted,
+            metadata: EventMetadata::with_ui_element_and_timestamp(Some(self.element.clone())),
+        })
+    }
+}
+
+/// The application that currently has focus.
+struct ApplicationState {
+    name: String,
+    process_id: u32,
+    since: Instant,
+}
+
+/// State owned by the processor thread.
+struct Processor {
+    sink: Arc<EventSink>,
+    text_input: Option<TextInput>,
+    application: Option<ApplicationState>,
+    last_click: Option<Instant>,
+    last_tab: Option<Instant>,
+    last_alt_tab: Option<Instant>,
+}
+
+/// The Linux-specific recorder
+pub struct LinuxRecorder {
+    id: u64,
+    requests: mpsc::Sender<Request>,
+    stop_indicator: Arc<AtomicBool>,
+}
+
+impl LinuxRecorder {
+    /// Create a new Linux recorder and start listening
+    pub async fn new(
+        config: WorkflowRecorderConfig,
+        event_tx: broadcast::Sender<WorkflowEvent>,
+    ) -> Result<Self> {
+        info!("Initializing Linux recorder");
+        debug!("Recorder config: {:?}", config);
+
+        if std::env::var_os("DISPLAY").is_none() {
+            return Err(WorkflowRecorderError::InitializationError(
+                "Recording on Linux needs an X11 display (DISPLAY is not set)".to_string(),
+            ));
+        }
+
+        let stop_indicator = Arc::new(AtomicBool::new(false));
+        let (requests, requests_rx) = mpsc::channel();
+        let sink = Arc::new(EventSink {
+            event_tx,
+            config,
+            last_event_time: Mutex::new(Instant::now()),
+            events_this_second: Mutex::new((0, Instant::now())),
+        });
+
+        Self::start_atspi_listener(requests.clone(), Arc::clone(&stop_indicator)).await?;
+
+        let processor = Processor {
+            sink: Arc::clone(&sink),
+            text_input: None,
+            application: None,
+            last_click: None,
+            last_tab: None,
+            last_alt_tab: None,
+        };
+        thread::spawn(move || processor.run(requests_rx));
+
+        let id = NEXT_RECORDER_ID.fetch_add(1, Ordering::SeqCst);
+        *input_target() = Some(InputTarget {
+            recorder_id: id,
+            sink,
+            requests: requests.clone(),
+        });
+        Self::start_input_listener();
+
+        Ok(Self {
+            id,
+            requests,
+            stop_indicator,
+        })
+    }
+
+    /// Stop recording, completing the text input in progress. The processor and AT-SPI
+    /// threads end, and the input listener stops delivering to this recorder.
+    pub fn stop(&self) -> Result<()> {
+        debug!("Stopping Linux recorder...");
+        self.stop_indicator.store(true, Ordering::SeqCst);
+        {
+            let mut target = input_target();
+            if target.as_ref().is_some_and(|t| t.recorder_id == self.id) {
+                *target = None;
+            }
+        }
+        let (done_tx, done_rx) = mpsc::channel();
+        if self.requests.send(Request::Stop(done_tx)).is_ok()
+            && done_rx.recv_timeout(Duration::from_secs(2)).is_err()
+        {
+            warn!("Linux recorder processor did not acknowledge stop");
+        }
+        info!("Linux recorder stopped");
+        Ok(())
+    }
+
+    /// Subscribe to AT-SPI events on a thread of its own, returning once subscribed.
+    async fn start_atspi_listener(
+        requests: mpsc::Sender<Request>,
+        stop_indicator: Arc<AtomicBool>,
+    ) -> Result<()> {
+        let (ready_tx, ready_rx) = oneshot::channel();
+        thread::spawn(move || {
+            let runtime = match tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(runtime) => runtime,
+                Err(e) => {
+                    let _ = ready_tx.send(Err(e.to_string()));
+                    return;
+                }
+            };
+            runtime.block_on(async move {
+                let connection = match Self::connect_atspi().await {
+                    Ok(connection) => {
+                        let _ = ready_tx.send(Ok(()));
+                        connection
+                    }
+                    Err(e) => {
+                        let _ = ready_tx.send(Err(e.to_string()));
+                        return;
+                    }
+                };
+                let events = connection.event_stream();
+                tokio::pin!(events);
+                while !stop_indicator.load(Ordering::SeqCst) {
+                    let Ok(next) =
+                        tokio::time::timeout(Duration::from_millis(200), events.next()).await
+                    else {
+                        continue;
+                    };
+                    let request = match next {
+                        None => break,
+                        Some(Ok(event)) => match Self::atspi_request(event) {
+                            Some(request) => request,
+                            None => continue,
+                        },
+                        Some(Err(e)) => {
+                            debug!("Ignoring undecodable AT-SPI event: {}", e);
+                            continue;
+                        }
+                    };
+                    if requests.send(request).is_err() {
+                        break;
+                    }
+                }
+            });
+        });
+
+        match ready_rx.await {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(e)) => Err(WorkflowRecorderError::InitializationError(format!(
+                "Failed to subscribe to AT-SPI events: {e}"
+            ))),
+            Err(_) => Err(WorkflowRecorderError::InitializationError(
+                "AT-SPI listener thread exited during startup".to_string(),
+            )),
+        }
+    }
+
+    async fn connect_atspi() -> std::result::Result<AccessibilityConnection, atspi::AtspiError> {
+        let connection = AccessibilityConnection::new().await?;
+        connection.register_event::<StateChangedEvent>().await?;
+        connection.register_event::<TextChangedEvent>().await?;
+        connection.register_event::<ActivateEvent>().await?;
+        Ok(connection)
+    }
+
+    fn atspi_request(event: Event) -> Option<Request> {
+        match event {
+            Event::Object(ObjectEvents::StateChanged(event))
+                if event.state == State::Focused && event.enabled =>
+            {
+                Some(Request::Focus(event.item.into()))
+            }
+            Event::Object(ObjectEvents::TextChanged(event)) => Some(Request::TextChanged {
+                object: event.item.into(),
+                inserted: match event.operation {
+                    Operation::Insert => Some(event.length.max(0) as usize),
+                    Operation::Delete => None,
+                },
+            }),
+            Event::Window(WindowEvents::Activate(event)) => {
+                Some(Request::WindowActivated(event.item.into()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Listen to raw input on a thread of its own, unless already listening; `rdev`
+    /// uses the X RECORD extension.
+    fn start_input_listener() {
+        if INPUT_LISTENER_STARTED.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        thread::spawn(move || {
+            let mut modifiers = Modifiers::default();
+            let mut last_position: Option<Position> = None;
+            let mut last_mouse_move = Instant::now();
+
+            if let Err(error) = rdev::listen(move |event: rdev::Event| {
+                // Modifiers and the pointer are tracked between recordings too, so that
+                // the next one starts with their actual state.
+                let is_modifier = match event.event_type {
+                    EventType::KeyPress(key) => modifiers.update(key, true),
+                    EventType::KeyRelease(key) => modifiers.update(key, false),
+                    EventType::MouseMove { x, y } => {
+                        last_position = Some(Position {
+                            x: x as i32,
+                            y: y as i32,
+                        });
+                        false
+                    }
+                    _ => false,
+                };
+                let target = input_target();
+                let Some(InputTarget { sink, requests, .. }) = target.as_ref() else {
+                    return;
+                };
+                let config = &sink.config;
+
+                match event.event_type {
+                    EventType::KeyPress(key) => {
+                        let key_code = key_to_u32(&key);
+
+                        if !is_modifier && config.record_hotkeys {
+                            if let Some(hotkey) = Self::hotkey(&modifiers, key) {
+                                if hotkey.combination == "Alt+Tab" {
+                                    let _ = requests.send(Request::AltTab);
+                                }
+                                sink.send(WorkflowEvent::Hotkey(hotkey));
+                            }
+                        }
+
+                        if config.record_text_input_completion
+                            && (key == Key::Return || key == Key::Tab)
+                            && !modifiers.alt
+                        {
+                            let _ = requests.send(Request::CompletionKey { key_code });
+                        }
+
+                        if config.record_keyboard {
+                            let character = event
+                                .name
+                                .as_deref()
+                                .and_then(|name| name.chars().next())
+                                .filter(|c| !c.is_control());
+                            sink.send(WorkflowEvent::Keyboard(Self::keyboard_event(
+                                key_code, true, &modifiers, character,
+                            )));
+                        }
+                    }
+                    EventType::KeyRelease(key) => {
+                        if config.record_keyboard {
+                            sink.send(WorkflowEvent::Keyboard(Self::keyboard_event(
+                                key_to_u32(&key),
+                                false,
+                                &modifiers,
+                                None,
+                            )));
+                        }
+                    }
+                    EventType::ButtonPress(button) | EventType::ButtonRelease(button) => {
+                        let Some(position) = last_position else {
+                            return;
+                        };
+                        let button = match button {
+                            Button::Left => MouseButton::Left,
+                            Button::Right => MouseButton::Right,
+                            Button::Middle => MouseButton::Middle,
+                            _ => return,
+                        };
+                        let pressed = matches!(event.event_type, EventType::ButtonPress(_));
+                        if pressed && config.capture_ui_elements {
+                            // The processor sends the mouse-down event once it has
+                            // resolved the element under the pointer.
+                            let _ = requests.send(Request::ButtonPress { button, position });
+                        } else if config.record_mouse {
+                            sink.send(WorkflowEvent::Mouse(MouseEvent {
+                                event_type: if pressed {
+                                    MouseEventType::Down
+                                } else {
+                                    MouseEventType::Up
+                                },
+                                button,
+                                position,
+                                scroll_delta: None,
+                                drag_start: None,
+                                metadata: EventMetadata::with_ui_element_and_timestamp(None),
+                            }));
+                        }
+                    }
+                    EventType::MouseMove { .. } => {
+                        let Some(position) = last_position else {
+                            return;
+                        };
+                        if config.record_mouse
+                            && last_mouse_move.elapsed().as_millis()
+                                >= config.mouse_move_throttle_ms as u128
+                        {
+                            last_mouse_move = Instant::now();
+                            sink.send(WorkflowEvent::Mouse(MouseEvent {
+                                event_type: MouseEventType::Move,
+                                button: MouseButton::Left,
+                                position,
+                                scroll_delta: None,
+                                drag_start: None,
+                                metadata: EventMetadata::with_ui_element_and_timestamp(None),
+                            }));
+                        }
+                    }
+                    EventType::Wheel { delta_x, delta_y } => {
+                        if let (Some(position), true) = (last_position, config.record_mouse) {
+                            sink.send(WorkflowEvent::Mouse(MouseEvent {
+                                event_type: MouseEventType::Wheel,
+                                button: MouseButton::Middle,
+                                position,
+                                scroll_delta: Some((delta_x as i32, delta_y as i32)),
+                                drag_start: None,
+                                metadata: EventMetadata::with_ui_element_and_timestamp(None),
+                            }));
+                        }
+                    }
+                }
+            }) {
+                error!("Failed to listen for X input events: {:?}", error);
+                // Let the next recorder try again.
+                INPUT_LISTENER_STARTED.store(false, Ordering::SeqCst);
+            }
+        });
+    }
+
+    fn keyboard_event(
+        key_code: u32,
+        is_key_down: bool,
+        modifiers: &Modifiers,
+        character: Option<char>,
+    ) -> KeyboardEvent {
+        KeyboardEvent {
+            key_code,
+            is_key_down,
+            ctrl_pressed: modifiers.ctrl,
+            alt_pressed: modifiers.alt,
+            shift_pressed: modifiers.shift,
+            win_pressed: modifiers.meta,
+            character,
+            scan_code: None,
+            metadata: EventMetadata::with_ui_element_and_timestamp(None),
+        }
+    }
+
+    /// A shortcut: a key pressed while Ctrl, Alt or Super is held.
+    fn hotkey(modifiers: &Modifiers, key: Key) -> Option<HotkeyEvent> {
+        if !(modifiers.ctrl || modifiers.alt || modifiers.meta) {
+            return None;
+        }
+        let mut parts = Vec::new();
+        if modifiers.ctrl {
+            parts.push("Ctrl");
+        }
+        if modifiers.alt {
+            parts.push("Alt");
+        }
+        if modifiers.shift {
+            parts.push("Shift");
+        }
+        if modifiers.meta {
+            parts.push("Super");
+        }
+        let key_name = key_name(key);
+        parts.push(&key_name);
+        let combination = parts.join("+");
+
+        let action = match combination.as_str() {
+            "Ctrl+C" => Some("Copy"),
+            "Ctrl+V" => Some("Paste"),
+            "Ctrl+X" => Some("Cut"),
+            "Ctrl+Z" => Some("Undo"),
+            "Ctrl+Y" | "Ctrl+Shift+Z" => Some("Redo"),
+            "Ctrl+S" => Some("Save"),
+            "Alt+Tab" => Some("Switch Window"),
+            "Super+D" => Some("Show Desktop"),
+            _ => None,
+        };
+
+        Some(HotkeyEvent {
+            combination,
+            action: action.map(str::to_string),
+            is_global: true,
+            metadata: EventMetadata::with_ui_element_and_timestamp(None),
+        })
+    }
+}
+
+/// The label of a key in a hotkey combination, e.g. `C`, `Tab` or `F4`.
+fn key_name(key: Key) -> String {
+    match key_to_u32(&key) {
+        code @ (0x30..=0x39 | 0x41..=0x5A) => char::from(code as u8).to_string(),
+        _ => match key {
+            Key::Return => "Enter".to_string(),
+            Key::Escape => "Esc".to_string(),
+            other => format!("{other:?}"),
+        },
+    }
+}
+
+fn input_target() -> std::sync::MutexGuard<'static, Option<InputTarget>> {
+    INPUT_TARGET.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn capture_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+fn is_text_input(role: &str) -> bool {
+    TEXT_INPUT_ROLES.contains(&role.to_lowercase().as_str())
+}
+
+fn interaction_type(name: &str, role: &str) -> ButtonInteractionType {
+    let name = name.to_lowercase();
+    match role {
+        "toggle button" | "check box" | "radio button" | "check menu item" | "radio menu item"
+        | "switch" => ButtonInteractionType::Toggle,
+        "combo box" => ButtonInteractionType::DropdownToggle,
+        _ if ["submit", "save", "ok", "apply", "confirm"].contains(&name.as_str()) => {
+            ButtonInteractionType::Submit
+        }
+        _ if ["cancel", "close", "dismiss"].contains(&name.as_str()) => {
+            ButtonInteractionType::Cancel
+        }
+        _ => ButtonInteractionType::Click,
+    }
+}
+
+impl Processor {
+    fn run(mut self, requests: mpsc::Receiver<Request>) {
+        info!("Linux recorder processor thread started");
+        for request in requests {
+            match request {
+                Request::ButtonPress { button, position } => {
+                    self.handle_button_press(button, position)
+                }
+                Request::CompletionKey { key_code } => {
+                    if key_code == 0x09 {
+                        self.last_tab = Some(Instant::now());
+                    }
+                    self.complete_text_input();
+                }
+                Request::AltTab => self.last_alt_tab = Some(Instant::now()),
+                Request::Focus(object) => self.handle_focus(object),
+                Request::TextChanged { object, inserted } => {
+                    self.handle_text_changed(object, inserted)
+                }
+                Request::WindowActivated(object) => {
+                    if let Some(element) = self.element(&object) {
+                        self.check_application_switch(&element);
+                    }
+                }
+                Request::Stop(done) => {
+                    self.complete_text_input();
+                    self.text_input = None;
+                    let _ = done.send(());
+                    break;
+                }
+            }
+        }
+        info!("Linux recorder processor thread stopped");
+    }
+
+    fn config(&self) -> &WorkflowRecorderConfig {
+        &self.sink.config
+    }
+
+    fn element(&self, object: &Accessible) -> Option<UIElement> {
+        match convert_atspi_object_to_terminator(&object.destination, &object.path) {
+            Ok(element) => Some(element),
+            Err(e) => {
+                debug!("Could not resolve AT-SPI object {}: {}", object.path, e);
+                None
+            }
+        }
+    }
+
+    /// Whether the ignore lists of the configuration exclude `element`.
+    fn is_ignored(&self, element: &UIElement) -> bool {
+        let config = self.config();
+        let matches = |value: &str, patterns: &std::collections::HashSet<String>| {
+            let value = value.to_lowercase();
+            !value.is_empty()
+                && patterns
+                    .iter()
+                    .any(|pattern| value.contains(&pattern.to_lowercase()))
+        };
+        matches(&element.application_name(), &config.ignore_applications)
+            || matches(&element.window_title(), &config.ignore_window_titles)
+            || matches(&element.name_or_empty(), &config.ignore_focus_patterns)
+    }
+
+    fn handle_button_press(&mut self, button: MouseButton, position: Position) {
+        let element = match element_at_point(position.x, position.y) {
+            Ok(element) if !self.is_ignored(&element) => Some(element),
+            Ok(_) => None,
+            Err(e) => {
+                debug!("No element at ({}, {}): {}", position.x, position.y, e);
+                None
+            }
+        };
+
+        if let (MouseButton::Left, Some(element)) = (button, &element) {
+            self.last_click = Some(Instant::now());
+            let role = element.role();
+            if is_text_input(&role) {
+                // Typing into the field is recorded as TextInputCompleted instead.
+                if self.config().record_text_input_completion {
+                    self.start_text_input(element.clone(), FieldFocusMethod::MouseClick);
+                }
+            } else {
+                let name = element.name_or_empty();
+                let relative_position = element.bounds().ok().and_then(|(x, y, w, h)| {
+                    (w > 0.0 && h > 0.0).then(|| {
+                        (
+                            ((position.x as f64 - x) / w) as f32,
+                            ((position.y as f64 - y) / h) as f32,
+                        )
+                    })
+                });
+                let child_text_content = element
+                    .children()
+                    .unwrap_or_default()
+                    .iter()
+                    .filter_map(|child| child.name())
+                    .map(|name| name.trim().to_string())
+                    .filter(|name| !name.is_empty())
+                    .collect();
+                self.sink.send(WorkflowEvent::Click(ClickEvent {
+                    interaction_type: interaction_type(&name, &role),
+                    element_text: name,
+                    element_role: role,
+                    was_enabled: element.is_enabled().unwrap_or(true),
+                    click_position: Some(position),
+                    element_description: None,
+                    child_text_content,
+                    relative_position,
+                    metadata: EventMetadata::with_ui_element_and_timestamp(Some(element.clone())),
+                }));
+            }
+        }
+
+        if self.config().record_mouse {
+            self.sink.send(WorkflowEvent::Mouse(MouseEvent {
+                event_type: MouseEventType::Down,
+                button,
+                position,
+                scroll_delta: None,
+                drag_start: None,
+                metadata: EventMetadata {
+                    ui_element: element,
+                    timestamp: Some(capture_timestamp()),
+                    screenshot: None,
+                    window: None,
+                    masked_element: None,
+                },
+            }));
+        }
+    }
+
+    fn handle_focus(&mut self, object: Accessible) {
+        let Some(element) = self.element(&object) else {
+            return;
+        };
+        if self.is_ignored(&element) {
+            return;
+        }
+        self.check_application_switch(&element);
+
+        if self
+            .text_input
+            .as_ref()
+            .is_some_and(|input| input.element == element)
+        {
+            return;
+        }
+        self.complete_text_input();
+        self.text_input = None;
+
+        if self.config().record_text_input_completion && is_text_input(&element.role()) {
+            let recent =
+                |at: Option<Instant>| at.is_some_and(|at| at.elapsed() < FOCUS_ATTRIBUTION_WINDOW);
+            let focus_method = if recent(self.last_click) {
+                FieldFocusMethod::MouseClick
+            } else if recent(self.last_tab) {
+                FieldFocusMethod::KeyboardNav
+            } else {
+                FieldFocusMethod::Unknown
+            };
+            self.start_text_input(element, focus_method);
+            if let Some(input) = self.text_input.as_mut() {
+                input.object = Some(object);
+            }
+        }
+    }
+
+    fn handle_text_changed(&mut self, object: Accessible, inserted: Option<usize>) {
+        let Some(input) = self.text_input.as_mut() else {
+            return;
+        };
+        let is_tracked = match &input.object {
+            Some(tracked) => *tracked == object,
+            None => {
+                let is_tracked =
+                    convert_atspi_object_to_terminator(&object.destination, &object.path)
+                        .is_ok_and(|element| element == input.element);
+                if is_tracked {
+                    input.object = Some(object);
+                }
+                is_tracked
+            }
+        };
+        if !is_tracked {
+            return;
+        }
+        match inserted {
+            // Several characters in one signal: pasted, auto-filled or a suggestion
+            Some(length) if length > 1 => input.pasted += 1,
+            _ => input.typed += 1,
+        }
+    }
+
+    fn start_text_input(&mut self, element: UIElement, focus_method: FieldFocusMethod) {
+        if self
+            .text_input
+            .as_ref()
+            .is_some_and(|input| input.element == element)
+        {
+            return;
+        }
+        self.complete_text_input();
+        self.text_input = Some(TextInput::new(element, focus_method));
+    }
+
+    /// Emit the text typed since the field got focus (or since the last completion).
+    fn complete_text_input(&mut self) {
+        let Some(input) = self.text_input.as_mut() else {
+            return;
+        };
+        if let Some(event) = input.completion_event() {
+            self.sink.send(WorkflowEvent::TextInputCompleted(event));
+        }
+        input.typed = 0;
+        input.pasted = 0;
+        input.start_time = Instant::now();
+    }
+
+    fn check_application_switch(&mut self, element: &UIElement) {
+        if !self.config().record_application_switches {
+            return;
+        }
+        let name = element.application_name();
+        let Ok(process_id) = element.process_id() else {
+            return;
+        };
+        if name.is_empty()
+            || self
+                .application
+                .as_ref()
+                .is_some_and(|app| app.process_id == process_id && app.name == name)
+        {
+            return;
+        }
+
+        let now = Instant::now();
+        let previous = self.application.replace(ApplicationState {
+            name: name.clone(),
+            process_id,
+            since: now,
+        });
+        let dwell_time_ms = previous
+            .as_ref()
+            .map(|app| now.duration_since(app.since).as_millis() as u64);
+        if dwell_time_ms.is_some_and(|ms| ms < self.config().app_switch_dwell_time_threshold_ms) {
+            return;
+        }
+
+        let switch_method = if self
+            .last_alt_tab
+            .take()
+            .is_some_and(|at| at.elapsed() < ALT_TAB_ATTRIBUTION_WINDOW)
+        {
+            ApplicationSwitchMethod::AltTab
+        } else if self
+            .last_click
+            .is_some_and(|at| at.elapsed() < FOCUS_ATTRIBUTION_WINDOW)
+        {
+            ApplicationSwitchMethod::WindowClick
+        } else {
+            ApplicationSwitchMethod::Other
+        };
+
+        self.sink
+            .send(WorkflowEvent::ApplicationSwitch(ApplicationSwitchEvent {
+                from_application: previous.as_ref().map(|app| app.name.clone()),
+                to_application: name,
+                from_process_id: previous.as_ref().map(|app| app.process_id),
+                to_process_id: process_id,
+                switch_method,
+                dwell_time_ms,
+                switch_count: None,
+                metadata: EventMetadata::with_ui_element_and_timestamp(Some(element.clone())),
+            }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hotkey_combinations() {
+        let ctrl = Modifiers {
+            ctrl: true,
+            ..Modifiers::default()
+        };
+        let hotkey = LinuxRecorder::hotkey(&ctrl, Key::KeyC).unwrap();
+        assert_eq!(hotkey.combination, "Ctrl+C");
+        assert_eq!(hotkey.action.as_deref(), Some("Copy"));
+
+        let ctrl_shift = Modifiers {
+            shift: true,
+            ..ctrl
+        };
+        let hotkey = LinuxRecorder::hotkey(&ctrl_shift, Key::KeyZ).unwrap();
+        assert_eq!(hotkey.combination, "Ctrl+Shift+Z");
+        assert_eq!(hotkey.action.as_deref(), Some("Redo"));
+
+        let alt = Modifiers {
+            alt: true,
+            ..Modifiers::default()
+        };
+        let hotkey = LinuxRecorder::hotkey(&alt, Key::F4).unwrap();
+        assert_eq!(hotkey.combination, "Alt+F4");
+        assert_eq!(hotkey.action, None);
+
+        let shift = Modifiers {
+            shift: true,
+            ..Modifiers::default()
+        };
+        assert!(LinuxRecorder::hotkey(&shift, Key::KeyA).is_none());
+    }
+
+    #[test]
+    fn test_interaction_types_use_atspi_roles() {
+        assert_eq!(
+            interaction_type("Bold", "toggle button"),
+            ButtonInteractionType::Toggle
+        );
+        assert_eq!(
+            interaction_type("Country", "combo box"),
+            ButtonInteractionType::DropdownToggle
+        );
+        assert_eq!(
+            interaction_type("OK", "push button"),
+            ButtonInteractionType::Submit
+        );
+        assert_eq!(
+            interaction_type("New", "push button"),
+            ButtonInteractionType::Click
+        );
+        assert!(is_text_input("password text"));
+        assert!(!is_text_input("push button"));
+    }
+}
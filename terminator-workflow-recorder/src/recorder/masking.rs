@@ -0,0 +1,538 @@
+//! Masking of passwords and personal data before events leave the recorder.
+//!
+//! Every event passes through a [`PiiMasker`] before it reaches `event_stream()` or the
+//! saved workflow. A masked value is replaced with a token such as `[EMAIL_1]`; the same
+//! value always gets the same token within a recording, so two fields filled with the same
+//! address still look alike to later processing (e.g. turning values into variables).
+
+use crate::events::SerializableUIElement;
+use crate::{Result, WorkflowEvent, WorkflowRecorderError};
+use regex::Regex;
+use std::collections::HashMap;
+use terminator::UIElement;
+
+/// Kinds of personal data that can be detected in recorded text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PiiDetector {
+    Email,
+    Iban,
+    CreditCard,
+    PhoneNumber,
+}
+
+impl PiiDetector {
+    pub const ALL: [PiiDetector; 4] = [
+        PiiDetector::Email,
+        PiiDetector::Iban,
+        PiiDetector::CreditCard,
+        PiiDetector::PhoneNumber,
+    ];
+
+    fn token_kind(&self) -> &'static str {
+        match self {
+            PiiDetector::Email => "EMAIL",
+            PiiDetector::Iban => "IBAN",
+            PiiDetector::CreditCard => "CREDIT_CARD",
+            PiiDetector::PhoneNumber => "PHONE",
+        }
+    }
+
+    fn pattern(&self) -> &'static str {
+        match self {
+            PiiDetector::Email => r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}",
+            PiiDetector::Iban => r"\b[A-Z]{2}\d{2}(?: ?[A-Z0-9]){11,30}\b",
+            PiiDetector::CreditCard => r"\b\d(?:[ -]?\d){12,18}\b",
+            PiiDetector::PhoneNumber => {
+                r"(?:\+\d{1,3}[ .-]?)?(?:\(\d{1,4}\)[ .-]?)?\d{2,4}[ .-]\d{3,4}(?:[ .-]?\d{2,4})?\b|\+\d{8,15}\b"
+            }
+        }
+    }
+}
+
+/// Redaction rule for one application.
+#[derive(Debug, Clone, Default)]
+pub struct AppRedactionRule {
+    /// Application/process name, matched case-insensitively as a substring
+    pub application: String,
+
+    /// Mask all text entered, pasted or selected in this application
+    pub mask_all_text: bool,
+
+    /// Additional regex patterns to mask in this application
+    pub patterns: Vec<String>,
+}
+
+/// Configuration of what the recorder masks
+#[derive(Debug, Clone)]
+pub struct PiiMaskingConfig {
+    /// Mask text entered in password fields (the element's `is_password` property,
+    /// or `type=password` for DOM inputs), including the raw keystrokes
+    pub mask_password_fields: bool,
+
+    /// Detectors applied to typed text, clipboard contents, text selections and DOM input values
+    pub detectors: Vec<PiiDetector>,
+
+    /// Per-application redaction rules
+    pub app_rules: Vec<AppRedactionRule>,
+}
+
+impl Default for PiiMaskingConfig {
+    fn default() -> Self {
+        Self {
+            mask_password_fields: true,
+            detectors: Vec::new(),
+            app_rules: Vec::new(),
+        }
+    }
+}
+
+impl PiiMaskingConfig {
+    /// Password fields and every built-in detector
+    pub fn all() -> Self {
+        Self {
+            detectors: PiiDetector::ALL.to_vec(),
+            ..Self::default()
+        }
+    }
+}
+
+struct CompiledRule {
+    application: String,
+    mask_all_text: bool,
+    patterns: Vec<Regex>,
+}
+
+/// Hands out one token per distinct masked value.
+#[derive(Default)]
+struct Tokens {
+    by_value: HashMap<(&'static str, String), String>,
+    counts: HashMap<&'static str, usize>,
+}
+
+impl Tokens {
+    fn get(&mut self, kind: &'static str, value: &str) -> String {
+        if let Some(token) = self.by_value.get(&(kind, value.to_string())) {
+            return token.clone();
+        }
+        let count = self.counts.entry(kind).or_default();
+        *count += 1;
+        let token = format!("[{kind}_{count}]");
+        self.by_value
+            .insert((kind, value.to_string()), token.clone());
+        token
+    }
+}
+
+/// Applies a [`PiiMaskingConfig`] to recorded events.
+pub struct PiiMasker {
+    mask_password_fields: bool,
+    detectors: Vec<(PiiDetector, Regex)>,
+    rules: Vec<CompiledRule>,
+    tokens: Tokens,
+    /// Whether the last element the user clicked or typed into is a password field;
+    /// keystrokes usually carry no element of their own.
+    in_password_field: bool,
+    /// Application of the last event that had an element
+    current_application: Option<String>,
+    /// Whether anything was masked since the current event started
+    masked: bool,
+}
+
+impl PiiMasker {
+    pub fn new(config: &PiiMaskingConfig) -> Result<Self> {
+        let detectors = config
+            .detectors
+            .iter()
+            .map(|d| (*d, Regex::new(d.pattern()).expect("built-in PII pattern")))
+            .collect();
+        let rules = config
+            .app_rules
+            .iter()
+            .map(|rule| {
+                let patterns = rule
+                    .patterns
+                    .iter()
+                    .map(|p| {
+                        Regex::new(p).map_err(|e| {
+                            WorkflowRecorderError::InitializationError(format!(
+                                "Invalid redaction pattern '{p}' for '{}': {e}",
+                                rule.application
+                            ))
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(CompiledRule {
+                    application: rule.application.to_lowercase(),
+                    mask_all_text: rule.mask_all_text,
+                    patterns,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            mask_password_fields: config.mask_password_fields,
+            detectors,
+            rules,
+            tokens: Tokens::default(),
+            in_password_field: false,
+            current_application: None,
+            masked: false,
+        })
+    }
+
+    /// Mask the sensitive values of `event` in place. Returns whether anything was
+    /// masked, in which case screenshots of the event would show what was hidden.
+    pub fn mask_event(&mut self, event: &mut WorkflowEvent) -> bool {
+        self.masked = false;
+        let element = event.metadata().ui_element.clone();
+        if let Some(element) = &element {
+            if !self.rules.is_empty() {
+                self.current_application = Some(element.application_name().to_lowercase());
+            }
+        }
+        let is_password =
+            |element: &Option<UIElement>| element.as_ref().is_some_and(element_is_password);
+
+        match event {
+            WorkflowEvent::Keyboard(e) => {
+                if element.is_some() {
+                    self.in_password_field = is_password(&element);
+                }
+                // Key-up events and keys without a character give the text away too.
+                let password = self.mask_password_fields && self.in_password_field;
+                if password || self.mask_all_text() {
+                    e.character = None;
+                    e.key_code = 0;
+                    e.scan_code = None;
+                    self.masked = true;
+                }
+            }
+            WorkflowEvent::Click(_) => {
+                self.in_password_field = is_password(&element);
+            }
+            WorkflowEvent::Clipboard(e) => {
+                if let Some(content) = &e.content {
+                    e.content = Some(self.mask_text(content));
+                }
+            }
+            WorkflowEvent::TextSelection(e) => {
+                e.selected_text = self.mask_text(&e.selected_text);
+            }
+            WorkflowEvent::TextInputCompleted(e) => {
+                let password =
+                    e.field_type.to_lowercase().contains("password") || is_password(&element);
+                self.in_password_field = password;
+                e.text_value = self.mask_value(&e.text_value, password);
+            }
+            WorkflowEvent::BrowserTextInput(e) => {
+                let password = e
+                    .dom_element
+                    .as_ref()
+                    .is_some_and(|d| d.input_type.as_deref() == Some("password"));
+                e.text = self.mask_value(&e.text, password);
+                if let Some(dom) = &mut e.dom_element {
+                    dom.input_value = dom
+                        .input_value
+                        .as_ref()
+                        .map(|v| self.mask_value(v, password));
+                }
+            }
+            WorkflowEvent::BrowserClick(e) => {
+                if let Some(dom) = &mut e.dom_element {
+                    let password = dom.input_type.as_deref() == Some("password");
+                    self.in_password_field = password;
+                    dom.input_value = dom
+                        .input_value
+                        .as_ref()
+                        .map(|v| self.mask_value(v, password));
+                }
+            }
+            _ => {}
+        }
+        if let Some(window) = &mut event.metadata_mut().window {
+            window.title = self.mask_text(&window.title);
+        }
+        if let Some(element) = &element {
+            let snapshot = self.mask_element(element);
+            event.metadata_mut().masked_element = Some(snapshot);
+        }
+        self.masked
+    }
+
+    /// What `element` shows now, with its value dropped in password fields (or when all
+    /// text is masked) and masked otherwise.
+    fn mask_element(&mut self, element: &UIElement) -> SerializableUIElement {
+        let hide_value =
+            (self.mask_password_fields && self.in_password_field) || self.mask_all_text();
+        let mut snapshot = SerializableUIElement::from(element);
+        snapshot.value = match snapshot.value.take() {
+            Some(value) if !hide_value => Some(self.mask_text(&value)),
+            Some(_) => {
+                self.masked = true;
+                None
+            }
+            None => None,
+        };
+        snapshot.name = snapshot.name.take().map(|name| self.mask_text(&name));
+        snapshot.window_title = snapshot
+            .window_title
+            .take()
+            .map(|title| self.mask_text(&title));
+        snapshot
+    }
+
+    fn mask_value(&mut self, value: &str, password: bool) -> String {
+        if password && self.mask_password_fields && !value.is_empty() {
+            self.masked = true;
+            self.tokens.get("PASSWORD", value)
+        } else {
+            self.mask_text(value)
+        }
+    }
+
+    fn mask_all_text(&self) -> bool {
+        self.active_rules().any(|rule| rule.mask_all_text)
+    }
+
+    fn active_rules(&self) -> impl Iterator<Item = &CompiledRule> {
+        let application = self.current_application.as_deref();
+        self.rules
+            .iter()
+            .filter(move |rule| application.is_some_and(|a| a.contains(&rule.application)))
+    }
+
+    /// Replace every detected value in `text` with its token.
+    pub fn mask_text(&mut self, text: &str) -> String {
+        if text.is_empty() {
+            return String::new();
+        }
+        if self.mask_all_text() {
+            self.masked = true;
+            return self.tokens.get("REDACTED", text);
+        }
+
+        let Self {
+            detectors,
+            rules,
+            tokens,
+            current_application,
+            ..
+        } = self;
+        let mut masked = text.to_string();
+        for (detector, regex) in detectors.iter() {
+            masked = regex
+                .replace_all(&masked, |caps: &regex::Captures| {
+                    let found = &caps[0];
+                    if *detector == PiiDetector::CreditCard && !luhn_valid(found) {
+                        return found.to_string();
+                    }
+                    tokens.get(detector.token_kind(), found)
+                })
+                .into_owned();
+        }
+        let application = current_application.as_deref();
+        for rule in rules
+            .iter()
+            .filter(|rule| application.is_some_and(|a| a.contains(&rule.application)))
+        {
+            for regex in &rule.patterns {
+                masked = regex
+                    .replace_all(&masked, |caps: &regex::Captures| {
+                        tokens.get("REDACTED", &caps[0])
+                    })
+                    .into_owned();
+            }
+        }
+        if masked != text {
+            self.masked = true;
+        }
+        masked
+    }
+}
+
+fn element_is_password(element: &UIElement) -> bool {
+    element.is_password().unwrap_or(false)
+}
+
+/// Luhn checksum of the digits in `number`, to tell card numbers from other long numbers.
+fn luhn_valid(number: &str) -> bool {
+    let digits: Vec<u32> = number.chars().filter_map(|c| c.to_digit(10)).collect();
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| {
+            if i % 2 == 1 {
+                let doubled = d * 2;
+                if doubled > 9 {
+                    doubled - 9
+                } else {
+                    doubled
+                }
+            } else {
+                d
+            }
+        })
+        .sum();
+    digits.len() >= 13 && sum.is_multiple_of(10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::SerializableEventMetadata;
+    use crate::{
+        ClipboardAction, ClipboardEvent, EventMetadata, FieldFocusMethod, KeyboardEvent,
+        TextInputCompletedEvent, TextInputMethod,
+    };
+    use serde_json::json;
+    use std::sync::Arc;
+    use terminator::platforms::fixture::FixtureEngine;
+    use terminator::Desktop;
+
+    fn text_input(field_type: &str, value: &str) -> WorkflowEvent {
+        WorkflowEvent::TextInputCompleted(TextInputCompletedEvent {
+            text_value: value.to_string(),
+            field_name: Some("Field".to_string()),
+            field_type: field_type.to_string(),
+            input_method: TextInputMethod::Typed,
+            focus_method: FieldFocusMethod::MouseClick,
+            typing_duration_ms: 100,
+            keystroke_count: 3,
+            metadata: EventMetadata::empty(),
+        })
+    }
+
+    fn typed_value(event: &WorkflowEvent) -> &str {
+        match event {
+            WorkflowEvent::TextInputCompleted(e) => &e.text_value,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_detectors_use_stable_tokens() {
+        let mut masker = PiiMasker::new(&PiiMaskingConfig::all()).unwrap();
+
+        assert_eq!(
+            masker.mask_text("Mail mario@example.com or call +39 333 1234567"),
+            "Mail [EMAIL_1] or call [PHONE_1]"
+        );
+        assert_eq!(
+            masker.mask_text("IBAN IT60X0542811101000000123456, card 4111 1111 1111 1111"),
+            "IBAN [IBAN_1], card [CREDIT_CARD_1]"
+        );
+        // Same value, same token; a number failing the Luhn check is not a card.
+        assert_eq!(masker.mask_text("mario@example.com"), "[EMAIL_1]");
+        assert_eq!(masker.mask_text("luigi@example.com"), "[EMAIL_2]");
+        assert_eq!(
+            masker.mask_text("order 1234567890123"),
+            "order 1234567890123"
+        );
+        assert_eq!(masker.mask_text("Mario Rossi"), "Mario Rossi");
+    }
+
+    #[test]
+    fn test_password_fields_and_clipboard() {
+        let mut masker = PiiMasker::new(&PiiMaskingConfig::default()).unwrap();
+
+        let mut password = text_input("password text", "hunter2");
+        assert!(masker.mask_event(&mut password));
+        assert_eq!(typed_value(&password), "[PASSWORD_1]");
+
+        // Detectors are off by default.
+        let mut email = text_input("Edit", "mario@example.com");
+        assert!(!masker.mask_event(&mut email));
+        assert_eq!(typed_value(&email), "mario@example.com");
+
+        let mut masker = PiiMasker::new(&PiiMaskingConfig {
+            app_rules: vec![AppRedactionRule {
+                application: "bank".to_string(),
+                patterns: vec!["[0-9]+".to_string()],
+                ..Default::default()
+            }],
+            ..PiiMaskingConfig::all()
+        })
+        .unwrap();
+        let mut paste = WorkflowEvent::Clipboard(ClipboardEvent {
+            action: ClipboardAction::Paste,
+            content: Some("mario@example.com".to_string()),
+            content_size: Some(17),
+            format: None,
+            truncated: false,
+            metadata: EventMetadata::empty(),
+        });
+        masker.mask_event(&mut paste);
+        match paste {
+            WorkflowEvent::Clipboard(e) => assert_eq!(e.content.as_deref(), Some("[EMAIL_1]")),
+            _ => unreachable!(),
+        }
+        // Application rules only apply once an event from that application was seen.
+        assert_eq!(masker.mask_text("account 42"), "account 42");
+        assert!(PiiMasker::new(&PiiMaskingConfig {
+            app_rules: vec![AppRedactionRule {
+                application: "bank".to_string(),
+                patterns: vec!["(".to_string()],
+                ..Default::default()
+            }],
+            ..PiiMaskingConfig::default()
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_key_up_events_in_password_fields() {
+        let mut masker = PiiMasker::new(&PiiMaskingConfig::default()).unwrap();
+        masker.mask_event(&mut text_input("password text", "hunter2"));
+
+        let mut key_up = WorkflowEvent::Keyboard(KeyboardEvent {
+            key_code: 0x48,
+            is_key_down: false,
+            ctrl_pressed: false,
+            alt_pressed: false,
+            shift_pressed: false,
+            win_pressed: false,
+            character: None,
+            scan_code: Some(35),
+            metadata: EventMetadata::empty(),
+        });
+        masker.mask_event(&mut key_up);
+        match key_up {
+            WorkflowEvent::Keyboard(e) => assert_eq!((e.key_code, e.scan_code), (0, None)),
+            _ => unreachable!(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_masked_events_keep_a_masked_element_snapshot() {
+        let engine = Arc::new(FixtureEngine::new());
+        engine.load(Some(
+            serde_json::from_value(json!({
+                "pid": 7,
+                "application_name": "Demo",
+                "window_title": "Demo",
+                "tree": {
+                    "attributes": {"role": "Window", "name": "Demo"},
+                    "children": [{"attributes": {
+                        "role": "Edit", "name": "Email", "value": "mario@example.com"
+                    }}]
+                }
+            }))
+            .unwrap(),
+        ));
+        let desktop = Desktop::with_engine(engine);
+        let edit = desktop.locator("role:Edit").first(None).await.unwrap();
+
+        let mut masker = PiiMasker::new(&PiiMaskingConfig::all()).unwrap();
+        let mut event = text_input("Edit", "mario@example.com");
+        event.metadata_mut().ui_element = Some(edit.clone());
+        masker.mask_event(&mut event);
+        // Later changes to the live element don't reach the exported event.
+        edit.type_text(" luigi@example.com", false).unwrap();
+
+        let exported = SerializableEventMetadata::from(event.metadata())
+            .ui_element
+            .unwrap();
+        assert_eq!(exported.name.as_deref(), Some("Email"));
+        assert_eq!(exported.value.as_deref(), Some("[EMAIL_1]"));
+    }
+}
@@ -0,0 +1,286 @@
+//! Screenshots of high-level events.
+//!
+//! When enabled, every click, text input and application switch gets a downscaled
+//! screenshot of its monitor and a crop around its element, referenced from the event's
+//! `EventMetadata::screenshot`. Images are kept as base64 PNG while recording; `save()`
+//! either keeps them embedded or writes them next to the workflow JSON file.
+
+use crate::{
+    EventScreenshot, RecordedWorkflow, Result, ScreenshotImage, WorkflowEvent,
+    WorkflowRecorderError,
+};
+use base64::{engine::general_purpose, Engine as _};
+use image::{codecs::png::PngEncoder, imageops, ExtendedColorType, ImageEncoder, RgbaImage};
+use std::path::Path;
+use std::sync::Arc;
+use terminator::{Desktop, UIElement};
+use tokio::task::JoinHandle;
+use tracing::debug;
+
+/// Where `WorkflowRecorder::save` puts event screenshots
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScreenshotStorage {
+    /// PNG files in a `<name>_screenshots` directory next to the JSON file
+    #[default]
+    Files,
+    /// Base64 data inside the JSON file
+    Embedded,
+}
+
+/// Configuration of per-event screenshots
+#[derive(Debug, Clone)]
+pub struct EventScreenshotConfig {
+    /// Capture screenshots for clicks, text inputs and application switches
+    pub enabled: bool,
+
+    /// Width monitor screenshots are downscaled to (aspect ratio is kept)
+    pub max_width: u32,
+
+    /// Pixels of context kept around the element in the element crop
+    pub crop_padding: u32,
+
+    /// Where screenshots are stored when the workflow is saved
+    pub storage: ScreenshotStorage,
+}
+
+impl Default for EventScreenshotConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_width: 1280,
+            crop_padding: 24,
+            storage: ScreenshotStorage::Files,
+        }
+    }
+}
+
+/// Takes the screenshots of high-level events.
+pub(crate) struct ScreenshotCapturer {
+    desktop: Desktop,
+    config: EventScreenshotConfig,
+}
+
+impl ScreenshotCapturer {
+    pub fn new(config: &EventScreenshotConfig) -> Result<Self> {
+        let desktop = Desktop::new_default().map_err(|e| {
+            WorkflowRecorderError::InitializationError(format!(
+                "Failed to initialize screenshot capture: {e}"
+            ))
+        })?;
+        Ok(Self {
+            desktop,
+            config: config.clone(),
+        })
+    }
+
+    /// Start taking the screenshots of `event` if it is a high-level event. The capture
+    /// runs on its own task and the image work on a blocking thread, so the event
+    /// pipeline keeps draining events meanwhile. Failures are logged and yield `None`.
+    pub fn start(
+        self: &Arc<Self>,
+        event: &WorkflowEvent,
+    ) -> Option<JoinHandle<Option<EventScreenshot>>> {
+        if !matches!(
+            event,
+            WorkflowEvent::Click(_)
+                | WorkflowEvent::BrowserClick(_)
+                | WorkflowEvent::TextInputCompleted(_)
+                | WorkflowEvent::ApplicationSwitch(_)
+        ) {
+            return None;
+        }
+        let capturer = Arc::clone(self);
+        let element = event.metadata().ui_element.clone();
+        Some(tokio::spawn(async move {
+            match capturer.capture(element).await {
+                Ok(screenshot) => Some(screenshot),
+                Err(e) => {
+                    debug!("Failed to capture event screenshot: {}", e);
+                    None
+                }
+            }
+        }))
+    }
+
+    async fn capture(
+        &self,
+        element: Option<UIElement>,
+    ) -> std::result::Result<EventScreenshot, String> {
+        let monitor = match element.as_ref().and_then(|e| e.monitor().ok()) {
+            Some(monitor) => monitor,
+            None => self
+                .desktop
+                .get_active_monitor()
+                .await
+                .map_err(|e| e.to_string())?,
+        };
+        let shot = monitor
+            .capture(&self.desktop)
+            .await
+            .map_err(|e| e.to_string())?;
+        let config = self.config.clone();
+        tokio::task::spawn_blocking(move || -> std::result::Result<EventScreenshot, String> {
+            let image = RgbaImage::from_raw(shot.width, shot.height, shot.image_data)
+                .ok_or("screenshot has an unexpected size")?;
+
+            // Screenshots are in physical pixels; element bounds are in screen coordinates.
+            let scale = f64::from(image.width()) / f64::from(monitor.width.max(1));
+            let crop = element
+                .and_then(|e| e.bounds().ok())
+                .and_then(|(x, y, width, height)| {
+                    let padding = f64::from(config.crop_padding);
+                    let left = ((x - f64::from(monitor.x)) * scale - padding).max(0.0);
+                    let top = ((y - f64::from(monitor.y)) * scale - padding).max(0.0);
+                    let right = ((x + width - f64::from(monitor.x)) * scale + padding)
+                        .min(f64::from(image.width()));
+                    let bottom = ((y + height - f64::from(monitor.y)) * scale + padding)
+                        .min(f64::from(image.height()));
+                    (right > left && bottom > top).then(|| {
+                        imageops::crop_imm(
+                            &image,
+                            left as u32,
+                            top as u32,
+                            (right - left) as u32,
+                            (bottom - top) as u32,
+                        )
+                        .to_image()
+                    })
+                });
+
+            let screen = if image.width() > config.max_width {
+                let height = (f64::from(image.height()) * f64::from(config.max_width)
+                    / f64::from(image.width())) as u32;
+                imageops::resize(
+                    &image,
+                    config.max_width,
+                    height.max(1),
+                    imageops::FilterType::Triangle,
+                )
+            } else {
+                image
+            };
+
+            Ok(EventScreenshot {
+                screen: Some(encode(&screen)?),
+                element: crop.as_ref().map(encode).transpose()?,
+            })
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+}
+
+fn encode(image: &RgbaImage) -> std::result::Result<ScreenshotImage, String> {
+    let mut png = Vec::new();
+    PngEncoder::new(&mut png)
+        .write_image(
+            image.as_raw(),
+            image.width(),
+            image.height(),
+            ExtendedColorType::Rgba8,
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(ScreenshotImage {
+        width: image.width(),
+        height: image.height(),
+        path: None,
+        data: Some(general_purpose::STANDARD.encode(png)),
+    })
+}
+
+/// Save `workflow` to `path`, moving embedded screenshots into PNG files in a
+/// `<name>_screenshots` directory next to it.
+pub(crate) fn save_with_screenshot_files(workflow: &RecordedWorkflow, path: &Path) -> Result<()> {
+    let save_error = |e: &dyn std::fmt::Display| {
+        WorkflowRecorderError::SaveError(format!("Failed to save screenshots: {e}"))
+    };
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "workflow".to_string());
+    let dir_name = format!("{stem}_screenshots");
+    let dir = path.with_file_name(&dir_name);
+
+    let mut workflow = workflow.clone();
+    for (index, event) in workflow.events.iter_mut().enumerate() {
+        let Some(screenshot) = event.event.metadata_mut().screenshot.as_mut() else {
+            continue;
+        };
+        for (kind, image) in [
+            ("screen", &mut screenshot.screen),
+            ("element", &mut screenshot.element),
+        ] {
+            let Some(image) = image else { continue };
+            let Some(data) = image.data.take() else {
+                continue;
+            };
+            let png = general_purpose::STANDARD
+                .decode(data)
+                .map_err(|e| save_error(&e))?;
+            std::fs::create_dir_all(&dir).map_err(|e| save_error(&e))?;
+            let file_name = format!("event_{index:04}_{kind}.png");
+            std::fs::write(dir.join(&file_name), png).map_err(|e| save_error(&e))?;
+            image.path = Some(format!("{dir_name}/{file_name}"));
+        }
+    }
+
+    workflow
+        .save_to_file(path)
+        .map_err(|e| WorkflowRecorderError::SaveError(format!("Failed to save workflow: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventMetadata, HotkeyEvent};
+
+    #[test]
+    fn test_save_with_screenshot_files() {
+        let image = RgbaImage::from_pixel(4, 2, image::Rgba([255, 0, 0, 255]));
+        let mut metadata = EventMetadata::empty();
+        metadata.screenshot = Some(EventScreenshot {
+            screen: Some(encode(&image).unwrap()),
+            element: None,
+        });
+        let mut workflow = RecordedWorkflow::new("Screens".to_string());
+        workflow.add_event(WorkflowEvent::Hotkey(HotkeyEvent {
+            combination: "Ctrl+S".to_string(),
+            action: None,
+            is_global: true,
+            metadata,
+        }));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("demo.json");
+        save_with_screenshot_files(&workflow, &path).unwrap();
+
+        let png = dir.path().join("demo_screenshots/event_0000_screen.png");
+        assert_eq!(image::open(png).unwrap().width(), 4);
+        let saved: RecordedWorkflow =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        let screen = saved.events[0]
+            .event
+            .metadata()
+            .screenshot
+            .as_ref()
+            .and_then(|s| s.screen.clone())
+            .unwrap();
+        assert_eq!(
+            screen.path.as_deref(),
+            Some("demo_screenshots/event_0000_screen.png")
+        );
+        assert_eq!(screen.data, None);
+        // The recording itself keeps the embedded image.
+        assert!(workflow.events[0]
+            .event
+            .metadata()
+            .screenshot
+            .as_ref()
+            .unwrap()
+            .screen
+            .as_ref()
+            .unwrap()
+            .data
+            .is_some());
+    }
+}
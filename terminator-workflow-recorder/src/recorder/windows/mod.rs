@@ -1079,6 +1079,9 @@ impl WindowsRecorder {
                             metadata: EventMetadata {
                                 ui_element: None,
                                 timestamp: Some(Self::capture_timestamp()),
+                                screenshot: None,
+                                window: None,
+                                masked_element: None,
                             },
                         };
 
@@ -1121,6 +1124,9 @@ impl WindowsRecorder {
                             metadata: EventMetadata {
                                 ui_element: None,
                                 timestamp: Some(Self::capture_timestamp()),
+                                screenshot: None,
+                                window: None,
+                                masked_element: None,
                             },
                         };
                         Self::send_filtered_event_static(
@@ -1157,6 +1163,9 @@ impl WindowsRecorder {
                                     metadata: EventMetadata {
                                         ui_element: None,
                                         timestamp: Some(Self::capture_timestamp()),
+                                        screenshot: None,
+                                        window: None,
+                                        masked_element: None,
                                     },
                                 };
                                 Self::send_filtered_event_static(
@@ -1195,6 +1204,9 @@ impl WindowsRecorder {
                                     metadata: EventMetadata {
                                         ui_element: None,
                                         timestamp: Some(Self::capture_timestamp()),
+                                        screenshot: None,
+                                        window: None,
+                                        masked_element: None,
                                     },
                                 };
                                 Self::send_filtered_event_static(
@@ -1237,6 +1249,9 @@ impl WindowsRecorder {
                                 metadata: EventMetadata {
                                     ui_element: None,
                                     timestamp: Some(Self::capture_timestamp()),
+                                    screenshot: None,
+                                    window: None,
+                                    masked_element: None,
                                 },
                             };
                             Self::send_filtered_event_static(
@@ -1270,6 +1285,9 @@ impl WindowsRecorder {
                                 metadata: EventMetadata {
                                     ui_element,
                                     timestamp: Some(Self::capture_timestamp()),
+                                    screenshot: None,
+                                    window: None,
+                                    masked_element: None,
                                 },
                             };
                             Self::send_filtered_event_static(
@@ -1320,6 +1338,9 @@ impl WindowsRecorder {
                     metadata: EventMetadata {
                         ui_element: None,
                         timestamp: Some(Self::capture_timestamp()),
+                        screenshot: None,
+                        window: None,
+                        masked_element: None,
                     }, // TODO: Pass UI element context from caller
                 });
             }
@@ -1386,6 +1407,9 @@ impl WindowsRecorder {
                             metadata: EventMetadata {
                                 ui_element,
                                 timestamp: Some(Self::capture_timestamp()),
+                                screenshot: None,
+                                window: None,
+                                masked_element: None,
                             },
                         };
 
@@ -2382,6 +2406,9 @@ impl WindowsRecorder {
                 metadata: crate::EventMetadata {
                     ui_element: ui_element.clone(),
                     timestamp: Some(Self::capture_timestamp()),
+                    screenshot: None,
+                    window: None,
+                    masked_element: None,
                 },
             };
 
@@ -2698,6 +2725,10 @@ impl WindowsRecorder {
                                                     xpath: browser_dom_info.xpath,
                                                     inner_text: browser_dom_info.inner_text,
                                                     input_value: browser_dom_info.input_value,
+                                                    input_type: browser_dom_info
+                                                        .attributes
+                                                        .get("type")
+                                                        .cloned(),
                                                     is_visible: browser_dom_info.is_visible,
                                                     is_interactive: browser_dom_info.is_interactive,
                                                     aria_label: browser_dom_info.aria_label,
@@ -2834,6 +2865,9 @@ impl WindowsRecorder {
             metadata: EventMetadata {
                 ui_element,
                 timestamp: Some(Self::capture_timestamp()),
+                screenshot: None,
+                window: None,
+                masked_element: None,
             },
         };
         Self::send_filtered_event_static(
@@ -2869,6 +2903,9 @@ impl WindowsRecorder {
             metadata: EventMetadata {
                 ui_element,
                 timestamp: Some(Self::capture_timestamp()),
+                screenshot: None,
+                window: None,
+                masked_element: None,
             },
         };
         Self::send_filtered_event_static(
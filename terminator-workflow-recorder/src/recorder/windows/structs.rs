@@ -1,5 +1,4 @@
 use crate::events::EventMetadata;
-use rdev::Key;
 use std::time::Instant;
 use terminator::UIElement;
 use tracing::{error, info};
@@ -443,82 +442,7 @@ impl DoubleClickTracker {
     }
 }
 
-/// Convert a Key to a u32
-pub fn key_to_u32(key: &Key) -> u32 {
-    match key {
-        Key::KeyA => 0x41,
-        Key::KeyB => 0x42,
-        Key::KeyC => 0x43,
-        Key::KeyD => 0x44,
-        Key::KeyE => 0x45,
-        Key::KeyF => 0x46,
-        Key::KeyG => 0x47,
-        Key::KeyH => 0x48,
-        Key::KeyI => 0x49,
-        Key::KeyJ => 0x4A,
-        Key::KeyK => 0x4B,
-        Key::KeyL => 0x4C,
-        Key::KeyM => 0x4D,
-        Key::KeyN => 0x4E,
-        Key::KeyO => 0x4F,
-        Key::KeyP => 0x50,
-        Key::KeyQ => 0x51,
-        Key::KeyR => 0x52,
-        Key::KeyS => 0x53,
-        Key::KeyT => 0x54,
-        Key::KeyU => 0x55,
-        Key::KeyV => 0x56,
-        Key::KeyW => 0x57,
-        Key::KeyX => 0x58,
-        Key::KeyY => 0x59,
-        Key::KeyZ => 0x5A,
-        Key::Num0 => 0x30,
-        Key::Num1 => 0x31,
-        Key::Num2 => 0x32,
-        Key::Num3 => 0x33,
-        Key::Num4 => 0x34,
-        Key::Num5 => 0x35,
-        Key::Num6 => 0x36,
-        Key::Num7 => 0x37,
-        Key::Num8 => 0x38,
-        Key::Num9 => 0x39,
-        Key::Escape => 0x1B,
-        Key::Backspace => 0x08,
-        Key::Tab => 0x09,
-        Key::Return => 0x0D,
-        Key::Space => 0x20,
-        Key::LeftArrow => 0x25,
-        Key::UpArrow => 0x26,
-        Key::RightArrow => 0x27,
-        Key::DownArrow => 0x28,
-        Key::Delete => 0x2E,
-        Key::Home => 0x24,
-        Key::End => 0x23,
-        Key::PageUp => 0x21,
-        Key::PageDown => 0x22,
-        Key::F1 => 0x70,
-        Key::F2 => 0x71,
-        Key::F3 => 0x72,
-        Key::F4 => 0x73,
-        Key::F5 => 0x74,
-        Key::F6 => 0x75,
-        Key::F7 => 0x76,
-        Key::F8 => 0x77,
-        Key::F9 => 0x78,
-        Key::F10 => 0x79,
-        Key::F11 => 0x7A,
-        Key::F12 => 0x7B,
-        Key::ShiftLeft => 0xA0,
-        Key::ShiftRight => 0xA1,
-        Key::ControlLeft => 0xA2,
-        Key::ControlRight => 0xA3,
-        Key::Alt => 0xA4,
-        Key::AltGr => 0xA5,
-        Key::MetaLeft => 0x5B,
-        Key::MetaRight => 0x5C,
-        _ => 0,
-    }
-}
+pub use crate::recorder::keys::key_to_u32;
 
 #[cfg(test)]
 mod tests {
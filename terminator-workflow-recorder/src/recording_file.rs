@@ -0,0 +1,346 @@
+//! Versioned recording files.
+//!
+//! A recording is saved either as a JSON document (the `RecordedWorkflow` fields plus
+//! `format_version`) or, for paths ending in `.gz`, as gzip-compressed JSON Lines: a
+//! `header` line followed by one line per `event` and `marker` and a closing `end`
+//! line. The JSON Lines encoding is written and read incrementally with
+//! `RecordingWriter` and `RecordingReader`, so a long recording never has to be held in
+//! memory.
+//!
+//! Files from older format versions are read as well; files from newer versions are
+//! rejected instead of being read partially.
+
+use crate::events::{SerializableRecordedEvent, SerializableRecordedWorkflow};
+use crate::{RecordedEvent, RecordedWorkflow, Result, WorkflowMarker, WorkflowRecorderError};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+/// Format version written by this crate.
+///
+/// - 1: JSON document without `format_version`, written before versioning
+/// - 2: `format_version` added; JSON Lines encoding
+///
+/// Both have the same fields, so reading version 1 needs no migration. When a field
+/// changes, bump the version and upgrade the older layout where headers and events are
+/// read.
+pub const RECORDING_FORMAT_VERSION: u32 = 2;
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// How a recording file is encoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RecordingEncoding {
+    /// Pretty-printed JSON document
+    #[default]
+    Json,
+    /// gzip-compressed JSON Lines, written and read incrementally
+    JsonLinesGzip,
+}
+
+impl RecordingEncoding {
+    /// `JsonLinesGzip` for paths ending in `.gz` (e.g. `recording.jsonl.gz`), `Json` otherwise
+    pub fn for_path(path: &Path) -> Self {
+        match path.extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("gz") => Self::JsonLinesGzip,
+            _ => Self::Json,
+        }
+    }
+}
+
+/// First line of a JSON Lines recording
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RecordingHeader {
+    pub format_version: u32,
+    pub name: String,
+    pub start_time: u64,
+}
+
+/// A line of a JSON Lines recording, e.g. `{"event": {...}}`
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Line<'a> {
+    Header(&'a RecordingHeader),
+    Event(SerializableRecordedEvent),
+    Marker(&'a WorkflowMarker),
+    End { end_time: Option<u64> },
+}
+
+/// Save `workflow` to `path`, encoded as `RecordingEncoding::for_path` says.
+pub fn write_recording(workflow: &RecordedWorkflow, path: &Path) -> Result<()> {
+    match RecordingEncoding::for_path(path) {
+        RecordingEncoding::Json => {
+            let serializable = SerializableRecordedWorkflow::from(workflow);
+            std::fs::write(path, serde_json::to_vec_pretty(&serializable)?)?;
+        }
+        RecordingEncoding::JsonLinesGzip => {
+            let mut writer = RecordingWriter::create(path, &workflow.name, workflow.start_time)?;
+            for event in &workflow.events {
+                writer.write_event(event)?;
+            }
+            for marker in &workflow.markers {
+                writer.write_marker(marker)?;
+            }
+            writer.finish(workflow.end_time)?;
+        }
+    }
+    Ok(())
+}
+
+/// Load a recording in any encoding and format version.
+pub fn read_recording(path: &Path) -> Result<RecordedWorkflow> {
+    RecordingReader::open(path)?.into_workflow()
+}
+
+/// Writes a recording as gzip-compressed JSON Lines, one event at a time.
+pub struct RecordingWriter<W: Write> {
+    out: GzEncoder<W>,
+}
+
+impl RecordingWriter<BufWriter<File>> {
+    pub fn create(path: &Path, name: &str, start_time: u64) -> Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?), name, start_time)
+    }
+}
+
+impl<W: Write> RecordingWriter<W> {
+    /// Start a recording named `name` on `out`
+    pub fn new(out: W, name: &str, start_time: u64) -> Result<Self> {
+        let mut writer = Self {
+            out: GzEncoder::new(out, Compression::default()),
+        };
+        writer.write_line(&Line::Header(&RecordingHeader {
+            format_version: RECORDING_FORMAT_VERSION,
+            name: name.to_string(),
+            start_time,
+        }))?;
+        Ok(writer)
+    }
+
+    pub fn write_event(&mut self, event: &RecordedEvent) -> Result<()> {
+        self.write_line(&Line::Event(event.into()))
+    }
+
+    pub fn write_marker(&mut self, marker: &WorkflowMarker) -> Result<()> {
+        self.write_line(&Line::Marker(marker))
+    }
+
+    /// Close the recording and return the underlying writer, flushed.
+    pub fn finish(mut self, end_time: Option<u64>) -> Result<W> {
+        self.write_line(&Line::End { end_time })?;
+        let mut out = self.out.finish()?;
+        out.flush()?;
+        Ok(out)
+    }
+
+    fn write_line(&mut self, line: &Line) -> Result<()> {
+        serde_json::to_writer(&mut self.out, line)?;
+        self.out.write_all(b"\n")?;
+        Ok(())
+    }
+}
+
+/// Reads a recording one event at a time. Iterating yields the events; markers and the
+/// end time are available once all events have been read.
+///
+/// JSON Lines files are read incrementally. JSON documents have no such structure and
+/// are loaded at once.
+pub struct RecordingReader {
+    header: RecordingHeader,
+    source: Source,
+    markers: Vec<WorkflowMarker>,
+    end_time: Option<u64>,
+}
+
+enum Source {
+    Lines(std::io::Lines<Box<dyn BufRead + Send>>),
+    Document(std::vec::IntoIter<Value>),
+}
+
+impl RecordingReader {
+    pub fn open(path: &Path) -> Result<Self> {
+        Self::new(File::open(path)?)
+    }
+
+    /// Read a recording from `reader`, gzip-compressed or not.
+    pub fn new<R: Read + Send + 'static>(reader: R) -> Result<Self> {
+        let mut reader = BufReader::new(reader);
+        let mut reader: Box<dyn BufRead + Send> = if reader.fill_buf()?.starts_with(&GZIP_MAGIC) {
+            Box::new(BufReader::new(GzDecoder::new(reader)))
+        } else {
+            Box::new(reader)
+        };
+
+        let mut first_line = String::new();
+        reader.read_line(&mut first_line)?;
+        if let Ok(Value::Object(mut line)) = serde_json::from_str(&first_line) {
+            if let Some(header) = line.remove("header") {
+                let version = format_version(&header)?;
+                let mut header: RecordingHeader = serde_json::from_value(header)?;
+                header.format_version = version;
+                return Ok(Self {
+                    header,
+                    source: Source::Lines(reader.lines()),
+                    markers: Vec::new(),
+                    end_time: None,
+                });
+            }
+        }
+
+        let mut content = first_line;
+        reader.read_to_string(&mut content)?;
+        Self::from_document(serde_json::from_str(&content)?)
+    }
+
+    fn from_document(mut document: Value) -> Result<Self> {
+        let version = format_version(&document)?;
+        let events = match document.get_mut("events").map(Value::take) {
+            Some(Value::Array(events)) => events,
+            _ => return Err(format_error("the recording has no events")),
+        };
+        let field = |key: &str| document.get(key).cloned().unwrap_or(Value::Null);
+        Ok(Self {
+            header: RecordingHeader {
+                format_version: version,
+                name: serde_json::from_value(field("name"))?,
+                start_time: serde_json::from_value(field("start_time"))?,
+            },
+            source: Source::Document(events.into_iter()),
+            markers: serde_json::from_value::<Option<_>>(field("markers"))?.unwrap_or_default(),
+            end_time: serde_json::from_value(field("end_time"))?,
+        })
+    }
+
+    /// Name, start time and format version the file was written with
+    pub fn header(&self) -> &RecordingHeader {
+        &self.header
+    }
+
+    /// Markers read so far
+    pub fn markers(&self) -> &[WorkflowMarker] {
+        &self.markers
+    }
+
+    /// End time, once read
+    pub fn end_time(&self) -> Option<u64> {
+        self.end_time
+    }
+
+    /// Read the remaining events into a `RecordedWorkflow`.
+    pub fn into_workflow(mut self) -> Result<RecordedWorkflow> {
+        let events = self.by_ref().collect::<Result<Vec<_>>>()?;
+        Ok(RecordedWorkflow {
+            name: self.header.name,
+            start_time: self.header.start_time,
+            end_time: self.end_time,
+            events,
+            markers: self.markers,
+        })
+    }
+
+    /// Read the remaining events into a `SerializableRecordedWorkflow`. Unlike
+    /// `into_workflow`, it keeps the elements of the events as saved, including those
+    /// no longer on screen.
+    pub fn into_serializable_workflow(mut self) -> Result<SerializableRecordedWorkflow> {
+        let events = std::iter::from_fn(|| self.next_value())
+            .map(|event| Ok(serde_json::from_value(event?)?))
+            .collect::<Result<Vec<SerializableRecordedEvent>>>()?;
+        Ok(SerializableRecordedWorkflow {
+            format_version: self.header.format_version,
+            name: self.header.name,
+            start_time: self.header.start_time,
+            end_time: self.end_time,
+            events,
+            markers: self.markers,
+        })
+    }
+
+    /// The next event as written
+    fn next_value(&mut self) -> Option<Result<Value>> {
+        let Self {
+            source,
+            markers,
+            end_time,
+            ..
+        } = self;
+        let lines = match source {
+            Source::Document(events) => return events.next().map(Ok),
+            Source::Lines(lines) => lines,
+        };
+        for line in lines {
+            let line = match line {
+                Ok(line) if line.trim().is_empty() => continue,
+                Ok(line) => line,
+                Err(e) => return Some(Err(e.into())),
+            };
+            let parsed = serde_json::from_str(&line)
+                .map_err(WorkflowRecorderError::from)
+                .and_then(|line| read_line(line, markers, end_time));
+            match parsed {
+                Ok(Some(event)) => return Some(Ok(event)),
+                Ok(None) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        None
+    }
+}
+
+impl Iterator for RecordingReader {
+    type Item = Result<RecordedEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_value()
+            .map(|event| Ok(serde_json::from_value(event?)?))
+    }
+}
+
+/// Returns the event of an `event` line; records markers and the end time.
+fn read_line(
+    line: Value,
+    markers: &mut Vec<WorkflowMarker>,
+    end_time: &mut Option<u64>,
+) -> Result<Option<Value>> {
+    let Value::Object(line) = line else {
+        return Err(format_error("expected a JSON object per line"));
+    };
+    let Some((kind, mut value)) = line.into_iter().next() else {
+        return Err(format_error("empty line object"));
+    };
+    match kind.as_str() {
+        "event" => return Ok(Some(value)),
+        "marker" => markers.push(serde_json::from_value(value)?),
+        "end" => {
+            let value = value.get_mut("end_time").map(Value::take);
+            *end_time = serde_json::from_value(value.unwrap_or(Value::Null))?;
+        }
+        other => return Err(format_error(&format!("unknown line type '{other}'"))),
+    }
+    Ok(None)
+}
+
+/// `format_version` of a document or header; 1 when missing.
+fn format_version(value: &Value) -> Result<u32> {
+    let version = match value.get("format_version") {
+        None => 1,
+        Some(version) => version
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| format_error("format_version is not a number"))?,
+    };
+    if version > RECORDING_FORMAT_VERSION {
+        return Err(format_error(&format!(
+            "format_version {version} is newer than the supported version {RECORDING_FORMAT_VERSION}"
+        )));
+    }
+    Ok(version)
+}
+
+fn format_error(message: &str) -> WorkflowRecorderError {
+    WorkflowRecorderError::FormatError(message.to_string())
+}
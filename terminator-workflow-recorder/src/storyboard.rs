@@ -0,0 +1,180 @@
+//! HTML storyboard of a recording.
+
+use crate::{RecordedWorkflow, ScreenshotImage, WorkflowEvent};
+
+/// Render `workflow` as a self-contained HTML page: one card per user action (raw keyboard
+/// and mouse events are left out), with the event's screenshots, under a heading for each
+/// marker. Screenshots saved as files are referenced by their relative path, so the page
+/// should be written next to the workflow JSON file.
+pub fn render_storyboard(workflow: &RecordedWorkflow) -> String {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n", escape(&workflow.name)));
+    html.push_str(STYLE);
+    html.push_str("</head>\n<body>\n");
+    html.push_str(&format!("<h1>{}</h1>\n", escape(&workflow.name)));
+
+    let mut markers = workflow.markers.iter().peekable();
+    let mut step = 0;
+    html.push_str("<section class=\"steps\">\n");
+    for recorded in &workflow.events {
+        if recorded.event.is_raw_input() {
+            continue;
+        }
+        while let Some(marker) = markers.next_if(|m| m.timestamp <= recorded.timestamp) {
+            html.push_str(&format!(
+                "</section>\n<h2>{}</h2>\n<section class=\"steps\">\n",
+                escape(&marker.label)
+            ));
+        }
+        step += 1;
+        let offset = recorded.timestamp.saturating_sub(workflow.start_time) as f64 / 1000.0;
+        html.push_str("<article>\n");
+        html.push_str(&format!(
+            "<header><span class=\"step\">{step}</span> <span class=\"time\">+{offset:.1}s</span></header>\n<p>{}</p>\n",
+            escape(&describe(&recorded.event))
+        ));
+        if let Some(screenshot) = &recorded.event.metadata().screenshot {
+            for (class, image) in [
+                ("element", &screenshot.element),
+                ("screen", &screenshot.screen),
+            ] {
+                if let Some(img) = image.as_ref().and_then(image_tag) {
+                    html.push_str(&format!("<div class=\"{class}\">{img}</div>\n"));
+                }
+            }
+        }
+        html.push_str("</article>\n");
+    }
+    html.push_str("</section>\n</body>\n</html>\n");
+    html
+}
+
+fn image_tag(image: &ScreenshotImage) -> Option<String> {
+    image.src().map(|src| {
+        format!(
+            "<img src=\"{}\" width=\"{}\" height=\"{}\" loading=\"lazy\">",
+            escape(&src),
+            image.width,
+            image.height
+        )
+    })
+}
+
+/// One-line description of a user action.
+fn describe(event: &WorkflowEvent) -> String {
+    match event {
+        WorkflowEvent::Click(e) => format!("Clicked '{}' ({})", e.element_text, e.element_role),
+        WorkflowEvent::BrowserClick(e) => match &e.dom_element {
+            Some(dom) => format!(
+                "Clicked '{}' <{}> in the browser",
+                dom.inner_text.as_deref().unwrap_or_default().trim(),
+                dom.tag_name
+            ),
+            None => "Clicked in the browser".to_string(),
+        },
+        WorkflowEvent::TextInputCompleted(e) => format!(
+            "Typed '{}' into '{}'",
+            e.text_value,
+            e.field_name.as_deref().unwrap_or(&e.field_type)
+        ),
+        WorkflowEvent::BrowserTextInput(e) => format!("Typed '{}' in the browser", e.text),
+        WorkflowEvent::ApplicationSwitch(e) => format!("Switched to {}", e.to_application),
+        WorkflowEvent::BrowserTabNavigation(e) => format!(
+            "{:?} tab in {}: {}",
+            e.action,
+            e.browser,
+            e.to_url
+                .as_deref()
+                .or(e.to_title.as_deref())
+                .unwrap_or_default()
+        ),
+        WorkflowEvent::Hotkey(e) => format!("Pressed {}", e.combination),
+        WorkflowEvent::Clipboard(e) => format!("{:?} clipboard", e.action),
+        WorkflowEvent::TextSelection(e) => format!("Selected '{}'", e.selected_text),
+        WorkflowEvent::DragDrop(e) => format!(
+            "Dragged from ({}, {}) to ({}, {})",
+            e.start_position.x, e.start_position.y, e.end_position.x, e.end_position.y
+        ),
+        WorkflowEvent::Mouse(_) | WorkflowEvent::Keyboard(_) => String::new(),
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const STYLE: &str = "<style>
+body { font-family: system-ui, sans-serif; margin: 2rem; background: #f6f7f9; color: #1f2328; }
+h2 { margin-top: 2rem; border-bottom: 1px solid #d0d7de; }
+.steps { display: grid; grid-template-columns: repeat(auto-fill, minmax(360px, 1fr)); gap: 1rem; }
+article { background: #fff; border: 1px solid #d0d7de; border-radius: 8px; padding: 0.75rem; }
+header { display: flex; justify-content: space-between; color: #57606a; font-size: 0.85rem; }
+.step { font-weight: bold; }
+img { max-width: 100%; height: auto; border: 1px solid #d0d7de; }
+.element img { border-color: #cf222e; }
+</style>
+";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ButtonInteractionType, ClickEvent, EventMetadata, EventScreenshot, RecordedEvent,
+        WorkflowMarker,
+    };
+
+    #[test]
+    fn test_render_storyboard() {
+        let mut metadata = EventMetadata::empty();
+        metadata.screenshot = Some(EventScreenshot {
+            screen: Some(ScreenshotImage {
+                width: 640,
+                height: 360,
+                path: Some("demo_screenshots/event_0000_screen.png".to_string()),
+                data: None,
+            }),
+            element: Some(ScreenshotImage {
+                width: 80,
+                height: 30,
+                path: None,
+                data: Some("iVBORw0KGgo=".to_string()),
+            }),
+        });
+        let workflow = RecordedWorkflow {
+            name: "Demo <1>".to_string(),
+            start_time: 1000,
+            end_time: Some(3000),
+            events: vec![RecordedEvent {
+                timestamp: 2500,
+                event: WorkflowEvent::Click(ClickEvent {
+                    element_text: "Save".to_string(),
+                    interaction_type: ButtonInteractionType::Click,
+                    element_role: "Button".to_string(),
+                    was_enabled: true,
+                    click_position: None,
+                    element_description: None,
+                    child_text_content: vec![],
+                    relative_position: None,
+                    metadata,
+                }),
+                metadata: None,
+            }],
+            markers: vec![WorkflowMarker {
+                label: "Save the form".to_string(),
+                timestamp: 2000,
+            }],
+        };
+
+        let html = render_storyboard(&workflow);
+        assert!(html.contains("<h1>Demo &lt;1&gt;</h1>"));
+        assert!(html.contains("<h2>Save the form</h2>"));
+        assert!(html.contains("+1.5s"));
+        assert!(html.contains("Clicked 'Save' (Button)"));
+        assert!(html.contains("src=\"demo_screenshots/event_0000_screen.png\""));
+        assert!(html.contains("src=\"data:image/png;base64,iVBORw0KGgo=\""));
+    }
+}
@@ -0,0 +1,176 @@
+use terminator_workflow_recorder::{
+    PostProcessConfig, PostProcessPass, PostProcessPipeline, RecordedEvent, RecordedWorkflow,
+    TextInputMethod, WorkflowEvent,
+};
+
+fn load_fixture(name: &str) -> RecordedWorkflow {
+    let path = format!("{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"));
+    let content = std::fs::read_to_string(path).expect("fixture exists");
+    serde_json::from_str(&content).expect("fixture is a valid recording")
+}
+
+fn summary(workflow: &RecordedWorkflow) -> Vec<String> {
+    workflow
+        .events
+        .iter()
+        .map(|recorded| match &recorded.event {
+            WorkflowEvent::Click(e) => format!("click {}", e.element_text),
+            WorkflowEvent::TextInputCompleted(e) => {
+                format!("input {:?}={}", e.field_name, e.text_value)
+            }
+            WorkflowEvent::ApplicationSwitch(e) => {
+                format!("switch {:?}->{}", e.from_application, e.to_application)
+            }
+            WorkflowEvent::Mouse(_) => "mouse".to_string(),
+            other => format!("{other:?}"),
+        })
+        .collect()
+}
+
+#[test]
+fn test_default_pipeline_cleans_noisy_form() {
+    let mut workflow = load_fixture("noisy_form.json");
+    let report = PostProcessPipeline::default().process(&mut workflow);
+
+    assert_eq!(
+        summary(&workflow),
+        vec![
+            "switch Some(\"explorer\")->Contoso CRM",
+            "mouse",
+            "input Some(\"Name\")=John",
+            "input Some(\"Country\")=France",
+            "click Save",
+        ]
+    );
+
+    let WorkflowEvent::TextInputCompleted(name) = &workflow.events[2].event else {
+        panic!("expected the merged name input");
+    };
+    assert_eq!(name.keystroke_count, 6);
+    assert_eq!(name.typing_duration_ms, 1000);
+    assert_eq!(name.input_method, TextInputMethod::Typed);
+
+    let WorkflowEvent::TextInputCompleted(country) = &workflow.events[3].event else {
+        panic!("expected the dropdown selection");
+    };
+    assert_eq!(country.field_type, "ComboBox");
+    assert_eq!(country.input_method, TextInputMethod::Suggestion);
+    assert_eq!(workflow.events[3].timestamp, 6000);
+
+    let passes: Vec<(&str, usize)> = report
+        .passes
+        .iter()
+        .map(|p| (p.pass.as_str(), p.changes.len()))
+        .collect();
+    assert_eq!(
+        passes,
+        vec![
+            ("drop_repeated_clicks", 1),
+            ("drop_noop_app_switches", 1),
+            ("collapse_dropdown_selection", 1),
+            ("collapse_click_then_type", 2),
+            ("merge_field_edits", 1),
+        ]
+    );
+    assert_eq!(report.passes[0].events_before, 13);
+    assert_eq!(report.passes[4].events_after, 5);
+    assert!(report.notes().contains(
+        &"merge_field_edits: merged edit 'Jon' into final value 'John' of Name".to_string()
+    ));
+}
+
+#[test]
+fn test_alt_tab_cycling_becomes_one_switch() {
+    let mut workflow = load_fixture("alt_tab_cycling.json");
+    let report = PostProcessPipeline::default().process(&mut workflow);
+
+    // Clicks two seconds apart are kept: the repeated click window is one second.
+    assert_eq!(
+        summary(&workflow),
+        vec![
+            "switch Some(\"Notepad\")->Calculator",
+            "click Seven",
+            "click Seven",
+        ]
+    );
+    let WorkflowEvent::ApplicationSwitch(switch) = &workflow.events[0].event else {
+        panic!("expected an application switch");
+    };
+    assert_eq!(switch.switch_count, Some(3));
+    assert_eq!(
+        report.notes(),
+        vec!["drop_noop_app_switches: merged 3 consecutive switches into one to Calculator"]
+    );
+}
+
+#[test]
+fn test_disabled_config_and_custom_pass() {
+    struct DropClicks;
+
+    impl PostProcessPass for DropClicks {
+        fn name(&self) -> &str {
+            "drop_clicks"
+        }
+
+        fn apply(&self, events: &mut Vec<RecordedEvent>) -> Vec<String> {
+            let before = events.len();
+            events.retain(|e| !matches!(e.event, WorkflowEvent::Click(_)));
+            vec![format!("dropped {} clicks", before - events.len())]
+        }
+    }
+
+    let mut workflow = load_fixture("noisy_form.json");
+    let report = PostProcessPipeline::from_config(&PostProcessConfig::disabled())
+        .with_pass(DropClicks)
+        .process(&mut workflow);
+
+    assert_eq!(workflow.events.len(), 7);
+    assert_eq!(report.notes(), vec!["drop_clicks: dropped 6 clicks"]);
+}
+
+#[test]
+fn test_clicks_and_edits_need_the_same_element() {
+    let click = |timestamp: u64, interaction: &str, window: &str| {
+        serde_json::json!({"timestamp": timestamp, "event": {"Click": {
+            "element_text": "", "interaction_type": interaction, "element_role": "CheckBox",
+            "was_enabled": true,
+            "metadata": {"timestamp": timestamp, "window": {"title": window}}
+        }}})
+    };
+    let edit = |timestamp: u64, value: &str, window: Option<&str>| {
+        serde_json::json!({"timestamp": timestamp, "event": {"TextInputCompleted": {
+            "text_value": value, "field_type": "Edit", "input_method": "Typed",
+            "focus_method": "Unknown", "typing_duration_ms": 100, "keystroke_count": 1,
+            "metadata": {"timestamp": timestamp, "window": window.map(|title| serde_json::json!({"title": title}))}
+        }}})
+    };
+    let double_click = serde_json::json!({"timestamp": 2150, "event": {"Mouse": {
+        "event_type": "DoubleClick", "button": "Left", "position": {"x": 1, "y": 1},
+        "metadata": {"timestamp": 2150}
+    }}});
+    let mut workflow: RecordedWorkflow = serde_json::from_value(serde_json::json!({
+        "name": "Identity", "start_time": 0, "events": [
+            // A toggle clicked twice, a double-click, clicks in two windows
+            click(1000, "Toggle", "Settings"),
+            click(1100, "Toggle", "Settings"),
+            edit(1500, "a", Some("Settings")),
+            click(2000, "Click", "Settings"),
+            click(2100, "Click", "Settings"),
+            double_click,
+            edit(2500, "b", Some("Settings")),
+            click(3000, "Click", "Settings"),
+            click(3100, "Click", "Preferences"),
+            // Unnamed fields: different windows, then no identity at all
+            edit(4000, "first", Some("Settings")),
+            edit(4100, "second", Some("Preferences")),
+            edit(5000, "third", None),
+            edit(5100, "fourth", None),
+        ]
+    }))
+    .unwrap();
+
+    let report =
+        PostProcessPipeline::from_config(&PostProcessConfig::default()).process(&mut workflow);
+    assert!(report.is_empty(), "{:?}", report.notes());
+    assert_eq!(workflow.events.len(), 13);
+}
@@ -0,0 +1,111 @@
+use terminator_workflow_recorder::{
+    read_recording, write_recording, RecordedWorkflow, RecordingReader, RecordingWriter,
+    WorkflowEvent, WorkflowMarker, RECORDING_FORMAT_VERSION,
+};
+
+fn fixture_path(name: &str) -> std::path::PathBuf {
+    std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+fn event_summary(workflow: &RecordedWorkflow) -> Vec<(u64, String)> {
+    workflow
+        .events
+        .iter()
+        .map(|e| (e.timestamp, format!("{:?}", e.event)))
+        .collect()
+}
+
+#[test]
+fn test_unversioned_recording_is_read_as_version_1() {
+    let reader = RecordingReader::open(&fixture_path("noisy_form.json")).unwrap();
+    assert_eq!(reader.header().format_version, 1);
+    assert_eq!(reader.header().name, "Fill customer form");
+
+    let workflow = reader.into_workflow().unwrap();
+    assert_eq!(workflow.events.len(), 13);
+}
+
+#[test]
+fn test_round_trip_in_both_encodings() {
+    let mut workflow = read_recording(&fixture_path("noisy_form.json")).unwrap();
+    workflow.end_time = Some(workflow.start_time + 60_000);
+    workflow.markers.push(WorkflowMarker {
+        label: "Details".to_string(),
+        timestamp: workflow.start_time + 5000,
+    });
+    let dir = tempfile::tempdir().unwrap();
+
+    for file_name in ["recording.json", "recording.jsonl.gz"] {
+        let path = dir.path().join(file_name);
+        write_recording(&workflow, &path).unwrap();
+
+        let reader = RecordingReader::open(&path).unwrap();
+        assert_eq!(reader.header().format_version, RECORDING_FORMAT_VERSION);
+        let loaded = reader.into_workflow().unwrap();
+        assert_eq!(loaded.name, workflow.name);
+        assert_eq!(loaded.end_time, workflow.end_time);
+        assert_eq!(loaded.markers, workflow.markers);
+        assert_eq!(event_summary(&loaded), event_summary(&workflow));
+    }
+
+    let json = std::fs::metadata(dir.path().join("recording.json")).unwrap();
+    let compact = std::fs::metadata(dir.path().join("recording.jsonl.gz")).unwrap();
+    assert!(compact.len() < json.len());
+}
+
+#[test]
+fn test_streaming_write_and_read() {
+    let workflow = read_recording(&fixture_path("alt_tab_cycling.json")).unwrap();
+    let mut writer = RecordingWriter::new(Vec::new(), "Streamed", 1000).unwrap();
+    for round in 0..100u64 {
+        for event in &workflow.events {
+            let mut event = event.clone();
+            event.timestamp += round * 10_000;
+            writer.write_event(&event).unwrap();
+        }
+    }
+    let bytes = writer.finish(Some(2_000_000)).unwrap();
+
+    let mut reader = RecordingReader::new(std::io::Cursor::new(bytes)).unwrap();
+    assert_eq!(reader.header().name, "Streamed");
+    let first = reader.next().unwrap().unwrap();
+    assert!(matches!(first.event, WorkflowEvent::ApplicationSwitch(_)));
+    // The end time is the last line, so it is only known once all events are read.
+    assert_eq!(reader.end_time(), None);
+    assert_eq!(reader.by_ref().count(), workflow.events.len() * 100 - 1);
+    assert_eq!(reader.end_time(), Some(2_000_000));
+}
+
+#[test]
+fn test_newer_format_version_is_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("future.json");
+    std::fs::write(
+        &path,
+        r#"{"format_version": 99, "name": "Future", "start_time": 0, "events": []}"#,
+    )
+    .unwrap();
+
+    let error = read_recording(&path).unwrap_err().to_string();
+    assert!(error.contains("format_version 99"), "{error}");
+}
+
+#[test]
+fn test_load_from_file_keeps_elements_no_longer_on_screen() {
+    let mut document: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(fixture_path("noisy_form.json")).unwrap()).unwrap();
+    document["events"][1]["event"]["Click"]["metadata"]["ui_element"] =
+        serde_json::json!({ "role": "Edit", "name": "Name", "bounds": [10.0, 20.0, 200.0, 24.0] });
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("recording.json");
+    std::fs::write(&path, document.to_string()).unwrap();
+
+    let loaded = serde_json::to_value(RecordedWorkflow::load_from_file(&path).unwrap()).unwrap();
+    assert_eq!(loaded["format_version"], 1);
+    assert_eq!(
+        loaded["events"][1]["event"]["Click"]["metadata"]["ui_element"]["name"],
+        "Name"
+    );
+}
@@ -430,6 +430,11 @@ pub trait UIElementImpl: Send + Sync + Debug {
     // New method for keyboard focusable
     fn is_keyboard_focusable(&self) -> Result<bool, AutomationError>;
 
+    /// Whether the element is a password field whose content should not be recorded.
+    fn is_password(&self) -> Result<bool, AutomationError> {
+        Ok(self.role().to_lowercase().contains("password"))
+    }
+
     // New method for mouse drag
     fn mouse_drag(
         &self,
@@ -896,6 +901,11 @@ impl UIElement {
         self.inner.is_keyboard_focusable()
     }
 
+    /// Check if element is a password field
+    pub fn is_password(&self) -> Result<bool, AutomationError> {
+        self.inner.is_password()
+    }
+
     /// Drag mouse from start to end coordinates
     pub fn mouse_drag(
         &self,
@@ -195,6 +195,25 @@ impl PlatformHealthCheck for LinuxHealthChecker {
         // TODO: Implement actual AT-SPI or X11 accessibility checks
         let mut result = HealthCheckResult::healthy("linux");
         result.add_diagnostic("note", "AT-SPI health checks not yet implemented");
+        result.add_diagnostic("display", std::env::var("DISPLAY").ok());
+        if let Some(headless) = crate::platforms::linux_headless::active_status() {
+            if !headless.is_running() {
+                let stopped: Vec<_> = headless
+                    .processes
+                    .iter()
+                    .filter(|p| !p.running)
+                    .map(|p| p.name.as_str())
+                    .collect();
+                result.desktop_accessible = false;
+                result.can_enumerate_elements = false;
+                result.error_message = Some(format!(
+                    "Headless session processes exited: {}",
+                    stopped.join(", ")
+                ));
+                result.update_status();
+            }
+            result.add_diagnostic("headless", headless);
+        }
         result
     }
 }
@@ -43,6 +43,11 @@ pub enum Browser {
 #[cfg(target_os = "windows")]
 pub use platforms::windows::{convert_uiautomation_element_to_terminator, get_process_name_by_pid};
 
+#[cfg(target_os = "linux")]
+pub use platforms::linux::{convert_atspi_object_to_terminator, element_at_point};
+#[cfg(target_os = "linux")]
+pub use platforms::linux_headless::{HeadlessConfig, HeadlessSession, HeadlessStatus};
+
 // Define a new struct to hold click result information - move to module level
 pub struct ClickResult {
     pub method: String,
@@ -125,7 +130,7 @@ pub struct UINode {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub id: Option<String>,
     pub attributes: UIElementAttributes,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub children: Vec<UINode>,
 }
 
@@ -222,13 +227,59 @@ pub struct ScreenshotResult {
 /// The main entry point for UI automation
 pub struct Desktop {
     engine: Arc<dyn platforms::AccessibilityEngine>,
+    /// Keeps the headless display alive for desktops made with `new_headless`
+    #[cfg(target_os = "linux")]
+    headless: Option<Arc<HeadlessSession>>,
 }
 
 impl Desktop {
     #[instrument(skip(use_background_apps, activate_app))]
     pub fn new(use_background_apps: bool, activate_app: bool) -> Result<Self, AutomationError> {
         let engine = platforms::create_engine(use_background_apps, activate_app)?;
-        Ok(Self { engine })
+        Ok(Self {
+            engine,
+            #[cfg(target_os = "linux")]
+            headless: None,
+        })
+    }
+
+    /// Start a headless desktop (Xvfb, a private session bus and AT-SPI) and connect to it
+    ///
+    /// Linux only. The display and its processes are stopped when the last clone of this
+    /// `Desktop` is dropped. Apps started afterwards, through this desktop or as child
+    /// processes, open on the headless display. Call this before creating any other
+    /// `Desktop`, since the accessibility connection is shared by the whole process,
+    /// and before starting other threads, since it sets environment variables.
+    pub fn new_headless() -> Result<Self, AutomationError> {
+        #[cfg(target_os = "linux")]
+        {
+            let session = HeadlessSession::start(HeadlessConfig::default())?;
+            let mut desktop = Self::new(false, false)?;
+            desktop.headless = Some(session);
+            Ok(desktop)
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            Err(AutomationError::UnsupportedPlatform(
+                "headless desktops are only supported on Linux".to_string(),
+            ))
+        }
+    }
+
+    /// Create a desktop backed by a custom engine, e.g. a
+    /// [`FixtureEngine`](platforms::fixture::FixtureEngine) replaying recorded UI trees
+    pub fn with_engine(engine: Arc<dyn platforms::AccessibilityEngine>) -> Self {
+        Self {
+            engine,
+            #[cfg(target_os = "linux")]
+            headless: None,
+        }
+    }
+
+    /// The headless session this desktop runs on, if it was made with `new_headless`
+    #[cfg(target_os = "linux")]
+    pub fn headless_session(&self) -> Option<&HeadlessSession> {
+        self.headless.as_deref()
     }
 
     /// Initializet the desktop without arguments
@@ -775,6 +826,8 @@ impl Clone for Desktop {
     fn clone(&self) -> Self {
         Self {
             engine: self.engine.clone(),
+            #[cfg(target_os = "linux")]
+            headless: self.headless.clone(),
         }
     }
 }
@@ -0,0 +1,1187 @@
+//! An engine that answers from recorded UI trees instead of a live desktop.
+//!
+//! Each [`FixtureWindow`] is a snapshot of one window (usually taken with
+//! [`FixtureWindow::capture`] while a workflow runs). Loaded into a [`FixtureEngine`],
+//! selectors resolve against the snapshot and actions such as clicks or typing are
+//! appended to an action log instead of being performed. Typed values, toggles and
+//! selections are applied to the snapshot so later reads see them.
+
+use crate::element::UIElementImpl;
+use crate::platforms::{AccessibilityEngine, PropertyLoadingMode, TreeBuildConfig};
+use crate::{
+    AutomationError, Browser, ClickResult, CommandOutput, Desktop, HighlightHandle, Locator,
+    Monitor, ScreenshotResult, Selector, UIElement, UIElementAttributes, UINode,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+/// Depth searched by selectors when the caller does not give one, as on Windows
+const DEFAULT_SEARCH_DEPTH: usize = 50;
+
+/// A recorded window: its UI tree plus the process it belongs to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FixtureWindow {
+    pub pid: u32,
+    #[serde(default)]
+    pub application_name: String,
+    #[serde(default)]
+    pub window_title: String,
+    pub tree: UINode,
+}
+
+impl FixtureWindow {
+    /// Snapshot the window that currently has keyboard focus, with complete attributes
+    pub fn capture(desktop: &Desktop) -> Result<Self, AutomationError> {
+        let focused = desktop.focused_element()?;
+        let pid = focused.process_id()?;
+        let window_title = focused.window_title();
+        let config = TreeBuildConfig {
+            property_mode: PropertyLoadingMode::Complete,
+            timeout_per_operation_ms: Some(100),
+            yield_every_n_elements: Some(50),
+            batch_size: Some(50),
+            max_depth: None,
+        };
+        let title = (!window_title.is_empty()).then_some(window_title.as_str());
+        let mut tree = desktop.get_window_tree(pid, title, Some(config))?;
+        strip_password_values(&mut tree);
+        Ok(Self {
+            pid,
+            application_name: focused.application_name(),
+            window_title,
+            tree,
+        })
+    }
+}
+
+/// Drop what was typed into password fields, so a recording doesn't keep it
+fn strip_password_values(node: &mut UINode) {
+    let attributes = &mut node.attributes;
+    let is_password = attributes.role.to_lowercase().contains("password")
+        || matches!(
+            attributes.properties.get("IsPassword"),
+            Some(Some(serde_json::Value::Bool(true)))
+        );
+    if is_password {
+        attributes.value = None;
+        attributes.text = None;
+    }
+    node.children.iter_mut().for_each(strip_password_values);
+}
+
+/// An action a workflow performed against a fixture
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FixtureAction {
+    pub action: String,
+    /// The element acted on, as `role|name` (or `#id` when it has no name); none for
+    /// desktop-wide actions such as a global key press
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
+
+/// The loaded snapshot. Elements remember the generation they were found in, so an
+/// element from an earlier snapshot never resolves against a later one.
+struct Snapshot {
+    root: UINode,
+    pid: Option<u32>,
+    generation: u64,
+}
+
+struct FixtureState {
+    snapshot: RwLock<Snapshot>,
+    actions: Mutex<Vec<FixtureAction>>,
+}
+
+impl FixtureState {
+    fn record(&self, action: &str, target: Option<String>, value: Option<String>) {
+        self.actions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(FixtureAction {
+                action: action.to_string(),
+                target,
+                value,
+            });
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, Snapshot> {
+        self.snapshot.read().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Accessibility engine backed by a [`FixtureWindow`]; see the module docs
+#[derive(Clone)]
+pub struct FixtureEngine {
+    state: Arc<FixtureState>,
+}
+
+impl Default for FixtureEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FixtureEngine {
+    /// An engine with an empty desktop; call [`load`](Self::load) to give it a window
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(FixtureState {
+                snapshot: RwLock::new(Snapshot {
+                    root: desktop_node(None),
+                    pid: None,
+                    generation: 0,
+                }),
+                actions: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// Replace the desktop with `window` (or an empty desktop), discarding any values
+    /// set on the previous snapshot
+    pub fn load(&self, window: Option<FixtureWindow>) {
+        let mut snapshot = self
+            .state
+            .snapshot
+            .write()
+            .unwrap_or_else(|e| e.into_inner());
+        snapshot.pid = window.as_ref().map(|w| w.pid);
+        snapshot.root = desktop_node(window);
+        snapshot.generation += 1;
+    }
+
+    /// Actions performed since the last call, in order
+    pub fn take_actions(&self) -> Vec<FixtureAction> {
+        std::mem::take(&mut *self.state.actions.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    fn element(&self, path: Vec<usize>) -> UIElement {
+        let generation = self.state.read().generation;
+        UIElement::new(Box::new(FixtureElement {
+            state: self.state.clone(),
+            path,
+            generation,
+        }))
+    }
+
+    /// The search root as a path, or an error if `root` is not a current fixture element
+    fn root_path(&self, root: Option<&UIElement>) -> Result<Vec<usize>, AutomationError> {
+        let Some(root) = root else {
+            return Ok(Vec::new());
+        };
+        let element = root
+            .as_any()
+            .downcast_ref::<FixtureElement>()
+            .ok_or_else(|| {
+                AutomationError::InvalidArgument("search root is not a fixture element".to_string())
+            })?;
+        if element.generation != self.state.read().generation {
+            return Err(stale_element());
+        }
+        Ok(element.path.clone())
+    }
+
+    fn window_path(&self) -> Result<Vec<usize>, AutomationError> {
+        if self.state.read().pid.is_some() {
+            Ok(vec![0, 0])
+        } else {
+            Err(AutomationError::ElementNotFound(
+                "no window is loaded in the fixture".to_string(),
+            ))
+        }
+    }
+
+    fn search(
+        &self,
+        selector: &Selector,
+        root: &[usize],
+        depth: usize,
+    ) -> Result<Vec<Vec<usize>>, AutomationError> {
+        let snapshot = self.state.read();
+        let Some(root_node) = node_at(&snapshot.root, root) else {
+            return Err(stale_element());
+        };
+        match selector {
+            Selector::Chain(selectors) => {
+                let Some((first, rest)) = selectors.split_first() else {
+                    return Err(AutomationError::InvalidArgument(
+                        "Selector chain cannot be empty".to_string(),
+                    ));
+                };
+                drop(snapshot);
+                let mut current = self.search(first, root, depth)?;
+                for selector in rest {
+                    if let Selector::Nth(index) = selector {
+                        let len = current.len() as i32;
+                        let index = if *index < 0 { index + len } else { *index };
+                        current = if (0..len).contains(&index) {
+                            vec![current.swap_remove(index as usize)]
+                        } else {
+                            Vec::new()
+                        };
+                    } else {
+                        let mut next = Vec::new();
+                        for path in &current {
+                            next.extend(self.search(selector, path, DEFAULT_SEARCH_DEPTH)?);
+                        }
+                        current = next;
+                    }
+                }
+                Ok(current)
+            }
+            Selector::Parent => Ok(if root.is_empty() {
+                Vec::new()
+            } else {
+                vec![root[..root.len() - 1].to_vec()]
+            }),
+            Selector::Has(inner) => {
+                let candidates = descendants(root_node, root, depth);
+                drop(snapshot);
+                let mut matches = Vec::new();
+                for path in candidates {
+                    if !self.search(inner, &path, depth)?.is_empty() {
+                        matches.push(path);
+                    }
+                }
+                Ok(matches)
+            }
+            Selector::RightOf(inner)
+            | Selector::LeftOf(inner)
+            | Selector::Above(inner)
+            | Selector::Below(inner)
+            | Selector::Near(inner) => {
+                let candidates = descendants(root_node, root, depth);
+                drop(snapshot);
+                let anchor = self.search(inner, root, depth)?.into_iter().next();
+                let snapshot = self.state.read();
+                let anchor_bounds = anchor
+                    .as_deref()
+                    .and_then(|path| node_at(&snapshot.root, path))
+                    .and_then(|node| node.attributes.bounds)
+                    .ok_or_else(|| {
+                        AutomationError::ElementNotFound(format!(
+                            "No anchor with bounds for layout selector: {selector:?}"
+                        ))
+                    })?;
+                Ok(candidates
+                    .into_iter()
+                    .filter(|path| Some(path) != anchor.as_ref())
+                    .filter(|path| {
+                        node_at(&snapshot.root, path)
+                            .and_then(|node| node.attributes.bounds)
+                            .is_some_and(|bounds| in_layout(selector, anchor_bounds, bounds))
+                    })
+                    .collect())
+            }
+            Selector::Nth(_) => Err(AutomationError::InvalidSelector(
+                "Nth selector must be used as part of a chain (e.g. 'list >> nth=0')".to_string(),
+            )),
+            Selector::Filter(_) => Err(AutomationError::UnsupportedOperation(
+                "`Filter` selector not supported".to_string(),
+            )),
+            Selector::Invalid(reason) => Err(AutomationError::InvalidSelector(reason.clone())),
+            _ => Ok(descendants(root_node, root, depth)
+                .into_iter()
+                .filter(|path| {
+                    node_at(&snapshot.root, path).is_some_and(|node| matches(node, selector))
+                })
+                .collect()),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl AccessibilityEngine for FixtureEngine {
+    fn get_root_element(&self) -> UIElement {
+        self.element(Vec::new())
+    }
+
+    fn get_element_by_id(&self, id: i32) -> Result<UIElement, AutomationError> {
+        let selector = Selector::Id(id.to_string());
+        self.find_element(&selector, None, None)
+    }
+
+    fn get_focused_element(&self) -> Result<UIElement, AutomationError> {
+        let focused = {
+            let snapshot = self.state.read();
+            descendants(&snapshot.root, &[], usize::MAX)
+                .into_iter()
+                .find(|path| {
+                    node_at(&snapshot.root, path)
+                        .is_some_and(|node| node.attributes.is_focused == Some(true))
+                })
+        };
+        match focused {
+            Some(path) => Ok(self.element(path)),
+            None => self.window_path().map(|path| self.element(path)),
+        }
+    }
+
+    fn get_applications(&self) -> Result<Vec<UIElement>, AutomationError> {
+        let count = self.state.read().root.children.len();
+        Ok((0..count).map(|i| self.element(vec![i])).collect())
+    }
+
+    fn get_application_by_name(&self, name: &str) -> Result<UIElement, AutomationError> {
+        let selector = Selector::Role {
+            role: "Application".to_string(),
+            name: Some(name.to_string()),
+        };
+        self.find_element(&selector, None, None)
+    }
+
+    fn get_application_by_pid(
+        &self,
+        pid: i32,
+        _timeout: Option<Duration>,
+    ) -> Result<UIElement, AutomationError> {
+        if self.state.read().pid == Some(pid as u32) {
+            Ok(self.element(vec![0]))
+        } else {
+            Err(AutomationError::ElementNotFound(format!(
+                "No application with PID {pid} in the fixture"
+            )))
+        }
+    }
+
+    fn find_element(
+        &self,
+        selector: &Selector,
+        root: Option<&UIElement>,
+        _timeout: Option<Duration>,
+    ) -> Result<UIElement, AutomationError> {
+        let root = self.root_path(root)?;
+        self.search(selector, &root, DEFAULT_SEARCH_DEPTH)?
+            .into_iter()
+            .next()
+            .map(|path| self.element(path))
+            .ok_or_else(|| {
+                AutomationError::ElementNotFound(format!("{selector:?} not found in the fixture"))
+            })
+    }
+
+    fn find_elements(
+        &self,
+        selector: &Selector,
+        root: Option<&UIElement>,
+        _timeout: Option<Duration>,
+        depth: Option<usize>,
+    ) -> Result<Vec<UIElement>, AutomationError> {
+        let root = self.root_path(root)?;
+        Ok(self
+            .search(selector, &root, depth.unwrap_or(DEFAULT_SEARCH_DEPTH))?
+            .into_iter()
+            .map(|path| self.element(path))
+            .collect())
+    }
+
+    fn open_application(&self, app_name: &str) -> Result<UIElement, AutomationError> {
+        self.state
+            .record("open_application", None, Some(app_name.to_string()));
+        self.window_path()
+            .map(|path| self.element(path))
+            .or_else(|_| Ok(self.get_root_element()))
+    }
+
+    fn activate_application(&self, app_name: &str) -> Result<(), AutomationError> {
+        self.state
+            .record("activate_application", None, Some(app_name.to_string()));
+        Ok(())
+    }
+
+    fn open_url(&self, url: &str, browser: Option<Browser>) -> Result<UIElement, AutomationError> {
+        let value = match browser {
+            Some(browser) => format!("{url} ({browser:?})"),
+            None => url.to_string(),
+        };
+        self.state.record("open_url", None, Some(value));
+        self.window_path()
+            .map(|path| self.element(path))
+            .or_else(|_| Ok(self.get_root_element()))
+    }
+
+    fn open_file(&self, file_path: &str) -> Result<(), AutomationError> {
+        self.state
+            .record("open_file", None, Some(file_path.to_string()));
+        Ok(())
+    }
+
+    async fn run_command(
+        &self,
+        windows_command: Option<&str>,
+        unix_command: Option<&str>,
+    ) -> Result<CommandOutput, AutomationError> {
+        let command = windows_command.or(unix_command).map(str::to_string);
+        self.state.record("run_command", None, command);
+        Ok(CommandOutput {
+            exit_status: Some(0),
+            stdout: String::new(),
+            stderr: String::new(),
+        })
+    }
+
+    async fn list_monitors(&self) -> Result<Vec<Monitor>, AutomationError> {
+        Ok(vec![fixture_monitor()])
+    }
+
+    async fn get_primary_monitor(&self) -> Result<Monitor, AutomationError> {
+        Ok(fixture_monitor())
+    }
+
+    async fn get_active_monitor(&self) -> Result<Monitor, AutomationError> {
+        Ok(fixture_monitor())
+    }
+
+    async fn get_monitor_by_id(&self, id: &str) -> Result<Monitor, AutomationError> {
+        let monitor = fixture_monitor();
+        if monitor.id == id {
+            Ok(monitor)
+        } else {
+            Err(AutomationError::ElementNotFound(format!(
+                "Monitor with ID '{id}' not found"
+            )))
+        }
+    }
+
+    async fn get_monitor_by_name(&self, name: &str) -> Result<Monitor, AutomationError> {
+        let monitor = fixture_monitor();
+        if monitor.name == name {
+            Ok(monitor)
+        } else {
+            Err(AutomationError::ElementNotFound(format!(
+                "Monitor with name '{name}' not found"
+            )))
+        }
+    }
+
+    async fn capture_monitor_by_id(&self, _id: &str) -> Result<ScreenshotResult, AutomationError> {
+        Err(no_pixels())
+    }
+
+    async fn ocr_image_path(&self, _image_path: &str) -> Result<String, AutomationError> {
+        Err(no_pixels())
+    }
+
+    async fn ocr_screenshot(
+        &self,
+        _screenshot: &ScreenshotResult,
+    ) -> Result<String, AutomationError> {
+        Err(no_pixels())
+    }
+
+    fn activate_browser_window_by_title(&self, title: &str) -> Result<(), AutomationError> {
+        self.state
+            .record("activate_browser_window", None, Some(title.to_string()));
+        Ok(())
+    }
+
+    async fn get_current_browser_window(&self) -> Result<UIElement, AutomationError> {
+        self.window_path().map(|path| self.element(path))
+    }
+
+    async fn get_current_window(&self) -> Result<UIElement, AutomationError> {
+        self.window_path().map(|path| self.element(path))
+    }
+
+    async fn get_current_application(&self) -> Result<UIElement, AutomationError> {
+        self.window_path().map(|_| self.element(vec![0]))
+    }
+
+    fn press_key(&self, key: &str) -> Result<(), AutomationError> {
+        self.state.record("press_key", None, Some(key.to_string()));
+        Ok(())
+    }
+
+    fn set_zoom(&self, percentage: u32) -> Result<(), AutomationError> {
+        self.state
+            .record("set_zoom", None, Some(percentage.to_string()));
+        Ok(())
+    }
+
+    fn get_window_tree(
+        &self,
+        pid: u32,
+        _title: Option<&str>,
+        config: TreeBuildConfig,
+    ) -> Result<UINode, AutomationError> {
+        let snapshot = self.state.read();
+        if snapshot.pid != Some(pid) {
+            return Err(AutomationError::ElementNotFound(format!(
+                "No window for PID {pid} in the fixture"
+            )));
+        }
+        let mut tree = node_at(&snapshot.root, &[0, 0])
+            .cloned()
+            .unwrap_or_default();
+        if let Some(max_depth) = config.max_depth {
+            truncate(&mut tree, max_depth);
+        }
+        Ok(tree)
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+/// An element of the loaded snapshot, addressed by child indices from the desktop root
+#[derive(Clone)]
+struct FixtureElement {
+    state: Arc<FixtureState>,
+    path: Vec<usize>,
+    generation: u64,
+}
+
+impl std::fmt::Debug for FixtureElement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FixtureElement")
+            .field("path", &self.path)
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
+impl FixtureElement {
+    fn with_node<R>(&self, f: impl FnOnce(&UINode) -> R) -> Result<R, AutomationError> {
+        let snapshot = self.state.read();
+        if snapshot.generation != self.generation {
+            return Err(stale_element());
+        }
+        node_at(&snapshot.root, &self.path)
+            .map(f)
+            .ok_or_else(stale_element)
+    }
+
+    fn update(&self, f: impl FnOnce(&mut UIElementAttributes)) -> Result<(), AutomationError> {
+        let mut snapshot = self
+            .state
+            .snapshot
+            .write()
+            .unwrap_or_else(|e| e.into_inner());
+        if snapshot.generation != self.generation {
+            return Err(stale_element());
+        }
+        let mut node = &mut snapshot.root;
+        for &index in &self.path {
+            node = node.children.get_mut(index).ok_or_else(stale_element)?;
+        }
+        f(&mut node.attributes);
+        Ok(())
+    }
+
+    fn related(&self, path: Vec<usize>) -> UIElement {
+        UIElement::new(Box::new(Self {
+            state: self.state.clone(),
+            path,
+            generation: self.generation,
+        }))
+    }
+
+    fn record(&self, action: &str, value: Option<String>) -> Result<(), AutomationError> {
+        let target = self.with_node(describe)?;
+        self.state.record(action, Some(target), value);
+        Ok(())
+    }
+}
+
+impl UIElementImpl for FixtureElement {
+    fn object_id(&self) -> usize {
+        let mut hasher = DefaultHasher::new();
+        (self.generation, &self.path).hash(&mut hasher);
+        hasher.finish() as usize
+    }
+
+    fn id(&self) -> Option<String> {
+        self.with_node(|node| node.id.clone()).ok().flatten()
+    }
+
+    fn role(&self) -> String {
+        self.with_node(|node| node.attributes.role.clone())
+            .unwrap_or_default()
+    }
+
+    fn attributes(&self) -> UIElementAttributes {
+        self.with_node(|node| node.attributes.clone())
+            .unwrap_or_default()
+    }
+
+    fn children(&self) -> Result<Vec<UIElement>, AutomationError> {
+        let count = self.with_node(|node| node.children.len())?;
+        Ok((0..count)
+            .map(|i| self.related([self.path.as_slice(), &[i]].concat()))
+            .collect())
+    }
+
+    fn parent(&self) -> Result<Option<UIElement>, AutomationError> {
+        Ok(self
+            .path
+            .split_last()
+            .map(|(_, parent)| self.related(parent.to_vec())))
+    }
+
+    fn bounds(&self) -> Result<(f64, f64, f64, f64), AutomationError> {
+        self.with_node(|node| node.attributes.bounds)?
+            .ok_or_else(|| {
+                AutomationError::PlatformError("no bounds were recorded for this element".into())
+            })
+    }
+
+    fn click(&self) -> Result<ClickResult, AutomationError> {
+        self.record("click", None)?;
+        Ok(recorded_click())
+    }
+
+    fn double_click(&self) -> Result<ClickResult, AutomationError> {
+        self.record("double_click", None)?;
+        Ok(recorded_click())
+    }
+
+    fn right_click(&self) -> Result<(), AutomationError> {
+        self.record("right_click", None)
+    }
+
+    fn hover(&self) -> Result<(), AutomationError> {
+        self.record("hover", None)
+    }
+
+    fn focus(&self) -> Result<(), AutomationError> {
+        self.record("focus", None)
+    }
+
+    fn invoke(&self) -> Result<(), AutomationError> {
+        self.record("invoke", None)
+    }
+
+    fn type_text(&self, text: &str, _use_clipboard: bool) -> Result<(), AutomationError> {
+        self.record("type_text", Some(text.to_string()))?;
+        self.update(|attributes| {
+            let mut value = attributes.value.take().unwrap_or_default();
+            value.push_str(text);
+            attributes.value = Some(value);
+        })
+    }
+
+    fn press_key(&self, key: &str) -> Result<(), AutomationError> {
+        self.record("press_key", Some(key.to_string()))
+    }
+
+    fn get_text(&self, max_depth: usize) -> Result<String, AutomationError> {
+        fn collect(node: &UINode, depth: usize, max_depth: usize, out: &mut Vec<String>) {
+            let attributes = &node.attributes;
+            if let Some(text) = [&attributes.value, &attributes.text, &attributes.name]
+                .into_iter()
+                .flatten()
+                .find(|text| !text.is_empty())
+            {
+                out.push(text.clone());
+            }
+            if depth < max_depth {
+                for child in &node.children {
+                    collect(child, depth + 1, max_depth, out);
+                }
+            }
+        }
+        self.with_node(|node| {
+            let mut texts = Vec::new();
+            collect(node, 0, max_depth, &mut texts);
+            texts.join("\n")
+        })
+    }
+
+    fn set_value(&self, value: &str) -> Result<(), AutomationError> {
+        self.record("set_value", Some(value.to_string()))?;
+        self.update(|attributes| attributes.value = Some(value.to_string()))
+    }
+
+    fn is_enabled(&self) -> Result<bool, AutomationError> {
+        self.with_node(|node| node.attributes.enabled.unwrap_or(true))
+    }
+
+    fn is_visible(&self) -> Result<bool, AutomationError> {
+        self.with_node(is_visible)
+    }
+
+    fn is_focused(&self) -> Result<bool, AutomationError> {
+        self.with_node(|node| node.attributes.is_focused == Some(true))
+    }
+
+    fn perform_action(&self, action: &str) -> Result<(), AutomationError> {
+        self.record(action, None)
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn create_locator(&self, selector: Selector) -> Result<Locator, AutomationError> {
+        let engine = FixtureEngine {
+            state: self.state.clone(),
+        };
+        Ok(Locator::new(Arc::new(engine), selector).within(UIElement::new(self.clone_box())))
+    }
+
+    fn scroll(&self, direction: &str, amount: f64) -> Result<(), AutomationError> {
+        self.record("scroll", Some(format!("{direction} {amount}")))
+    }
+
+    fn activate_window(&self) -> Result<(), AutomationError> {
+        self.record("activate_window", None)
+    }
+
+    fn minimize_window(&self) -> Result<(), AutomationError> {
+        self.record("minimize_window", None)
+    }
+
+    fn maximize_window(&self) -> Result<(), AutomationError> {
+        self.record("maximize_window", None)
+    }
+
+    fn clone_box(&self) -> Box<dyn UIElementImpl> {
+        Box::new(self.clone())
+    }
+
+    fn is_keyboard_focusable(&self) -> Result<bool, AutomationError> {
+        self.with_node(|node| node.attributes.is_keyboard_focusable == Some(true))
+    }
+
+    fn mouse_drag(
+        &self,
+        start_x: f64,
+        start_y: f64,
+        end_x: f64,
+        end_y: f64,
+    ) -> Result<(), AutomationError> {
+        self.record(
+            "mouse_drag",
+            Some(format!("{start_x},{start_y} -> {end_x},{end_y}")),
+        )
+    }
+
+    fn mouse_click_and_hold(&self, x: f64, y: f64) -> Result<(), AutomationError> {
+        self.record("mouse_click_and_hold", Some(format!("{x},{y}")))
+    }
+
+    fn mouse_move(&self, x: f64, y: f64) -> Result<(), AutomationError> {
+        self.record("mouse_move", Some(format!("{x},{y}")))
+    }
+
+    fn mouse_release(&self) -> Result<(), AutomationError> {
+        self.record("mouse_release", None)
+    }
+
+    fn application(&self) -> Result<Option<UIElement>, AutomationError> {
+        Ok((!self.path.is_empty()).then(|| self.related(vec![self.path[0]])))
+    }
+
+    fn window(&self) -> Result<Option<UIElement>, AutomationError> {
+        Ok((self.path.len() >= 2).then(|| self.related(self.path[..2].to_vec())))
+    }
+
+    fn highlight(
+        &self,
+        _color: Option<u32>,
+        _duration: Option<Duration>,
+        _text: Option<&str>,
+        _text_position: Option<crate::TextPosition>,
+        _font_style: Option<crate::FontStyle>,
+    ) -> Result<HighlightHandle, AutomationError> {
+        Ok(HighlightHandle {
+            should_close: Arc::new(AtomicBool::new(false)),
+            handle: None,
+        })
+    }
+
+    fn set_transparency(&self, percentage: u8) -> Result<(), AutomationError> {
+        self.record("set_transparency", Some(percentage.to_string()))
+    }
+
+    fn process_id(&self) -> Result<u32, AutomationError> {
+        match self.state.read().pid {
+            Some(pid) if !self.path.is_empty() => Ok(pid),
+            _ => Err(AutomationError::ElementNotFound(
+                "the desktop root has no process".to_string(),
+            )),
+        }
+    }
+
+    fn capture(&self) -> Result<ScreenshotResult, AutomationError> {
+        Err(no_pixels())
+    }
+
+    fn close(&self) -> Result<(), AutomationError> {
+        self.record("close", None)
+    }
+
+    fn url(&self) -> Option<String> {
+        self.with_node(|node| property(node, "url")).ok().flatten()
+    }
+
+    fn select_option(&self, option_name: &str) -> Result<(), AutomationError> {
+        self.record("select_option", Some(option_name.to_string()))?;
+        self.update(|attributes| attributes.value = Some(option_name.to_string()))
+    }
+
+    fn list_options(&self) -> Result<Vec<String>, AutomationError> {
+        self.with_node(|node| {
+            node.children
+                .iter()
+                .filter_map(|child| child.attributes.name.clone())
+                .filter(|name| !name.is_empty())
+                .collect()
+        })
+    }
+
+    fn is_toggled(&self) -> Result<bool, AutomationError> {
+        self.with_node(|node| node.attributes.is_toggled == Some(true))
+    }
+
+    fn set_toggled(&self, state: bool) -> Result<(), AutomationError> {
+        self.record("set_toggled", Some(state.to_string()))?;
+        self.update(|attributes| attributes.is_toggled = Some(state))
+    }
+
+    fn get_range_value(&self) -> Result<f64, AutomationError> {
+        self.with_node(|node| node.attributes.value.clone())?
+            .and_then(|value| value.trim().parse().ok())
+            .ok_or_else(|| {
+                AutomationError::UnsupportedOperation(
+                    "element has no numeric value in the fixture".to_string(),
+                )
+            })
+    }
+
+    fn set_range_value(&self, value: f64) -> Result<(), AutomationError> {
+        self.record("set_range_value", Some(value.to_string()))?;
+        self.update(|attributes| attributes.value = Some(value.to_string()))
+    }
+
+    fn is_selected(&self) -> Result<bool, AutomationError> {
+        self.with_node(|node| node.attributes.is_selected == Some(true))
+    }
+
+    fn set_selected(&self, state: bool) -> Result<(), AutomationError> {
+        self.record("set_selected", Some(state.to_string()))?;
+        self.update(|attributes| attributes.is_selected = Some(state))
+    }
+
+    fn monitor(&self) -> Result<Monitor, AutomationError> {
+        Ok(fixture_monitor())
+    }
+}
+
+fn desktop_node(window: Option<FixtureWindow>) -> UINode {
+    let children = window
+        .map(|window| {
+            vec![UINode {
+                id: None,
+                attributes: UIElementAttributes {
+                    role: "Application".to_string(),
+                    name: Some(window.application_name),
+                    ..Default::default()
+                },
+                children: vec![window.tree],
+            }]
+        })
+        .unwrap_or_default();
+    UINode {
+        id: None,
+        attributes: UIElementAttributes {
+            role: "Desktop".to_string(),
+            name: Some("Fixture desktop".to_string()),
+            ..Default::default()
+        },
+        children,
+    }
+}
+
+fn fixture_monitor() -> Monitor {
+    Monitor {
+        id: "fixture".to_string(),
+        name: "Fixture display".to_string(),
+        is_primary: true,
+        width: 1920,
+        height: 1080,
+        x: 0,
+        y: 0,
+        scale_factor: 1.0,
+        work_area: None,
+    }
+}
+
+fn recorded_click() -> ClickResult {
+    ClickResult {
+        method: "Fixture".to_string(),
+        coordinates: None,
+        details: "Recorded, not performed".to_string(),
+    }
+}
+
+fn stale_element() -> AutomationError {
+    AutomationError::ElementNotFound("element belongs to an earlier fixture".to_string())
+}
+
+fn no_pixels() -> AutomationError {
+    AutomationError::UnsupportedOperation("fixtures do not record screen pixels".to_string())
+}
+
+fn node_at<'a>(root: &'a UINode, path: &[usize]) -> Option<&'a UINode> {
+    path.iter()
+        .try_fold(root, |node, &index| node.children.get(index))
+}
+
+/// Paths of the descendants of `root` (at `root_path`) up to `depth` levels down,
+/// depth-first in document order
+fn descendants(root: &UINode, root_path: &[usize], depth: usize) -> Vec<Vec<usize>> {
+    fn walk(node: &UINode, path: &mut Vec<usize>, depth: usize, out: &mut Vec<Vec<usize>>) {
+        if depth == 0 {
+            return;
+        }
+        for (index, child) in node.children.iter().enumerate() {
+            path.push(index);
+            out.push(path.clone());
+            walk(child, path, depth - 1, out);
+            path.pop();
+        }
+    }
+    let mut out = Vec::new();
+    walk(root, &mut root_path.to_vec(), depth, &mut out);
+    out
+}
+
+fn truncate(node: &mut UINode, depth: usize) {
+    if depth == 0 {
+        node.children.clear();
+    }
+    for child in &mut node.children {
+        truncate(child, depth.saturating_sub(1));
+    }
+}
+
+fn describe(node: &UINode) -> String {
+    let role = &node.attributes.role;
+    match (&node.attributes.name, &node.id) {
+        (Some(name), _) if !name.is_empty() => format!("{role}|{name}"),
+        (_, Some(id)) => format!("#{id}"),
+        _ => role.clone(),
+    }
+}
+
+fn contains_ci(haystack: &Option<String>, needle: &str) -> bool {
+    haystack
+        .as_deref()
+        .is_some_and(|h| h.to_lowercase().contains(&needle.to_lowercase()))
+}
+
+fn property(node: &UINode, key: &str) -> Option<String> {
+    node.attributes
+        .properties
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(key))
+        .and_then(|(_, value)| value.as_ref())
+        .map(|value| match value {
+            serde_json::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        })
+}
+
+fn attribute(node: &UINode, key: &str) -> Option<String> {
+    let attributes = &node.attributes;
+    match key.to_lowercase().as_str() {
+        "role" => Some(attributes.role.clone()),
+        "name" => attributes.name.clone(),
+        "label" => attributes.label.clone(),
+        "text" => attributes.text.clone(),
+        "value" => attributes.value.clone(),
+        "description" => attributes.description.clone(),
+        _ => property(node, key),
+    }
+}
+
+/// Elements count as visible unless they were recorded with an empty bounding box
+fn is_visible(node: &UINode) -> bool {
+    node.attributes
+        .bounds
+        .is_none_or(|(_, _, width, height)| width > 0.0 && height > 0.0)
+}
+
+fn matches(node: &UINode, selector: &Selector) -> bool {
+    let attributes = &node.attributes;
+    match selector {
+        Selector::Role { role, name } => {
+            attributes.role.eq_ignore_ascii_case(role)
+                && name
+                    .as_deref()
+                    .is_none_or(|n| contains_ci(&attributes.name, n))
+        }
+        Selector::Id(id) => {
+            let id = id.strip_prefix('#').unwrap_or(id);
+            node.id
+                .as_deref()
+                .is_some_and(|node_id| node_id.starts_with(id))
+        }
+        Selector::Name(name) => contains_ci(&attributes.name, name),
+        Selector::Text(text) => [&attributes.name, &attributes.text, &attributes.value]
+            .into_iter()
+            .any(|value| contains_ci(value, text)),
+        Selector::NativeId(automation_id) => {
+            property(node, "AutomationId").as_deref() == Some(automation_id.as_str())
+        }
+        Selector::ClassName(class_name) => {
+            property(node, "ClassName").as_deref() == Some(class_name.as_str())
+        }
+        Selector::Attributes(expected) => expected.iter().all(|(key, value)| {
+            attribute(node, key).is_some_and(|actual| actual.eq_ignore_ascii_case(value))
+        }),
+        Selector::Visible(visible) => is_visible(node) == *visible,
+        Selector::LocalizedRole(localized_role) => property(node, "LocalizedControlType")
+            .unwrap_or_else(|| attributes.role.clone())
+            .eq_ignore_ascii_case(localized_role),
+        _ => false,
+    }
+}
+
+fn in_layout(
+    selector: &Selector,
+    anchor: (f64, f64, f64, f64),
+    candidate: (f64, f64, f64, f64),
+) -> bool {
+    let (anchor_left, anchor_top) = (anchor.0, anchor.1);
+    let (anchor_right, anchor_bottom) = (anchor.0 + anchor.2, anchor.1 + anchor.3);
+    let (left, top) = (candidate.0, candidate.1);
+    let (right, bottom) = (candidate.0 + candidate.2, candidate.1 + candidate.3);
+    let vertical_overlap = top < anchor_bottom && bottom > anchor_top;
+    let horizontal_overlap = left < anchor_right && right > anchor_left;
+    match selector {
+        Selector::RightOf(_) => left >= anchor_right && vertical_overlap,
+        Selector::LeftOf(_) => right <= anchor_left && vertical_overlap,
+        Selector::Above(_) => bottom <= anchor_top && horizontal_overlap,
+        Selector::Below(_) => top >= anchor_bottom && horizontal_overlap,
+        Selector::Near(_) => {
+            const NEAR_THRESHOLD: f64 = 50.0;
+            let dx = (anchor_left + anchor.2 / 2.0) - (left + candidate.2 / 2.0);
+            let dy = (anchor_top + anchor.3 / 2.0) - (top + candidate.3 / 2.0);
+            (dx * dx + dy * dy).sqrt() < NEAR_THRESHOLD
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(role: &str, name: &str, children: Vec<UINode>) -> UINode {
+        UINode {
+            id: None,
+            attributes: UIElementAttributes {
+                role: role.to_string(),
+                name: (!name.is_empty()).then(|| name.to_string()),
+                ..Default::default()
+            },
+            children,
+        }
+    }
+
+    fn login_window() -> FixtureWindow {
+        FixtureWindow {
+            pid: 42,
+            application_name: "Login".to_string(),
+            window_title: "Sign in".to_string(),
+            tree: node(
+                "Window",
+                "Sign in",
+                vec![
+                    node(
+                        "Group",
+                        "Form",
+                        vec![
+                            node("Edit", "User name", vec![]),
+                            node("Button", "Sign in", vec![]),
+                        ],
+                    ),
+                    node("Button", "Cancel", vec![]),
+                ],
+            ),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_selectors_resolve_against_the_snapshot() {
+        let engine = Arc::new(FixtureEngine::new());
+        engine.load(Some(login_window()));
+        let desktop = Desktop::with_engine(engine.clone());
+
+        let button = desktop
+            .locator("role:Group|name:Form >> role:button")
+            .first(None)
+            .await
+            .unwrap();
+        assert_eq!(button.name().as_deref(), Some("Sign in"));
+        assert_eq!(button.process_id().unwrap(), 42);
+        assert_eq!(button.window_title(), "Sign in");
+
+        let buttons = desktop
+            .locator("role:Button")
+            .all(None, None)
+            .await
+            .unwrap();
+        assert_eq!(buttons.len(), 2);
+        let last = desktop
+            .locator("role:Button >> nth=-1")
+            .first(None)
+            .await
+            .unwrap();
+        assert_eq!(last.name().as_deref(), Some("Cancel"));
+        assert!(desktop
+            .locator("role:Button|name:Register")
+            .first(None)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_actions_are_recorded_not_performed() {
+        let engine = Arc::new(FixtureEngine::new());
+        engine.load(Some(login_window()));
+        let desktop = Desktop::with_engine(engine.clone());
+
+        let edit = desktop.locator("role:Edit").first(None).await.unwrap();
+        edit.set_value("").unwrap();
+        edit.type_text("alice", false).unwrap();
+        assert_eq!(edit.text(0).unwrap(), "alice");
+        let sign_in = desktop
+            .locator("name:Sign in >> role:Button")
+            .first(None)
+            .await
+            .unwrap();
+        sign_in.click().unwrap();
+        desktop.press_key("{Enter}").await.unwrap();
+
+        let actions = engine.take_actions();
+        let summary: Vec<_> = actions
+            .iter()
+            .map(|a| (a.action.as_str(), a.target.as_deref(), a.value.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("set_value", Some("Edit|User name"), Some("")),
+                ("type_text", Some("Edit|User name"), Some("alice")),
+                ("click", Some("Button|Sign in"), None),
+                ("press_key", None, Some("{Enter}")),
+            ]
+        );
+        assert!(engine.take_actions().is_empty());
+
+        // Loading the next snapshot drops typed values and invalidates old elements
+        engine.load(Some(login_window()));
+        assert!(edit.text(0).is_err());
+        let edit = desktop.locator("role:Edit").first(None).await.unwrap();
+        assert_eq!(edit.text(0).unwrap(), "User name");
+    }
+}
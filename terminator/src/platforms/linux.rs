@@ -10,7 +10,6 @@ use std::fmt::Debug;
 use std::future::Future;
 use std::hash::{Hash, Hasher};
 use std::pin::Pin;
-use std::process::Command;
 use std::sync::Arc;
 use std::sync::{mpsc, OnceLock};
 use std::thread;
@@ -1211,6 +1210,120 @@ impl LinuxEngine {
     }
 }
 
+const NULL_PATH: &str = "/org/a11y/atspi/accessible/null";
+
+static SHARED_ENGINE: OnceLock<LinuxEngine> = OnceLock::new();
+
+/// The engine behind [`convert_atspi_object_to_terminator`] and [`element_at_point`],
+/// connected once and kept for the lifetime of the process.
+fn shared_engine() -> Result<&'static LinuxEngine, AutomationError> {
+    if let Some(engine) = SHARED_ENGINE.get() {
+        return Ok(engine);
+    }
+    let engine = LinuxEngine::new(false, false)?;
+    Ok(SHARED_ENGINE.get_or_init(|| engine))
+}
+
+/// Wrap the AT-SPI object at `path` owned by bus name `destination` (e.g. the source of
+/// an AT-SPI event) as a [`UIElement`].
+pub fn convert_atspi_object_to_terminator(
+    destination: &str,
+    path: &str,
+) -> Result<UIElement, AutomationError> {
+    let engine = shared_engine()?;
+    Ok(UIElement::new(Box::new(LinuxUIElement {
+        connection: Arc::clone(&engine.connection),
+        destination: destination.to_string(),
+        path: path.to_string(),
+    })))
+}
+
+/// The deepest element under the screen point (`x`, `y`), looked up in the active window
+/// when it contains the point and in any other showing window otherwise.
+pub fn element_at_point(x: i32, y: i32) -> Result<UIElement, AutomationError> {
+    let engine = shared_engine()?.clone();
+    let (resp_tx, resp_rx) = mpsc::channel();
+    let req = Box::new(move || {
+        erase_future(async move {
+            let connection = engine.connection.as_ref();
+            let mut candidate: Option<(String, String)> = None;
+            'apps: for app in engine.root.0.get_children().await? {
+                let app = app
+                    .into_accessible_proxy(connection)
+                    .await
+                    .map_err(|e: zbus::Error| AutomationError::PlatformError(e.to_string()))?;
+                let Ok(windows) = app.get_children().await else {
+                    continue;
+                };
+                for window in windows {
+                    let (destination, path) = (window.name.to_string(), window.path.to_string());
+                    let Ok(component) = ComponentProxy::builder(connection)
+                        .destination(destination.as_str())?
+                        .path(path.as_str())?
+                        .build()
+                        .await
+                    else {
+                        continue;
+                    };
+                    if !component
+                        .contains(x, y, CoordType::Screen)
+                        .await
+                        .unwrap_or(false)
+                    {
+                        continue;
+                    }
+                    let window = window
+                        .into_accessible_proxy(connection)
+                        .await
+                        .map_err(|e: zbus::Error| AutomationError::PlatformError(e.to_string()))?;
+                    let states = window.get_state().await.unwrap_or_default();
+                    if states.contains(State::Active) {
+                        candidate = Some((destination, path));
+                        break 'apps;
+                    }
+                    if candidate.is_none() && states.contains(State::Showing) {
+                        candidate = Some((destination, path));
+                    }
+                }
+            }
+            let (mut destination, mut path) = candidate.ok_or_else(|| {
+                AutomationError::ElementNotFound(format!("No window found at ({x}, {y})"))
+            })?;
+            // Descend while a child contains the point; the depth bound guards against
+            // applications that report themselves as their own child.
+            for _ in 0..64 {
+                let component = ComponentProxy::builder(connection)
+                    .destination(destination.as_str())?
+                    .path(path.as_str())?
+                    .build()
+                    .await?;
+                match component
+                    .get_accessible_at_point(x, y, CoordType::Screen)
+                    .await
+                {
+                    Ok(child)
+                        if child.path.as_str() != NULL_PATH && child.path.as_str() != path =>
+                    {
+                        destination = child.name.to_string();
+                        path = child.path.to_string();
+                    }
+                    _ => break,
+                }
+            }
+            Ok(vec![UIElement::new(Box::new(LinuxUIElement {
+                connection: Arc::clone(&engine.connection),
+                destination,
+                path,
+            }))])
+        })
+    });
+    get_worker().send((req, resp_tx)).unwrap();
+    resp_rx
+        .recv()
+        .map_err(|e| AutomationError::PlatformError(e.to_string()))?
+        .map(|mut elements| elements.remove(0))
+}
+
 #[async_trait::async_trait]
 impl AccessibilityEngine for LinuxEngine {
     fn get_root_element(&self) -> UIElement {
@@ -1506,10 +1619,13 @@ impl AccessibilityEngine for LinuxEngine {
             AutomationError::InvalidArgument("Unix command is required for Linux".to_string())
         })?;
 
-        let output = Command::new("sh")
+        // kill_on_drop: a caller that gives up on the command (e.g. a step timeout) stops it
+        let output = tokio::process::Command::new("sh")
             .arg("-c")
             .arg(command)
+            .kill_on_drop(true)
             .output()
+            .await
             .map_err(|e| {
                 AutomationError::PlatformError(format!("Failed to execute command: {}", e))
             })?;
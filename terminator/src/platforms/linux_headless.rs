@@ -0,0 +1,470 @@
+//! Headless Linux desktop: Xvfb, a private session bus and the AT-SPI registry.
+//!
+//! A [`HeadlessSession`] starts the processes a server needs to automate GUI apps
+//! without a real display, points this process (and every app it launches) at them
+//! through `DISPLAY` and `DBUS_SESSION_BUS_ADDRESS`, and stops them when dropped.
+//!
+//! Changing the environment races with every other thread that reads it, so the
+//! session must be started while the process is still single-threaded, e.g. before
+//! building the tokio runtime.
+
+use crate::AutomationError;
+use serde::Serialize;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, warn};
+
+/// First display number tried when none is configured, clear of the usual `:0`/`:1`.
+const FIRST_DISPLAY: u32 = 99;
+const DISPLAY_SEARCH_RANGE: u32 = 100;
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+const SUPERVISE_INTERVAL: Duration = Duration::from_secs(2);
+const STOP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Directories the AT-SPI helpers are installed to by common distributions.
+const AT_SPI_DIRS: &[&str] = &[
+    "/usr/libexec",
+    "/usr/lib/at-spi2-core",
+    "/usr/libexec/at-spi2-core",
+    "/usr/lib/x86_64-linux-gnu/at-spi2-core",
+    "/usr/lib/aarch64-linux-gnu/at-spi2-core",
+];
+
+/// The session running in this process; there can only be one since it owns the environment.
+static ACTIVE: Mutex<Option<Weak<HeadlessSession>>> = Mutex::new(None);
+
+/// Settings for [`HeadlessSession::start`]
+#[derive(Debug, Clone)]
+pub struct HeadlessConfig {
+    pub width: u32,
+    pub height: u32,
+    pub depth: u32,
+    /// X display number; the first free one from `:99` when `None`
+    pub display: Option<u32>,
+}
+
+impl Default for HeadlessConfig {
+    fn default() -> Self {
+        Self {
+            width: 1920,
+            height: 1080,
+            depth: 24,
+            display: None,
+        }
+    }
+}
+
+/// State of one supervised process
+#[derive(Debug, Clone, Serialize)]
+pub struct HeadlessProcessStatus {
+    pub name: String,
+    pub pid: u32,
+    pub running: bool,
+}
+
+/// Snapshot of the headless session, as reported by the health checker
+#[derive(Debug, Clone, Serialize)]
+pub struct HeadlessStatus {
+    pub display: String,
+    pub bus_address: String,
+    pub screen: String,
+    pub processes: Vec<HeadlessProcessStatus>,
+}
+
+impl HeadlessStatus {
+    /// Whether every supervised process is still alive
+    pub fn is_running(&self) -> bool {
+        self.processes.iter().all(|p| p.running)
+    }
+}
+
+struct Supervised {
+    name: &'static str,
+    child: Child,
+    exited: bool,
+}
+
+/// Xvfb, a private D-Bus session bus and AT-SPI, supervised for the lifetime of the value.
+///
+/// Start it before creating any [`Desktop`](crate::Desktop): the AT-SPI connection is
+/// opened once per process from the session bus in the environment.
+pub struct HeadlessSession {
+    display: String,
+    bus_address: String,
+    screen: String,
+    processes: Mutex<Vec<Supervised>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl HeadlessSession {
+    /// Start Xvfb, the session bus and AT-SPI, and point this process at them.
+    ///
+    /// This sets environment variables, so call it before the process starts other
+    /// threads (such as a multi-threaded tokio runtime). The variables are left in
+    /// place when the session is dropped, for the same reason.
+    pub fn start(config: HeadlessConfig) -> Result<Arc<Self>, AutomationError> {
+        let mut active = ACTIVE.lock().unwrap_or_else(|e| e.into_inner());
+        if active.as_ref().and_then(Weak::upgrade).is_some() {
+            return Err(AutomationError::InvalidArgument(
+                "a headless session is already running in this process".to_string(),
+            ));
+        }
+
+        let xvfb = find_program("Xvfb", &[]).ok_or_else(|| {
+            AutomationError::PlatformError(
+                "Xvfb not found in PATH; install it (e.g. `apt install xvfb`)".to_string(),
+            )
+        })?;
+        let dbus_daemon = find_program("dbus-daemon", &[]).ok_or_else(|| {
+            AutomationError::PlatformError("dbus-daemon not found in PATH".to_string())
+        })?;
+
+        let number = match config.display {
+            Some(number) => number,
+            None => find_free_display()?,
+        };
+        let display = format!(":{number}");
+        let screen = format!("{}x{}x{}", config.width, config.height, config.depth);
+
+        // Children are collected as they start so a failure part-way tears down the rest
+        let mut starting = StartingProcesses::default();
+
+        let mut xvfb_cmd = Command::new(&xvfb);
+        xvfb_cmd
+            .arg(&display)
+            .args(["-screen", "0", &screen, "-nolisten", "tcp"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        starting.spawn("Xvfb", &mut xvfb_cmd)?;
+        let socket = PathBuf::from(format!("/tmp/.X11-unix/X{number}"));
+        starting.wait_until("Xvfb", || socket.exists())?;
+
+        let mut dbus_cmd = Command::new(&dbus_daemon);
+        dbus_cmd
+            .args(["--session", "--nofork", "--print-address"])
+            .env("DISPLAY", &display)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null());
+        let dbus = starting.spawn("dbus-daemon", &mut dbus_cmd)?;
+        // Read on a thread so a daemon that never prints can't hang startup; stopping
+        // it on failure closes the pipe and ends the thread
+        let (sender, receiver) = mpsc::channel();
+        if let Some(stdout) = dbus.stdout.take() {
+            thread::spawn(move || {
+                let line = BufReader::new(stdout).lines().next().and_then(Result::ok);
+                let _ = sender.send(line);
+            });
+        }
+        let bus_address = match receiver.recv_timeout(STARTUP_TIMEOUT) {
+            Ok(line) => line
+                .map(|line| line.trim().to_string())
+                .filter(|line| !line.is_empty()),
+            Err(RecvTimeoutError::Timeout) => {
+                return Err(AutomationError::Timeout(format!(
+                    "dbus-daemon did not print a bus address after {}s",
+                    STARTUP_TIMEOUT.as_secs()
+                )))
+            }
+            Err(RecvTimeoutError::Disconnected) => None,
+        }
+        .ok_or_else(|| {
+            AutomationError::PlatformError("dbus-daemon did not print a bus address".to_string())
+        })?;
+
+        let session_env = [
+            ("DISPLAY", display.as_str()),
+            ("DBUS_SESSION_BUS_ADDRESS", bus_address.as_str()),
+        ];
+        match find_program("at-spi-bus-launcher", AT_SPI_DIRS) {
+            Some(launcher) => {
+                let mut cmd = Command::new(launcher);
+                cmd.arg("--launch-immediately");
+                starting.spawn("at-spi-bus-launcher", quiet(&mut cmd, &session_env))?;
+                if find_program("dbus-send", &[]).is_some() {
+                    starting.wait_until("at-spi-bus-launcher", || a11y_bus_ready(&session_env))?;
+                }
+            }
+            None => warn!("at-spi-bus-launcher not found; relying on D-Bus activation of AT-SPI"),
+        }
+        match find_program("at-spi2-registryd", AT_SPI_DIRS) {
+            Some(registryd) => {
+                let mut cmd = Command::new(registryd);
+                starting.spawn("at-spi2-registryd", quiet(&mut cmd, &session_env))?;
+            }
+            None => warn!("at-spi2-registryd not found; relying on D-Bus activation of AT-SPI"),
+        }
+
+        // GTK apps only export their accessibility tree when this is enabled
+        if find_program("gsettings", &[]).is_some() {
+            let mut cmd = Command::new("gsettings");
+            cmd.args([
+                "set",
+                "org.gnome.desktop.interface",
+                "toolkit-accessibility",
+                "true",
+            ]);
+            match quiet(&mut cmd, &session_env).status() {
+                Ok(status) if status.success() => {}
+                other => debug!("Enabling toolkit-accessibility failed: {:?}", other),
+            }
+        }
+
+        std::env::set_var("DISPLAY", &display);
+        std::env::set_var("DBUS_SESSION_BUS_ADDRESS", &bus_address);
+        // Would point at the host's accessibility bus instead of ours
+        std::env::remove_var("AT_SPI_BUS_ADDRESS");
+        // Qt's AT-SPI bridge otherwise only starts when it detects a screen reader
+        std::env::set_var("QT_ACCESSIBILITY", "1");
+        std::env::set_var("QT_LINUX_ACCESSIBILITY_ALWAYS_ON", "1");
+
+        let session = Arc::new(Self {
+            display,
+            bus_address,
+            screen,
+            processes: Mutex::new(starting.finish()),
+            stop: Arc::new(AtomicBool::new(false)),
+        });
+        info!(
+            "Headless session started on display {} ({}), session bus {}",
+            session.display, session.screen, session.bus_address
+        );
+        spawn_supervisor(Arc::downgrade(&session), session.stop.clone());
+        *active = Some(Arc::downgrade(&session));
+        Ok(session)
+    }
+
+    /// The X display apps are started on, e.g. `:99`
+    pub fn display(&self) -> &str {
+        &self.display
+    }
+
+    /// Address of the private session bus
+    pub fn bus_address(&self) -> &str {
+        &self.bus_address
+    }
+
+    pub fn status(&self) -> HeadlessStatus {
+        let mut processes = self.processes.lock().unwrap_or_else(|e| e.into_inner());
+        HeadlessStatus {
+            display: self.display.clone(),
+            bus_address: self.bus_address.clone(),
+            screen: self.screen.clone(),
+            processes: processes
+                .iter_mut()
+                .map(|p| HeadlessProcessStatus {
+                    name: p.name.to_string(),
+                    pid: p.child.id(),
+                    running: !p.exited && matches!(p.child.try_wait(), Ok(None)),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Drop for HeadlessSession {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        let processes = std::mem::take(self.processes.get_mut().unwrap_or_else(|e| e.into_inner()));
+        stop_processes(processes);
+        info!("Headless session on display {} stopped", self.display);
+    }
+}
+
+/// Status of the headless session running in this process, if any.
+pub fn active_status() -> Option<HeadlessStatus> {
+    let active = ACTIVE.lock().unwrap_or_else(|e| e.into_inner());
+    active
+        .as_ref()
+        .and_then(Weak::upgrade)
+        .map(|session| session.status())
+}
+
+#[derive(Default)]
+struct StartingProcesses(Vec<Supervised>);
+
+impl StartingProcesses {
+    fn spawn(
+        &mut self,
+        name: &'static str,
+        cmd: &mut Command,
+    ) -> Result<&mut Child, AutomationError> {
+        let child = cmd
+            .spawn()
+            .map_err(|e| AutomationError::PlatformError(format!("failed to start {name}: {e}")))?;
+        debug!("Started {} (pid {})", name, child.id());
+        self.0.push(Supervised {
+            name,
+            child,
+            exited: false,
+        });
+        Ok(&mut self.0.last_mut().expect("just pushed").child)
+    }
+
+    /// Wait for `ready`, failing early if the process `name` exits.
+    fn wait_until(
+        &mut self,
+        name: &str,
+        mut ready: impl FnMut() -> bool,
+    ) -> Result<(), AutomationError> {
+        let deadline = Instant::now() + STARTUP_TIMEOUT;
+        loop {
+            if ready() {
+                return Ok(());
+            }
+            if let Some(process) = self.0.iter_mut().find(|p| p.name == name) {
+                if let Ok(Some(status)) = process.child.try_wait() {
+                    process.exited = true;
+                    return Err(AutomationError::PlatformError(format!(
+                        "{name} exited during startup ({status})"
+                    )));
+                }
+            }
+            if Instant::now() >= deadline {
+                return Err(AutomationError::Timeout(format!(
+                    "{name} was not ready after {}s",
+                    STARTUP_TIMEOUT.as_secs()
+                )));
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+    }
+
+    fn finish(mut self) -> Vec<Supervised> {
+        std::mem::take(&mut self.0)
+    }
+}
+
+impl Drop for StartingProcesses {
+    fn drop(&mut self) {
+        stop_processes(std::mem::take(&mut self.0));
+    }
+}
+
+/// Log once when a supervised process dies; apps on the display stop working at that point.
+fn spawn_supervisor(session: Weak<HeadlessSession>, stop: Arc<AtomicBool>) {
+    thread::spawn(move || {
+        while !stop.load(Ordering::Relaxed) {
+            thread::sleep(SUPERVISE_INTERVAL);
+            let Some(session) = session.upgrade() else {
+                break;
+            };
+            let mut processes = session.processes.lock().unwrap_or_else(|e| e.into_inner());
+            for process in processes.iter_mut().filter(|p| !p.exited) {
+                if let Ok(Some(status)) = process.child.try_wait() {
+                    process.exited = true;
+                    error!(
+                        "{} (pid {}) of the headless session on {} exited: {}",
+                        process.name,
+                        process.child.id(),
+                        session.display,
+                        status
+                    );
+                }
+            }
+        }
+    });
+}
+
+/// Stop processes in reverse start order: SIGTERM, then SIGKILL if they linger.
+fn stop_processes(processes: Vec<Supervised>) {
+    for mut process in processes.into_iter().rev() {
+        if process.exited || !matches!(process.child.try_wait(), Ok(None)) {
+            continue;
+        }
+        // SIGTERM lets Xvfb remove its lock file and socket
+        unsafe {
+            libc::kill(process.child.id() as libc::pid_t, libc::SIGTERM);
+        }
+        let deadline = Instant::now() + STOP_TIMEOUT;
+        while matches!(process.child.try_wait(), Ok(None)) && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(25));
+        }
+        if matches!(process.child.try_wait(), Ok(None)) {
+            let _ = process.child.kill();
+            let _ = process.child.wait();
+        }
+    }
+}
+
+fn quiet<'a>(cmd: &'a mut Command, env: &[(&str, &str)]) -> &'a mut Command {
+    cmd.envs(env.iter().copied())
+        .env_remove("AT_SPI_BUS_ADDRESS")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+}
+
+/// Whether the AT-SPI launcher owns `org.a11y.Bus` yet. Asking the bus itself would
+/// start a second launcher through D-Bus activation while ours is still starting.
+fn a11y_bus_ready(env: &[(&str, &str)]) -> bool {
+    let mut cmd = Command::new("dbus-send");
+    cmd.args([
+        "--session",
+        "--print-reply",
+        "--reply-timeout=1000",
+        "--dest=org.freedesktop.DBus",
+        "/org/freedesktop/DBus",
+        "org.freedesktop.DBus.NameHasOwner",
+        "string:org.a11y.Bus",
+    ]);
+    quiet(&mut cmd, env)
+        .stdout(Stdio::piped())
+        .output()
+        .is_ok_and(|output| {
+            output.status.success()
+                && String::from_utf8_lossy(&output.stdout).contains("boolean true")
+        })
+}
+
+fn find_free_display() -> Result<u32, AutomationError> {
+    (FIRST_DISPLAY..FIRST_DISPLAY + DISPLAY_SEARCH_RANGE)
+        .find(|n| display_is_free(Path::new("/tmp"), *n))
+        .ok_or_else(|| {
+            AutomationError::PlatformError(format!(
+                "no free X display between :{FIRST_DISPLAY} and :{}",
+                FIRST_DISPLAY + DISPLAY_SEARCH_RANGE - 1
+            ))
+        })
+}
+
+fn display_is_free(tmp: &Path, number: u32) -> bool {
+    !tmp.join(format!(".X{number}-lock")).exists()
+        && !tmp.join(".X11-unix").join(format!("X{number}")).exists()
+}
+
+fn find_program(name: &str, extra_dirs: &[&str]) -> Option<PathBuf> {
+    let path_dirs = std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).collect::<Vec<_>>())
+        .unwrap_or_default();
+    path_dirs
+        .into_iter()
+        .chain(extra_dirs.iter().map(PathBuf::from))
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_is_free_checks_lock_and_socket() {
+        let tmp = std::env::temp_dir().join(format!("terminator-headless-{}", std::process::id()));
+        std::fs::create_dir_all(tmp.join(".X11-unix")).unwrap();
+        assert!(display_is_free(&tmp, 5));
+        std::fs::write(tmp.join(".X5-lock"), "").unwrap();
+        assert!(!display_is_free(&tmp, 5));
+        std::fs::write(tmp.join(".X11-unix").join("X6"), "").unwrap();
+        assert!(!display_is_free(&tmp, 6));
+        assert!(display_is_free(&tmp, 7));
+        std::fs::remove_dir_all(&tmp).unwrap();
+    }
+}
@@ -3344,6 +3344,7 @@ impl AccessibilityEngine for MacOSEngine {
         let output = tokio::process::Command::new("/bin/sh")
             .arg("-c")
             .arg(command_str)
+            .kill_on_drop(true) // Stop the command when the caller stops waiting for it
             .output()
             .await // Await the async output
             .map_err(|e| AutomationError::PlatformError(e.to_string()))?;
@@ -205,8 +205,11 @@ pub trait AccessibilityEngine: Send + Sync {
     fn as_any(&self) -> &dyn std::any::Any;
 }
 
+pub mod fixture;
 #[cfg(target_os = "linux")]
 pub mod linux;
+#[cfg(target_os = "linux")]
+pub mod linux_headless;
 #[cfg(target_os = "macos")]
 pub mod macos;
 #[cfg(target_os = "macos")]
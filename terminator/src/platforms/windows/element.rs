@@ -1155,6 +1155,12 @@ impl UIElementImpl for WindowsUIElement {
         })
     }
 
+    fn is_password(&self) -> Result<bool, AutomationError> {
+        self.element.0.is_password().map_err(|e| {
+            AutomationError::PlatformError(format!("Failed to get password state: {e}"))
+        })
+    }
+
     fn perform_action(&self, action: &str) -> Result<(), AutomationError> {
         // actions those don't take args
         match action {
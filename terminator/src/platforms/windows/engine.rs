@@ -2615,6 +2615,7 @@ impl AccessibilityEngine for WindowsEngine {
                 "-Command",
                 command_str,
             ])
+            .kill_on_drop(true) // Stop the command when the caller stops waiting for it
             .output()
             .await // Await the async output
             .map_err(|e| AutomationError::PlatformError(e.to_string()))?;
@@ -193,6 +193,18 @@ fn get_configurable_attributes(
         }
     };
 
+    // Lets consumers of a complete tree (e.g. fixture capture) leave password contents out
+    if matches!(
+        property_mode,
+        crate::platforms::PropertyLoadingMode::Complete
+    ) && element.is_password().unwrap_or(false)
+    {
+        attrs.properties.insert(
+            "IsPassword".to_string(),
+            Some(serde_json::Value::Bool(true)),
+        );
+    }
+
     // Check if element is keyboard focusable and add bounds if it is
     if let Ok(is_focusable) = element.is_keyboard_focusable() {
         if is_focusable {